@@ -0,0 +1,77 @@
+#!/usr/bin/env python3
+"""Sign release binaries for `lasersell self-update`.
+
+Usage: sign_release.py <version> <file>...
+
+Writes <file>.sig next to each file given: the base58 ed25519 signature
+over the release manifest "lasersell <version> <file name> <sha256>\\n",
+which self_update::verify_release rebuilds from latest.txt and the download
+and checks against the key built into the binary. Signing the version keeps
+an older binary from being served as a newer release.
+
+Inputs:
+  RELEASE_SIGNING_KEYPAIR: the signing keypair as a Solana keypair JSON
+    array of 64 bytes (required)
+  LASERSELL_RELEASE_SIGNING_KEY: the base58 pubkey the binaries were built
+    with; when set, the keypair must match it
+"""
+
+import hashlib
+import json
+import os
+import sys
+
+from cryptography.hazmat.primitives.asymmetric.ed25519 import Ed25519PrivateKey
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+B58_ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+
+
+def b58encode(data: bytes) -> str:
+    number = int.from_bytes(data, "big")
+    encoded = ""
+    while number:
+        number, rem = divmod(number, 58)
+        encoded = B58_ALPHABET[rem] + encoded
+    leading_zeros = len(data) - len(data.lstrip(b"\0"))
+    return "1" * leading_zeros + encoded
+
+
+def release_manifest(version: str, path: str) -> bytes:
+    with open(path, "rb") as f:
+        digest = hashlib.sha256(f.read()).hexdigest()
+    return f"lasersell {version} {os.path.basename(path)} {digest}\n".encode()
+
+
+def main() -> int:
+    if len(sys.argv) < 3:
+        print("usage: sign_release.py <version> <file>...", file=sys.stderr)
+        return 1
+    version = sys.argv[1]
+    raw = os.environ.get("RELEASE_SIGNING_KEYPAIR", "").strip()
+    if not raw:
+        print("Missing required env var: RELEASE_SIGNING_KEYPAIR", file=sys.stderr)
+        return 1
+    keypair = bytes(json.loads(raw))
+    if len(keypair) != 64:
+        print("RELEASE_SIGNING_KEYPAIR is not a 64-byte keypair", file=sys.stderr)
+        return 1
+    signing_key = Ed25519PrivateKey.from_private_bytes(keypair[:32])
+    pubkey = b58encode(signing_key.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw))
+    expected = os.environ.get("LASERSELL_RELEASE_SIGNING_KEY", "").strip()
+    if expected and expected != pubkey:
+        print(
+            f"Signing key {pubkey} does not match LASERSELL_RELEASE_SIGNING_KEY {expected}",
+            file=sys.stderr,
+        )
+        return 1
+    for path in sys.argv[2:]:
+        signature = signing_key.sign(release_manifest(version, path))
+        with open(f"{path}.sig", "w", encoding="utf-8") as f:
+            f.write(b58encode(signature) + "\n")
+        print(f"signed {path} as {version} with {pubkey}")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tokio::sync::watch;
+
+/// Ends each frame on the attach socket.
+const FRAME_END: u8 = 0;
+/// Under `ui.accessible`, how often the whole view is printed again; in
+/// between only lines whose words changed are.
+#[cfg(unix)]
+const ACCESSIBLE_SUMMARY_EVERY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Publishes a text view of the engine to `lasersell attach` processes over
+/// a Unix socket, so another terminal can watch without running a second
+/// engine. Clients only read; nothing they send is used.
+pub(super) struct AttachServer {
+    path: PathBuf,
+    frames: watch::Sender<String>,
+}
+
+impl AttachServer {
+    /// Listens on `path`, replacing a socket left by a daemon that is gone.
+    /// `None` if another daemon is listening there or the socket cannot be
+    /// created; the daemon runs without it.
+    #[cfg(unix)]
+    pub(super) fn bind(path: &Path) -> Option<Self> {
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::io::AsyncWriteExt;
+        use tokio::net::UnixListener;
+        use tracing::{debug, info, warn};
+
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            warn!(
+                event = "attach_socket_in_use",
+                path = %path.display(),
+                "another daemon serves `lasersell attach`; this one will not"
+            );
+            return None;
+        }
+        let _ = std::fs::remove_file(path);
+        let listener = match UnixListener::bind(path) {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!(event = "attach_socket_unavailable", path = %path.display(), error = %err);
+                return None;
+            }
+        };
+        let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+        info!(event = "attach_socket_listening", path = %path.display());
+        let (frames, _) = watch::channel(String::new());
+        let server = Self {
+            path: path.to_path_buf(),
+            frames: frames.clone(),
+        };
+        tokio::spawn(async move {
+            loop {
+                let mut stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        debug!(event = "attach_accept_failed", error = %err);
+                        continue;
+                    }
+                };
+                let mut rx = frames.subscribe();
+                tokio::spawn(async move {
+                    loop {
+                        let mut frame = rx.borrow_and_update().clone().into_bytes();
+                        frame.push(FRAME_END);
+                        if stream.write_all(&frame).await.is_err() || rx.changed().await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        Some(server)
+    }
+
+    #[cfg(not(unix))]
+    pub(super) fn bind(_path: &Path) -> Option<Self> {
+        None
+    }
+
+    /// Whether anyone is attached, so idle daemons skip rendering frames.
+    pub(super) fn has_clients(&self) -> bool {
+        self.frames.receiver_count() > 0
+    }
+
+    pub(super) fn publish(&self, frame: String) {
+        self.frames.send_replace(frame);
+    }
+}
+
+impl Drop for AttachServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// `lasersell attach`: shows the running daemon's view, redrawn on every
+/// heartbeat, until the daemon stops or the user presses Ctrl-C.
+#[cfg(unix)]
+pub async fn run_attach_cli(path: &Path) -> Result<()> {
+    use anyhow::Context;
+    use std::io::Write;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let theme = crate::util::theme::current();
+    let stream = tokio::net::UnixStream::connect(path)
+        .await
+        .with_context(|| format!("connect to {}; is the daemon running?", path.display()))?;
+    let mut reader = BufReader::new(stream);
+    let mut frame = Vec::new();
+    let mut printed = String::new();
+    let mut summary_at: Option<std::time::Instant> = None;
+    loop {
+        frame.clear();
+        if reader.read_until(FRAME_END, &mut frame).await? == 0 {
+            if theme.ansi() {
+                print!("\x1b[0m");
+            }
+            println!("the daemon stopped");
+            return Ok(());
+        }
+        if frame.last() == Some(&FRAME_END) {
+            frame.pop();
+        }
+        let frame = String::from_utf8_lossy(&frame);
+        if theme.accessible() {
+            let lines = if summary_at.is_none_or(|at| at.elapsed() >= ACCESSIBLE_SUMMARY_EVERY) {
+                summary_at = Some(std::time::Instant::now());
+                frame.lines().filter(|line| !line.is_empty()).collect()
+            } else {
+                changed_lines(&printed, &frame)
+            };
+            let mut stdout = std::io::stdout().lock();
+            for line in lines {
+                writeln!(stdout, "{line}")?;
+            }
+            stdout.flush()?;
+            printed = frame.into_owned();
+            continue;
+        }
+        let (header, rest) = frame.split_once('\n').unwrap_or((&frame, ""));
+        let mut stdout = std::io::stdout().lock();
+        write!(
+            stdout,
+            "{}{}{}\n{rest}",
+            theme.clear_screen(),
+            theme.accent(header),
+            theme.background()
+        )?;
+        stdout.flush()?;
+    }
+}
+
+/// Lines of `next` whose words differ from every line of `previous`;
+/// lines where only numbers moved (PnL, ages, countdowns) are left out.
+#[cfg(unix)]
+fn changed_lines<'a>(previous: &str, next: &'a str) -> Vec<&'a str> {
+    let words = |line: &str| -> String {
+        line.split(|ch: char| ch.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("#")
+    };
+    let seen: std::collections::HashSet<String> = previous.lines().map(words).collect();
+    next.lines()
+        .filter(|line| !line.is_empty() && !seen.contains(&words(line)))
+        .collect()
+}
+
+#[cfg(not(unix))]
+pub async fn run_attach_cli(_path: &Path) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "attach needs Unix domain sockets, which this platform does not have"
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    #[tokio::test]
+    async fn clients_get_the_latest_frame_and_each_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("attach.sock");
+        let server = AttachServer::bind(&path).unwrap();
+        assert!(!server.has_clients());
+        server.publish("sessions: none\n".to_string());
+        assert!(AttachServer::bind(&path).is_none());
+
+        let stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut frame = Vec::new();
+        reader.read_until(FRAME_END, &mut frame).await.unwrap();
+        assert_eq!(frame, b"sessions: none\n\0");
+        assert!(server.has_clients());
+
+        server.publish("sessions: 1\n".to_string());
+        frame.clear();
+        reader.read_until(FRAME_END, &mut frame).await.unwrap();
+        assert_eq!(frame, b"sessions: 1\n\0");
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn accessible_updates_skip_number_only_changes() {
+        let previous = "stream connected\nabc  +1.50%  12s ago\n";
+        let next = "stream DOWN\nabc  +2.75%  13s ago\ndef  +0.10%  1s ago\n";
+        assert_eq!(
+            changed_lines(previous, next),
+            ["stream DOWN", "def  +0.10%  1s ago"]
+        );
+    }
+}
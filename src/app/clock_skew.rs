@@ -0,0 +1,90 @@
+use tracing::{info, warn};
+
+/// How often the stream server's clock is sampled with a ping.
+pub(super) const CLOCK_SKEW_CHECK_SECS: u64 = 60;
+/// Skew beyond which deadlines and latency metrics are visibly wrong.
+const SKEW_WARN_MS: u64 = 1_000;
+
+/// Offset of the stream server's clock from ours, from ping/pong round
+/// trips. Positive means the server is ahead.
+#[derive(Default)]
+pub(super) struct ClockSkew {
+    ping_sent_ms: Option<u64>,
+    last_check_ms: u64,
+    skew_ms: Option<i64>,
+    warned: bool,
+}
+
+impl ClockSkew {
+    pub(super) fn is_due(&self, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.last_check_ms) >= CLOCK_SKEW_CHECK_SECS * 1_000
+    }
+
+    pub(super) fn ping_sent(&mut self, now_ms: u64) {
+        self.ping_sent_ms = Some(now_ms);
+        self.last_check_ms = now_ms;
+    }
+
+    /// Records the server time from a pong. The server stamped it somewhere
+    /// in the round trip, so the midpoint is taken as its local time and half
+    /// the round trip is the uncertainty.
+    pub(super) fn observe_pong(&mut self, server_time_ms: u64, now_ms: u64) {
+        let Some(sent_ms) = self.ping_sent_ms.take() else {
+            return;
+        };
+        let rtt_ms = now_ms.saturating_sub(sent_ms);
+        let midpoint_ms = sent_ms + rtt_ms / 2;
+        let skew_ms = server_time_ms as i64 - midpoint_ms as i64;
+        self.skew_ms = Some(skew_ms);
+        let beyond = skew_ms.unsigned_abs().saturating_sub(rtt_ms / 2) > SKEW_WARN_MS;
+        if beyond && !self.warned {
+            self.warned = true;
+            warn!(
+                event = "clock_skew_detected",
+                skew_ms,
+                rtt_ms,
+                "local clock is {}ms {} the stream server; deadlines and latency metrics \
+                 are off by that much until NTP fixes it",
+                skew_ms.unsigned_abs(),
+                if skew_ms > 0 { "behind" } else { "ahead of" }
+            );
+        } else if !beyond && self.warned {
+            self.warned = false;
+            info!(event = "clock_skew_resolved", skew_ms, rtt_ms);
+        }
+    }
+
+    pub(super) fn skew_ms(&self) -> Option<i64> {
+        self.skew_ms
+    }
+
+    /// A server timestamp moved onto the local clock.
+    pub(super) fn to_local_ms(&self, server_ms: u64) -> u64 {
+        server_ms.saturating_add_signed(-self.skew_ms.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_skew_from_round_trip_midpoint() {
+        let mut skew = ClockSkew::default();
+        assert!(skew.is_due(CLOCK_SKEW_CHECK_SECS * 1_000));
+        skew.observe_pong(50_000, 10_000);
+        assert_eq!(skew.skew_ms(), None, "pong without a ping is ignored");
+
+        skew.ping_sent(10_000);
+        assert!(!skew.is_due(10_000 + CLOCK_SKEW_CHECK_SECS * 1_000 - 1));
+        skew.observe_pong(12_600, 10_200);
+        assert_eq!(skew.skew_ms(), Some(2_500));
+        assert!(skew.warned);
+        assert_eq!(skew.to_local_ms(12_600), 10_100);
+
+        skew.ping_sent(20_000);
+        skew.observe_pong(19_950, 20_100);
+        assert_eq!(skew.skew_ms(), Some(-100));
+        assert!(!skew.warned);
+    }
+}
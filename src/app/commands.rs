@@ -0,0 +1,640 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use secrecy::SecretString;
+use solana_sdk::pubkey::Pubkey;
+use time::OffsetDateTime;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
+use tracing::{warn, Level};
+
+use crate::config::KeyBindings;
+use crate::events::{AppCommand, SellAmount, SellTime};
+use crate::util::logging::ConsoleLogCommand;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// First words of the commands below, which no shortcut may take over.
+const COMMAND_WORDS: [&str; 26] = [
+    "quit",
+    "exit",
+    "exposure",
+    "congestion",
+    "metrics",
+    "fees",
+    "logs",
+    "notifications",
+    "ack",
+    "rent",
+    "sweep",
+    "portfolio",
+    "show",
+    "why",
+    "sessions",
+    "settings",
+    "set",
+    "wallets",
+    "wallet",
+    "log",
+    "buy",
+    "limits",
+    "limit",
+    "insights",
+    "sell-at",
+    "sell",
+];
+
+/// Parses one console line. Blank lines yield `None`.
+///
+/// ```text
+/// sell <mint>               sell a session or any token held, at the configured slippage
+/// sell <mint> 50% | 1200    sell half the tokens held, or 1200 tokens
+/// sell <mint> slip 35%      sell now at 35% slippage, even above slippage_max_bps
+/// buy <mint> 0.1 [slip 20%] buy 0.1 SOL of a token; its session opens when it lands
+/// limit <mint> 50% | off    sell once profit reaches 50% of the entry; kept across restarts
+/// limits                    armed limit sells
+/// insights                  strategy suggestions from the journal
+/// insights dismiss <id>     hide one for good; kept across restarts
+/// sell-at <mint> 15m | off  sell in 15m (also 90s, 2h, 1h30m, or an RFC 3339 time)
+/// exposure                  open cost basis by quote token and market type
+/// congestion                priority fee and skipped-slot gauge
+/// metrics                   sell latency p50/p95: stream, build/send and confirm
+/// fees                      network, priority and tip fees per sell, and their share of PnL
+/// logs                      log file sizes and the last error line
+/// notifications             errors, stream disconnects and sells since the last `ack`
+/// ack                       mark them read (also `notifications ack`)
+/// rent                      SOL locked in token account rent, and how much is reclaimable
+/// sweep                     what a dust sweep would sell, burn and close
+/// sweep run                 do it and print the SOL reclaimed
+/// portfolio                 every token the wallet holds, valued, most valuable first
+/// portfolio sell <n>        sell row n of the last `portfolio` listing like `sell <mint>`
+/// show <mint>               PnL sparkline, proceeds, sell timeline and signature
+/// why <mint>                every step of the latest sell: signal, attempts, errors, outcome
+/// sessions                  open sessions with last PnL and its age; shows stream outages
+/// sessions prune            drop failed or emptied sessions without waiting for their TTL
+/// settings                  strategy and sell settings with staged changes
+/// set <setting> <value>     stage a change, e.g. `set tp 6%`; checked as it is staged
+/// set tp 10% for <mint>     change one exit of that position now; the config is untouched
+/// settings apply | cancel   use and save the staged changes, or drop them
+/// wallets                   configured wallets; `*` marks the signing one
+/// wallet use <name>         unlock another wallet and sign with it; closes open sessions
+/// log warn+ | info+ | all   filter console lines by level
+/// log search <text>         show only lines containing text (`log search` clears)
+/// log pause | resume        hold console output, then print what was held
+/// quit
+/// ```
+pub fn parse_command(line: &str) -> Result<Option<AppCommand>> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        [] => Ok(None),
+        ["quit" | "exit"] => Ok(Some(AppCommand::Quit)),
+        ["exposure"] => Ok(Some(AppCommand::ShowExposure)),
+        ["congestion"] => Ok(Some(AppCommand::ShowCongestion)),
+        ["metrics"] => Ok(Some(AppCommand::ShowMetrics)),
+        ["fees"] => Ok(Some(AppCommand::ShowFees)),
+        ["logs"] => Ok(Some(AppCommand::ShowLogs)),
+        ["notifications"] => Ok(Some(AppCommand::ShowNotifications)),
+        ["ack"] | ["notifications", "ack"] => Ok(Some(AppCommand::AckNotifications)),
+        ["rent"] => Ok(Some(AppCommand::ShowRent)),
+        ["sweep"] => Ok(Some(AppCommand::Sweep { execute: false })),
+        ["sweep", "run"] => Ok(Some(AppCommand::Sweep { execute: true })),
+        ["portfolio"] => Ok(Some(AppCommand::ShowPortfolio)),
+        ["portfolio", "sell", n] => Ok(Some(AppCommand::SellHolding {
+            n: n.parse()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| anyhow!("portfolio row must be a number from 1, got {n}"))?,
+        })),
+        ["portfolio", ..] => Err(anyhow!("usage: portfolio [sell <n>]")),
+        ["show", mint] => Ok(Some(AppCommand::ShowMint {
+            mint: Pubkey::from_str(mint).map_err(|_| anyhow!("invalid mint {mint}"))?,
+        })),
+        ["why", mint] => Ok(Some(AppCommand::Why {
+            mint: Pubkey::from_str(mint).map_err(|_| anyhow!("invalid mint {mint}"))?,
+        })),
+        ["sessions"] => Ok(Some(AppCommand::ShowSessions)),
+        ["sessions", "prune"] => Ok(Some(AppCommand::PruneSessions)),
+        ["settings"] => Ok(Some(AppCommand::ShowSettings)),
+        ["settings", "apply"] => Ok(Some(AppCommand::ApplySettings)),
+        ["settings", "cancel"] => Ok(Some(AppCommand::CancelSettings)),
+        ["set", field, value, "for", mint] => Ok(Some(AppCommand::SetPositionSetting {
+            mint: Pubkey::from_str(mint).map_err(|_| anyhow!("invalid mint {mint}"))?,
+            field: field.to_string(),
+            value: value.to_string(),
+        })),
+        ["set", field, value] => Ok(Some(AppCommand::SetSetting {
+            field: field.to_string(),
+            value: value.to_string(),
+        })),
+        ["wallets"] => Ok(Some(AppCommand::ShowWallets)),
+        ["wallet", "use", name] => Ok(Some(AppCommand::UseWallet {
+            name: name.to_string(),
+            passphrase: None,
+        })),
+        ["wallet", ..] => Err(anyhow!("usage: wallet use <name>")),
+        ["set", ..] => Err(anyhow!("usage: set <setting> <value>, e.g. set tp 6%")),
+        ["log", rest @ ..] => Ok(Some(AppCommand::ConsoleLog(parse_log_command(rest)?))),
+        ["buy", mint, sol, rest @ ..] => {
+            let mint = Pubkey::from_str(mint).map_err(|_| anyhow!("invalid mint {mint}"))?;
+            let lamports = sol
+                .parse::<f64>()
+                .ok()
+                .filter(|sol| sol.is_finite() && *sol > 0.0)
+                .map(|sol| (sol * LAMPORTS_PER_SOL).round() as u64)
+                .filter(|lamports| *lamports > 0)
+                .ok_or_else(|| anyhow!("buy amount must be a positive SOL amount, got {sol}"))?;
+            let slippage_bps = match rest {
+                [] => None,
+                ["slip", pct] => Some(parse_slippage_pct(pct)?),
+                _ => return Err(anyhow!("usage: buy <mint> <sol> [slip <percent>%]")),
+            };
+            Ok(Some(AppCommand::Buy {
+                mint,
+                lamports,
+                slippage_bps,
+            }))
+        }
+        ["buy", ..] => Err(anyhow!("usage: buy <mint> <sol> [slip <percent>%]")),
+        ["limits"] => Ok(Some(AppCommand::ShowLimits)),
+        ["limit", mint, pct] => {
+            let mint = Pubkey::from_str(mint).map_err(|_| anyhow!("invalid mint {mint}"))?;
+            let profit_pct = match *pct {
+                "off" => None,
+                pct => Some(
+                    pct.strip_suffix('%')
+                        .and_then(|pct| pct.parse::<f64>().ok())
+                        .filter(|pct| pct.is_finite() && *pct > 0.0)
+                        .ok_or_else(|| {
+                            anyhow!("limit must be a positive profit percentage, got {pct}")
+                        })?,
+                ),
+            };
+            Ok(Some(AppCommand::SetLimit { mint, profit_pct }))
+        }
+        ["limit", ..] => Err(anyhow!("usage: limit <mint> <percent>% | off")),
+        ["insights"] => Ok(Some(AppCommand::ShowInsights)),
+        ["insights", "dismiss", id] => Ok(Some(AppCommand::DismissInsight { id: id.to_string() })),
+        ["insights", ..] => Err(anyhow!("usage: insights [dismiss <id>]")),
+        ["sell-at", mint, when] => Ok(Some(AppCommand::SellAt {
+            mint: Pubkey::from_str(mint).map_err(|_| anyhow!("invalid mint {mint}"))?,
+            when: match *when {
+                "off" => None,
+                when => Some(parse_sell_time(when)?),
+            },
+        })),
+        ["sell-at", ..] => Err(anyhow!(
+            "usage: sell-at <mint> <duration>|<RFC 3339 time>|off"
+        )),
+        ["sell", mint, rest @ ..] => {
+            let mint = Pubkey::from_str(mint).map_err(|_| anyhow!("invalid mint {mint}"))?;
+            let (amount, rest) = match rest {
+                [amount, rest @ ..] if *amount != "slip" => {
+                    (Some(parse_sell_amount(amount)?), rest)
+                }
+                rest => (None, rest),
+            };
+            let slippage_bps = match rest {
+                [] => None,
+                ["slip", pct] => Some(parse_slippage_pct(pct)?),
+                _ => {
+                    return Err(anyhow!(
+                        "usage: sell <mint> [<tokens>|<percent>%] [slip <percent>%]"
+                    ))
+                }
+            };
+            Ok(Some(AppCommand::Sell {
+                mint,
+                amount,
+                slippage_bps,
+            }))
+        }
+        [other, ..] => Err(anyhow!("unknown command {other}")),
+    }
+}
+
+/// `ui.keybindings`: one-word stand-ins for the start of a command.
+#[derive(Debug, Default)]
+pub struct Shortcuts {
+    pause: Option<String>,
+    sell: Option<String>,
+    details: Option<String>,
+    ack: Option<String>,
+}
+
+impl Shortcuts {
+    /// Fails on a key that is not one word, is a command already, or is
+    /// bound to two actions.
+    pub fn new(bindings: &KeyBindings) -> Result<Self> {
+        let mut bound: Vec<&str> = Vec::new();
+        for (action, key) in [
+            ("pause", &bindings.pause),
+            ("sell", &bindings.sell),
+            ("details", &bindings.details),
+            ("ack", &bindings.ack),
+        ] {
+            let Some(key) = key.as_deref() else {
+                continue;
+            };
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return Err(anyhow!("ui.keybindings.{action} must be one word, got {key:?}"));
+            }
+            if COMMAND_WORDS.contains(&key) {
+                return Err(anyhow!("ui.keybindings.{action}: {key} is already a command"));
+            }
+            if bound.contains(&key) {
+                return Err(anyhow!("ui.keybindings.{action}: {key} is bound twice"));
+            }
+            bound.push(key);
+        }
+        Ok(Self {
+            pause: bindings.pause.clone(),
+            sell: bindings.sell.clone(),
+            details: bindings.details.clone(),
+            ack: bindings.ack.clone(),
+        })
+    }
+
+    /// [`parse_command`], after expanding a shortcut at the start of `line`.
+    pub fn parse(&self, line: &str) -> Result<Option<AppCommand>> {
+        let mut words = line.split_whitespace();
+        let Some(first) = words.next() else {
+            return Ok(None);
+        };
+        let rest = words.collect::<Vec<_>>().join(" ");
+        let is = |key: &Option<String>| key.as_deref() == Some(first);
+        if is(&self.pause) {
+            if !rest.is_empty() {
+                return Err(anyhow!("usage: {first} (pauses or resumes console output)"));
+            }
+            return Ok(Some(AppCommand::ConsoleLog(ConsoleLogCommand::TogglePause)));
+        }
+        if is(&self.ack) {
+            if !rest.is_empty() {
+                return Err(anyhow!("usage: {first} (marks notifications read)"));
+            }
+            return Ok(Some(AppCommand::AckNotifications));
+        }
+        if is(&self.sell) || is(&self.details) {
+            if rest.is_empty() {
+                return Err(anyhow!("usage: {first} <mint>"));
+            }
+            let command = if is(&self.sell) { "sell" } else { "show" };
+            return parse_command(&format!("{command} {rest}"));
+        }
+        parse_command(line)
+    }
+}
+
+fn parse_log_command(words: &[&str]) -> Result<ConsoleLogCommand> {
+    Ok(match words {
+        ["error+"] => ConsoleLogCommand::MinLevel(Level::ERROR),
+        ["warn+"] => ConsoleLogCommand::MinLevel(Level::WARN),
+        ["info+"] => ConsoleLogCommand::MinLevel(Level::INFO),
+        ["debug+"] => ConsoleLogCommand::MinLevel(Level::DEBUG),
+        ["all"] => ConsoleLogCommand::MinLevel(Level::TRACE),
+        ["search"] => ConsoleLogCommand::Search(None),
+        ["search", text @ ..] => ConsoleLogCommand::Search(Some(text.join(" "))),
+        ["pause"] => ConsoleLogCommand::Pause,
+        ["resume"] => ConsoleLogCommand::Resume,
+        _ => {
+            return Err(anyhow!(
+                "usage: log warn+|info+|debug+|all, log search [text], log pause|resume"
+            ))
+        }
+    })
+}
+
+fn parse_sell_amount(raw: &str) -> Result<SellAmount> {
+    let (number, percent) = match raw.strip_suffix('%') {
+        Some(number) => (number, true),
+        None => (raw, false),
+    };
+    let value = number
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite() && *value > 0.0);
+    match (value, percent) {
+        (Some(pct), true) if pct <= 100.0 => Ok(SellAmount::Percent(pct)),
+        (Some(tokens), false) => Ok(SellAmount::Tokens(tokens)),
+        _ => Err(anyhow!(
+            "sell amount must be a token count or a percentage up to 100%, got {raw}"
+        )),
+    }
+}
+
+/// `90`, `90s`, `15m`, `2h` or `1h30m` from now, or an RFC 3339 time such
+/// as `2026-10-15T14:00:00Z`.
+fn parse_sell_time(raw: &str) -> Result<SellTime> {
+    if let Ok(at) = OffsetDateTime::parse(raw, &time::format_description::well_known::Rfc3339) {
+        let at_ms = at.unix_timestamp_nanos() / 1_000_000;
+        return u64::try_from(at_ms)
+            .map(SellTime::AtMs)
+            .map_err(|_| anyhow!("sell-at time {raw} is before 1970"));
+    }
+    let invalid = || anyhow!("invalid duration or time {raw}; e.g. 90s, 15m, 1h30m");
+    if let Ok(secs) = raw.parse::<u64>() {
+        return Ok(SellTime::AfterSec(secs));
+    }
+    let mut secs = 0u64;
+    let mut digits = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3_600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = digits.parse().map_err(|_| invalid())?;
+        secs = value
+            .checked_mul(unit)
+            .and_then(|value| secs.checked_add(value))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(invalid());
+    }
+    Ok(SellTime::AfterSec(secs))
+}
+
+fn parse_slippage_pct(raw: &str) -> Result<u16> {
+    raw.trim_end_matches('%')
+        .parse::<f64>()
+        .ok()
+        .filter(|pct| pct.is_finite() && *pct > 0.0 && *pct <= 100.0)
+        .map(|pct| (pct * 100.0).round() as u16)
+        .ok_or_else(|| anyhow!("slippage must be a percentage between 0 and 100, got {raw}"))
+}
+
+/// Forwards commands typed on stdin until it closes.
+pub fn spawn_stdin_reader(cmd_tx: mpsc::UnboundedSender<AppCommand>, shortcuts: Shortcuts) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            match shortcuts.parse(&line) {
+                Ok(Some(AppCommand::UseWallet { name, .. })) => {
+                    // Prompted here, on the task that owns stdin, so the
+                    // reader cannot swallow the passphrase line.
+                    let passphrase = match prompt_wallet_passphrase(&name).await {
+                        Ok(passphrase) => passphrase,
+                        Err(err) => {
+                            warn!(event = "command_invalid", error = %err);
+                            continue;
+                        }
+                    };
+                    if cmd_tx
+                        .send(AppCommand::UseWallet { name, passphrase })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Ok(Some(cmd)) => {
+                    if cmd_tx.send(cmd).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => warn!(event = "command_invalid", error = %err),
+            }
+        }
+    });
+}
+
+/// `None` when `LASERSELL_WALLET_PASSPHRASE` is set or nothing was typed;
+/// plaintext keypairs need no passphrase.
+async fn prompt_wallet_passphrase(name: &str) -> Result<Option<SecretString>> {
+    if std::env::var("LASERSELL_WALLET_PASSPHRASE").is_ok_and(|value| !value.trim().is_empty()) {
+        return Ok(None);
+    }
+    let prompt = format!("passphrase for wallet {name} (enter if none): ");
+    let passphrase = tokio::task::spawn_blocking(move || rpassword::prompt_password(prompt))
+        .await
+        .map_err(|err| anyhow!("passphrase prompt failed: {err}"))??;
+    Ok((!passphrase.is_empty()).then(|| SecretString::new(passphrase)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "So11111111111111111111111111111111111111112";
+
+    #[test]
+    fn parses_sell_with_slippage_override() {
+        let Some(AppCommand::Sell {
+            mint,
+            amount,
+            slippage_bps,
+        }) = parse_command(&format!("sell {MINT} slip 35%")).unwrap()
+        else {
+            panic!("expected sell command");
+        };
+        assert_eq!(mint.to_string(), MINT);
+        assert_eq!(amount, None);
+        assert_eq!(slippage_bps, Some(3_500));
+        assert!(matches!(
+            parse_command(&format!("  sell {MINT}  ")).unwrap(),
+            Some(AppCommand::Sell {
+                amount: None,
+                slippage_bps: None,
+                ..
+            })
+        ));
+        let amount = |line: &str| match parse_command(line).unwrap() {
+            Some(AppCommand::Sell { amount, .. }) => amount,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(
+            amount(&format!("sell {MINT} 50% slip 10%")),
+            Some(SellAmount::Percent(50.0))
+        );
+        assert_eq!(
+            amount(&format!("sell {MINT} 1200.5")),
+            Some(SellAmount::Tokens(1200.5))
+        );
+        assert!(parse_command("").unwrap().is_none());
+        assert!(matches!(
+            parse_command(&format!("show {MINT}")).unwrap(),
+            Some(AppCommand::ShowMint { .. })
+        ));
+        assert!(matches!(
+            parse_command(&format!("why {MINT}")).unwrap(),
+            Some(AppCommand::Why { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_buy_in_sol() {
+        let Some(AppCommand::Buy {
+            mint,
+            lamports,
+            slippage_bps,
+        }) = parse_command(&format!("buy {MINT} 0.25 slip 20%")).unwrap()
+        else {
+            panic!("expected buy command");
+        };
+        assert_eq!(mint.to_string(), MINT);
+        assert_eq!(lamports, 250_000_000);
+        assert_eq!(slippage_bps, Some(2_000));
+        assert!(parse_command(&format!("buy {MINT}")).is_err());
+        assert!(parse_command(&format!("buy {MINT} 0")).is_err());
+        assert!(parse_command(&format!("buy {MINT} 1 slip")).is_err());
+    }
+
+    #[test]
+    fn parses_insight_dismissals() {
+        assert!(matches!(
+            parse_command("insights dismiss execution_drag").unwrap(),
+            Some(AppCommand::DismissInsight { id }) if id == "execution_drag"
+        ));
+        assert!(matches!(parse_command("insights").unwrap(), Some(AppCommand::ShowInsights)));
+        assert!(parse_command("insights dismiss").is_err());
+    }
+
+    #[test]
+    fn parses_limits() {
+        assert!(matches!(
+            parse_command(&format!("limit {MINT} 50%")).unwrap(),
+            Some(AppCommand::SetLimit {
+                profit_pct: Some(pct),
+                ..
+            }) if pct == 50.0
+        ));
+        assert!(matches!(
+            parse_command(&format!("limit {MINT} off")).unwrap(),
+            Some(AppCommand::SetLimit {
+                profit_pct: None,
+                ..
+            })
+        ));
+        assert!(parse_command(&format!("limit {MINT} 50")).is_err());
+        assert!(parse_command(&format!("limit {MINT} -10%")).is_err());
+    }
+
+    #[test]
+    fn parses_portfolio_rows() {
+        assert!(matches!(
+            parse_command("portfolio").unwrap(),
+            Some(AppCommand::ShowPortfolio)
+        ));
+        assert!(matches!(
+            parse_command("portfolio sell 3").unwrap(),
+            Some(AppCommand::SellHolding { n: 3 })
+        ));
+        assert!(parse_command("portfolio sell 0").is_err());
+        assert!(parse_command("portfolio sell all").is_err());
+    }
+
+    #[test]
+    fn parses_sell_at_durations_and_times() {
+        let when = |arg: &str| match parse_command(&format!("sell-at {MINT} {arg}")) {
+            Ok(Some(AppCommand::SellAt { when, .. })) => Ok(when),
+            Ok(other) => panic!("unexpected {other:?}"),
+            Err(err) => Err(err),
+        };
+        assert_eq!(when("90").unwrap(), Some(SellTime::AfterSec(90)));
+        assert_eq!(when("15m").unwrap(), Some(SellTime::AfterSec(900)));
+        assert_eq!(when("1h30m").unwrap(), Some(SellTime::AfterSec(5_400)));
+        assert_eq!(
+            when("2023-11-14T22:13:20Z").unwrap(),
+            Some(SellTime::AtMs(1_700_000_000_000))
+        );
+        assert_eq!(when("off").unwrap(), None);
+        assert!(when("15").is_ok());
+        assert!(when("15x").is_err());
+        assert!(when("1h30").is_err());
+        assert!(when("h").is_err());
+    }
+
+    #[test]
+    fn parses_position_settings() {
+        let Some(AppCommand::SetPositionSetting { mint, field, value }) =
+            parse_command(&format!("set tp 10% for {MINT}")).unwrap()
+        else {
+            panic!("expected a position setting");
+        };
+        assert_eq!(
+            (mint.to_string(), field.as_str(), value.as_str()),
+            (MINT.to_string(), "tp", "10%")
+        );
+        assert!(parse_command("set tp 10% for nope").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_sell_commands() {
+        assert!(parse_command("sell nope").is_err());
+        assert!(parse_command(&format!("sell {MINT} slip 120%")).is_err());
+        assert!(parse_command(&format!("sell {MINT} slip")).is_err());
+        assert!(parse_command(&format!("sell {MINT} 150%")).is_err());
+        assert!(parse_command(&format!("sell {MINT} 0")).is_err());
+        assert!(parse_command(&format!("sell {MINT} 10 20")).is_err());
+        assert!(parse_command("buy").is_err());
+        assert!(parse_command("sessions purge").is_err());
+    }
+
+    #[test]
+    fn parses_console_log_controls() {
+        let parse = |line: &str| match parse_command(line).unwrap() {
+            Some(AppCommand::ConsoleLog(cmd)) => cmd,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(parse("log warn+"), ConsoleLogCommand::MinLevel(Level::WARN));
+        assert_eq!(
+            parse("log search sell failed"),
+            ConsoleLogCommand::Search(Some("sell failed".to_string()))
+        );
+        assert_eq!(parse("log search"), ConsoleLogCommand::Search(None));
+        assert_eq!(parse("log pause"), ConsoleLogCommand::Pause);
+        assert!(parse_command("log louder").is_err());
+    }
+
+    #[test]
+    fn shortcuts_expand_and_are_checked() {
+        let bindings = |pause: &str, sell: &str| KeyBindings {
+            pause: Some(pause.to_string()),
+            sell: Some(sell.to_string()),
+            details: Some("d".to_string()),
+            ack: Some("x".to_string()),
+        };
+        let shortcuts = Shortcuts::new(&bindings("p", "s")).unwrap();
+        assert!(matches!(
+            shortcuts.parse("p").unwrap(),
+            Some(AppCommand::ConsoleLog(ConsoleLogCommand::TogglePause))
+        ));
+        assert!(matches!(
+            shortcuts.parse(&format!(" s {MINT} 50%")).unwrap(),
+            Some(AppCommand::Sell {
+                amount: Some(SellAmount::Percent(50.0)),
+                ..
+            })
+        ));
+        assert!(matches!(
+            shortcuts.parse(&format!("d {MINT}")).unwrap(),
+            Some(AppCommand::ShowMint { .. })
+        ));
+        assert!(matches!(
+            shortcuts.parse("x").unwrap(),
+            Some(AppCommand::AckNotifications)
+        ));
+        assert!(matches!(
+            shortcuts.parse("sessions").unwrap(),
+            Some(AppCommand::ShowSessions)
+        ));
+        assert!(matches!(
+            parse_command("notifications ack").unwrap(),
+            Some(AppCommand::AckNotifications)
+        ));
+        assert!(shortcuts.parse("s").is_err());
+        assert!(shortcuts.parse("p now").is_err());
+        assert!(Shortcuts::default().parse("p").is_err());
+
+        for (pause, sell) in [("sell", "s"), ("p", "p"), ("p", "s s"), ("", "s"), ("p", "d")] {
+            assert!(Shortcuts::new(&bindings(pause, sell)).is_err(), "{pause:?} {sell:?}");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+/// How long a confirmed sell is remembered.
+const KEEP_MS: u64 = 24 * 60 * 60 * 1_000;
+/// Newest entries kept when more sells than that confirm within `KEEP_MS`.
+const MAX_ENTRIES: usize = 1_000;
+
+/// One exit signal whose sell confirmed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CompletedSell {
+    position_id: u64,
+    mint: String,
+    reason: String,
+    /// The position size the signal carried; a take-profit ladder sends
+    /// one signal per level, each with what the previous level left.
+    position_tokens: u64,
+    signature: String,
+    at_ms: u64,
+}
+
+/// Exit signals already sold, persisted as JSON next to the journal. After
+/// a reconnect or a restart the stream may resend a signal for a position
+/// that is gone; matching it here keeps the engine from selling twice.
+#[derive(Debug, Default)]
+pub(super) struct CompletedSells {
+    path: Option<PathBuf>,
+    entries: Mutex<VecDeque<CompletedSell>>,
+}
+
+impl CompletedSells {
+    /// Loads the sells confirmed in the last day from `path`; a missing
+    /// file means none.
+    pub(super) fn load(path: PathBuf, now_ms: u64) -> Result<Self> {
+        let mut entries: VecDeque<CompletedSell> = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .with_context(|| format!("parse completed sells {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("read completed sells {}", path.display()))
+            }
+        };
+        prune(&mut entries, now_ms);
+        Ok(Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// The signature of the sell that already answered this signal.
+    pub(super) fn find(
+        &self,
+        position_id: u64,
+        mint: &Pubkey,
+        reason: &str,
+        position_tokens: u64,
+    ) -> Option<String> {
+        let mint = mint.to_string();
+        self.entries
+            .lock()
+            .iter()
+            .rev()
+            .find(|entry| {
+                entry.position_id == position_id
+                    && entry.mint == mint
+                    && entry.reason == reason
+                    && entry.position_tokens == position_tokens
+            })
+            .map(|entry| entry.signature.clone())
+    }
+
+    /// Remembers a confirmed sell and saves the set.
+    pub(super) fn record(
+        &self,
+        position_id: u64,
+        mint: &Pubkey,
+        reason: &str,
+        position_tokens: u64,
+        signature: &str,
+        now_ms: u64,
+    ) -> Result<()> {
+        let mut entries = self.entries.lock();
+        entries.push_back(CompletedSell {
+            position_id,
+            mint: mint.to_string(),
+            reason: reason.to_string(),
+            position_tokens,
+            signature: signature.to_string(),
+            at_ms: now_ms,
+        });
+        prune(&mut entries, now_ms);
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let raw = serde_json::to_vec(&*entries).context("serialize completed sells")?;
+        crate::util::fs_utils::atomic_write(path, &raw, Some(0o600))
+            .with_context(|| format!("write completed sells {}", path.display()))
+    }
+}
+
+fn prune(entries: &mut VecDeque<CompletedSell>, now_ms: u64) {
+    entries.retain(|entry| now_ms.saturating_sub(entry.at_ms) < KEEP_MS);
+    while entries.len() > MAX_ENTRIES {
+        entries.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn remembers_sold_signals_across_restarts() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("completed_sells.json");
+        let mint = Pubkey::new_unique();
+        let sells = CompletedSells::load(path.clone(), 0).unwrap();
+        assert_eq!(sells.find(7, &mint, "target", 1_000), None);
+        sells
+            .record(7, &mint, "take_profit", 1_000, "first", 1_000)
+            .unwrap();
+
+        let reloaded = CompletedSells::load(path.clone(), 2_000).unwrap();
+        assert_eq!(
+            reloaded.find(7, &mint, "take_profit", 1_000).as_deref(),
+            Some("first")
+        );
+        // The next ladder level carries what the first one left.
+        assert_eq!(reloaded.find(7, &mint, "take_profit", 600), None);
+        assert_eq!(
+            reloaded.find(7, &Pubkey::new_unique(), "take_profit", 1_000),
+            None
+        );
+
+        let expired = CompletedSells::load(path, 1_000 + KEEP_MS).unwrap();
+        assert_eq!(expired.find(7, &mint, "take_profit", 1_000), None);
+    }
+}
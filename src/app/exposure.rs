@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::config::ExposureConfig;
+use crate::market::{MarketType, QuoteToken};
+
+/// One open position's contribution to wallet exposure.
+pub(super) struct OpenExposure {
+    pub(super) quote: QuoteToken,
+    /// `None` when the stream has not sent a market context for the mint.
+    pub(super) market_type: Option<MarketType>,
+    pub(super) cost_basis_units: u64,
+}
+
+#[derive(Debug, Default)]
+struct QuoteExposure {
+    total_units: u64,
+    positions: usize,
+    by_market: BTreeMap<Option<MarketType>, u64>,
+}
+
+/// Open cost basis grouped by quote token, then by market type.
+#[derive(Debug, Default)]
+pub(super) struct ExposureReport {
+    by_quote: BTreeMap<QuoteToken, QuoteExposure>,
+}
+
+impl ExposureReport {
+    pub(super) fn from_positions(positions: impl IntoIterator<Item = OpenExposure>) -> Self {
+        let mut report = Self::default();
+        for position in positions {
+            let quote = report.by_quote.entry(position.quote).or_default();
+            quote.total_units = quote.total_units.saturating_add(position.cost_basis_units);
+            quote.positions += 1;
+            *quote.by_market.entry(position.market_type).or_default() += position.cost_basis_units;
+        }
+        report
+    }
+
+    pub(super) fn render(&self) -> String {
+        if self.by_quote.is_empty() {
+            return "no open positions with a known cost basis\n".to_string();
+        }
+        let mut out = String::new();
+        for (quote, exposure) in &self.by_quote {
+            let _ = writeln!(
+                out,
+                "{:<18} {} {} across {} positions",
+                quote.symbol(),
+                format_units(exposure.total_units, *quote),
+                quote.symbol(),
+                exposure.positions
+            );
+            for (market_type, units) in &exposure.by_market {
+                let _ = writeln!(
+                    out,
+                    "  {:<16} {} ({:.0}%)",
+                    market_label(*market_type),
+                    format_units(*units, *quote),
+                    share_pct(*units, exposure.total_units)
+                );
+            }
+        }
+        out
+    }
+
+    /// Caps currently exceeded, as `(stable key, message)` pairs.
+    pub(super) fn breaches(&self, cfg: &ExposureConfig) -> Vec<(String, String)> {
+        let mut breaches = Vec::new();
+        for (quote, exposure) in &self.by_quote {
+            let cap = match quote {
+                QuoteToken::Sol => cfg.max_sol,
+                QuoteToken::Usd1 => cfg.max_usd1,
+            };
+            let cap_units = (cap * 10f64.powi(quote.decimals() as i32)).round() as u64;
+            if cap > 0.0 && exposure.total_units > cap_units {
+                breaches.push((
+                    quote.symbol().to_string(),
+                    format!(
+                        "open {} exposure {} exceeds the {} cap",
+                        quote.symbol(),
+                        format_units(exposure.total_units, *quote),
+                        format_units(cap_units, *quote)
+                    ),
+                ));
+            }
+            if cfg.max_market_type_pct <= 0.0 {
+                continue;
+            }
+            for (market_type, units) in &exposure.by_market {
+                let share = share_pct(*units, exposure.total_units);
+                if exposure.positions > 1 && share > cfg.max_market_type_pct {
+                    breaches.push((
+                        format!("{}:{}", quote.symbol(), market_label(*market_type)),
+                        format!(
+                            "{:.0}% of open {} exposure is in {} (cap {:.0}%)",
+                            share,
+                            quote.symbol(),
+                            market_label(*market_type),
+                            cfg.max_market_type_pct
+                        ),
+                    ));
+                }
+            }
+        }
+        breaches
+    }
+}
+
+fn market_label(market_type: Option<MarketType>) -> &'static str {
+    market_type.map_or("unknown", MarketType::label)
+}
+
+fn share_pct(units: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        units as f64 / total as f64 * 100.0
+    }
+}
+
+fn format_units(units: u64, quote: QuoteToken) -> String {
+    let decimals = quote.decimals();
+    let scale = 10u64.pow(decimals);
+    format!(
+        "{}.{:0width$}",
+        units / scale,
+        units % scale,
+        width = decimals as usize
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(quote: QuoteToken, market_type: MarketType, cost_basis_units: u64) -> OpenExposure {
+        OpenExposure {
+            quote,
+            market_type: Some(market_type),
+            cost_basis_units,
+        }
+    }
+
+    #[test]
+    fn groups_by_quote_and_flags_caps() {
+        let report = ExposureReport::from_positions([
+            position(QuoteToken::Sol, MarketType::PumpFun, 1_500_000_000),
+            position(QuoteToken::Sol, MarketType::PumpFun, 500_000_000),
+            position(QuoteToken::Sol, MarketType::PumpSwap, 500_000_000),
+            position(QuoteToken::Usd1, MarketType::RaydiumCpmm, 25_000_000),
+        ]);
+        let rendered = report.render();
+        assert!(rendered.contains("SOL                2.500000000 SOL across 3 positions\n"));
+        assert!(rendered.contains("  pump_fun         2.000000000 (80%)\n"));
+        assert!(rendered.contains("USD1               25.000000 USD1 across 1 positions\n"));
+
+        let cfg = ExposureConfig {
+            max_market_type_pct: 75.0,
+            max_sol: 2.0,
+            max_usd1: 100.0,
+        };
+        let keys: Vec<String> = report
+            .breaches(&cfg)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        // A lone USD1 position is 100% concentrated but not flagged.
+        assert_eq!(keys, vec!["SOL", "SOL:pump_fun"]);
+        assert!(report.breaches(&ExposureConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use solana_sdk::pubkey::Pubkey;
+use tracing::debug;
+
+use super::sweep::{Priced, Sweeper};
+
+/// `stream.fallback_quote_sec`: while the stream is down, open sessions are
+/// quoted every so often through the exit API by simulating the unsigned
+/// full sell to SOL. The engine treats each quote like a stream PnL update,
+/// so PnL and limits keep working in degraded mode, and the strategy too
+/// with `stream.fallback_exits`.
+#[derive(Debug)]
+pub(super) struct FallbackQuoter {
+    interval_ms: u64,
+    last_started_ms: u64,
+    running: Arc<AtomicBool>,
+    /// Lamports each mint would sell for, quoted since the last heartbeat.
+    quotes: Arc<Mutex<Vec<(Pubkey, u64)>>>,
+    /// Highest profit seen per mint, from the stream and from these quotes,
+    /// for the trailing stop.
+    peaks: HashMap<Pubkey, i64>,
+    /// Mints whose sell a quote started in this outage; not quoted again.
+    exited: HashSet<Pubkey>,
+}
+
+impl FallbackQuoter {
+    pub(super) fn new(interval_sec: u64) -> Self {
+        Self {
+            interval_ms: interval_sec.saturating_mul(1_000),
+            last_started_ms: 0,
+            running: Arc::new(AtomicBool::new(false)),
+            quotes: Arc::new(Mutex::new(Vec::new())),
+            peaks: HashMap::new(),
+            exited: HashSet::new(),
+        }
+    }
+
+    /// Records a profit sample and returns the peak for `mint` so far.
+    pub(super) fn note_profit(&mut self, mint: Pubkey, profit_units: i64) -> i64 {
+        let peak = self.peaks.entry(mint).or_insert(profit_units);
+        *peak = (*peak).max(profit_units);
+        *peak
+    }
+
+    pub(super) fn peak(&self, mint: &Pubkey) -> Option<i64> {
+        self.peaks.get(mint).copied()
+    }
+
+    pub(super) fn note_exit(&mut self, mint: Pubkey) {
+        self.exited.insert(mint);
+    }
+
+    /// The stream is back: quotes still arriving are dropped and every
+    /// session may be quoted again in the next outage.
+    pub(super) fn reset(&mut self) {
+        self.quotes.lock().clear();
+        self.exited.clear();
+    }
+
+    /// Whether a round of quotes is due; only one runs at a time.
+    pub(super) fn is_due(&self, now_ms: u64) -> bool {
+        self.interval_ms > 0
+            && !self.running.load(Ordering::SeqCst)
+            && now_ms.saturating_sub(self.last_started_ms) >= self.interval_ms
+    }
+
+    /// Quotes `sessions` (mint and token amount) in the background.
+    pub(super) fn start(&mut self, now_ms: u64, sweeper: Sweeper, sessions: Vec<(Pubkey, u64)>) {
+        self.peaks
+            .retain(|mint, _| sessions.iter().any(|(open, _)| open == mint));
+        let sessions: Vec<_> = sessions
+            .into_iter()
+            .filter(|(mint, tokens)| *tokens > 0 && !self.exited.contains(mint))
+            .collect();
+        if sessions.is_empty() {
+            return;
+        }
+        self.last_started_ms = now_ms;
+        self.running.store(true, Ordering::SeqCst);
+        let running = self.running.clone();
+        let quotes = self.quotes.clone();
+        tokio::spawn(async move {
+            for (mint, tokens) in sessions {
+                match sweeper.quote(&mint, tokens).await {
+                    Ok(Priced::Net(lamports)) => quotes.lock().push((mint, lamports)),
+                    Ok(Priced::Unsellable(reason)) => debug!(
+                        event = "fallback_quote_unsellable",
+                        mint = %mint,
+                        reason = %reason
+                    ),
+                    Err(err) => debug!(
+                        event = "fallback_quote_failed",
+                        mint = %mint,
+                        error = %format!("{err:#}")
+                    ),
+                }
+            }
+            running.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Quotes that arrived since the last call.
+    pub(super) fn take_quotes(&self) -> Vec<(Pubkey, u64)> {
+        std::mem::take(&mut *self.quotes.lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_peaks_and_rounds() {
+        let mut quoter = FallbackQuoter::new(15);
+        let mint = Pubkey::new_unique();
+        assert_eq!(quoter.note_profit(mint, 50), 50);
+        assert_eq!(quoter.note_profit(mint, -20), 50);
+        assert_eq!(quoter.note_profit(mint, 80), 80);
+
+        assert!(quoter.is_due(15_000));
+        quoter.last_started_ms = 15_000;
+        assert!(!quoter.is_due(29_999));
+        assert!(quoter.is_due(30_000));
+        quoter.running.store(true, Ordering::SeqCst);
+        assert!(!quoter.is_due(60_000));
+        assert!(!FallbackQuoter::new(0).is_due(60_000), "0 disables");
+
+        quoter.quotes.lock().push((mint, 1));
+        quoter.note_exit(mint);
+        quoter.reset();
+        assert!(quoter.take_quotes().is_empty());
+        assert!(quoter.exited.is_empty());
+        assert_eq!(quoter.note_profit(mint, 0), 80, "peaks outlive the outage");
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+
+use crate::events::history::sparkline;
+
+/// Heartbeats kept for the graphs: ten minutes at one per second.
+const SAMPLES: usize = 600;
+/// Width of each graph; every column covers `SAMPLES / COLUMNS` heartbeats.
+const COLUMNS: usize = 60;
+
+/// What happened between two heartbeats.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct HealthSample {
+    /// Slowest RPC call that succeeded, if any did.
+    rpc_latency_ms: Option<u64>,
+    stream_events: u64,
+    reconnects: u32,
+}
+
+/// RPC latency, stream event rate and stream reconnects per heartbeat, for
+/// the graphs in `metrics`.
+#[derive(Debug, Default)]
+pub(super) struct HealthHistory {
+    samples: VecDeque<HealthSample>,
+    current: HealthSample,
+}
+
+impl HealthHistory {
+    pub(super) fn note_stream_event(&mut self) {
+        self.current.stream_events += 1;
+    }
+
+    pub(super) fn note_reconnect(&mut self) {
+        self.current.reconnects += 1;
+    }
+
+    /// Closes the heartbeat's sample; `rpc_latency_ms` is the slowest RPC
+    /// call since the previous heartbeat.
+    pub(super) fn heartbeat(&mut self, rpc_latency_ms: Option<u64>) {
+        self.current.rpc_latency_ms = rpc_latency_ms;
+        if self.samples.len() >= SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(std::mem::take(&mut self.current));
+    }
+
+    /// One sparkline per metric over the kept heartbeats, the oldest on the
+    /// left, with the latest column and the extreme beside it; `accessible`
+    /// leaves the sparklines out.
+    pub(super) fn render(&self, heartbeat_secs: u64, accessible: bool) -> String {
+        if self.samples.is_empty() {
+            return "no health samples yet\n".to_string();
+        }
+        let samples: Vec<HealthSample> = self.samples.iter().copied().collect();
+        let per_column = samples.len().div_ceil(COLUMNS);
+        let columns: Vec<&[HealthSample]> = samples.chunks(per_column).collect();
+        let latency: Vec<i64> = columns
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .filter_map(|sample| sample.rpc_latency_ms)
+                    .max()
+                    .unwrap_or(0) as i64
+            })
+            .collect();
+        let events_per_sec: Vec<f64> = columns
+            .iter()
+            .map(|column| {
+                let events: u64 = column.iter().map(|sample| sample.stream_events).sum();
+                events as f64 / (column.len() as u64 * heartbeat_secs.max(1)) as f64
+            })
+            .collect();
+        let reconnects: Vec<i64> = columns
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|sample| i64::from(sample.reconnects))
+                    .sum()
+            })
+            .collect();
+
+        let graph = |values: &[i64]| {
+            if accessible {
+                String::new()
+            } else {
+                sparkline(values)
+            }
+        };
+        let span_secs = samples.len() as u64 * heartbeat_secs;
+        let mut out = format!(
+            "health over the last {}, {}s per column\n",
+            format_span(span_secs),
+            per_column as u64 * heartbeat_secs
+        );
+        let _ = writeln!(
+            out,
+            "{:<18}{}  now {} ms  max {} ms",
+            "rpc latency",
+            graph(&latency),
+            latency.last().copied().unwrap_or(0),
+            latency.iter().max().copied().unwrap_or(0)
+        );
+        let rate_bars: Vec<i64> = events_per_sec
+            .iter()
+            .map(|rate| (rate * 10.0).round() as i64)
+            .collect();
+        let _ = writeln!(
+            out,
+            "{:<18}{}  now {:.1}/s  max {:.1}/s",
+            "stream events",
+            graph(&rate_bars),
+            events_per_sec.last().copied().unwrap_or(0.0),
+            events_per_sec.iter().copied().fold(0.0, f64::max)
+        );
+        let _ = writeln!(
+            out,
+            "{:<18}{}  total {}",
+            "stream reconnects",
+            graph(&reconnects),
+            reconnects.iter().sum::<i64>()
+        );
+        out
+    }
+}
+
+fn format_span(secs: u64) -> String {
+    if secs < 120 {
+        format!("{secs}s")
+    } else {
+        format!("{}m", secs / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_aggregate_heartbeats() {
+        let mut history = HealthHistory::default();
+        assert_eq!(history.render(1, false), "no health samples yet\n");
+        for second in 0..120u64 {
+            for _ in 0..second % 4 {
+                history.note_stream_event();
+            }
+            if second == 30 {
+                history.note_reconnect();
+            }
+            history.heartbeat((second % 10 == 0).then_some(100 + second));
+        }
+
+        let rendered = history.render(1, false);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "health over the last 2m, 2s per column");
+        assert!(lines[1].starts_with("rpc latency       ▄"));
+        assert!(lines[1].ends_with("  now 0 ms  max 210 ms"));
+        assert!(lines[2].ends_with("  now 2.5/s  max 2.5/s"));
+        assert!(lines[3].ends_with("  total 1"));
+        assert_eq!(lines[3].chars().filter(|bar| *bar == '█').count(), 1);
+        assert_eq!(
+            history.render(1, true).lines().nth(1),
+            Some("rpc latency         now 0 ms  max 210 ms")
+        );
+        assert_eq!(
+            lines[3].chars().filter(|bar| *bar == '▁').count(),
+            COLUMNS - 1
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_latest_heartbeats() {
+        let mut history = HealthHistory::default();
+        for _ in 0..SAMPLES + 5 {
+            history.heartbeat(None);
+        }
+        assert_eq!(history.samples.len(), SAMPLES);
+        assert!(history
+            .render(1, false)
+            .starts_with("health over the last 10m, 10s per column\n"));
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+
+use crate::journal::analyzer::Insight;
+
+/// Strategy suggestions from the journal analyzer. Ones dismissed with
+/// `insights dismiss <id>` are persisted as JSON next to the journal and
+/// stay quiet across restarts.
+#[derive(Debug, Default)]
+pub(super) struct Insights {
+    path: Option<PathBuf>,
+    dismissed: Mutex<BTreeSet<String>>,
+    /// The latest analysis, dismissed ones left out.
+    current: Mutex<BTreeMap<&'static str, String>>,
+}
+
+impl Insights {
+    /// Loads the dismissed ids from `path`; a missing file means none.
+    pub(super) fn load(path: PathBuf) -> Result<Self> {
+        let dismissed = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .with_context(|| format!("parse dismissed insights {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("read dismissed insights {}", path.display()))
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            dismissed: Mutex::new(dismissed),
+            current: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Replaces the current suggestions with `found` and returns the ones
+    /// that were not current before and have not been dismissed.
+    pub(super) fn update(&self, found: Vec<Insight>) -> Vec<Insight> {
+        let dismissed = self.dismissed.lock();
+        let mut current = self.current.lock();
+        let previous = std::mem::take(&mut *current);
+        let mut new = Vec::new();
+        for insight in found {
+            if dismissed.contains(insight.id) {
+                continue;
+            }
+            current.insert(insight.id, insight.message.clone());
+            if !previous.contains_key(insight.id) {
+                new.push(insight);
+            }
+        }
+        new
+    }
+
+    /// Returns whether `id` was a current suggestion.
+    pub(super) fn dismiss(&self, id: &str) -> Result<bool> {
+        if self.current.lock().remove(id).is_none() {
+            return Ok(false);
+        }
+        let mut dismissed = self.dismissed.lock();
+        dismissed.insert(id.to_string());
+        let Some(path) = self.path.as_ref() else {
+            return Ok(true);
+        };
+        let raw = serde_json::to_vec_pretty(&*dismissed).context("serialize dismissed insights")?;
+        crate::util::fs_utils::atomic_write(path, &raw, Some(0o600))
+            .with_context(|| format!("write dismissed insights {}", path.display()))?;
+        Ok(true)
+    }
+
+    pub(super) fn render(&self) -> String {
+        let current = self.current.lock();
+        if current.is_empty() {
+            return "no strategy insights\n".to_string();
+        }
+        let mut out = String::new();
+        for (id, message) in current.iter() {
+            let _ = writeln!(out, "{id:<20} {message}");
+        }
+        out.push_str("`insights dismiss <id>` hides one for good\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn insight(id: &'static str) -> Insight {
+        Insight {
+            id,
+            message: format!("{id} message"),
+        }
+    }
+
+    #[test]
+    fn dismissed_insights_stay_hidden_after_a_restart() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dismissed_insights.json");
+        let insights = Insights::load(path.clone()).unwrap();
+        let found = vec![insight("execution_drag"), insight("deadline_dominates")];
+        assert_eq!(insights.update(found.clone()).len(), 2);
+        assert!(insights.update(found.clone()).is_empty(), "surfaced once");
+        assert!(insights.dismiss("execution_drag").unwrap());
+        assert!(!insights.dismiss("stop_loss_too_tight").unwrap());
+        assert!(!insights.render().contains("execution_drag"));
+
+        let reloaded = Insights::load(path).unwrap();
+        assert_eq!(reloaded.update(found), vec![insight("deadline_dominates")]);
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+
+/// Completed sells kept for the percentiles.
+const SAMPLES: usize = 200;
+
+/// Where one completed sell spent its time.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct SellTiming {
+    /// Server trigger (`triggered_at_ms`) to the signal arriving here. `None`
+    /// for sells the stream did not trigger. Includes any clock skew.
+    pub(super) stream_ms: Option<u64>,
+    /// Signal arriving to the first send accepted: queueing, tx build or
+    /// rebuild, signing and simulation.
+    pub(super) to_send_ms: u64,
+    /// First send to confirmation: landing and the RPC's confirm.
+    pub(super) to_confirm_ms: u64,
+}
+
+impl SellTiming {
+    pub(super) fn trigger_to_send_ms(&self) -> Option<u64> {
+        self.stream_ms.map(|stream| stream + self.to_send_ms)
+    }
+
+    pub(super) fn trigger_to_confirm_ms(&self) -> Option<u64> {
+        self.trigger_to_send_ms()
+            .map(|send| send + self.to_confirm_ms)
+    }
+}
+
+/// Signal-to-send timing of recent sells, for `metrics` and the
+/// `sell_latency` log line.
+#[derive(Default)]
+pub(super) struct LatencyMetrics {
+    samples: Mutex<VecDeque<SellTiming>>,
+}
+
+/// `(p50, p95)` over one stage.
+type Percentiles = Option<(u64, u64)>;
+
+impl LatencyMetrics {
+    pub(super) fn record(&self, timing: SellTiming) {
+        let mut samples = self.samples.lock();
+        if samples.len() >= SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(timing);
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.samples.lock().len()
+    }
+
+    fn percentiles(&self, stage: impl Fn(&SellTiming) -> Option<u64>) -> Percentiles {
+        let mut values: Vec<u64> = self.samples.lock().iter().filter_map(stage).collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        let rank = |pct: usize| values[(values.len() * pct).div_ceil(100).max(1) - 1];
+        Some((rank(50), rank(95)))
+    }
+
+    pub(super) fn trigger_to_send(&self) -> Percentiles {
+        self.percentiles(SellTiming::trigger_to_send_ms)
+    }
+
+    pub(super) fn trigger_to_confirm(&self) -> Percentiles {
+        self.percentiles(SellTiming::trigger_to_confirm_ms)
+    }
+
+    pub(super) fn render(&self) -> String {
+        let count = self.len();
+        if count == 0 {
+            return "no completed sells yet\n".to_string();
+        }
+        let rows: [(&str, Percentiles); 5] = [
+            ("trigger -> send", self.trigger_to_send()),
+            ("trigger -> confirm", self.trigger_to_confirm()),
+            ("  stream", self.percentiles(|timing| timing.stream_ms)),
+            (
+                "  signal -> send",
+                self.percentiles(|timing| Some(timing.to_send_ms)),
+            ),
+            (
+                "  send -> confirm",
+                self.percentiles(|timing| Some(timing.to_confirm_ms)),
+            ),
+        ];
+        let mut out = format!("sell latency over the last {count} sell(s)\n");
+        for (label, percentiles) in rows {
+            let _ = match percentiles {
+                Some((p50, p95)) => writeln!(out, "{label:<20} p50 {p50:>6} ms  p95 {p95:>6} ms"),
+                None => writeln!(out, "{label:<20} no stream-triggered sells"),
+            };
+        }
+        out.push_str(
+            "stream is server clock to ours; signal -> send is queueing and the build API; \
+             send -> confirm is landing and your RPC\n",
+        );
+        out
+    }
+}
+
+pub(super) fn latency_metrics() -> &'static LatencyMetrics {
+    static METRICS: OnceLock<LatencyMetrics> = OnceLock::new();
+    METRICS.get_or_init(LatencyMetrics::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_per_stage() {
+        let metrics = LatencyMetrics::default();
+        for ms in 1..=20 {
+            metrics.record(SellTiming {
+                stream_ms: Some(10),
+                to_send_ms: ms * 10,
+                to_confirm_ms: ms * 100,
+            });
+        }
+        metrics.record(SellTiming {
+            stream_ms: None,
+            to_send_ms: 5,
+            to_confirm_ms: 5,
+        });
+        assert_eq!(metrics.trigger_to_send(), Some((110, 200)));
+        assert_eq!(metrics.trigger_to_confirm(), Some((1_110, 2_100)));
+        let rendered = metrics.render();
+        assert!(rendered.starts_with("sell latency over the last 21 sell(s)"));
+        assert!(rendered.contains("  stream             p50     10 ms"));
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use solana_sdk::pubkey::Pubkey;
+
+/// Client-side limit sells armed with `limit <mint> <pct>%`: profit
+/// thresholds, in percent of the entry, that trigger a manual sell when a
+/// PnL update reaches them. They work on top of the server-side strategy
+/// and are persisted as JSON next to the journal, so they survive a
+/// restart; one that fires is disarmed.
+#[derive(Debug, Default)]
+pub(super) struct Limits {
+    path: Option<PathBuf>,
+    armed: BTreeMap<Pubkey, f64>,
+}
+
+impl Limits {
+    /// Loads the armed limits from `path`; a missing file means none.
+    pub(super) fn load(path: PathBuf) -> Result<Self> {
+        let armed = match fs::read_to_string(&path) {
+            Ok(raw) => {
+                let stored: BTreeMap<String, f64> = serde_json::from_str(&raw)
+                    .with_context(|| format!("parse limits {}", path.display()))?;
+                stored
+                    .into_iter()
+                    .filter_map(|(mint, pct)| Some((Pubkey::from_str(&mint).ok()?, pct)))
+                    .collect()
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err).with_context(|| format!("read limits {}", path.display())),
+        };
+        Ok(Self {
+            path: Some(path),
+            armed,
+        })
+    }
+
+    pub(super) fn arm(&mut self, mint: Pubkey, profit_pct: f64) -> Result<()> {
+        self.armed.insert(mint, profit_pct);
+        self.save()
+    }
+
+    /// Returns whether a limit was armed for `mint`.
+    pub(super) fn disarm(&mut self, mint: &Pubkey) -> Result<bool> {
+        if self.armed.remove(mint).is_none() {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// The armed threshold for `mint` if this PnL update reaches it. The
+    /// entry is what the position cost, proceeds less profit.
+    pub(super) fn reached(
+        &self,
+        mint: &Pubkey,
+        profit_units: i64,
+        proceeds_units: u64,
+    ) -> Option<f64> {
+        let threshold = *self.armed.get(mint)?;
+        let entry = (proceeds_units as i64).saturating_sub(profit_units);
+        if entry <= 0 {
+            return None;
+        }
+        let profit_pct = profit_units as f64 / entry as f64 * 100.0;
+        (profit_pct >= threshold).then_some(threshold)
+    }
+
+    pub(super) fn render(&self) -> String {
+        if self.armed.is_empty() {
+            return "no limits armed\n".to_string();
+        }
+        let mut out = String::new();
+        for (mint, pct) in &self.armed {
+            let _ = writeln!(out, "{mint:<44} sell at {pct:+}% profit");
+        }
+        out
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let stored: BTreeMap<String, f64> = self
+            .armed
+            .iter()
+            .map(|(mint, pct)| (mint.to_string(), *pct))
+            .collect();
+        let raw = serde_json::to_vec_pretty(&stored).context("serialize limits")?;
+        crate::util::fs_utils::atomic_write(path, &raw, Some(0o600))
+            .with_context(|| format!("write limits {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn limits_fire_at_the_threshold_and_persist() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("limits.json");
+        let mint = Pubkey::new_unique();
+        let mut limits = Limits::load(path.clone()).unwrap();
+        limits.arm(mint, 50.0).unwrap();
+
+        // Entry of 1 SOL: +0.4 SOL is 40%, +0.5 SOL is 50%.
+        assert_eq!(limits.reached(&mint, 400_000_000, 1_400_000_000), None);
+        assert_eq!(
+            limits.reached(&mint, 500_000_000, 1_500_000_000),
+            Some(50.0)
+        );
+        assert_eq!(
+            limits.reached(&Pubkey::new_unique(), 500_000_000, 1_500_000_000),
+            None
+        );
+
+        let mut reloaded = Limits::load(path.clone()).unwrap();
+        assert!(reloaded.render().contains("sell at +50% profit"));
+        assert!(reloaded.disarm(&mint).unwrap());
+        assert!(!reloaded.disarm(&mint).unwrap());
+        assert!(Limits::load(path).unwrap().armed.is_empty());
+    }
+}
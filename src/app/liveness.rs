@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use tracing::warn;
+
+use crate::config::LivenessConfig;
+use crate::events::EVENT_SCHEMA_VERSION;
+use crate::util::fs_utils::atomic_write;
+
+/// What the heartbeat file says about the engine loop at one tick.
+pub(super) struct LivenessSnapshot {
+    pub(super) at_ms: u64,
+    pub(super) open_sessions: usize,
+    pub(super) stream_connected: bool,
+    /// `None` until the first stream message arrives.
+    pub(super) last_stream_message_ms: Option<u64>,
+}
+
+impl LivenessSnapshot {
+    fn to_json(&self) -> String {
+        serde_json::json!({
+            "schema_version": EVENT_SCHEMA_VERSION,
+            "at_unix_ms": self.at_ms,
+            "open_sessions": self.open_sessions,
+            "stream_connected": self.stream_connected,
+            "last_stream_message_age_ms": self
+                .last_stream_message_ms
+                .map(|at| self.at_ms.saturating_sub(at)),
+        })
+        .to_string()
+    }
+}
+
+/// Rewrites `liveness.path` every `liveness.interval_sec` from the engine's
+/// heartbeat, so the file only stays fresh while the loop is turning.
+pub(super) struct LivenessFile {
+    path: PathBuf,
+    interval_ms: u64,
+    last_written_ms: Option<u64>,
+}
+
+impl LivenessFile {
+    pub(super) fn from_config(cfg: &LivenessConfig) -> Option<Self> {
+        let path = cfg
+            .path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())?;
+        Some(Self {
+            path: PathBuf::from(path),
+            interval_ms: cfg.interval_sec.saturating_mul(1_000),
+            last_written_ms: None,
+        })
+    }
+
+    pub(super) fn is_due(&self, now_ms: u64) -> bool {
+        self.last_written_ms
+            .is_none_or(|last| now_ms.saturating_sub(last) >= self.interval_ms)
+    }
+
+    pub(super) fn write(&mut self, snapshot: &LivenessSnapshot) {
+        self.last_written_ms = Some(snapshot.at_ms);
+        if let Err(err) = atomic_write(&self.path, snapshot.to_json().as_bytes(), None) {
+            warn!(
+                event = "liveness_write_failed",
+                path = %self.path.display(),
+                error = %err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_on_interval_with_stream_message_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("heartbeat.json");
+        let cfg = LivenessConfig {
+            path: Some(path.display().to_string()),
+            interval_sec: 10,
+        };
+        let mut file = LivenessFile::from_config(&cfg).unwrap();
+        assert!(file.is_due(1_000));
+        file.write(&LivenessSnapshot {
+            at_ms: 20_000,
+            open_sessions: 2,
+            stream_connected: true,
+            last_stream_message_ms: Some(18_500),
+        });
+        assert!(!file.is_due(29_999));
+        assert!(file.is_due(30_000));
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["open_sessions"], 2);
+        assert_eq!(written["last_stream_message_age_ms"], 1_500);
+        assert!(LivenessFile::from_config(&LivenessConfig::default()).is_none());
+    }
+}
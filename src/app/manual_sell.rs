@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use super::sweep::parse_token_accounts;
+use crate::events::SellAmount;
+use crate::network::{rpc_result, RpcPriority, RpcScheduler};
+use crate::tx::policy::associated_token_address;
+
+/// What the wallet can sell of one mint: the balance of its associated
+/// token account, the one the exit API sells from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Holding {
+    pub(super) amount: u64,
+    pub(super) decimals: u8,
+}
+
+/// Reads the wallet's balance of `mint` for a `sell` with no session behind
+/// it. Filtering by mint lets the node pick the token program.
+pub(super) async fn fetch_holding(
+    http: &Client,
+    rpc_url: &str,
+    rpc_scheduler: &RpcScheduler,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Holding> {
+    rpc_scheduler.acquire(RpcPriority::SellCritical).await;
+    let result = rpc_result(
+        http,
+        rpc_url,
+        "getTokenAccountsByOwner",
+        json!([
+            owner.to_string(),
+            { "mint": mint.to_string() },
+            { "encoding": "jsonParsed", "commitment": "processed" }
+        ]),
+    )
+    .await?;
+    holding_in_ata(&result, owner, mint)?
+        .ok_or_else(|| anyhow!("wallet holds no associated token account for {mint}"))
+}
+
+/// The balance of `owner`'s associated token account for `mint` among the
+/// accounts in `result`; other accounts for the mint cannot be sold from.
+fn holding_in_ata(result: &Value, owner: &Pubkey, mint: &Pubkey) -> Result<Option<Holding>> {
+    let accounts = parse_token_accounts(result, spl_token::id())?;
+    Ok(accounts
+        .into_iter()
+        .find(|account| account.address == associated_token_address(owner, mint, &account.program))
+        .map(|account| Holding {
+            amount: account.amount,
+            decimals: account.decimals,
+        }))
+}
+
+/// Raw token units to sell out of `held`. A token count needs the mint's
+/// decimals and may not exceed the holding.
+pub(super) fn tokens_to_sell(
+    amount: Option<SellAmount>,
+    held: u64,
+    decimals: Option<u8>,
+) -> Result<u64> {
+    let tokens = match amount {
+        None => held,
+        Some(SellAmount::Percent(pct)) if pct >= 100.0 => held,
+        Some(SellAmount::Percent(pct)) => (held as f64 * pct / 100.0).floor() as u64,
+        Some(SellAmount::Tokens(count)) => {
+            let decimals = decimals.ok_or_else(|| anyhow!("token decimals unknown"))?;
+            let raw = (count * 10f64.powi(i32::from(decimals))).round();
+            if raw > held as f64 {
+                return Err(anyhow!(
+                    "wallet holds {} tokens, fewer than {count}",
+                    held as f64 / 10f64.powi(i32::from(decimals))
+                ));
+            }
+            raw as u64
+        }
+    };
+    if tokens == 0 {
+        return Err(anyhow!("nothing to sell"));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::rent::TOKEN_2022_PROGRAM;
+
+    #[test]
+    fn reads_the_ata_and_scales_amounts() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let account = |address: Pubkey, amount: &str| {
+            json!({
+                "pubkey": address.to_string(),
+                "account": {
+                    "lamports": 2_039_280,
+                    "owner": TOKEN_2022_PROGRAM.to_string(),
+                    "data": { "parsed": { "info": {
+                        "mint": mint.to_string(),
+                        "tokenAmount": { "amount": amount, "decimals": 6 }
+                    } } }
+                }
+            })
+        };
+        let ata = associated_token_address(&owner, &mint, &TOKEN_2022_PROGRAM);
+        let result = json!({ "value": [
+            account(Pubkey::new_unique(), "500000"),
+            account(ata, "1500000"),
+        ] });
+        assert_eq!(
+            holding_in_ata(&result, &owner, &mint).unwrap(),
+            Some(Holding {
+                amount: 1_500_000,
+                decimals: 6
+            })
+        );
+        let elsewhere = json!({ "value": [account(Pubkey::new_unique(), "500000")] });
+        assert_eq!(holding_in_ata(&elsewhere, &owner, &mint).unwrap(), None);
+
+        assert_eq!(tokens_to_sell(None, 2_000_000, None).unwrap(), 2_000_000);
+        assert_eq!(
+            tokens_to_sell(Some(SellAmount::Percent(25.0)), 2_000_000, None).unwrap(),
+            500_000
+        );
+        assert_eq!(
+            tokens_to_sell(Some(SellAmount::Tokens(1.25)), 2_000_000, Some(6)).unwrap(),
+            1_250_000
+        );
+        assert!(tokens_to_sell(Some(SellAmount::Tokens(3.0)), 2_000_000, Some(6)).is_err());
+        assert!(tokens_to_sell(Some(SellAmount::Tokens(1.0)), 2_000_000, None).is_err());
+        assert!(tokens_to_sell(Some(SellAmount::Percent(10.0)), 5, None).is_err());
+    }
+}
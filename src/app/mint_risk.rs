@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::network::{rpc_result, RpcPriority, RpcScheduler};
+
+/// Reads `mint` and lists what about it can make sells fail or cost more
+/// than quoted. Empty for a plain mint with both authorities revoked.
+pub(super) async fn fetch_mint_risks(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    rpc_scheduler: &RpcScheduler,
+    mint: &Pubkey,
+) -> Result<Vec<String>> {
+    rpc_scheduler.acquire(RpcPriority::Background).await;
+    let result = rpc_result(
+        client,
+        rpc_url,
+        "getAccountInfo",
+        serde_json::json!([
+            mint.to_string(),
+            { "commitment": "confirmed", "encoding": "jsonParsed" }
+        ]),
+    )
+    .await?;
+    let info = result
+        .pointer("/value/data/parsed/info")
+        .ok_or_else(|| anyhow!("mint account missing or not a token mint"))?;
+    Ok(mint_risks(info))
+}
+
+/// Warnings for a `jsonParsed` mint's `info`: authorities still held, and
+/// Token-2022 extensions that can block, tax or claw back a sell.
+fn mint_risks(info: &Value) -> Vec<String> {
+    let set = |value: Option<&Value>| value.is_some_and(|value| !value.is_null());
+    let mut risks = Vec::new();
+    if set(info.get("mintAuthority")) {
+        risks.push("mint authority enabled".to_string());
+    }
+    if set(info.get("freezeAuthority")) {
+        risks.push("freeze authority enabled".to_string());
+    }
+    let extensions = info.get("extensions").and_then(Value::as_array);
+    for extension in extensions.into_iter().flatten() {
+        let state = extension.get("state");
+        let field = |pointer: &str| state.and_then(|state| state.pointer(pointer));
+        match extension.get("extension").and_then(Value::as_str) {
+            Some("transferHook") if set(field("/programId")) => {
+                risks.push("transfer hook".to_string());
+            }
+            Some("transferFeeConfig") => {
+                let bps = field("/newerTransferFee/transferFeeBasisPoints")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0);
+                if bps > 0 {
+                    risks.push(format!("transfer fee {bps} bps"));
+                }
+            }
+            Some("permanentDelegate") if set(field("/delegate")) => {
+                risks.push("permanent delegate".to_string());
+            }
+            Some("nonTransferable") => risks.push("non-transferable".to_string()),
+            Some("defaultAccountState")
+                if field("/accountState").and_then(Value::as_str) == Some("frozen") =>
+            {
+                risks.push("accounts start frozen".to_string());
+            }
+            Some("pausableConfig") if set(field("/authority")) => {
+                risks.push("pausable".to_string());
+            }
+            _ => {}
+        }
+    }
+    risks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_authorities_and_risky_extensions() {
+        let revoked = serde_json::json!({
+            "mintAuthority": null,
+            "freezeAuthority": null,
+            "extensions": [
+                { "extension": "transferHook", "state": { "programId": null } },
+                { "extension": "metadataPointer", "state": {} }
+            ]
+        });
+        assert!(mint_risks(&revoked).is_empty());
+
+        let authority = Pubkey::new_unique().to_string();
+        let risky = serde_json::json!({
+            "mintAuthority": authority,
+            "freezeAuthority": authority,
+            "extensions": [
+                {
+                    "extension": "transferFeeConfig",
+                    "state": { "newerTransferFee": { "transferFeeBasisPoints": 500 } }
+                },
+                { "extension": "permanentDelegate", "state": { "delegate": authority } },
+                { "extension": "defaultAccountState", "state": { "accountState": "frozen" } }
+            ]
+        });
+        assert_eq!(
+            mint_risks(&risky),
+            [
+                "mint authority enabled",
+                "freeze authority enabled",
+                "transfer fee 500 bps",
+                "permanent delegate",
+                "accounts start frozen",
+            ]
+        );
+    }
+}
@@ -1,36 +1,124 @@
-use std::collections::HashMap;
+mod attach;
+mod clock_skew;
+mod commands;
+mod completed;
+mod exposure;
+mod fallback;
+mod health;
+mod latency;
+mod insights;
+mod limits;
+mod liveness;
+mod manual_sell;
+mod mint_risk;
+mod portfolio;
+mod rebuild;
+mod rent;
+mod replay;
+mod reserve;
+mod retry_policy;
+mod schedule;
+mod self_transfer;
+mod sell_queue;
+mod sessions;
+mod settings;
+mod sweep;
+mod timeline;
+mod treasury;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
+use secrecy::SecretString;
 use lasersell_sdk::stream::client::StrategyConfigBuilder;
 use lasersell_sdk::stream::proto::{
     AutoBuyConfigMsg, MarketContextMsg, MirrorConfigMsg, StrategyConfigMsg, TakeProfitLevelMsg,
     WatchWalletEntryMsg,
 };
-use lasersell_sdk::tx::{SendTarget, TxSubmitError};
+use lasersell_sdk::exit_api::SellOutput;
+use lasersell_sdk::tx::TxSubmitError;
 use parking_lot::RwLock as ParkingRwLock;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Keypair;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::VersionedTransaction;
 use tokio::sync::{mpsc, Mutex};
 use tracing::{debug, info, warn};
 
-use crate::config::{Config, SellConfig, StrategyConfig, WatchWalletConfig};
-use crate::events::{emit, AppCommand, AppEvent};
+use crate::config::{
+    is_public_rpc, Config, ExposureConfig, SellConfig, SellOutputPreference, StrategyConfig,
+    WatchWalletConfig, DEFAULT_WALLET_NAME,
+};
+use crate::events::{emit, AppCommand, AppEvent, SellAmount, SellTime};
+use crate::integration::{ExpectedPosition, EXPECTED_POSITION_TTL_SECS};
+use crate::journal::imports::ImportStore;
+use crate::journal::{self, analyzer, Journal, TradeRecord};
 use crate::market::context_from_msg::market_context_from_msg;
-use crate::market::{usd1_mint, MarketContext};
-use crate::network::{rpc_result, StreamClient, StreamEvent, StreamHandle};
+use crate::market::{usd1_mint, MarketContext, QuoteToken};
+use crate::network::{
+    rpc_health, rpc_result, CongestionMonitor, RpcPriority, RpcScheduler, StreamClient, StreamEvent, StreamHandle,
+};
 use crate::stream::InMemoryMarketStreamState;
-use crate::tx::{send_tx, sign_unsigned_tx};
+use crate::tx::audit::Purpose;
+use crate::tx::onchain_error::{self, AccountFrozen, OnchainFailure};
+use crate::tx::policy::Intent;
+use crate::tx::preflight::{check_blockhash, BlockhashExpired};
+use crate::tx::senders::{self, SellSender};
+use crate::tx::{
+    decode_unsigned_tx, fetch_current_slot, fetch_sell_settlement, send_tx, sign_unsigned_tx,
+    simulate_tx, SellSettlement, SimulationRejected, BASE_FEE_LAMPORTS_PER_SIGNATURE,
+};
+use crate::util::clock::now_unix_ms;
+use crate::util::systemd;
+
+pub use attach::run_attach_cli;
+pub use commands::{spawn_stdin_reader, Shortcuts};
+pub use replay::run_replay_cli;
+pub use sweep::run_sweep_cli;
+use clock_skew::ClockSkew;
+use completed::CompletedSells;
+use exposure::{ExposureReport, OpenExposure};
+use fallback::FallbackQuoter;
+use health::HealthHistory;
+use latency::{latency_metrics, SellTiming};
+use insights::Insights;
+use limits::Limits;
+use attach::AttachServer;
+use liveness::{LivenessFile, LivenessSnapshot};
+use rebuild::{resolve_sell_output, Built, RefreshRoute, SellRebuilder, TxSource};
+use reserve::FeeReserve;
+use retry_policy::{FailureClass, RetryAction, RetryPolicy, RESEND_BACKOFF_MS};
+use schedule::SellSchedule;
+use mint_risk::fetch_mint_risks;
+use self_transfer::{find_self_transfer, TransferDirection};
+use sell_queue::{SellPriority, SellQueue};
+use sessions::{SessionRow, SessionsView, DEGRADED_REMINDER_SECS};
+use settings::{SettingsDraft, SettingsField};
+use portfolio::Portfolio;
+use sweep::Sweeper;
+use timeline::{SellTimelines, TimelineEntry};
+use treasury::TreasurySweeper;
 
 const HEARTBEAT_INTERVAL_SECS: u64 = 1;
 const AUTOSELL_REFRESH_TIMEOUT_MS: u64 = 1_500;
+const AUTOSELL_MIN_CONFIRM_MS: u64 = 1_000;
 const BALANCE_POLL_SECS: u64 = 5;
 const BALANCE_POLL_PUBLIC_RPC_SECS: u64 = 15;
+const JOURNAL_ANALYZE_SECS: u64 = 600;
+const SESSION_PRUNE_INTERVAL_SECS: u64 = 60;
+const SETTLEMENT_ATTEMPTS: u32 = 4;
+const SETTLEMENT_RETRY_MS: u64 = 500;
+const LOW_LATENCY_KEEPALIVE_SECS: u64 = 15;
+/// Under `performance.low_latency`, how often an idle RPC connection is used
+/// so a sell never pays for a fresh TLS handshake.
+const CONNECTION_WARM_SECS: u64 = 20;
 
 fn balance_poll_interval(rpc_url: &str) -> Duration {
-    if rpc_url.trim().contains("publicnode.com") || rpc_url.trim().contains("api.mainnet-beta.solana.com") {
+    if is_public_rpc(rpc_url) {
         Duration::from_secs(BALANCE_POLL_PUBLIC_RPC_SECS)
     } else {
         Duration::from_secs(BALANCE_POLL_SECS)
@@ -42,11 +130,30 @@ struct PositionSnapshot {
     position_id: u64,
     token_program: Option<String>,
     tokens: u64,
+    /// Quote units paid to open the position, when the stream reported it.
+    cost_basis_units: Option<u64>,
+    opened_at_ms: Option<u64>,
+    /// Set when the sell failed or the balance went to zero; the session is
+    /// pruned once it has been stale for `sessions.stale_ttl_sec`.
+    stale_since_ms: Option<u64>,
+    /// `strategy.tighten_after_sec` steps already pushed for this position.
+    tighten_step: usize,
+    /// Strategy a buy bot registered for this position, used in place of
+    /// the configured one.
+    strategy: Option<StrategyConfig>,
+    /// The tokens arrived by transfer from one of `account.own_wallets`.
+    self_transfer: bool,
+    /// What the mint account says could make sells fail, e.g. a freeze
+    /// authority still held; shown under the session in `sessions`.
+    mint_risks: Vec<String>,
 }
 
 enum LoopControl {
     Break,
+    Continue,
     DropCommands,
+    /// `wallet use` opened a new stream session; its events replace the old.
+    SwitchStream(mpsc::UnboundedReceiver<StreamEvent>),
 }
 
 struct AppEngine {
@@ -54,21 +161,87 @@ struct AppEngine {
     keypair_bytes: [u8; 64],
     rpc_http: reqwest::Client,
     rpc_url: String,
-    send_target: SendTarget,
+    rpc_scheduler: Arc<RpcScheduler>,
+    congestion: Arc<CongestionMonitor>,
+    wallet_pubkey: Pubkey,
+    send_target: Arc<dyn SellSender>,
     stream_handle: Arc<StreamHandle>,
     market_contexts: Arc<ParkingRwLock<HashMap<Pubkey, MarketContext>>>,
     stream_states: Arc<ParkingRwLock<HashMap<Pubkey, Arc<InMemoryMarketStreamState>>>>,
     position_snapshots: Arc<ParkingRwLock<HashMap<Pubkey, PositionSnapshot>>>,
     in_flight_auto_sells: Arc<Mutex<HashMap<u64, mpsc::UnboundedSender<String>>>>,
+    journal: Option<Arc<Journal>>,
+    deadline_timeout_sec: u64,
+    strategy: StrategyConfig,
+    /// `strategy` as the journal analyzer reads it.
+    insight_strategy: Arc<ParkingRwLock<StrategyConfig>>,
+    insights: Arc<Insights>,
+    sell_rebuilder: Arc<SellRebuilder>,
+    sell_queue: Option<Arc<SellQueue>>,
+    sell_timelines: Arc<SellTimelines>,
+    completed_sells: Arc<CompletedSells>,
+    /// One-off slippage for manual sells, keyed by position id and consumed
+    /// by the exit signal the request produces.
+    manual_slippage: HashMap<u64, u16>,
+    stale_session_ttl_sec: u64,
+    exposure_caps: ExposureConfig,
+    /// Keys of exposure caps already warned about, so each breach logs once.
+    exposure_breaches: HashSet<String>,
+    /// Positions registered through the integration API, with the time they
+    /// were registered, waiting for the stream to report them open.
+    expected_positions: HashMap<Pubkey, (u64, ExpectedPosition)>,
+    limits: Limits,
+    sell_schedule: SellSchedule,
+    own_wallets: Arc<Vec<Pubkey>>,
+    stream_connected: Option<bool>,
+    /// Set while the stream is reconnecting.
+    stream_down_since_ms: Option<u64>,
+    stream_down_reminded_ms: u64,
+    /// `stream.fallback_quote_sec`: exit API quotes while the stream is down.
+    fallback_quotes: FallbackQuoter,
+    liveness: Option<LivenessFile>,
+    attach: Option<AttachServer>,
+    last_stream_message_ms: Option<u64>,
+    health: HealthHistory,
+    clock_skew: ClockSkew,
+    /// Set while a `sweep` is planning or running.
+    sweeping: Arc<AtomicBool>,
+    /// The last `portfolio` listing, which `portfolio sell <n>` refers to.
+    portfolio: Arc<ParkingRwLock<Option<Portfolio>>>,
+    /// Name of the signing wallet among `Config::wallet_choices`.
+    active_wallet: String,
+    /// The wallet the balance poller reads; follows `wallet use`.
+    balance_wallet: Arc<ParkingRwLock<Pubkey>>,
+    /// `treasury.sweep_to`, run by the balance poller.
+    treasury: Option<Arc<TreasurySweeper>>,
+    /// `risk.min_sol_reserve`, fed by the balance poller.
+    fee_reserve: Arc<FeeReserve>,
+    /// The config the engine started with, plus applied settings.
+    config: Config,
+    /// Where applied settings are saved; `None` with `--env-config`.
+    config_path: Option<PathBuf>,
+    settings: Option<SettingsDraft>,
 }
 
 pub async fn run(
     cfg: Config,
     keypair: Keypair,
+    config_path: Option<PathBuf>,
     mut cmd_rx: Option<mpsc::UnboundedReceiver<AppCommand>>,
 ) -> Result<()> {
-    let (mut engine, mut evt_rx) = AppEngine::new(cfg, keypair).await?;
+    let (mut engine, mut evt_rx) = AppEngine::new(cfg, keypair, config_path).await?;
     let mut heartbeat = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    let mut session_prune = tokio::time::interval(Duration::from_secs(SESSION_PRUNE_INTERVAL_SECS));
+    if let Some(timeout) = systemd::watchdog_timeout() {
+        if timeout < Duration::from_secs(HEARTBEAT_INTERVAL_SECS * 2) {
+            warn!(
+                event = "systemd_watchdog_too_short",
+                watchdog_ms = timeout.as_millis() as u64,
+                heartbeat_sec = HEARTBEAT_INTERVAL_SECS,
+                "WatchdogSec is shorter than two heartbeats; systemd may restart a healthy daemon"
+            );
+        }
+    }
 
     loop {
         tokio::select! {
@@ -87,17 +260,30 @@ pub async fn run(
             } => {
                 match engine.handle_user_command(cmd).await? {
                     LoopControl::Break => break,
+                    LoopControl::Continue => {}
                     LoopControl::DropCommands => {
                         cmd_rx = None;
                     }
+                    LoopControl::SwitchStream(rx) => {
+                        evt_rx = rx;
+                    }
                 }
             }
             _ = heartbeat.tick() => {
                 engine.handle_heartbeat();
             }
+            _ = session_prune.tick() => {
+                engine.expire_expected_positions();
+                if engine.stale_session_ttl_sec > 0 {
+                    engine
+                        .prune_stale_sessions(engine.stale_session_ttl_sec.saturating_mul(1_000), false)
+                        .await;
+                }
+            }
         }
     }
 
+    systemd::notify_stopping();
     Ok(())
 }
 
@@ -105,47 +291,57 @@ impl AppEngine {
     async fn new(
         cfg: Config,
         keypair: Keypair,
+        config_path: Option<PathBuf>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<StreamEvent>)> {
-        let runtime_sell = Arc::new(ParkingRwLock::new(cfg.sell.clone()));
+        let runtime_sell = Arc::new(ParkingRwLock::new(cfg.effective_sell()));
         let wallet_pubkey = cfg.wallet_pubkey(&keypair)?;
         let keypair_bytes = keypair.to_bytes();
-        let rpc_http = reqwest::Client::builder()
-            .no_proxy()
+        let low_latency = cfg.performance.low_latency;
+        let mut rpc_http = cfg
+            .http_client_builder()?
             .connect_timeout(cfg.rpc_connect_timeout())
-            .timeout(cfg.rpc_request_timeout())
-            .build()?;
+            .timeout(cfg.rpc_request_timeout());
+        if low_latency {
+            rpc_http = rpc_http
+                .pool_idle_timeout(None)
+                .tcp_keepalive(Duration::from_secs(LOW_LATENCY_KEEPALIVE_SECS));
+        }
+        let rpc_http = rpc_http.build()?;
         let rpc_url = cfg.http_rpc_url();
-        let send_target = cfg.resolve_send_target()?;
+        if low_latency {
+            let sell = runtime_sell.read();
+            info!(
+                event = "low_latency_profile",
+                simulate_before_send = sell.simulate_before_send,
+                check_blockhash = sell.check_blockhash,
+                hedge_request_ms = sell.hedge_request_ms,
+                confirm_timeout_sec = sell.confirm_timeout_sec,
+                "performance.low_latency is on"
+            );
+        }
+        if let Some(proxy_url) = cfg.network.proxy_url.as_deref() {
+            warn!(
+                event = "proxy_rpc_only",
+                proxy = %crate::util::logging::redact_url(proxy_url),
+                "network.proxy_url applies to RPC traffic; the exit API and stream connect directly"
+            );
+        }
+        let send_target = senders::from_config(&cfg)?;
 
-        let balance_http = reqwest::Client::builder()
-            .no_proxy()
+        let balance_http = cfg
+            .http_client_builder()?
             .connect_timeout(Duration::from_secs(5))
             .timeout(Duration::from_secs(10))
             .build()?;
-        spawn_wallet_balance_poller(balance_http.clone(), rpc_url.clone(), wallet_pubkey);
-        spawn_usd1_balance_poller(balance_http, rpc_url.clone(), wallet_pubkey);
+        let rpc_scheduler = Arc::new(RpcScheduler::new(cfg.rpc_requests_per_sec()));
+        let congestion = Arc::new(CongestionMonitor::default());
+        congestion.spawn(balance_http.clone(), rpc_url.clone(), rpc_scheduler.clone());
+        if low_latency {
+            spawn_connection_warmer(rpc_http.clone(), rpc_url.clone(), rpc_scheduler.clone());
+        }
 
-        let stream_send_mode = Some(cfg.send_mode_str().to_string());
-        let (watch_wallets, mirror_config) = if cfg.mirror.enabled {
-            (
-                build_watch_wallet_entries(&cfg.watch_wallets, &wallet_pubkey.to_string()),
-                build_mirror_config(&cfg.mirror),
-            )
-        } else {
-            (Vec::new(), None)
-        };
-        let stream_client = StreamClient::new(
-            cfg.account.api_key.clone(),
-            cfg.account.local,
-            wallet_pubkey.to_string(),
-            strategy_to_msg(&cfg.strategy),
-            cfg.strategy.deadline_timeout_sec,
-            stream_send_mode,
-            cfg.account.tip_lamports,
-            watch_wallets,
-            mirror_config,
-        );
-        let (stream_handle, evt_rx) = stream_client.connect(&keypair).await?;
+        let (stream_handle, evt_rx) = connect_stream(&cfg, &keypair, wallet_pubkey).await?;
+        let sell_rebuilder = Arc::new(sell_rebuilder_for(&cfg, wallet_pubkey)?);
         let stream_handle = Arc::new(stream_handle);
 
         let market_contexts = Arc::new(ParkingRwLock::new(HashMap::<Pubkey, MarketContext>::new()));
@@ -156,10 +352,34 @@ impl AppEngine {
         let position_snapshots = Arc::new(ParkingRwLock::new(
             HashMap::<Pubkey, PositionSnapshot>::new(),
         ));
+        seed_imported_positions(&position_snapshots);
         let in_flight_auto_sells = Arc::new(Mutex::new(HashMap::<
             u64,
             mpsc::UnboundedSender<String>,
         >::new()));
+        let journal = match Journal::open_default() {
+            Ok(journal) => Some(Arc::new(journal)),
+            Err(err) => {
+                warn!(event = "journal_unavailable", error = %err);
+                None
+            }
+        };
+        let insight_strategy = Arc::new(ParkingRwLock::new(cfg.strategy.clone()));
+        let insights = Arc::new(load_insights());
+        if let Some(journal) = journal.clone() {
+            spawn_journal_analyzer(journal, insight_strategy.clone(), insights.clone());
+        }
+        let treasury = TreasurySweeper::from_config(&cfg, &keypair, journal.clone())?.map(Arc::new);
+        let fee_reserve = Arc::new(FeeReserve::new(cfg.risk.min_sol_reserve));
+        let balance_wallet = Arc::new(ParkingRwLock::new(wallet_pubkey));
+        spawn_balance_poller(
+            balance_http,
+            rpc_url.clone(),
+            balance_wallet.clone(),
+            rpc_scheduler.clone(),
+            fee_reserve.clone(),
+            treasury.clone(),
+        );
 
         Ok((
             Self {
@@ -167,12 +387,64 @@ impl AppEngine {
                 keypair_bytes,
                 rpc_http,
                 rpc_url,
+                rpc_scheduler,
+                congestion,
+                wallet_pubkey,
                 send_target,
                 stream_handle,
                 market_contexts,
                 stream_states,
                 position_snapshots,
                 in_flight_auto_sells,
+                journal,
+                deadline_timeout_sec: cfg.strategy.deadline_timeout_sec,
+                strategy: cfg.strategy.clone(),
+                insight_strategy,
+                insights,
+                sell_rebuilder,
+                sell_queue: (cfg.sell.max_concurrent_sells > 0)
+                    .then(|| Arc::new(SellQueue::new(cfg.sell.max_concurrent_sells))),
+                sell_timelines: Arc::new(SellTimelines::default()),
+                completed_sells: Arc::new(load_completed_sells()),
+                manual_slippage: HashMap::new(),
+                stale_session_ttl_sec: cfg.sessions.stale_ttl_sec,
+                exposure_caps: cfg.exposure.clone(),
+                exposure_breaches: HashSet::new(),
+                expected_positions: HashMap::new(),
+                limits: load_limits(),
+                sell_schedule: SellSchedule::default(),
+                own_wallets: Arc::new(
+                    cfg.account
+                        .own_wallets
+                        .iter()
+                        .filter_map(|wallet| Pubkey::from_str(wallet.trim()).ok())
+                        .collect(),
+                ),
+                stream_connected: None,
+                stream_down_since_ms: None,
+                stream_down_reminded_ms: 0,
+                fallback_quotes: FallbackQuoter::new(cfg.stream.fallback_quote_sec),
+                liveness: LivenessFile::from_config(&cfg.liveness),
+                attach: crate::util::paths::attach_socket_path()
+                    .ok()
+                    .and_then(|path| AttachServer::bind(&path)),
+                last_stream_message_ms: None,
+                health: HealthHistory::default(),
+                clock_skew: ClockSkew::default(),
+                active_wallet: cfg
+                    .wallet_choices()
+                    .into_iter()
+                    .find(|wallet| wallet.keypair_path == cfg.account.keypair_path.trim())
+                    .map(|wallet| wallet.name)
+                    .unwrap_or_else(|| DEFAULT_WALLET_NAME.to_string()),
+                balance_wallet,
+                treasury,
+                fee_reserve,
+                sweeping: Arc::new(AtomicBool::new(false)),
+                portfolio: Arc::new(ParkingRwLock::new(None)),
+                config: cfg,
+                config_path,
+                settings: None,
             },
             evt_rx,
         ))
@@ -180,9 +452,13 @@ impl AppEngine {
 
     async fn handle_stream_event(&mut self, evt: StreamEvent) -> Result<()> {
         debug!(event = "app_stream_event", variant = stream_event_label(&evt));
+        if !matches!(evt, StreamEvent::ConnectionStatus { .. }) {
+            self.last_stream_message_ms = Some(now_unix_ms());
+            self.health.note_stream_event();
+        }
         match evt {
             StreamEvent::ConnectionStatus { connected } => {
-                emit(AppEvent::SolanaWsStatus { connected });
+                self.handle_connection_status(connected);
             }
             StreamEvent::BalanceUpdate {
                 mint,
@@ -213,11 +489,11 @@ impl AppEngine {
             StreamEvent::PositionClosed {
                 position_id,
                 mint,
-                token_account: _token_account,
+                token_account,
                 reason,
                 slot,
             } => {
-                self.handle_position_closed(position_id, mint, reason, slot)
+                self.handle_position_closed(position_id, mint, token_account, reason, slot)
                     .await;
             }
             StreamEvent::ExitSignalWithTx {
@@ -228,20 +504,25 @@ impl AppEngine {
                 position_tokens,
                 profit_units,
                 reason,
-                triggered_at_ms: _triggered_at_ms,
+                triggered_at_ms,
                 market_context,
                 unsigned_tx_b64,
             } => {
-                self.handle_exit_signal_with_tx(
+                let triggered_at_ms = Some(triggered_at_ms)
+                    .filter(|ms| *ms > 0)
+                    .map(|ms| self.clock_skew.to_local_ms(ms));
+                self.handle_exit_signal_with_tx(ExitSignal {
                     position_id,
                     mint,
                     token_program,
                     position_tokens,
                     profit_units,
                     reason,
+                    triggered_at_ms,
                     market_context,
                     unsigned_tx_b64,
-                )
+                    slippage_override_bps: None,
+                })
                 .await?;
             }
             StreamEvent::PnlUpdate {
@@ -250,26 +531,1453 @@ impl AppEngine {
                 proceeds_units,
             } => {
                 if let Ok(mint) = Pubkey::from_str(&mint) {
-                    emit(AppEvent::PnlUpdate {
-                        mint,
-                        profit_lamports: profit_units,
-                        proceeds_lamports: proceeds_units,
-                    });
+                    self.apply_pnl(mint, profit_units, proceeds_units);
+                }
+            }
+            StreamEvent::TradeTick {
+                mint,
+                price_quote,
+                time_ms,
+            } => {
+                if let Some(state) = self.stream_state(&mint) {
+                    state.record_trade(self.clock_skew.to_local_ms(time_ms), price_quote);
+                }
+            }
+            StreamEvent::LiquidityTrend { mint, trend } => {
+                if let Some(state) = self.stream_state(&mint) {
+                    state.set_liquidity_trend(trend);
                 }
             }
+            StreamEvent::ServerTime { server_time_ms } => {
+                self.clock_skew.observe_pong(server_time_ms, now_unix_ms());
+            }
         }
         Ok(())
     }
 
+    /// A PnL sample from the stream or a fallback quote: updates the quote
+    /// and fires an armed limit. Returns whether a limit started a sell.
+    fn apply_pnl(&mut self, mint: Pubkey, profit_units: i64, proceeds_units: u64) -> bool {
+        if let Some(state) = self.stream_states.read().get(&mint) {
+            state.set_quoted_proceeds(proceeds_units);
+        }
+        self.fallback_quotes.note_profit(mint, profit_units);
+        emit(AppEvent::PnlUpdate {
+            mint,
+            profit_lamports: profit_units,
+            proceeds_lamports: proceeds_units,
+        });
+        let Some(profit_pct) = self.limits.reached(&mint, profit_units, proceeds_units) else {
+            return false;
+        };
+        info!(event = "limit_triggered", mint = %mint, profit_pct, profit_units);
+        if let Err(err) = self.limits.disarm(&mint) {
+            warn!(event = "limits_save_failed", error = %err);
+        }
+        self.handle_manual_sell(mint, None, None);
+        true
+    }
+
+    /// While the stream is down, applies the exit API quotes that came in
+    /// and starts the next round when it is due. The server cannot send
+    /// exit signals meanwhile, so the strategy is checked here against each
+    /// quote, as `replay` does, and a position that reaches an exit is sold
+    /// like `sell <mint>`.
+    fn run_fallback_quotes(&mut self, now_ms: u64) {
+        let quotes = self.fallback_quotes.take_quotes();
+        if self.stream_down_since_ms.is_none() {
+            return;
+        }
+        for (mint, proceeds_lamports) in quotes {
+            self.apply_fallback_quote(mint, proceeds_lamports, now_ms);
+        }
+        if !self.fallback_quotes.is_due(now_ms) {
+            return;
+        }
+        // Quotes are simulated sells to SOL, so USD1 positions are left out.
+        let sessions: Vec<(Pubkey, u64)> = {
+            let snapshots = self.position_snapshots.read();
+            let market_contexts = self.market_contexts.read();
+            snapshots
+                .iter()
+                .filter(|(mint, snapshot)| {
+                    snapshot.stale_since_ms.is_none()
+                        && market_contexts
+                            .get(*mint)
+                            .is_none_or(|context| context.quote == QuoteToken::Sol)
+                })
+                .map(|(mint, snapshot)| (*mint, snapshot.tokens))
+                .collect()
+        };
+        if sessions.is_empty() {
+            return;
+        }
+        match self.sweeper() {
+            Ok(sweeper) => self.fallback_quotes.start(now_ms, sweeper, sessions),
+            Err(err) => debug!(event = "fallback_quote_failed", error = %err),
+        }
+    }
+
+    fn apply_fallback_quote(&mut self, mint: Pubkey, proceeds_lamports: u64, now_ms: u64) {
+        let Some((cost_basis, opened_at_ms, strategy)) =
+            self.position_snapshots.read().get(&mint).map(|snapshot| {
+                (
+                    snapshot.cost_basis_units,
+                    snapshot.opened_at_ms,
+                    snapshot.strategy.clone().unwrap_or_else(|| self.strategy.clone()),
+                )
+            })
+        else {
+            return;
+        };
+        let Some(cost_basis) = cost_basis else {
+            if let Some(state) = self.stream_states.read().get(&mint) {
+                state.set_quoted_proceeds(proceeds_lamports);
+            }
+            return;
+        };
+        let profit_units = proceeds_lamports as i64 - cost_basis as i64;
+        debug!(event = "fallback_quote", mint = %mint, proceeds_lamports, profit_units);
+        if self.apply_pnl(mint, profit_units, proceeds_lamports) {
+            self.fallback_quotes.note_exit(mint);
+            return;
+        }
+        if !self.config.stream.fallback_exits {
+            return;
+        }
+        let peak_profit_units = self.fallback_quotes.peak(&mint).unwrap_or(profit_units);
+        let held_ms = opened_at_ms.map_or(0, |at_ms| now_ms.saturating_sub(at_ms));
+        let strategy = strategy.tightened(strategy.tighten_step_at(held_ms / 1_000));
+        let Some(reason) =
+            replay::exit_reason(&strategy, cost_basis, profit_units, peak_profit_units, held_ms)
+        else {
+            return;
+        };
+        warn!(
+            event = "fallback_exit",
+            mint = %mint,
+            reason,
+            profit_units,
+            "STREAM DOWN: an exit API quote reached the strategy's {reason}; selling"
+        );
+        self.fallback_quotes.note_exit(mint);
+        self.handle_manual_sell(mint, None, None);
+    }
+
+    fn stream_state(&self, mint: &str) -> Option<Arc<InMemoryMarketStreamState>> {
+        let mint = Pubkey::from_str(mint).ok()?;
+        self.stream_states.read().get(&mint).cloned()
+    }
+
     async fn handle_user_command(&mut self, cmd: Option<AppCommand>) -> Result<LoopControl> {
         match cmd {
             Some(AppCommand::Quit) => Ok(LoopControl::Break),
+            Some(AppCommand::ShowExposure) => {
+                print!("{}", self.exposure_report().render());
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowCongestion) => {
+                match self.congestion.latest() {
+                    Some(reading) => print!(
+                        "{}",
+                        reading.render(self.runtime_sell.read().congestion_slippage_bump_bps)
+                    ),
+                    None => println!("no congestion sample yet"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowNotifications) => {
+                print!("{}", crate::events::inbox::render());
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::AckNotifications) => {
+                match crate::events::inbox::acknowledge() {
+                    0 => println!("no unread notifications"),
+                    count => println!("marked {count} notifications read"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowLogs) => {
+                match crate::util::paths::default_error_log_path() {
+                    Ok(error_log) => {
+                        let others: Vec<_> =
+                            crate::util::paths::default_debug_log_path().into_iter().collect();
+                        print!("{}", crate::util::logging::logs_report(&error_log, &others));
+                    }
+                    Err(err) => warn!(event = "logs_report_failed", error = %err),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowFees) => {
+                match self.journal.as_ref().map(|journal| journal.read_all()) {
+                    Some(Ok(records)) => {
+                        print!("{}", journal::fees::render(&records, now_unix_ms()))
+                    }
+                    Some(Err(err)) => warn!(event = "fees_report_failed", error = %err),
+                    None => println!("the trade journal is unavailable"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowRent) => {
+                self.spawn_rent_report();
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::Sweep { execute }) => {
+                self.spawn_sweep(execute);
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowPortfolio) => {
+                self.spawn_portfolio();
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::SellHolding { n }) => {
+                let mint = self.portfolio.read().as_ref().and_then(|portfolio| portfolio.mint(n));
+                match mint {
+                    Some(mint) => self.handle_manual_sell(mint, None, None),
+                    None => println!("no row {n} in the last listing; run `portfolio` first"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowMint { mint }) => {
+                match crate::events::history::mint_detail(&mint) {
+                    Some(detail) => print!("{detail}"),
+                    None => println!("no activity recorded for {mint}"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::Why { mint }) => {
+                match self.sell_timelines.render(&mint) {
+                    Some(timeline) => print!("{timeline}"),
+                    None => println!("no sell recorded for {mint} this run"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ConsoleLog(cmd)) => {
+                crate::util::logging::apply_console_command(cmd);
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowMetrics) => {
+                print!("{}", latency_metrics().render());
+                let accessible = crate::util::theme::current().accessible();
+                print!("{}", self.health.render(HEARTBEAT_INTERVAL_SECS, accessible));
+                if let Some(skew_ms) = self.clock_skew.skew_ms() {
+                    println!(
+                        "clock skew vs stream server: {skew_ms:+} ms (stream latency is corrected)"
+                    );
+                }
+                let unknown = crate::network::unknown_messages::shared().total();
+                if unknown > 0 {
+                    println!(
+                        "server sent {unknown} unrecognized stream messages; this client may be out of date, run `{}`",
+                        crate::util::update_check::update_command()
+                    );
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowWallets) => {
+                for wallet in self.config.wallet_choices() {
+                    let marker = if wallet.name == self.active_wallet { "*" } else { " " };
+                    println!("{marker} {:<16} {}", wallet.name, wallet.keypair_path);
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::UseWallet { name, passphrase }) => {
+                match self.switch_wallet(&name, passphrase).await {
+                    Ok(Some(evt_rx)) => Ok(LoopControl::SwitchStream(evt_rx)),
+                    Ok(None) => Ok(LoopControl::Continue),
+                    Err(err) => {
+                        warn!(event = "wallet_switch_failed", wallet = %name, error = %err);
+                        println!("still signing with {}: {err:#}", self.active_wallet);
+                        Ok(LoopControl::Continue)
+                    }
+                }
+            }
+            Some(AppCommand::ShowSettings) => {
+                let draft = self
+                    .settings
+                    .get_or_insert_with(|| SettingsDraft::new(&self.config));
+                print!("{}", draft.render(&self.config));
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::SetSetting { field, value }) => {
+                let staged = SettingsField::parse(&field).and_then(|field| {
+                    self.settings
+                        .get_or_insert_with(|| SettingsDraft::new(&self.config))
+                        .set(field, &value)
+                });
+                match staged {
+                    Ok((before, after)) => println!(
+                        "{field}: {before} -> {after} (pending; `settings apply` to use it)"
+                    ),
+                    Err(err) => println!("{field} not changed: {err}"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::SetPositionSetting { mint, field, value }) => {
+                if let Err(err) = self.override_position_strategy(mint, &field, &value) {
+                    println!("{field} not changed for {mint}: {err}");
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ApplySettings) => {
+                self.apply_settings();
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::CancelSettings) => {
+                match self.settings.take() {
+                    Some(draft) if !draft.is_empty() => println!("pending settings dropped"),
+                    _ => println!("no pending settings"),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowSessions) => {
+                print!("{}", self.sessions_view().render(now_unix_ms()));
+                if let Some(status) = self.fee_reserve.status() {
+                    println!("{status}");
+                }
+                let unread = crate::events::inbox::unread();
+                if unread > 0 {
+                    println!("{unread} unread notifications; `notifications` lists them");
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::PruneSessions) => {
+                self.prune_stale_sessions(0, true).await;
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ExpectPosition(position)) => {
+                let mint = position.mint;
+                info!(
+                    event = "expected_position_registered",
+                    mint = %mint,
+                    expected_tokens = position.expected_tokens,
+                    cost_basis_units = position.cost_basis_units,
+                    custom_strategy = position.strategy.is_some()
+                );
+                self.expected_positions
+                    .insert(mint, (now_unix_ms(), position));
+                // The buy may have landed before the bot got to register it.
+                self.apply_expected_position(mint);
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::Sell {
+                mint,
+                amount,
+                slippage_bps,
+            }) => {
+                self.handle_manual_sell(mint, amount, slippage_bps);
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::SetLimit { mint, profit_pct }) => {
+                let result = match profit_pct {
+                    Some(pct) => self.limits.arm(mint, pct).map(|()| true),
+                    None => self.limits.disarm(&mint),
+                };
+                match (result, profit_pct) {
+                    (Ok(_), Some(pct)) => info!(event = "limit_armed", mint = %mint, profit_pct = pct),
+                    (Ok(true), None) => info!(event = "limit_disarmed", mint = %mint),
+                    (Ok(false), None) => println!("no limit armed for {mint}"),
+                    (Err(err), _) => warn!(event = "limits_save_failed", error = %err),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowLimits) => {
+                print!("{}", self.limits.render());
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::ShowInsights) => {
+                print!("{}", self.insights.render());
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::DismissInsight { id }) => {
+                match self.insights.dismiss(&id) {
+                    Ok(true) => info!(event = "insight_dismissed", id = %id),
+                    Ok(false) => println!("no current insight {id}; `insights` lists them"),
+                    Err(err) => warn!(event = "insights_save_failed", error = %err),
+                }
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::SellAt { mint, when }) => {
+                self.schedule_sell(mint, when);
+                Ok(LoopControl::Continue)
+            }
+            Some(AppCommand::Buy {
+                mint,
+                lamports,
+                slippage_bps,
+            }) => {
+                self.handle_manual_buy(mint, lamports, slippage_bps);
+                Ok(LoopControl::Continue)
+            }
             None => Ok(LoopControl::DropCommands),
         }
     }
 
-    fn handle_heartbeat(&self) {
+    /// Looks up token account rent off the event loop and prints it.
+    fn spawn_rent_report(&self) {
+        let rpc_http = self.rpc_http.clone();
+        let rpc_url = self.rpc_url.clone();
+        let rpc_scheduler = self.rpc_scheduler.clone();
+        let wallet_pubkey = self.wallet_pubkey;
+        tokio::spawn(async move {
+            match rent::fetch_rent_report(&rpc_http, &rpc_url, &rpc_scheduler, &wallet_pubkey)
+                .await
+            {
+                Ok(report) => print!("{}", report.render()),
+                Err(err) => warn!(event = "rent_report_failed", error = %err),
+            }
+        });
+    }
+
+    /// Prints what `sweep` would do with the wallet's dust, and with
+    /// `execute` does it. Mints with an open session are left to it.
+    fn spawn_sweep(&self, execute: bool) {
+        if self.sweeping.swap(true, Ordering::SeqCst) {
+            println!("a sweep is already running");
+            return;
+        }
+        let sweeper = match self.sweeper() {
+            Ok(sweeper) => sweeper,
+            Err(err) => {
+                self.sweeping.store(false, Ordering::SeqCst);
+                warn!(event = "sweep_failed", error = %err);
+                return;
+            }
+        };
+        let skip: HashSet<Pubkey> = self.position_snapshots.read().keys().copied().collect();
+        let sweeping = self.sweeping.clone();
+        tokio::spawn(async move {
+            match sweeper.plan(&skip).await {
+                Ok(plan) => {
+                    print!("{}", plan.render());
+                    if execute {
+                        print!("{}", sweeper.execute(plan).await.render());
+                    } else {
+                        println!("`sweep run` does this");
+                    }
+                }
+                Err(err) => warn!(event = "sweep_failed", error = %err),
+            }
+            sweeping.store(false, Ordering::SeqCst);
+        });
+    }
+
+    fn sweeper(&self) -> Result<Sweeper> {
+        let keypair = Keypair::try_from(&self.keypair_bytes[..])?;
+        let sell = self.runtime_sell.read().clone();
+        Ok(Sweeper {
+            rpc_http: self.rpc_http.clone(),
+            rpc_url: self.rpc_url.clone(),
+            rpc_scheduler: self.rpc_scheduler.clone(),
+            send_target: self.send_target.clone(),
+            sell_rebuilder: self.sell_rebuilder.clone(),
+            keypair,
+            config: self.config.sweep.clone(),
+            slippage_bps: sell.slippage_max_bps,
+            confirm_timeout: Duration::from_secs(sell.confirm_timeout_sec),
+        })
+    }
+
+    /// Lists every token the wallet holds with its value and keeps the
+    /// listing for `portfolio sell <n>`. Open sessions are valued from the
+    /// stream's quote; everything else is priced like `sweep` prices dust.
+    fn spawn_portfolio(&self) {
+        let sweeper = match self.sweeper() {
+            Ok(sweeper) => sweeper,
+            Err(err) => {
+                warn!(event = "portfolio_failed", error = %err);
+                return;
+            }
+        };
+        let sessions: HashMap<Pubkey, Option<(u64, QuoteToken)>> = {
+            let snapshots = self.position_snapshots.read();
+            let market_contexts = self.market_contexts.read();
+            let stream_states = self.stream_states.read();
+            snapshots
+                .keys()
+                .map(|mint| {
+                    let quote = market_contexts
+                        .get(mint)
+                        .map(|context| context.quote)
+                        .unwrap_or_default();
+                    let proceeds = stream_states
+                        .get(mint)
+                        .and_then(|state| state.quote_sell_proceeds());
+                    (*mint, proceeds.map(|units| (units, quote)))
+                })
+                .collect()
+        };
+        let cache = self.portfolio.clone();
+        println!("pricing the wallet's tokens...");
+        tokio::spawn(async move {
+            match Portfolio::fetch(&sweeper, &sessions).await {
+                Ok(portfolio) => {
+                    print!("{}", portfolio.render());
+                    *cache.write() = Some(portfolio);
+                }
+                Err(err) => warn!(event = "portfolio_failed", error = %err),
+            }
+        });
+    }
+
+    /// Drops sessions that have been stale for at least `min_age_ms` and
+    /// have no sell in flight, logging each one before it is forgotten.
+    async fn prune_stale_sessions(&mut self, min_age_ms: u64, manual: bool) {
+        let in_flight: HashSet<u64> = self.in_flight_auto_sells.lock().await.keys().copied().collect();
+        let (pruned, remaining) = {
+            let mut snapshots = self.position_snapshots.write();
+            let stale = stale_session_mints(&snapshots, &in_flight, now_unix_ms(), min_age_ms);
+            let pruned: Vec<(Pubkey, PositionSnapshot)> = stale
+                .into_iter()
+                .filter_map(|mint| snapshots.remove(&mint).map(|snapshot| (mint, snapshot)))
+                .collect();
+            (pruned, snapshots.len())
+        };
+        for (mint, snapshot) in &pruned {
+            info!(
+                event = "session_archived",
+                mint = %mint,
+                position_id = snapshot.position_id,
+                tokens = snapshot.tokens,
+                cost_basis_units = snapshot.cost_basis_units,
+                stale_since_ms = snapshot.stale_since_ms
+            );
+            self.market_contexts.write().remove(mint);
+            self.stream_states.write().remove(mint);
+            self.manual_slippage.remove(&snapshot.position_id);
+            emit(AppEvent::SessionClosed { mint: *mint });
+        }
+        if manual || !pruned.is_empty() {
+            info!(event = "sessions_pruned", pruned = pruned.len(), remaining, manual);
+        }
+    }
+
+    fn exposure_report(&self) -> ExposureReport {
+        let snapshots = self.position_snapshots.read();
+        let market_contexts = self.market_contexts.read();
+        ExposureReport::from_positions(snapshots.iter().filter_map(|(mint, snapshot)| {
+            if snapshot.tokens == 0 || snapshot.stale_since_ms.is_some() {
+                return None;
+            }
+            let context = market_contexts.get(mint);
+            Some(OpenExposure {
+                quote: context.map(|context| context.quote).unwrap_or_default(),
+                market_type: context.map(|context| context.market_type),
+                cost_basis_units: snapshot.cost_basis_units?,
+            })
+        }))
+    }
+
+    /// Warns once per exposure cap as it is crossed; a cap warns again only
+    /// after exposure has dropped back under it.
+    fn check_exposure_caps(&mut self) {
+        let caps = &self.exposure_caps;
+        if caps.max_market_type_pct <= 0.0 && caps.max_sol <= 0.0 && caps.max_usd1 <= 0.0 {
+            return;
+        }
+        let breaches = self.exposure_report().breaches(&self.exposure_caps);
+        let mut current = HashSet::new();
+        for (key, message) in breaches {
+            if !self.exposure_breaches.contains(&key) {
+                warn!(event = "exposure_cap_exceeded", cap = %key, message = %message);
+            }
+            current.insert(key);
+        }
+        self.exposure_breaches = current;
+    }
+
+    /// A whole-position sell of an open session goes through the stream like
+    /// an exit signal. Anything else, a part of a position or a token with
+    /// no session, is sold directly through the exit API.
+    fn handle_manual_sell(
+        &mut self,
+        mint: Pubkey,
+        amount: Option<SellAmount>,
+        slippage_bps: Option<u16>,
+    ) {
+        if self.fee_reserve.blocks_sell(mint) {
+            return;
+        }
+        let position_id = self
+            .position_snapshots
+            .read()
+            .get(&mint)
+            .map(|snapshot| snapshot.position_id)
+            .filter(|id| *id != 0);
+        if let Some(bps) = slippage_bps {
+            let max_bps = self.runtime_sell.read().slippage_max_bps;
+            if bps > max_bps {
+                warn!(
+                    event = "manual_sell_slippage_above_max",
+                    mint = %mint,
+                    slippage_bps = bps,
+                    slippage_max_bps = max_bps,
+                    "manual sell slippage exceeds slippage_max_bps for this exit only"
+                );
+            }
+        }
+        let position_id = match (position_id, amount) {
+            (Some(position_id), None) => position_id,
+            (position_id, amount) => {
+                self.spawn_direct_sell(mint, position_id.unwrap_or(0), amount, slippage_bps);
+                return;
+            }
+        };
+        if self.stream_down_since_ms.is_some() {
+            self.spawn_exit_api_sell(mint, position_id, slippage_bps);
+            return;
+        }
+        if let Some(bps) = slippage_bps {
+            self.manual_slippage.insert(position_id, bps);
+        }
+        info!(event = "manual_sell_requested", mint = %mint, position_id, slippage_bps);
+        if let Err(err) = self.stream_handle.request_exit_signal(position_id, slippage_bps) {
+            self.manual_slippage.remove(&position_id);
+            warn!(event = "manual_sell_request_failed", mint = %mint, error = %err);
+        }
+    }
+
+    /// While the stream is down there is no exit signal to wait for, so the
+    /// sell tx is built through the exit API and run like one.
+    fn spawn_exit_api_sell(&self, mint: Pubkey, position_id: u64, slippage_bps: Option<u16>) {
+        let Some((tokens, token_program)) = self
+            .position_snapshots
+            .read()
+            .get(&mint)
+            .map(|snapshot| (snapshot.tokens, snapshot.token_program.clone()))
+        else {
+            return;
+        };
+        let quote = self
+            .market_contexts
+            .read()
+            .get(&mint)
+            .map(|context| context.quote)
+            .unwrap_or_default();
+        let sell_cfg = self.runtime_sell.read().clone();
+        let output = manual_sell_output(sell_cfg.output, quote);
+        let request = self.sell_rebuilder.request(
+            mint.to_string(),
+            tokens,
+            output,
+            slippage_bps.unwrap_or(sell_cfg.slippage_pad_bps),
+            None,
+        );
+        let profit_units = crate::events::history::last_pnl(&mint).map_or(0, |(_, profit)| profit);
+        info!(
+            event = "manual_sell_via_exit_api",
+            mint = %mint,
+            position_id,
+            slippage_bps = request.slippage_bps,
+            "stream is down; building the sell through the exit API"
+        );
+        let ctx = self.sell_context();
+        tokio::spawn(async move {
+            let unsigned_tx_b64 = match ctx
+                .sell_rebuilder
+                .build_hedged(&request, sell_cfg.hedge_request_ms)
+                .await
+            {
+                Ok(built) => built.tx,
+                Err(err) => {
+                    let error = format!("{err:#}");
+                    warn!(event = "manual_sell_request_failed", mint = %mint, error = %error);
+                    return;
+                }
+            };
+            let signal = ExitSignal {
+                position_id,
+                mint: mint.to_string(),
+                token_program,
+                position_tokens: tokens,
+                profit_units,
+                reason: "manual".to_string(),
+                triggered_at_ms: None,
+                market_context: None,
+                unsigned_tx_b64,
+                slippage_override_bps: slippage_bps,
+            };
+            let result = process_exit_signal_with_tx(ctx, signal).await;
+            if let Err(err) = result {
+                let error = format!("{err:#}");
+                warn!(event = "manual_sell_request_failed", mint = %mint, error = %error);
+            }
+        });
+    }
+
+    /// Buys through the exit API and registers the buy as an expected
+    /// position, so the session the stream opens when the tokens land
+    /// carries the SOL spent as its cost basis. The stream only sees tokens
+    /// that arrive while it is connected, so a buy is refused while it is
+    /// down, and while the mint already has a session.
+    fn handle_manual_buy(&mut self, mint: Pubkey, lamports: u64, slippage_bps: Option<u16>) {
+        if self.stream_down_since_ms.is_some() {
+            warn!(event = "manual_buy_stream_down", mint = %mint, "the stream would not see this buy");
+            return;
+        }
+        let has_session = self
+            .position_snapshots
+            .read()
+            .get(&mint)
+            .is_some_and(|snapshot| snapshot.position_id != 0);
+        if has_session {
+            warn!(event = "manual_buy_session_open", mint = %mint, "sell it first or buy a different mint");
+            return;
+        }
+        let slippage_bps = slippage_bps.unwrap_or(self.config.mirror.buy_slippage_bps);
+        self.expected_positions.insert(
+            mint,
+            (
+                now_unix_ms(),
+                ExpectedPosition {
+                    mint,
+                    expected_tokens: None,
+                    cost_basis_units: Some(lamports),
+                    strategy: None,
+                },
+            ),
+        );
+        info!(event = "manual_buy_requested", mint = %mint, lamports, slippage_bps);
+        let rpc_http = self.rpc_http.clone();
+        let keypair_bytes = self.keypair_bytes;
+        let rpc_url = self.rpc_url.clone();
+        let rpc_scheduler = self.rpc_scheduler.clone();
+        let send_target = self.send_target.clone();
+        let sell_rebuilder = self.sell_rebuilder.clone();
+        let confirm_timeout = Duration::from_secs(self.runtime_sell.read().confirm_timeout_sec);
+        tokio::spawn(async move {
+            let result = async {
+                let keypair = Keypair::try_from(&keypair_bytes[..]).context("decode keypair")?;
+                let unsigned_tx_b64 = sell_rebuilder
+                    .build_buy(mint.to_string(), lamports, slippage_bps)
+                    .await?;
+                let max_lamports =
+                    lamports.saturating_add(lamports.saturating_mul(u64::from(slippage_bps)) / 10_000);
+                let signed = sign_unsigned_tx(
+                    &unsigned_tx_b64,
+                    &keypair,
+                    Intent::Buy { max_lamports },
+                    Purpose::new("buy", Some(&mint)),
+                )?;
+                let _send_window = rpc_scheduler.begin_sell();
+                send_tx(
+                    &rpc_http,
+                    &rpc_url,
+                    &signed,
+                    send_target.as_ref(),
+                    confirm_timeout,
+                    &mut None,
+                )
+                .await
+            }
+            .await;
+            match result {
+                Ok(signature) => {
+                    info!(event = "manual_buy_complete", mint = %mint, lamports, signature = %signature);
+                }
+                Err(err) => {
+                    let error = format!("{err:#}");
+                    warn!(event = "manual_buy_failed", mint = %mint, error = %error);
+                }
+            }
+        });
+    }
+
+    /// Sells `amount` of `mint` (all of it when unset) without waiting for an
+    /// exit signal. The holding comes from the session when there is one and
+    /// from the wallet's token accounts otherwise; the session, if any, stays
+    /// open and the balance poller picks up what is left.
+    fn spawn_direct_sell(
+        &self,
+        mint: Pubkey,
+        position_id: u64,
+        amount: Option<SellAmount>,
+        slippage_bps: Option<u16>,
+    ) {
+        let snapshot_tokens = self
+            .position_snapshots
+            .read()
+            .get(&mint)
+            .filter(|snapshot| snapshot.position_id != 0)
+            .map(|snapshot| snapshot.tokens);
+        let quote = self
+            .market_contexts
+            .read()
+            .get(&mint)
+            .map(|context| context.quote)
+            .unwrap_or_default();
+        let mut sell_cfg = self.runtime_sell.read().clone();
+        if let Some(bps) = slippage_bps {
+            sell_cfg.slippage_pad_bps = bps;
+            sell_cfg.slippage_max_bps = sell_cfg.slippage_max_bps.max(bps);
+        }
+        let output = manual_sell_output(sell_cfg.output, quote);
+        let owner = Keypair::try_from(&self.keypair_bytes[..]).map(|keypair| keypair.pubkey());
+        let stream_handle = self.stream_handle.clone();
+        let rpc_http = self.rpc_http.clone();
+        let keypair_bytes = self.keypair_bytes;
+        let rpc_url = self.rpc_url.clone();
+        let rpc_scheduler = self.rpc_scheduler.clone();
+        let send_target = self.send_target.clone();
+        let in_flight_auto_sells = self.in_flight_auto_sells.clone();
+        let sell_rebuilder = self.sell_rebuilder.clone();
+        let sell_timelines = self.sell_timelines.clone();
+        tokio::spawn(async move {
+            // Holding the position's slot keeps an exit signal from starting a
+            // second sell of the same tokens meanwhile.
+            let (refresh_tx, refresh_rx) = mpsc::unbounded_channel::<String>();
+            if position_id != 0 {
+                let mut in_flight = in_flight_auto_sells.lock().await;
+                if in_flight.contains_key(&position_id) {
+                    warn!(event = "manual_sell_in_flight", mint = %mint, position_id);
+                    return;
+                }
+                in_flight.insert(position_id, refresh_tx);
+            }
+            let result = async {
+                let (held, decimals) = match (snapshot_tokens, amount) {
+                    (Some(tokens), None | Some(SellAmount::Percent(_))) => (tokens, None),
+                    _ => {
+                        let owner = owner.context("decode keypair")?;
+                        let holding = manual_sell::fetch_holding(
+                            &rpc_http,
+                            &rpc_url,
+                            &rpc_scheduler,
+                            &owner,
+                            &mint,
+                        )
+                        .await?;
+                        (holding.amount, Some(holding.decimals))
+                    }
+                };
+                let tokens = manual_sell::tokens_to_sell(amount, held, decimals)?;
+                info!(
+                    event = "manual_sell_requested",
+                    mint = %mint,
+                    position_id,
+                    tokens,
+                    held,
+                    slippage_bps = sell_cfg.slippage_pad_bps
+                );
+                let request = sell_rebuilder.request(
+                    mint.to_string(),
+                    tokens,
+                    output,
+                    sell_cfg.slippage_pad_bps,
+                    None,
+                );
+                let unsigned_tx_b64 = sell_rebuilder
+                    .build_hedged(&request, sell_cfg.hedge_request_ms)
+                    .await?
+                    .tx;
+                sell_timelines.begin(mint, position_id, now_unix_ms());
+                let _sell_window = rpc_scheduler.begin_sell();
+                let result = execute_auto_sell_with_refresh(
+                    stream_handle,
+                    refresh_rx,
+                    rpc_http.clone(),
+                    keypair_bytes,
+                    rpc_url.clone(),
+                    rpc_scheduler.clone(),
+                    send_target,
+                    mint,
+                    position_id,
+                    sell_cfg,
+                    unsigned_tx_b64,
+                    None,
+                    RefreshRoute {
+                        rebuilder: sell_rebuilder.clone(),
+                        request,
+                        via_stream: false,
+                    },
+                    &sell_timelines,
+                )
+                .await;
+                sell_timelines.note(
+                    &mint,
+                    now_unix_ms(),
+                    match &result {
+                        Ok((signature, ..)) => TimelineEntry::Confirmed {
+                            signature: signature.clone(),
+                        },
+                        Err(err) => TimelineEntry::GaveUp {
+                            error: format!("{err:#}"),
+                        },
+                    },
+                );
+                result.map(|(signature, ..)| (signature, tokens))
+            }
+            .await;
+            if position_id != 0 {
+                in_flight_auto_sells.lock().await.remove(&position_id);
+            }
+            match result {
+                Ok((signature, tokens)) => {
+                    info!(event = "manual_sell_complete", mint = %mint, tokens, signature = %signature);
+                }
+                Err(err) => {
+                    let error = format!("{err:#}");
+                    warn!(event = "manual_sell_request_failed", mint = %mint, error = %error);
+                }
+            }
+        });
+    }
+
+    /// Unlocks another configured wallet and moves signing, the stream
+    /// session and the balance poller to it. Open sessions belong to the old
+    /// wallet and are closed; its tokens stay where they are.
+    async fn switch_wallet(
+        &mut self,
+        name: &str,
+        mut passphrase: Option<SecretString>,
+    ) -> Result<Option<mpsc::UnboundedReceiver<StreamEvent>>> {
+        let wallet = self
+            .config
+            .wallet_choices()
+            .into_iter()
+            .find(|wallet| wallet.name == name)
+            .ok_or_else(|| anyhow!("no wallet named {name}; `wallets` lists them"))?;
+        if wallet.name == self.active_wallet {
+            println!("already signing with {name}");
+            return Ok(None);
+        }
+        if !self.in_flight_auto_sells.lock().await.is_empty() {
+            return Err(anyhow!("a sell is in flight; switch once it finishes"));
+        }
+
+        let path = PathBuf::from(&wallet.keypair_path);
+        let keypair = tokio::task::spawn_blocking(move || {
+            crate::wallet::load_keypair_from_path(&path, || {
+                passphrase
+                    .take()
+                    .or_else(|| {
+                        std::env::var("LASERSELL_WALLET_PASSPHRASE")
+                            .ok()
+                            .filter(|value| !value.trim().is_empty())
+                            .map(SecretString::new)
+                    })
+                    .ok_or_else(|| anyhow!("keystore passphrase required"))
+            })
+        })
+        .await
+        .context("unlock wallet")??;
+        let wallet_pubkey = self.config.wallet_pubkey(&keypair)?;
+        let (stream_handle, evt_rx) = connect_stream(&self.config, &keypair, wallet_pubkey).await?;
+        let sell_rebuilder = sell_rebuilder_for(&self.config, wallet_pubkey)?;
+
+        let closed: Vec<Pubkey> = self
+            .position_snapshots
+            .write()
+            .drain()
+            .map(|(mint, _)| mint)
+            .collect();
+        for mint in &closed {
+            emit(AppEvent::SessionClosed { mint: *mint });
+        }
+        self.market_contexts.write().clear();
+        self.stream_states.write().clear();
+        self.manual_slippage.clear();
+        self.expected_positions.clear();
+        self.exposure_breaches.clear();
+
+        info!(
+            event = "wallet_switched",
+            from = %self.active_wallet,
+            to = %name,
+            from_pubkey = %self.wallet_pubkey,
+            to_pubkey = %wallet_pubkey,
+            closed_sessions = closed.len()
+        );
+        self.keypair_bytes = keypair.to_bytes();
+        self.wallet_pubkey = wallet_pubkey;
+        if let Some(treasury) = self.treasury.as_ref() {
+            treasury.set_signer(&keypair);
+        }
+        self.fee_reserve.reset();
+        *self.balance_wallet.write() = wallet_pubkey;
+        self.sell_rebuilder = Arc::new(sell_rebuilder);
+        self.stream_handle = Arc::new(stream_handle);
+        self.strategy = self.config.strategy.clone();
+        *self.insight_strategy.write() = self.strategy.clone();
+        self.deadline_timeout_sec = self.config.strategy.deadline_timeout_sec;
+        self.active_wallet = wallet.name;
+        println!("now signing with {name} ({wallet_pubkey}); closed {} session(s)", closed.len());
+        Ok(Some(evt_rx))
+    }
+
+    /// Puts staged settings into effect and saves them to the config file.
+    fn apply_settings(&mut self) {
+        let Some(draft) = self.settings.take().filter(|draft| !draft.is_empty()) else {
+            println!("no pending settings");
+            return;
+        };
+        let (next, changed) = draft.into_parts();
+        // The deadline stays what the open session was configured with.
+        let mut strategy = next.strategy.clone();
+        strategy.deadline_timeout_sec = self.strategy.deadline_timeout_sec;
+        if let Err(err) = self.stream_handle.update_strategy(strategy_to_msg(&strategy)) {
+            warn!(event = "settings_apply_failed", error = %err);
+            println!("settings not applied: {err}");
+            return;
+        }
+        *self.insight_strategy.write() = strategy.clone();
+        self.strategy = strategy;
+        self.runtime_sell.write().slippage_max_bps = next.sell.slippage_max_bps;
+        let fields: Vec<_> = changed.iter().map(|field| field.key()).collect();
+        info!(event = "settings_applied", fields = %fields.join(","));
+        match &self.config_path {
+            Some(path) => {
+                let saved = Config::edit_file(path, |file| {
+                    for field in &changed {
+                        field.copy(&next, file);
+                    }
+                });
+                match saved {
+                    Ok(()) => println!("settings applied and saved to {}", path.display()),
+                    Err(err) => {
+                        warn!(event = "settings_save_failed", error = %err);
+                        println!("settings applied for this run but not saved: {err:#}");
+                    }
+                }
+            }
+            None => println!("settings applied for this run; --env-config has no file to save to"),
+        }
+        self.config = next;
+    }
+
+    /// The SDK reports the outcome of every reconnect attempt; only changes
+    /// are surfaced.
+    fn handle_connection_status(&mut self, connected: bool) {
+        if self.stream_connected == Some(connected) {
+            return;
+        }
+        // The first connect is what startup waits for; later ones are
+        // reconnects.
+        let first_connect = self.stream_connected.is_none();
+        self.stream_connected = Some(connected);
+        let now = now_unix_ms();
+        if connected {
+            if first_connect {
+                systemd::notify_ready();
+            }
+            if let Some(since) = self.stream_down_since_ms.take() {
+                info!(event = "stream_recovered", down_ms = now.saturating_sub(since));
+            }
+            self.fallback_quotes.reset();
+            if !first_connect {
+                self.health.note_reconnect();
+                if let Err(err) = self.stream_handle.reapply_strategy() {
+                    warn!(event = "strategy_reapply_failed", error = %err);
+                }
+            }
+        } else {
+            self.stream_down_since_ms = Some(now);
+            self.stream_down_reminded_ms = now;
+            warn!(
+                event = "stream_degraded",
+                open_sessions = self.sessions_view().rows.len(),
+                "STREAM DOWN: quotes are frozen at their last values unless \
+                 stream.fallback_quote_sec re-quotes them via the exit API; `sell <mint>` \
+                 still works via the exit API, `sessions` shows quote ages"
+            );
+        }
+        emit(AppEvent::SolanaWsStatus { connected });
+    }
+
+    fn sessions_view(&self) -> SessionsView {
+        let snapshots = self.position_snapshots.read();
+        let market_contexts = self.market_contexts.read();
+        let stream_states = self.stream_states.read();
+        let mut rows: Vec<SessionRow> = snapshots
+            .iter()
+            .filter(|(_, snapshot)| snapshot.tokens > 0 && snapshot.stale_since_ms.is_none())
+            .map(|(mint, snapshot)| SessionRow {
+                mint: *mint,
+                tokens: snapshot.tokens,
+                quote: market_contexts
+                    .get(mint)
+                    .map(|context| context.quote)
+                    .unwrap_or_default(),
+                last_pnl: crate::events::history::last_pnl(mint),
+                sell_at_ms: self.sell_schedule.due_at(
+                    mint,
+                    snapshot.opened_at_ms,
+                    self.strategy.max_hold_sec,
+                ),
+                strategy: snapshot.strategy.as_ref().map(settings::strategy_summary),
+                quoted_proceeds: stream_states
+                    .get(mint)
+                    .and_then(|state| state.quote_sell_proceeds()),
+                last_trade_ms: stream_states
+                    .get(mint)
+                    .and_then(|state| state.last_trade())
+                    .map(|(at_ms, _)| at_ms),
+                liquidity_trend: stream_states
+                    .get(mint)
+                    .and_then(|state| state.liquidity_trend()),
+                mint_risks: snapshot.mint_risks.clone(),
+            })
+            .collect();
+        rows.sort_by_key(|row| row.mint);
+        SessionsView {
+            stream_down_since_ms: self.stream_down_since_ms,
+            rows,
+        }
+    }
+
+    fn handle_heartbeat(&mut self) {
         emit(AppEvent::Heartbeat);
+        systemd::notify_watchdog();
+        self.health.heartbeat(rpc_health().take_slowest_ms());
+        if let Some(since) = self.stream_down_since_ms {
+            let now = now_unix_ms();
+            if now.saturating_sub(self.stream_down_reminded_ms) >= DEGRADED_REMINDER_SECS * 1_000 {
+                self.stream_down_reminded_ms = now;
+                warn!(
+                    event = "stream_still_down",
+                    down_sec = now.saturating_sub(since) / 1_000,
+                    "STREAM DOWN, still reconnecting; quotes are stale"
+                );
+            }
+        }
+        if !self.strategy.tighten_after_sec.is_empty() {
+            self.apply_strategy_tightening();
+        }
+        self.fire_scheduled_sells();
+        let now = now_unix_ms();
+        self.run_fallback_quotes(now);
+        if self.stream_connected == Some(true) && self.clock_skew.is_due(now) {
+            match self.stream_handle.ping(now) {
+                Ok(()) => self.clock_skew.ping_sent(now),
+                Err(err) => debug!(event = "clock_skew_ping_failed", error = %err),
+            }
+        }
+        if self.liveness.as_ref().is_some_and(|file| file.is_due(now)) {
+            let snapshot = LivenessSnapshot {
+                at_ms: now,
+                open_sessions: self.sessions_view().rows.len(),
+                stream_connected: self.stream_connected == Some(true),
+                last_stream_message_ms: self.last_stream_message_ms,
+            };
+            if let Some(file) = self.liveness.as_mut() {
+                file.write(&snapshot);
+            }
+        }
+        if self.attach.as_ref().is_some_and(AttachServer::has_clients) {
+            let frame = self.attach_frame(now);
+            if let Some(attach) = &self.attach {
+                attach.publish(frame);
+            }
+        }
+    }
+
+    /// What `lasersell attach` shows: a status line and the `sessions` view.
+    fn attach_frame(&self, now_ms: u64) -> String {
+        let stream = match self.stream_connected {
+            Some(true) => "connected",
+            Some(false) => "DOWN",
+            None => "connecting",
+        };
+        let unread = match crate::events::inbox::unread() {
+            0 => String::new(),
+            count => format!("  {count} unread"),
+        };
+        let low_sol = match self.fee_reserve.shortfall() {
+            Some(_) => "  SOL BELOW RESERVE",
+            None => "",
+        };
+        format!(
+            "lasersell {}  wallet {}  stream {stream}{low_sol}{unread}\n\
+             read-only view of the running daemon; Ctrl-C detaches\n\n{}",
+            env!("CARGO_PKG_VERSION"),
+            self.active_wallet,
+            self.sessions_view().render(now_ms)
+        )
+    }
+
+    /// Applies a buy bot's registration to the open position for `mint`:
+    /// fills in a missing cost basis and pushes its strategy. No-op until the
+    /// stream has reported the position.
+    fn apply_expected_position(&mut self, mint: Pubkey) {
+        let mut snapshots = self.position_snapshots.write();
+        let Some(snapshot) = snapshots
+            .get_mut(&mint)
+            .filter(|snapshot| snapshot.position_id != 0)
+        else {
+            return;
+        };
+        let Some((_, expected)) = self.expected_positions.remove(&mint) else {
+            return;
+        };
+        if snapshot.cost_basis_units.is_none() {
+            if let Some(cost_basis_units) = expected.cost_basis_units {
+                snapshot.cost_basis_units = Some(cost_basis_units);
+                emit(AppEvent::CostBasisSet {
+                    mint,
+                    cost_basis_lamports: cost_basis_units,
+                });
+            }
+        }
+        if let Some(custom) = expected.strategy.as_ref() {
+            let strategy = custom.apply_to(&self.strategy);
+            match self
+                .stream_handle
+                .update_position_strategy(snapshot.position_id, strategy_to_msg(&strategy))
+            {
+                Ok(()) => snapshot.strategy = Some(strategy),
+                Err(err) => {
+                    warn!(event = "expected_position_strategy_failed", mint = %mint, error = %err);
+                }
+            }
+        }
+        info!(
+            event = "expected_position_matched",
+            mint = %mint,
+            position_id = snapshot.position_id,
+            expected_tokens = expected.expected_tokens,
+            tokens = snapshot.tokens
+        );
+    }
+
+    /// Reads the mint in the background and attaches what it finds to the
+    /// session, for positions whose sells keep reverting.
+    fn spawn_mint_risk_check(&self, mint: Pubkey, position_id: u64) {
+        let rpc_http = self.rpc_http.clone();
+        let rpc_url = self.rpc_url.clone();
+        let rpc_scheduler = self.rpc_scheduler.clone();
+        let position_snapshots = self.position_snapshots.clone();
+        tokio::spawn(async move {
+            let risks = match fetch_mint_risks(&rpc_http, &rpc_url, &rpc_scheduler, &mint).await {
+                Ok(risks) if risks.is_empty() => return,
+                Ok(risks) => risks,
+                Err(err) => {
+                    debug!(event = "mint_risk_check_failed", mint = %mint, error = %err);
+                    return;
+                }
+            };
+            warn!(event = "mint_risk", mint = %mint, position_id, risks = %risks.join(", "));
+            if let Some(snapshot) = position_snapshots
+                .write()
+                .get_mut(&mint)
+                .filter(|snapshot| snapshot.position_id == position_id)
+            {
+                snapshot.mint_risks = risks;
+            }
+        });
+    }
+
+    /// Checks in the background whether a position opened or closed by a
+    /// transfer with one of `account.own_wallets`. Inbound transfers flag
+    /// the position so its sell is journaled as a self-transfer; outbound
+    /// ones get their own journal record.
+    fn spawn_self_transfer_check(
+        &self,
+        mint: Pubkey,
+        position_id: u64,
+        token_account: String,
+        direction: TransferDirection,
+    ) {
+        if self.own_wallets.is_empty() {
+            return;
+        }
+        let rpc_http = self.rpc_http.clone();
+        let rpc_url = self.rpc_url.clone();
+        let rpc_scheduler = self.rpc_scheduler.clone();
+        let wallet_pubkey = self.wallet_pubkey;
+        let own_wallets = self.own_wallets.clone();
+        let position_snapshots = self.position_snapshots.clone();
+        let journal = self.journal.clone();
+        tokio::spawn(async move {
+            let found = find_self_transfer(
+                &rpc_http,
+                &rpc_url,
+                &rpc_scheduler,
+                &token_account,
+                &mint,
+                &wallet_pubkey,
+                &own_wallets,
+                direction,
+            )
+            .await;
+            let (counterparty, signature) = match found {
+                Ok(Some(found)) => found,
+                Ok(None) => return,
+                Err(err) => {
+                    debug!(event = "self_transfer_check_failed", mint = %mint, error = %err);
+                    return;
+                }
+            };
+            info!(
+                event = "self_transfer_detected",
+                mint = %mint,
+                position_id,
+                direction = direction.label(),
+                counterparty = %counterparty,
+                signature = %signature
+            );
+            match direction {
+                TransferDirection::In => {
+                    if let Some(snapshot) = position_snapshots
+                        .write()
+                        .get_mut(&mint)
+                        .filter(|snapshot| snapshot.position_id == position_id)
+                    {
+                        snapshot.self_transfer = true;
+                    }
+                }
+                TransferDirection::Out => {
+                    let Some(journal) = journal.as_ref() else {
+                        return;
+                    };
+                    let record = TradeRecord {
+                        closed_at_ms: now_unix_ms(),
+                        mint: mint.to_string(),
+                        position_id,
+                        reason: "self_transfer".to_string(),
+                        signature,
+                        self_transfer: true,
+                        ..TradeRecord::default()
+                    };
+                    if let Err(err) = journal.append(&record) {
+                        warn!(event = "journal_write_failed", error = %err);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Drops registrations whose buy never showed up.
+    fn expire_expected_positions(&mut self) {
+        let cutoff = now_unix_ms().saturating_sub(EXPECTED_POSITION_TTL_SECS * 1_000);
+        self.expected_positions.retain(|mint, (registered_at_ms, _)| {
+            let keep = *registered_at_ms >= cutoff;
+            if !keep {
+                info!(event = "expected_position_expired", mint = %mint);
+            }
+            keep
+        });
+    }
+
+    /// `set <setting> <value> for <mint>`: pushes a strategy for that
+    /// position alone. It lasts as long as the session; tightening steps
+    /// reached so far are applied on top, and later ones build on it.
+    fn override_position_strategy(&mut self, mint: Pubkey, field: &str, value: &str) -> Result<()> {
+        let field = SettingsField::parse(field)?;
+        let mut snapshots = self.position_snapshots.write();
+        let snapshot = snapshots
+            .get_mut(&mint)
+            .filter(|snapshot| snapshot.position_id != 0)
+            .ok_or_else(|| anyhow!("no open session"))?;
+        let base = snapshot.strategy.as_ref().unwrap_or(&self.strategy);
+        let strategy = field.override_strategy(&self.config, base, value)?;
+        self.stream_handle.update_position_strategy(
+            snapshot.position_id,
+            strategy_to_msg(&strategy.tightened(snapshot.tighten_step)),
+        )?;
+        info!(
+            event = "position_strategy_overridden",
+            mint = %mint,
+            position_id = snapshot.position_id,
+            setting = field.key(),
+            strategy = %settings::strategy_summary(&strategy)
+        );
+        snapshot.strategy = Some(strategy);
+        Ok(())
+    }
+
+    /// `sell-at`: sells the open session for `mint` at `when`, or cancels
+    /// that when unset. Kept in memory only, like the session itself.
+    fn schedule_sell(&mut self, mint: Pubkey, when: Option<SellTime>) {
+        let Some(when) = when else {
+            if self.sell_schedule.clear(&mint) {
+                info!(event = "scheduled_sell_cancelled", mint = %mint);
+            } else {
+                println!("no sell-at time set for {mint}");
+            }
+            return;
+        };
+        let has_session = self
+            .position_snapshots
+            .read()
+            .get(&mint)
+            .is_some_and(|snapshot| snapshot.position_id != 0);
+        if !has_session {
+            warn!(event = "scheduled_sell_no_position", mint = %mint);
+            return;
+        }
+        let now_ms = now_unix_ms();
+        let at_ms = match when {
+            SellTime::AfterSec(secs) => now_ms.saturating_add(secs.saturating_mul(1_000)),
+            SellTime::AtMs(at_ms) => at_ms,
+        };
+        self.sell_schedule.set(mint, at_ms);
+        info!(
+            event = "scheduled_sell_set",
+            mint = %mint,
+            at_ms,
+            in_sec = at_ms.saturating_sub(now_ms) / 1_000
+        );
+    }
+
+    /// Sells the open positions whose `sell-at` time or
+    /// `strategy.max_hold_sec` has come.
+    fn fire_scheduled_sells(&mut self) {
+        let open: Vec<(Pubkey, Option<u64>)> = self
+            .position_snapshots
+            .read()
+            .iter()
+            .filter(|(_, snapshot)| snapshot.position_id != 0 && snapshot.stale_since_ms.is_none())
+            .map(|(mint, snapshot)| (*mint, snapshot.opened_at_ms))
+            .collect();
+        let due = self
+            .sell_schedule
+            .take_due(now_unix_ms(), &open, self.strategy.max_hold_sec);
+        for mint in due {
+            info!(event = "scheduled_sell_due", mint = %mint);
+            self.handle_manual_sell(mint, None, None);
+        }
+    }
+
+    /// Pushes a per-position strategy once a position reaches the next
+    /// `strategy.tighten_after_sec` step.
+    fn apply_strategy_tightening(&self) {
+        let now_ms = now_unix_ms();
+        let mut snapshots = self.position_snapshots.write();
+        for (mint, snapshot) in snapshots.iter_mut() {
+            let Some(opened_at_ms) = snapshot.opened_at_ms else {
+                continue;
+            };
+            if snapshot.position_id == 0 || snapshot.stale_since_ms.is_some() {
+                continue;
+            }
+            let held_sec = now_ms.saturating_sub(opened_at_ms) / 1_000;
+            let base = snapshot.strategy.as_ref().unwrap_or(&self.strategy);
+            let step = base.tighten_step_at(held_sec);
+            if step <= snapshot.tighten_step {
+                continue;
+            }
+            let strategy = base.tightened(step);
+            match self
+                .stream_handle
+                .update_position_strategy(snapshot.position_id, strategy_to_msg(&strategy))
+            {
+                Ok(()) => {
+                    snapshot.tighten_step = step;
+                    info!(
+                        event = "strategy_tightened",
+                        mint = %mint,
+                        position_id = snapshot.position_id,
+                        step,
+                        held_sec,
+                        target_profit_pct = strategy.target_profit.percent_value(),
+                        stop_loss_pct = strategy.stop_loss.percent_value()
+                    );
+                }
+                Err(err) => {
+                    warn!(event = "strategy_tighten_failed", mint = %mint, error = %err);
+                }
+            }
+        }
     }
 
     fn handle_balance_update(&self, mint: String, token_program: Option<String>, tokens: u64) {
@@ -281,11 +1989,23 @@ impl AppEngine {
                     position_id: 0,
                     token_program: None,
                     tokens: 0,
+                    cost_basis_units: None,
+                    opened_at_ms: None,
+                    stale_since_ms: None,
+                    tighten_step: 0,
+                    strategy: None,
+                    self_transfer: false,
+                    mint_risks: Vec::new(),
                 });
                 if token_program.is_some() {
                     entry.token_program = token_program;
                 }
                 entry.tokens = tokens;
+                if tokens == 0 {
+                    entry.stale_since_ms.get_or_insert_with(now_unix_ms);
+                } else {
+                    entry.stale_since_ms = None;
+                }
             }
             if let Some(stream_state) = self.stream_states.read().get(&mint).cloned() {
                 stream_state.set_position_tokens(Some(tokens));
@@ -296,11 +2016,11 @@ impl AppEngine {
 
     #[allow(clippy::too_many_arguments)]
     fn handle_position_opened(
-        &self,
+        &mut self,
         position_id: u64,
         mint: String,
         token_program: Option<String>,
-        _token_account: String,
+        token_account: String,
         tokens: u64,
         entry_quote_units: u64,
         _slot: u64,
@@ -321,14 +2041,28 @@ impl AppEngine {
                 context_for_state.as_ref(),
                 Some(tokens),
             );
-            self.position_snapshots.write().insert(
-                mint,
-                PositionSnapshot {
-                    position_id,
-                    token_program,
-                    tokens,
-                },
-            );
+            {
+                let mut snapshots = self.position_snapshots.write();
+                // Fall back to an imported cost basis for bags opened elsewhere.
+                let cost_basis_units = (entry_quote_units > 0)
+                    .then_some(entry_quote_units)
+                    .or_else(|| snapshots.get(&mint).and_then(|s| s.cost_basis_units));
+                snapshots.insert(
+                    mint,
+                    PositionSnapshot {
+                        position_id,
+                        token_program,
+                        tokens,
+                        cost_basis_units,
+                        opened_at_ms: Some(now_unix_ms()),
+                        stale_since_ms: None,
+                        tighten_step: 0,
+                        strategy: None,
+                        self_transfer: false,
+                        mint_risks: Vec::new(),
+                    },
+                );
+            }
             emit(AppEvent::SessionStarted { mint });
             emit(AppEvent::MintDetected { mint });
             emit(AppEvent::PositionTokensUpdated { mint, tokens });
@@ -338,18 +2072,32 @@ impl AppEngine {
                     cost_basis_lamports: entry_quote_units,
                 });
             }
+            self.apply_expected_position(mint);
+            self.spawn_mint_risk_check(mint, position_id);
+            if entry_quote_units == 0 {
+                self.spawn_self_transfer_check(
+                    mint,
+                    position_id,
+                    token_account,
+                    TransferDirection::In,
+                );
+            }
+            self.check_exposure_caps();
         }
     }
 
     async fn handle_position_closed(
-        &self,
+        &mut self,
         position_id: u64,
         mint: String,
+        token_account: Option<String>,
         reason: String,
         slot: u64,
     ) {
         if let Ok(mint) = Pubkey::from_str(&mint) {
-            {
+            // A completed sell removes the snapshot first, so one still here
+            // means the tokens left some other way.
+            let closed_without_sell = {
                 let mut snapshots = self.position_snapshots.write();
                 let should_remove = snapshots
                     .get(&mint)
@@ -360,10 +2108,20 @@ impl AppEngine {
                 if should_remove {
                     snapshots.remove(&mint);
                 }
+                should_remove
+            };
+            if let Some(token_account) = token_account.filter(|_| closed_without_sell) {
+                self.spawn_self_transfer_check(
+                    mint,
+                    position_id,
+                    token_account,
+                    TransferDirection::Out,
+                );
             }
             self.market_contexts.write().remove(&mint);
             self.stream_states.write().remove(&mint);
             self.in_flight_auto_sells.lock().await.remove(&position_id);
+            self.manual_slippage.remove(&position_id);
             debug!(
                 event = "position_closed",
                 mint = %mint,
@@ -375,42 +2133,35 @@ impl AppEngine {
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    async fn handle_exit_signal_with_tx(
-        &self,
-        position_id: u64,
-        mint: String,
-        token_program: Option<String>,
-        position_tokens: u64,
-        profit_units: i64,
-        reason: String,
-        market_context: Option<MarketContextMsg>,
-        unsigned_tx_b64: String,
-    ) -> Result<()> {
-        process_exit_signal_with_tx(
-            false, // CLI does not support pause/resume
-            position_id,
-            mint,
-            token_program,
-            position_tokens,
-            profit_units,
-            reason,
-            market_context,
-            unsigned_tx_b64,
-            self.stream_handle.clone(),
-            self.rpc_http.clone(),
-            self.keypair_bytes,
-            self.rpc_url.clone(),
-            self.send_target.clone(),
-            self.runtime_sell.clone(),
-            self.in_flight_auto_sells.clone(),
-            self.market_contexts.clone(),
-            self.stream_states.clone(),
-            self.position_snapshots.clone(),
-        )
-        .await
+    /// Handles to the engine state a sell task works with.
+    fn sell_context(&self) -> SellContext {
+        SellContext {
+            stream_handle: self.stream_handle.clone(),
+            rpc_http: self.rpc_http.clone(),
+            keypair_bytes: self.keypair_bytes,
+            rpc_url: self.rpc_url.clone(),
+            rpc_scheduler: self.rpc_scheduler.clone(),
+            congestion: self.congestion.clone(),
+            send_target: self.send_target.clone(),
+            runtime_sell: self.runtime_sell.clone(),
+            in_flight_auto_sells: self.in_flight_auto_sells.clone(),
+            market_contexts: self.market_contexts.clone(),
+            stream_states: self.stream_states.clone(),
+            position_snapshots: self.position_snapshots.clone(),
+            journal: self.journal.clone(),
+            deadline_timeout_sec: self.deadline_timeout_sec,
+            sell_rebuilder: self.sell_rebuilder.clone(),
+            sell_queue: self.sell_queue.clone(),
+            sell_timelines: self.sell_timelines.clone(),
+            completed_sells: self.completed_sells.clone(),
+            fee_reserve: self.fee_reserve.clone(),
+        }
     }
 
+    async fn handle_exit_signal_with_tx(&mut self, mut signal: ExitSignal) -> Result<()> {
+        signal.slippage_override_bps = self.manual_slippage.remove(&signal.position_id);
+        process_exit_signal_with_tx(self.sell_context(), signal).await
+    }
 }
 
 fn stream_event_label(evt: &StreamEvent) -> &'static str {
@@ -421,31 +2172,85 @@ fn stream_event_label(evt: &StreamEvent) -> &'static str {
         StreamEvent::PositionClosed { .. } => "position_closed",
         StreamEvent::ExitSignalWithTx { .. } => "exit_signal_with_tx",
         StreamEvent::PnlUpdate { .. } => "pnl_update",
+        StreamEvent::TradeTick { .. } => "trade_tick",
+        StreamEvent::LiquidityTrend { .. } => "liquidity_trend",
+        StreamEvent::ServerTime { .. } => "server_time",
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-async fn process_exit_signal_with_tx(
-    paused: bool,
-    position_id: u64,
-    mint: String,
-    token_program: Option<String>,
-    position_tokens: u64,
-    profit_units: i64,
-    reason: String,
-    market_context_msg: Option<MarketContextMsg>,
-    unsigned_tx_b64: String,
+/// What a sell needs from the engine, cloned into each sell's task.
+#[derive(Clone)]
+struct SellContext {
     stream_handle: Arc<StreamHandle>,
     rpc_http: reqwest::Client,
     keypair_bytes: [u8; 64],
     rpc_url: String,
-    send_target: SendTarget,
+    rpc_scheduler: Arc<RpcScheduler>,
+    congestion: Arc<CongestionMonitor>,
+    send_target: Arc<dyn SellSender>,
     runtime_sell: Arc<ParkingRwLock<SellConfig>>,
     in_flight_auto_sells: Arc<Mutex<HashMap<u64, mpsc::UnboundedSender<String>>>>,
     market_contexts: Arc<ParkingRwLock<HashMap<Pubkey, MarketContext>>>,
     stream_states: Arc<ParkingRwLock<HashMap<Pubkey, Arc<InMemoryMarketStreamState>>>>,
     position_snapshots: Arc<ParkingRwLock<HashMap<Pubkey, PositionSnapshot>>>,
-) -> Result<()> {
+    journal: Option<Arc<Journal>>,
+    deadline_timeout_sec: u64,
+    sell_rebuilder: Arc<SellRebuilder>,
+    sell_queue: Option<Arc<SellQueue>>,
+    sell_timelines: Arc<SellTimelines>,
+    completed_sells: Arc<CompletedSells>,
+    fee_reserve: Arc<FeeReserve>,
+}
+
+/// One exit to carry out: the stream's signal, or a manual sell shaped
+/// like one.
+struct ExitSignal {
+    position_id: u64,
+    mint: String,
+    token_program: Option<String>,
+    position_tokens: u64,
+    profit_units: i64,
+    reason: String,
+    triggered_at_ms: Option<u64>,
+    market_context: Option<MarketContextMsg>,
+    unsigned_tx_b64: String,
+    slippage_override_bps: Option<u16>,
+}
+
+async fn process_exit_signal_with_tx(ctx: SellContext, signal: ExitSignal) -> Result<()> {
+    let SellContext {
+        stream_handle,
+        rpc_http,
+        keypair_bytes,
+        rpc_url,
+        rpc_scheduler,
+        congestion,
+        send_target,
+        runtime_sell,
+        in_flight_auto_sells,
+        market_contexts,
+        stream_states,
+        position_snapshots,
+        journal,
+        deadline_timeout_sec,
+        sell_rebuilder,
+        sell_queue,
+        sell_timelines,
+        completed_sells,
+        fee_reserve,
+    } = ctx;
+    let ExitSignal {
+        position_id,
+        mint,
+        token_program,
+        position_tokens,
+        profit_units,
+        reason,
+        triggered_at_ms,
+        market_context: market_context_msg,
+        unsigned_tx_b64,
+        slippage_override_bps,
+    } = signal;
     info!(
         event = "app_exit_signal_processing",
         position_id,
@@ -461,7 +2266,22 @@ async fn process_exit_signal_with_tx(
             return Ok(());
         }
     };
+    let canonical_reason = canonical_sell_reason(&reason);
+    if let Some(signature) =
+        completed_sells.find(position_id, &mint_pubkey, canonical_reason, position_tokens)
+    {
+        info!(
+            event = "exit_signal_already_sold",
+            mint = %mint,
+            position_id,
+            reason = %reason,
+            signature = %signature,
+            "the stream resent a signal that was already sold; ignoring it"
+        );
+        return Ok(());
+    }
 
+    let output_context = market_context_msg.clone();
     let parsed_context = apply_market_context_update(
         mint_pubkey,
         market_context_msg,
@@ -476,23 +2296,63 @@ async fn process_exit_signal_with_tx(
         Some(position_tokens),
     );
 
-    position_snapshots.write().insert(
-        mint_pubkey,
-        PositionSnapshot {
-            position_id,
-            token_program: token_program.clone(),
-            tokens: position_tokens,
-        },
-    );
+    let (cost_basis_units, opened_at_ms) = {
+        let mut snapshots = position_snapshots.write();
+        let previous = snapshots.get(&mint_pubkey);
+        let cost_basis_units = previous.and_then(|snapshot| snapshot.cost_basis_units);
+        let opened_at_ms = previous.and_then(|snapshot| snapshot.opened_at_ms);
+        let tighten_step = previous.map_or(0, |snapshot| snapshot.tighten_step);
+        let strategy = previous.and_then(|snapshot| snapshot.strategy.clone());
+        let self_transfer = previous.is_some_and(|snapshot| snapshot.self_transfer);
+        let mint_risks = previous.map(|snapshot| snapshot.mint_risks.clone()).unwrap_or_default();
+        snapshots.insert(
+            mint_pubkey,
+            PositionSnapshot {
+                position_id,
+                token_program: token_program.clone(),
+                tokens: position_tokens,
+                cost_basis_units,
+                opened_at_ms,
+                stale_since_ms: None,
+                tighten_step,
+                strategy,
+                self_transfer,
+                mint_risks,
+            },
+        );
+        (cost_basis_units, opened_at_ms)
+    };
+    let held_ms = opened_at_ms.map(|opened| now_unix_ms().saturating_sub(opened));
+    let sell_deadline = remaining_deadline(opened_at_ms, deadline_timeout_sec, now_unix_ms())
+        .map(|remaining| tokio::time::Instant::now() + remaining);
 
-    if paused {
-        debug!(event = "app_exit_signal_skipped_paused", mint = %mint);
+    let mut in_flight = in_flight_auto_sells.lock().await;
+    if !in_flight.contains_key(&position_id) && fee_reserve.blocks_sell(mint_pubkey) {
         return Ok(());
     }
-
-    let mut in_flight = in_flight_auto_sells.lock().await;
+    let min_net = runtime_sell.read().min_net_proceeds_lamports;
+    let automatic = slippage_override_bps.is_none() && canonical_sell_reason(&reason) != "manual";
+    let sol_quoted = context_for_state.is_none_or(|context| context.quote == QuoteToken::Sol);
+    if min_net > 0 && automatic && sol_quoted && !in_flight.contains_key(&position_id) {
+        let tip = sell_rebuilder.tip_lamports.unwrap_or(0);
+        let net = expected_net_proceeds(cost_basis_units, profit_units, tip);
+        if let Some(net) = net.filter(|net| *net < i64::try_from(min_net).unwrap_or(i64::MAX)) {
+            info!(
+                event = "sell_skipped_below_floor",
+                mint = %mint,
+                position_id,
+                reason = %reason,
+                net_lamports = net,
+                min_net_proceeds_lamports = min_net,
+                "expected proceeds after fees are below sell.min_net_proceeds_lamports; \
+                 waiting for the next exit signal"
+            );
+            return Ok(());
+        }
+    }
     if let Some(existing_tx) = in_flight.get(&position_id) {
         debug!(event = "app_exit_signal_refreshing_inflight", position_id);
+        sell_timelines.note(&mint_pubkey, now_unix_ms(), TimelineEntry::SignalRefreshed);
         let _ = existing_tx.send(unsigned_tx_b64);
         return Ok(());
     }
@@ -510,6 +2370,25 @@ async fn process_exit_signal_with_tx(
     let stream_handle = stream_handle.clone();
     tokio::spawn(async move {
         let sell_reason = canonical_sell_reason(&reason).to_string();
+        let signal_received = tokio::time::Instant::now();
+        let stream_ms = triggered_at_ms.map(|at| now_unix_ms().saturating_sub(at));
+        // Sample the slot alongside the send, at background priority so it
+        // never takes a rate-limited RPC's turn from the sell; when the RPC
+        // is busy it may read a slot late.
+        let trigger_slot_task = {
+            let rpc_http = rpc_http.clone();
+            let rpc_url = rpc_url.clone();
+            let rpc_scheduler = rpc_scheduler.clone();
+            tokio::spawn(async move {
+                rpc_scheduler.acquire(RpcPriority::Background).await;
+                fetch_current_slot(&rpc_http, &rpc_url).await
+            })
+        };
+        let slot_rpc_http = rpc_http.clone();
+        let slot_rpc_url = rpc_url.clone();
+        let wallet_pubkey = Keypair::try_from(&keypair_bytes[..])
+            .map(|keypair| keypair.pubkey().to_string())
+            .unwrap_or_default();
 
         emit(AppEvent::SessionStarted { mint: mint_pubkey });
         emit(AppEvent::PositionTokensUpdated {
@@ -527,42 +2406,291 @@ async fn process_exit_signal_with_tx(
             reason: sell_reason.clone(),
             profit_lamports: profit_units,
         });
+        sell_timelines.begin(mint_pubkey, position_id, now_unix_ms());
+        sell_timelines.note(
+            &mint_pubkey,
+            now_unix_ms(),
+            TimelineEntry::SignalReceived {
+                reason: sell_reason.clone(),
+                profit_units,
+            },
+        );
 
-        let sell_cfg = runtime_sell.read().clone();
+        let mut refresh_rx = refresh_rx;
+        let mut unsigned_tx_b64 = unsigned_tx_b64;
+        let sell_slot = match sell_queue.as_ref() {
+            Some(queue) => {
+                let priority = SellPriority {
+                    stop_loss: sell_reason == "stop_loss",
+                    profit_units,
+                };
+                let slot = match queue.try_acquire() {
+                    Some(slot) => slot,
+                    None => {
+                        let depth = queue.depth() + 1;
+                        info!(
+                            event = "sell_queued",
+                            mint = %mint_pubkey,
+                            position_id,
+                            profit_units,
+                            queue_depth = depth
+                        );
+                        sell_timelines.note(
+                            &mint_pubkey,
+                            now_unix_ms(),
+                            TimelineEntry::Queued { depth },
+                        );
+                        let slot = queue.acquire(priority).await;
+                        // Signals that arrived while queued carry a fresher tx.
+                        while let Ok(newer) = refresh_rx.try_recv() {
+                            if !newer.trim().is_empty() {
+                                unsigned_tx_b64 = newer;
+                            }
+                        }
+                        slot
+                    }
+                };
+                Some(slot)
+            }
+            None => None,
+        };
+        let _sell_window = rpc_scheduler.begin_sell();
+        let mut sell_cfg = runtime_sell.read().clone();
+        if let Some(bps) = slippage_override_bps {
+            sell_cfg.slippage_pad_bps = bps;
+            sell_cfg.slippage_max_bps = sell_cfg.slippage_max_bps.max(bps);
+        } else {
+            let level = congestion.level();
+            let bump = level.slippage_bump_bps(sell_cfg.congestion_slippage_bump_bps);
+            if bump > 0 {
+                sell_cfg.slippage_pad_bps = sell_cfg
+                    .slippage_pad_bps
+                    .saturating_add(bump)
+                    .min(sell_cfg.slippage_max_bps);
+                info!(
+                    event = "sell_slippage_congestion_bump",
+                    mint = %mint_pubkey,
+                    level = level.label(),
+                    slippage_bps = sell_cfg.slippage_pad_bps
+                );
+            }
+        }
+        let mut rebuild = resolve_sell_output(sell_cfg.output, output_context.as_ref()).map(|output| {
+            sell_rebuilder.request(
+                mint.clone(),
+                position_tokens,
+                output,
+                sell_cfg.slippage_pad_bps,
+                output_context.clone(),
+            )
+        });
+        if let Some(request) = rebuild.as_ref() {
+            match sell_rebuilder.build_hedged(request, sell_cfg.hedge_request_ms).await {
+                Ok(built) => unsigned_tx_b64 = built.tx,
+                Err(err) => {
+                    warn!(event = "sell_output_rebuild_failed", mint = %mint_pubkey, error = format!("{err:#}"));
+                    rebuild = None;
+                }
+            }
+        }
+        // Stream refreshes are hedged with the same sell built by the exit
+        // API, paid out in the market's quote like the stream's.
+        let refresh = match rebuild {
+            Some(request) => RefreshRoute {
+                rebuilder: sell_rebuilder.clone(),
+                request,
+                via_stream: false,
+            },
+            None => RefreshRoute {
+                rebuilder: sell_rebuilder.clone(),
+                request: sell_rebuilder.request(
+                    mint.clone(),
+                    position_tokens,
+                    resolve_sell_output(SellOutputPreference::Quote, output_context.as_ref())
+                        .unwrap_or(SellOutput::Sol),
+                    sell_cfg.slippage_pad_bps,
+                    output_context.clone(),
+                ),
+                via_stream: true,
+            },
+        };
         let result = execute_auto_sell_with_refresh(
             stream_handle,
             refresh_rx,
             rpc_http,
             keypair_bytes,
             rpc_url,
+            rpc_scheduler.clone(),
             send_target,
             mint_pubkey,
             position_id,
             sell_cfg,
             unsigned_tx_b64,
+            sell_deadline,
+            refresh,
+            &sell_timelines,
         )
         .await;
+        // The sell has landed or given up; settling it must not hold up the
+        // next queued exit.
+        drop(sell_slot);
+        sell_timelines.note(
+            &mint_pubkey,
+            now_unix_ms(),
+            match &result {
+                Ok((signature, ..)) => TimelineEntry::Confirmed {
+                    signature: signature.clone(),
+                },
+                Err(err) => TimelineEntry::GaveUp {
+                    error: format!("{err:#}"),
+                },
+            },
+        );
 
         match result {
-            Ok((signature, slippage_bps)) => {
+            Ok((signature, slippage_bps, first_sent_at)) => {
+                if let Err(err) = completed_sells.record(
+                    position_id,
+                    &mint_pubkey,
+                    &sell_reason,
+                    position_tokens,
+                    &signature,
+                    now_unix_ms(),
+                ) {
+                    warn!(event = "completed_sells_write_failed", error = %format!("{err:#}"));
+                }
+                let elapsed_ms = signal_received.elapsed().as_millis() as u64;
+                let to_send_ms = first_sent_at
+                    .saturating_duration_since(signal_received)
+                    .as_millis() as u64;
+                let timing = SellTiming {
+                    stream_ms,
+                    to_send_ms,
+                    to_confirm_ms: elapsed_ms.saturating_sub(to_send_ms),
+                };
+                let metrics = latency_metrics();
+                metrics.record(timing);
+                let (send_p50, send_p95) = metrics.trigger_to_send().unzip();
+                let (confirm_p50, confirm_p95) = metrics.trigger_to_confirm().unzip();
+                info!(
+                    event = "sell_latency",
+                    mint = %mint_pubkey,
+                    stream_ms,
+                    trigger_to_send_ms = timing.trigger_to_send_ms(),
+                    trigger_to_confirm_ms = timing.trigger_to_confirm_ms(),
+                    signal_to_send_ms = to_send_ms,
+                    send_to_confirm_ms = timing.to_confirm_ms,
+                    samples = metrics.len(),
+                    send_p50_ms = send_p50,
+                    send_p95_ms = send_p95,
+                    confirm_p50_ms = confirm_p50,
+                    confirm_p95_ms = confirm_p95
+                );
+                let trigger_slot = match trigger_slot_task.await {
+                    Ok(Ok(slot)) => Some(slot),
+                    _ => None,
+                };
+                let settlement = settle_sell(
+                    &slot_rpc_http,
+                    &slot_rpc_url,
+                    &rpc_scheduler,
+                    &signature,
+                    &wallet_pubkey,
+                )
+                .await;
+                let confirm_slot = settlement.as_ref().map(|s| s.slot);
+                let proceeds_units = settlement.as_ref().map(|s| s.quote_received_units);
+                let execution_pnl_units =
+                    journal::execution_pnl(cost_basis_units, profit_units, proceeds_units);
+                let realized_pnl_units = proceeds_units
+                    .zip(cost_basis_units)
+                    .map(|(proceeds, cost)| proceeds - cost as i64);
                 info!(
-                    event = "sell_complete",
+                    event = "session_summary",
                     mint = %mint_pubkey,
+                    position_id,
                     signature = %signature,
                     reason = %sell_reason,
-                    slippage_bps
+                    slippage_bps,
+                    trigger_slot,
+                    confirm_slot,
+                    slot_delta = journal::slot_delta(trigger_slot, confirm_slot),
+                    elapsed_ms,
+                    signal_pnl_units = profit_units,
+                    execution_pnl_units,
+                    realized_pnl_units,
+                    fee_lamports = settlement.as_ref().map(|s| s.fee_lamports),
+                    priority_fee_lamports = settlement.as_ref().map(|s| s.priority_fee_lamports),
+                    tip_lamports = settlement.as_ref().map(|s| s.tip_lamports)
                 );
+                let self_transfer = position_snapshots
+                    .read()
+                    .get(&mint_pubkey)
+                    .is_some_and(|snapshot| snapshot.self_transfer);
+                if let Some(journal) = journal.as_ref() {
+                    let record = TradeRecord {
+                        closed_at_ms: now_unix_ms(),
+                        mint: mint_pubkey.to_string(),
+                        position_id,
+                        reason: sell_reason.clone(),
+                        signature: signature.clone(),
+                        slippage_bps,
+                        profit_units,
+                        trigger_slot,
+                        confirm_slot,
+                        elapsed_ms,
+                        cost_basis_units,
+                        proceeds_units,
+                        execution_pnl_units,
+                        fee_lamports: settlement.as_ref().map(|s| s.fee_lamports),
+                        priority_fee_lamports: settlement.as_ref().map(|s| s.priority_fee_lamports),
+                        tip_lamports: settlement.as_ref().map(|s| s.tip_lamports),
+                        paid_in_usd1: settlement
+                            .as_ref()
+                            .is_some_and(|s| s.received_usd1_units > 0),
+                        held_ms,
+                        self_transfer,
+                        swept_lamports: None,
+                    };
+                    if let Err(err) = journal.append(&record) {
+                        warn!(event = "journal_write_failed", error = %err);
+                    }
+                }
                 emit(AppEvent::SellComplete {
                     mint: mint_pubkey,
-                    signature,
+                    signature: signature.clone(),
                     reason: sell_reason,
                     slippage_bps,
+                    trigger_slot,
+                    confirm_slot,
                 });
+                if let Some(settlement) = settlement {
+                    emit(AppEvent::SellSettled {
+                        mint: mint_pubkey,
+                        signature,
+                        slot: settlement.slot,
+                        fee_lamports: settlement.fee_lamports,
+                        priority_fee_lamports: settlement.priority_fee_lamports,
+                        received_lamports: settlement.received_lamports,
+                        received_usd1_units: settlement.received_usd1_units,
+                        realized_pnl_units,
+                    });
+                }
                 emit(AppEvent::SessionClosed { mint: mint_pubkey });
                 position_snapshots.write().remove(&mint_pubkey);
                 market_contexts.write().remove(&mint_pubkey);
                 stream_states.write().remove(&mint_pubkey);
             }
+            Err(err) if err.is::<AccountFrozen>() => {
+                warn!(event = "account_frozen", mint = %mint_pubkey, position_id);
+                emit(AppEvent::SessionError {
+                    mint: mint_pubkey,
+                    error: err.to_string(),
+                });
+                if let Some(snapshot) = position_snapshots.write().get_mut(&mint_pubkey) {
+                    snapshot.stale_since_ms.get_or_insert_with(now_unix_ms);
+                }
+            }
             Err(err) => {
                 warn!(
                     event = "autosell_failed",
@@ -575,6 +2703,9 @@ async fn process_exit_signal_with_tx(
                     mint: mint_pubkey,
                     error: format!("{err:#}"),
                 });
+                if let Some(snapshot) = position_snapshots.write().get_mut(&mint_pubkey) {
+                    snapshot.stale_since_ms.get_or_insert_with(now_unix_ms);
+                }
             }
         }
 
@@ -584,23 +2715,62 @@ async fn process_exit_signal_with_tx(
     Ok(())
 }
 
+/// Looks up what a confirmed sell did to the wallet. `confirmed` can run a
+/// moment ahead of `getTransaction`, so a missing transaction is retried.
+async fn settle_sell(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    rpc_scheduler: &RpcScheduler,
+    signature: &str,
+    wallet: &str,
+) -> Option<SellSettlement> {
+    for attempt in 1..=SETTLEMENT_ATTEMPTS {
+        rpc_scheduler.acquire(RpcPriority::SellCritical).await;
+        match fetch_sell_settlement(http, rpc_url, signature, wallet).await {
+            Ok(Some(settlement)) => return Some(settlement),
+            Ok(None) => debug!(event = "sell_settlement_pending", signature, attempt),
+            Err(err) => {
+                debug!(event = "sell_settlement_fetch_failed", signature, attempt, error = %err)
+            }
+        }
+        if attempt < SETTLEMENT_ATTEMPTS {
+            tokio::time::sleep(Duration::from_millis(SETTLEMENT_RETRY_MS)).await;
+        }
+    }
+    warn!(
+        event = "sell_settlement_unavailable",
+        signature,
+        "realized PnL falls back to the signal estimate"
+    );
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn execute_auto_sell_with_refresh(
     stream_handle: Arc<StreamHandle>,
     mut refresh_rx: mpsc::UnboundedReceiver<String>,
     rpc_http: reqwest::Client,
     keypair_bytes: [u8; 64],
     rpc_url: String,
-    send_target: SendTarget,
+    rpc_scheduler: Arc<RpcScheduler>,
+    send_target: Arc<dyn SellSender>,
     mint: Pubkey,
     position_id: u64,
     sell_cfg: SellConfig,
     initial_unsigned_tx_b64: String,
-) -> Result<(String, u16)> {
+    deadline: Option<tokio::time::Instant>,
+    mut refresh: RefreshRoute,
+    timelines: &SellTimelines,
+) -> Result<(String, u16, tokio::time::Instant)> {
     let keypair = Keypair::try_from(&keypair_bytes[..]).context("decode keypair")?;
     let mut unsigned_tx_b64 = initial_unsigned_tx_b64;
+    let mut first_sent_at = None;
     let mut attempt = 1usize;
-    let mut refreshes_used = 0usize;
+    let mut policy = RetryPolicy::new(sell_cfg.max_retries);
+    let mut slippage_bumps = 0usize;
     let mut slippage_bps = sell_cfg.slippage_pad_bps;
+    // Set after a send failure: the next attempt sends the same signed tx.
+    let mut resend_tx: Option<VersionedTransaction> = None;
 
     loop {
         debug!(event = "app_autosell_attempt", mint = %mint, attempt, slippage_bps);
@@ -609,51 +2779,238 @@ async fn execute_auto_sell_with_refresh(
             attempt,
             slippage_bps,
         });
+        timelines.note(
+            &mint,
+            now_unix_ms(),
+            TimelineEntry::Attempt {
+                attempt,
+                slippage_bps,
+                resend: resend_tx.is_some(),
+            },
+        );
 
+        let attempt_started = tokio::time::Instant::now();
+        let confirm_timeout = attempt_confirm_timeout(
+            Duration::from_secs(sell_cfg.confirm_timeout_sec),
+            deadline.map(|deadline| deadline.saturating_duration_since(attempt_started)),
+        );
+        let resending = resend_tx.take();
+        let mut signed = None;
         let send_result = async {
-            let signed_tx = sign_unsigned_tx(&unsigned_tx_b64, &keypair)?;
+            if let Some(signed_tx) = resending {
+                let signed_tx = signed.insert(signed_tx);
+                return send_tx(
+                    &rpc_http,
+                    &rpc_url,
+                    signed_tx,
+                    send_target.as_ref(),
+                    confirm_timeout,
+                    &mut first_sent_at,
+                )
+                .await;
+            }
+            if sell_cfg.check_blockhash {
+                let unsigned = decode_unsigned_tx(&unsigned_tx_b64)?;
+                rpc_scheduler.acquire(RpcPriority::SellCritical).await;
+                if let Err(err) = check_blockhash(&rpc_http, &rpc_url, &unsigned).await {
+                    if err.is::<BlockhashExpired>() {
+                        return Err(err);
+                    }
+                    // Like the simulation, an unanswered check does not hold up the sell.
+                    debug!(event = "app_autosell_blockhash_check_unavailable", error = %err);
+                }
+            }
+            let signed_tx = signed.insert(sign_unsigned_tx(
+                &unsigned_tx_b64,
+                &keypair,
+                Intent::Sell,
+                Purpose::new("sell", Some(&mint)),
+            )?);
+            if sell_cfg.simulate_before_send {
+                rpc_scheduler.acquire(RpcPriority::SellCritical).await;
+                match simulate_tx(&rpc_http, &rpc_url, signed_tx).await {
+                    Ok(Some(error)) => return Err(SimulationRejected { error }.into()),
+                    Ok(None) => {}
+                    // A flaky simulation must not block the sell itself.
+                    Err(err) => debug!(event = "app_autosell_simulate_unavailable", error = %err),
+                }
+            }
             send_tx(
                 &rpc_http,
                 &rpc_url,
-                &signed_tx,
-                &send_target,
-                Duration::from_secs(sell_cfg.confirm_timeout_sec),
+                signed_tx,
+                send_target.as_ref(),
+                confirm_timeout,
+                &mut first_sent_at,
             )
             .await
         }
         .await;
 
         match send_result {
-            Ok(signature) => return Ok((signature, slippage_bps)),
+            Ok(signature) => {
+                let first_sent_at = first_sent_at.unwrap_or(attempt_started);
+                return Ok((signature, slippage_bps, first_sent_at));
+            }
             Err(err) => {
                 warn!(event = "app_autosell_attempt_failed", mint = %mint, attempt, error = format!("{err:#}"));
-                if refreshes_used >= sell_cfg.max_retries {
+                let class = FailureClass::of(&err);
+                let action = policy.next(class);
+                let failure = err.downcast_ref::<OnchainFailure>();
+                timelines.note(
+                    &mint,
+                    now_unix_ms(),
+                    TimelineEntry::Failed {
+                        attempt,
+                        class: class.label(),
+                        error: failure.map_or_else(|| format!("{err:#}"), |f| f.reason.clone()),
+                    },
+                );
+                // The token program says so when the sell lands; no other
+                // attempt can get past it.
+                if let Some(frozen) = onchain_error::account_frozen(&err, mint) {
+                    return Err(frozen.into());
+                }
+                if action == RetryAction::Abort {
+                    let why = if class == FailureClass::Fatal {
+                        "cannot succeed"
+                    } else {
+                        "out of retries"
+                    };
                     return Err(anyhow!(
-                        "autosell failed for position_id {position_id} after {attempt} attempts: {err:#}"
+                        "autosell failed for position_id {position_id} after {attempt} attempts ({why}; {}): {err:#}",
+                        policy.summary()
                     ));
                 }
+                if let Some(deadline) = deadline {
+                    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+                    let needed = match action {
+                        RetryAction::Resend => Duration::from_millis(AUTOSELL_MIN_CONFIRM_MS),
+                        _ => min_refresh_cycle(),
+                    };
+                    if remaining < needed {
+                        warn!(
+                            event = "deadline_exhausted",
+                            mint = %mint,
+                            position_id,
+                            attempt,
+                            remaining_ms = remaining.as_millis() as u64
+                        );
+                        return Err(anyhow!(
+                            "deadline_exhausted: no time left to retry position_id {position_id} after {attempt} attempts: {err:#}"
+                        ));
+                    }
+                }
                 emit(AppEvent::SellRetry {
                     mint,
                     attempt,
+                    elapsed_ms: attempt_started.elapsed().as_millis() as u64,
                     phase: classify_sell_retry_phase(&err).to_string(),
+                    class: class.label().to_string(),
+                    class_count: policy.count(class),
+                    reason: failure.map(|failure| failure.reason.clone()),
                     error: format!("{err:#}"),
                 });
 
-                slippage_bps = bumped_slippage_bps(slippage_bps, refreshes_used, &sell_cfg);
-                refreshes_used += 1;
+                let bump = match action {
+                    RetryAction::Refresh { bump } => bump,
+                    _ => {
+                        // Same tx, same blockhash: just put it on the wire again.
+                        debug!(event = "app_autosell_resend", mint = %mint, position_id, attempt);
+                        tokio::time::sleep(Duration::from_millis(RESEND_BACKOFF_MS)).await;
+                        resend_tx = signed;
+                        attempt += 1;
+                        continue;
+                    }
+                };
+                if bump {
+                    slippage_bps = bumped_slippage_bps(slippage_bps, slippage_bumps, &sell_cfg);
+                    slippage_bumps += 1;
+                }
+                // Drop any late answer to a previous hedged request; it carries stale slippage.
+                while refresh_rx.try_recv().is_ok() {}
                 debug!(event = "app_autosell_refresh_requested", mint = %mint, position_id, new_slippage_bps = slippage_bps);
-                stream_handle
-                    .request_exit_signal(position_id, Some(slippage_bps))
-                    .context("request sell refresh over stream")?;
-                unsigned_tx_b64 = recv_refreshed_sell_tx(&mut refresh_rx, position_id).await?;
+                timelines.note(&mint, now_unix_ms(), TimelineEntry::RefreshRequested { slippage_bps });
+                let refresh_started = tokio::time::Instant::now();
+                refresh.request.slippage_bps = slippage_bps;
+                let built = if refresh.via_stream {
+                    stream_handle
+                        .request_exit_signal(position_id, Some(slippage_bps))
+                        .context("request sell refresh over stream")?;
+                    recv_refreshed_sell_tx_hedged(
+                        &mut refresh_rx,
+                        &refresh,
+                        position_id,
+                        sell_cfg.hedge_request_ms,
+                    )
+                    .await?
+                } else {
+                    // Forced-output sells keep refreshing through the exit API.
+                    tokio::time::timeout(
+                        Duration::from_millis(AUTOSELL_REFRESH_TIMEOUT_MS),
+                        refresh.rebuilder.build_hedged(&refresh.request, sell_cfg.hedge_request_ms),
+                    )
+                    .await
+                    .map_err(|_| {
+                        anyhow!("timed out rebuilding sell tx (position_id={position_id})")
+                    })??
+                };
+                let latency_ms = refresh_started.elapsed().as_millis() as u64;
+                let (hedged, source) = (built.hedged, built.source.label());
+                emit(AppEvent::SellRefreshed {
+                    mint,
+                    attempt,
+                    latency_ms,
+                    hedged,
+                    source: source.to_string(),
+                });
+                timelines.note(
+                    &mint,
+                    now_unix_ms(),
+                    TimelineEntry::Refreshed { latency_ms, hedged, source },
+                );
+                let refreshed = built.tx;
+                unsigned_tx_b64 = refreshed;
                 attempt += 1;
             }
         }
     }
 }
 
+/// SOL a sell is expected to leave in the wallet: the position's cost basis
+/// plus the signal's profit, less the base fee and sender tip. `None` while
+/// the cost basis is unknown.
+fn expected_net_proceeds(
+    cost_basis_units: Option<u64>,
+    profit_units: i64,
+    tip_lamports: u64,
+) -> Option<i64> {
+    let cost_basis = i64::try_from(cost_basis_units?).ok()?;
+    let fees = BASE_FEE_LAMPORTS_PER_SIGNATURE.saturating_add(tip_lamports);
+    Some(
+        cost_basis
+            .saturating_add(profit_units)
+            .saturating_sub(i64::try_from(fees).unwrap_or(i64::MAX)),
+    )
+}
+
+/// Proceeds asset of a manual sell built through the exit API: the
+/// configured output, or the market's quote token on `auto`.
+fn manual_sell_output(preference: SellOutputPreference, quote: QuoteToken) -> SellOutput {
+    match (preference, quote) {
+        (SellOutputPreference::Sol, _) => SellOutput::Sol,
+        (SellOutputPreference::Usd1, _) => SellOutput::Usd1,
+        (_, QuoteToken::Usd1) => SellOutput::Usd1,
+        (_, QuoteToken::Sol) => SellOutput::Sol,
+    }
+}
+
 fn classify_sell_retry_phase(err: &anyhow::Error) -> &'static str {
-    if err.chain().any(|cause| {
+    if err.is::<BlockhashExpired>() {
+        "tx_blockhash"
+    } else if err.chain().any(|cause| cause.is::<SimulationRejected>()) {
+        "tx_simulate"
+    } else if err.chain().any(|cause| {
         matches!(
             cause.downcast_ref::<TxSubmitError>(),
             Some(TxSubmitError::ConfirmTimeout { .. } | TxSubmitError::TxFailed { .. })
@@ -675,19 +3032,67 @@ fn canonical_sell_reason(reason: &str) -> &str {
     }
 }
 
+/// Waits for a refreshed sell tx from the stream. If none has arrived after
+/// `hedge_ms`, builds the same sell through the exit API as well and takes
+/// whichever comes first.
+async fn recv_refreshed_sell_tx_hedged(
+    refresh_rx: &mut mpsc::UnboundedReceiver<String>,
+    refresh: &RefreshRoute,
+    position_id: u64,
+    hedge_ms: u64,
+) -> Result<Built> {
+    let from_stream = |tx, hedged| Built {
+        tx,
+        hedged,
+        source: TxSource::Stream,
+    };
+    let total = Duration::from_millis(AUTOSELL_REFRESH_TIMEOUT_MS);
+    let hedge_after = Duration::from_millis(hedge_ms);
+    if hedge_ms == 0 || hedge_after >= total {
+        let tx = recv_refreshed_sell_tx(refresh_rx, position_id, total).await?;
+        return Ok(from_stream(tx, false));
+    }
+    if let Ok(tx) = recv_refreshed_sell_tx(refresh_rx, position_id, hedge_after).await {
+        return Ok(from_stream(tx, false));
+    }
+    debug!(event = "app_autosell_refresh_hedged", position_id, hedge_ms);
+    let remaining = total - hedge_after;
+    let stream = recv_refreshed_sell_tx(refresh_rx, position_id, remaining);
+    let hedge = tokio::time::timeout(remaining, refresh.rebuilder.build(&refresh.request));
+    tokio::pin!(stream, hedge);
+    let from_exit_api = |tx| Built {
+        tx,
+        hedged: true,
+        source: TxSource::ExitApi,
+    };
+    tokio::select! {
+        result = &mut stream => match result {
+            Ok(tx) => Ok(from_stream(tx, true)),
+            Err(err) => match hedge.await {
+                Ok(built) => Ok(from_exit_api(built?)),
+                Err(_) => Err(err),
+            },
+        },
+        result = &mut hedge => match result {
+            Ok(Ok(tx)) => Ok(from_exit_api(tx)),
+            // The stream still has the rest of the window.
+            _ => Ok(from_stream(stream.await?, true)),
+        },
+    }
+}
+
 async fn recv_refreshed_sell_tx(
     refresh_rx: &mut mpsc::UnboundedReceiver<String>,
     position_id: u64,
+    wait: Duration,
 ) -> Result<String> {
+    let deadline = tokio::time::Instant::now() + wait;
     loop {
-        let next = tokio::time::timeout(
-            Duration::from_millis(AUTOSELL_REFRESH_TIMEOUT_MS),
-            refresh_rx.recv(),
-        )
-        .await
-        .map_err(|_| {
-            anyhow!("timed out waiting for refreshed sell tx (position_id={position_id})")
-        })?;
+        let next = tokio::time::timeout_at(deadline, refresh_rx.recv())
+            .await
+            .map_err(|_| {
+                anyhow!("timed out waiting for refreshed sell tx (position_id={position_id})")
+            })?;
         let payload =
             next.ok_or_else(|| anyhow!("sell refresh channel closed (position_id={position_id})"))?;
         let trimmed = payload.trim();
@@ -697,6 +3102,36 @@ async fn recv_refreshed_sell_tx(
     }
 }
 
+/// Time left until the position's deadline, if it has one that is still
+/// ahead. Sells triggered by the deadline itself get the flat retry budget.
+fn remaining_deadline(
+    opened_at_ms: Option<u64>,
+    deadline_timeout_sec: u64,
+    now_ms: u64,
+) -> Option<Duration> {
+    if deadline_timeout_sec == 0 {
+        return None;
+    }
+    let deadline_ms = opened_at_ms?.saturating_add(deadline_timeout_sec.saturating_mul(1_000));
+    let remaining_ms = deadline_ms.checked_sub(now_ms).filter(|ms| *ms > 0)?;
+    Some(Duration::from_millis(remaining_ms))
+}
+
+/// Shortest window worth spending on a refresh: waiting for the new tx plus
+/// a minimal confirm.
+fn min_refresh_cycle() -> Duration {
+    Duration::from_millis(AUTOSELL_REFRESH_TIMEOUT_MS + AUTOSELL_MIN_CONFIRM_MS)
+}
+
+fn attempt_confirm_timeout(configured: Duration, remaining: Option<Duration>) -> Duration {
+    match remaining {
+        Some(remaining) => configured
+            .min(remaining)
+            .max(Duration::from_millis(AUTOSELL_MIN_CONFIRM_MS)),
+        None => configured,
+    }
+}
+
 fn bumped_slippage_bps(current: u16, refreshes_used: usize, cfg: &SellConfig) -> u16 {
     let bump = if refreshes_used == 0 {
         cfg.slippage_retry_bump_bps_first
@@ -706,6 +3141,48 @@ fn bumped_slippage_bps(current: u16, refreshes_used: usize, cfg: &SellConfig) ->
     current.saturating_add(bump).min(cfg.slippage_max_bps)
 }
 
+/// Registers the wallet and opens a stream session for it.
+async fn connect_stream(
+    cfg: &Config,
+    keypair: &Keypair,
+    wallet_pubkey: Pubkey,
+) -> Result<(StreamHandle, mpsc::UnboundedReceiver<StreamEvent>)> {
+    let stream_send_mode = Some(cfg.send_mode_str().to_string());
+    let (watch_wallets, mirror_config) = if cfg.mirror.enabled {
+        (
+            build_watch_wallet_entries(&cfg.watch_wallets, &wallet_pubkey.to_string()),
+            build_mirror_config(&cfg.mirror),
+        )
+    } else {
+        (Vec::new(), None)
+    };
+    StreamClient::new(
+        cfg.account.api_key.clone(),
+        cfg.account.local,
+        wallet_pubkey.to_string(),
+        strategy_to_msg(&cfg.strategy),
+        cfg.strategy.deadline_timeout_sec,
+        stream_send_mode,
+        cfg.account.tip_lamports,
+        watch_wallets,
+        mirror_config,
+    )
+    .with_profile(cfg.active_profile())
+    .with_stall_timeout(cfg.stream.stall_timeout_sec)
+    .with_backup(cfg.stream.backup_url.as_deref())
+    .connect(keypair)
+    .await
+}
+
+fn sell_rebuilder_for(cfg: &Config, wallet_pubkey: Pubkey) -> Result<SellRebuilder> {
+    Ok(SellRebuilder {
+        client: crate::network::exit_api::exit_api_client(cfg)?,
+        user_pubkey: wallet_pubkey.to_string(),
+        send_mode: cfg.send_mode_str().to_string(),
+        tip_lamports: cfg.account.tip_lamports,
+    })
+}
+
 fn strategy_to_msg(strategy: &StrategyConfig) -> StrategyConfigMsg {
     let mut builder = StrategyConfigBuilder::new()
         .target_profit_pct(strategy.target_profit.percent_value())
@@ -781,6 +3258,146 @@ fn upsert_market_stream_state(
     }
 }
 
+/// Mints whose session has been stale for at least `min_age_ms` and has no
+/// sell in flight.
+fn stale_session_mints(
+    snapshots: &HashMap<Pubkey, PositionSnapshot>,
+    in_flight: &HashSet<u64>,
+    now_ms: u64,
+    min_age_ms: u64,
+) -> Vec<Pubkey> {
+    snapshots
+        .iter()
+        .filter(|(_, snapshot)| {
+            snapshot
+                .stale_since_ms
+                .is_some_and(|since| now_ms.saturating_sub(since) >= min_age_ms)
+                && !in_flight.contains(&snapshot.position_id)
+        })
+        .map(|(mint, _)| *mint)
+        .collect()
+}
+
+/// Limit sells armed before the last shutdown. An unreadable file is
+/// logged and the engine starts with none, leaving the file in place.
+fn load_limits() -> Limits {
+    match crate::util::paths::default_limits_path().and_then(Limits::load) {
+        Ok(limits) => limits,
+        Err(err) => {
+            warn!(event = "limits_unavailable", error = %err);
+            Limits::default()
+        }
+    }
+}
+
+/// Strategy insights dismissed before. An unreadable file is logged and
+/// none are hidden, leaving the file in place.
+fn load_insights() -> Insights {
+    match crate::util::paths::default_dismissed_insights_path().and_then(Insights::load) {
+        Ok(insights) => insights,
+        Err(err) => {
+            warn!(event = "insights_unavailable", error = %err);
+            Insights::default()
+        }
+    }
+}
+
+fn load_completed_sells() -> CompletedSells {
+    let loaded = crate::util::paths::default_completed_sells_path()
+        .and_then(|path| CompletedSells::load(path, now_unix_ms()));
+    match loaded {
+        Ok(completed) => completed,
+        Err(err) => {
+            warn!(event = "completed_sells_unavailable", error = %err);
+            CompletedSells::default()
+        }
+    }
+}
+
+/// Seeds snapshots from `lasersell import-positions` so positions opened
+/// with another tool carry a cost basis.
+fn seed_imported_positions(
+    position_snapshots: &ParkingRwLock<HashMap<Pubkey, PositionSnapshot>>,
+) {
+    let imported = match ImportStore::open_default().and_then(|store| store.load()) {
+        Ok(imported) => imported,
+        Err(err) => {
+            warn!(event = "imported_positions_unavailable", error = %err);
+            return;
+        }
+    };
+    let mut snapshots = position_snapshots.write();
+    for position in imported.values() {
+        let Ok(mint) = Pubkey::from_str(&position.mint) else {
+            continue;
+        };
+        snapshots.insert(
+            mint,
+            PositionSnapshot {
+                position_id: 0,
+                token_program: None,
+                tokens: position.tokens,
+                cost_basis_units: Some(position.cost_basis_units),
+                opened_at_ms: None,
+                stale_since_ms: None,
+                tighten_step: 0,
+                strategy: None,
+                self_transfer: false,
+                mint_risks: Vec::new(),
+            },
+        );
+        emit(AppEvent::CostBasisSet {
+            mint,
+            cost_basis_lamports: position.cost_basis_units,
+        });
+    }
+    if !imported.is_empty() {
+        info!(event = "imported_positions_loaded", count = imported.len());
+    }
+}
+
+/// Periodically reads what was appended to the journal and judges the last
+/// `analyzer::WINDOW` trades against the strategy in effect now. Each
+/// suggestion is surfaced when it appears, unless it was dismissed.
+fn spawn_journal_analyzer(
+    journal: Arc<Journal>,
+    strategy: Arc<ParkingRwLock<StrategyConfig>>,
+    insights: Arc<Insights>,
+) {
+    tokio::spawn(async move {
+        let mut recent = VecDeque::with_capacity(analyzer::WINDOW);
+        let mut offset = 0;
+        let mut ticker = tokio::time::interval(Duration::from_secs(JOURNAL_ANALYZE_SECS));
+        loop {
+            ticker.tick().await;
+            let (records, next) = match journal.read_from(offset) {
+                Ok(read) => read,
+                Err(err) => {
+                    debug!(event = "journal_read_failed", error = %err);
+                    continue;
+                }
+            };
+            if next < offset {
+                recent.clear();
+            }
+            offset = next;
+            for record in records {
+                if recent.len() == analyzer::WINDOW {
+                    recent.pop_front();
+                }
+                recent.push_back(record);
+            }
+            let found = analyzer::analyze(recent.make_contiguous(), &strategy.read());
+            for insight in insights.update(found) {
+                emit(AppEvent::StrategyInsight {
+                    id: insight.id.to_string(),
+                    message: insight.message,
+                });
+            }
+        }
+    });
+}
+
 /// Derive the Associated Token Account address for a wallet + mint.
 fn derive_ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
     // ATA PDA: seeds = [wallet, token_program, mint], program = ATA program
@@ -796,15 +3413,52 @@ fn derive_ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
     ata
 }
 
-fn spawn_wallet_balance_poller(rpc_http: reqwest::Client, rpc_url: String, wallet_pubkey: Pubkey) {
+/// Polls SOL and USD1 balances with one `getMultipleAccounts` call, skipping
+/// rounds while a sell is running so the exit path keeps the RPC budget.
+/// Each SOL balance is also checked against the fee reserve and offered to
+/// the treasury sweep.
+fn spawn_balance_poller(
+    rpc_http: reqwest::Client,
+    rpc_url: String,
+    wallet: Arc<ParkingRwLock<Pubkey>>,
+    rpc_scheduler: Arc<RpcScheduler>,
+    fee_reserve: Arc<FeeReserve>,
+    treasury: Option<Arc<TreasurySweeper>>,
+) {
     let poll = balance_poll_interval(&rpc_url);
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(poll);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
         loop {
             interval.tick().await;
-            match fetch_wallet_balance(&rpc_http, &rpc_url, &wallet_pubkey).await {
-                Ok(lamports) => {
+            if rpc_scheduler.sell_active() {
+                debug!(event = "balance_poll_deferred");
+                continue;
+            }
+            rpc_scheduler.acquire(RpcPriority::Background).await;
+            let wallet_pubkey = *wallet.read();
+            let usd1_ata = derive_ata(&wallet_pubkey, &usd1_mint());
+            match fetch_balances(&rpc_http, &rpc_url, &wallet_pubkey, &usd1_ata).await {
+                Ok((lamports, usd1_base_units)) => {
+                    fee_reserve.note_balance(lamports);
                     emit(AppEvent::BalanceUpdate { lamports });
+                    emit(AppEvent::Usd1BalanceUpdate {
+                        base_units: usd1_base_units,
+                    });
+                    if let Some(treasury) = treasury.as_ref() {
+                        treasury
+                            .after_balance(
+                                &rpc_http,
+                                &rpc_url,
+                                &rpc_scheduler,
+                                wallet_pubkey,
+                                lamports,
+                            )
+                            .await;
+                    }
+                }
+                Err(err) if rpc_health().is_degraded() => {
+                    debug!(event = "wallet_balance_fetch_error", error = %err);
                 }
                 Err(err) => {
                     warn!(event = "wallet_balance_fetch_error", error = %err);
@@ -814,75 +3468,74 @@ fn spawn_wallet_balance_poller(rpc_http: reqwest::Client, rpc_url: String, walle
     });
 }
 
-fn spawn_usd1_balance_poller(rpc_http: reqwest::Client, rpc_url: String, wallet_pubkey: Pubkey) {
-    let poll = balance_poll_interval(&rpc_url);
-    let usd1_ata = derive_ata(&wallet_pubkey, &usd1_mint());
+fn spawn_connection_warmer(
+    rpc_http: reqwest::Client,
+    rpc_url: String,
+    rpc_scheduler: Arc<RpcScheduler>,
+) {
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(poll);
+        let mut interval = tokio::time::interval(Duration::from_secs(CONNECTION_WARM_SECS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
         loop {
             interval.tick().await;
-            match fetch_usd1_balance(&rpc_http, &rpc_url, &usd1_ata).await {
-                Ok(base_units) => {
-                    emit(AppEvent::Usd1BalanceUpdate { base_units });
-                }
-                Err(err) => {
-                    warn!(event = "usd1_balance_fetch_error", error = %err);
-                }
+            if rpc_scheduler.sell_active() {
+                continue;
+            }
+            rpc_scheduler.acquire(RpcPriority::Background).await;
+            if let Err(err) = rpc_result(&rpc_http, &rpc_url, "getHealth", serde_json::json!([])).await
+            {
+                debug!(event = "connection_warm_failed", error = %err);
             }
         }
     });
 }
 
-async fn fetch_wallet_balance(
+/// Returns the wallet's lamports and its USD1 balance (0 when the token
+/// account does not exist yet).
+async fn fetch_balances(
     client: &reqwest::Client,
     rpc_url: &str,
     wallet_pubkey: &Pubkey,
-) -> Result<u64> {
+    usd1_ata: &Pubkey,
+) -> Result<(u64, u64)> {
     let result = rpc_result(
         client,
         rpc_url,
-        "getBalance",
+        "getMultipleAccounts",
         serde_json::json!([
-            wallet_pubkey.to_string(),
+            [wallet_pubkey.to_string(), usd1_ata.to_string()],
             {
-                "commitment": "processed"
+                "commitment": "processed",
+                "encoding": "jsonParsed"
             }
         ]),
     )
     .await?;
-
-    result
-        .get("value")
-        .and_then(|value| value.as_u64())
-        .ok_or_else(|| anyhow!("wallet balance missing"))
+    parse_balances(&result)
 }
 
-async fn fetch_usd1_balance(
-    client: &reqwest::Client,
-    rpc_url: &str,
-    ata: &Pubkey,
-) -> Result<u64> {
-    let result = rpc_result(
-        client,
-        rpc_url,
-        "getTokenAccountBalance",
-        serde_json::json!([ata.to_string()]),
-    )
-    .await;
-
-    match result {
-        Ok(value) => {
-            // getTokenAccountBalance returns { value: { amount: "123", decimals: 6, ... } }
-            value
-                .get("value")
-                .and_then(|v| v.get("amount"))
-                .and_then(|a| a.as_str())
-                .and_then(|s| s.parse::<u64>().ok())
-                .ok_or_else(|| anyhow!("usd1 token balance missing"))
-        }
-        Err(e) if e.to_string().contains("could not find account") => Ok(0),
-        Err(e) => Err(e),
-    }
+fn parse_balances(result: &serde_json::Value) -> Result<(u64, u64)> {
+    let accounts = result
+        .get("value")
+        .and_then(|value| value.as_array())
+        .ok_or_else(|| anyhow!("balance accounts missing"))?;
+    // A wallet that was never funded does not exist on-chain either.
+    let lamports = match accounts.first() {
+        Some(account) if !account.is_null() => account
+            .get("lamports")
+            .and_then(|lamports| lamports.as_u64())
+            .ok_or_else(|| anyhow!("wallet balance missing"))?,
+        _ => 0,
+    };
+    let usd1_base_units = match accounts.get(1) {
+        Some(account) if !account.is_null() => account
+            .pointer("/data/parsed/info/tokenAmount/amount")
+            .and_then(|amount| amount.as_str())
+            .and_then(|amount| amount.parse::<u64>().ok())
+            .ok_or_else(|| anyhow!("usd1 token balance missing"))?,
+        _ => 0,
+    };
+    Ok((lamports, usd1_base_units))
 }
 
 fn build_watch_wallet_entries(
@@ -933,7 +3586,26 @@ fn build_mirror_config(cfg: &crate::config::MirrorConfig) -> Option<MirrorConfig
 
 #[cfg(test)]
 mod tests {
-    use super::canonical_sell_reason;
+    use std::collections::{HashMap, HashSet};
+    use std::time::Duration;
+
+    use solana_sdk::pubkey::Pubkey;
+    use tokio::sync::mpsc;
+
+    use super::{
+        attempt_confirm_timeout, canonical_sell_reason, expected_net_proceeds, parse_balances,
+        recv_refreshed_sell_tx, remaining_deadline, stale_session_mints, PositionSnapshot,
+    };
+
+    #[test]
+    fn expected_net_proceeds_takes_off_fee_and_tip() {
+        assert_eq!(expected_net_proceeds(None, 1_000, 0), None);
+        assert_eq!(
+            expected_net_proceeds(Some(2_000_000), -500_000, 1_000_000),
+            Some(495_000)
+        );
+        assert_eq!(expected_net_proceeds(Some(1_000), -2_000, 0), Some(-6_000));
+    }
 
     #[test]
     fn canonical_sell_reason_normalizes_deadline_to_timeout() {
@@ -941,4 +3613,94 @@ mod tests {
         assert_eq!(canonical_sell_reason("deadline_timeout"), "timeout");
         assert_eq!(canonical_sell_reason("timeout"), "timeout");
     }
+
+    #[tokio::test]
+    async fn recv_refreshed_sell_tx_skips_blank_payloads_within_wait() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tx.send("  ".to_string()).unwrap();
+        tx.send(" tx-b64 ".to_string()).unwrap();
+        let payload = recv_refreshed_sell_tx(&mut rx, 1, Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(payload, "tx-b64");
+        assert!(recv_refreshed_sell_tx(&mut rx, 1, Duration::from_millis(10))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn remaining_deadline_only_applies_before_expiry() {
+        assert_eq!(remaining_deadline(Some(1_000), 0, 2_000), None);
+        assert_eq!(remaining_deadline(None, 30, 2_000), None);
+        assert_eq!(
+            remaining_deadline(Some(1_000), 30, 21_000),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(remaining_deadline(Some(1_000), 30, 31_000), None);
+    }
+
+    #[test]
+    fn confirm_timeout_shrinks_toward_deadline() {
+        let configured = Duration::from_secs(10);
+        assert_eq!(attempt_confirm_timeout(configured, None), configured);
+        assert_eq!(
+            attempt_confirm_timeout(configured, Some(Duration::from_secs(4))),
+            Duration::from_secs(4)
+        );
+        assert_eq!(
+            attempt_confirm_timeout(configured, Some(Duration::from_millis(10))),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn stale_sessions_skip_fresh_and_in_flight() {
+        let snapshot = |position_id, stale_since_ms| PositionSnapshot {
+            position_id,
+            token_program: None,
+            tokens: 0,
+            cost_basis_units: None,
+            opened_at_ms: None,
+            stale_since_ms,
+            tighten_step: 0,
+            strategy: None,
+            self_transfer: false,
+            mint_risks: Vec::new(),
+        };
+        let failed_long_ago = Pubkey::new_unique();
+        let failed_recently = Pubkey::new_unique();
+        let retrying = Pubkey::new_unique();
+        let healthy = Pubkey::new_unique();
+        let snapshots = HashMap::from([
+            (failed_long_ago, snapshot(1, Some(1_000))),
+            (failed_recently, snapshot(2, Some(9_500))),
+            (retrying, snapshot(3, Some(1_000))),
+            (healthy, snapshot(4, None)),
+        ]);
+        let in_flight = HashSet::from([3]);
+        assert_eq!(
+            stale_session_mints(&snapshots, &in_flight, 10_000, 5_000),
+            vec![failed_long_ago]
+        );
+        let mut all = stale_session_mints(&snapshots, &in_flight, 10_000, 0);
+        all.sort();
+        let mut expected = vec![failed_long_ago, failed_recently];
+        expected.sort();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn parse_balances_reads_wallet_and_usd1_accounts() {
+        let result = serde_json::json!({
+            "value": [
+                { "lamports": 1_500_000_000u64 },
+                { "lamports": 2_039_280, "data": { "parsed": { "info": {
+                    "tokenAmount": { "amount": "4200000", "decimals": 6 }
+                } } } }
+            ]
+        });
+        assert_eq!(parse_balances(&result).unwrap(), (1_500_000_000, 4_200_000));
+        let no_usd1 = serde_json::json!({ "value": [{ "lamports": 5 }, null] });
+        assert_eq!(parse_balances(&no_usd1).unwrap(), (5, 0));
+    }
 }
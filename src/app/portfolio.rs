@@ -0,0 +1,210 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+
+use super::sweep::{Priced, Sweeper, TokenAccount};
+use crate::market::{usd1_mint, QuoteToken};
+
+/// What a holding would sell for.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Valuation {
+    /// Quote units: the stream's quoted proceeds for a session, or the
+    /// amount itself for wrapped SOL and USD1.
+    Quoted { units: u64, quote: QuoteToken },
+    /// Lamports a simulated full sell to SOL would net.
+    Simulated(u64),
+    /// The exit API has no route, or the sell would fail on-chain.
+    Unsellable(String),
+    /// Pricing failed, or the session has no quote yet.
+    Unknown(String),
+}
+
+impl Valuation {
+    /// Lamports, for ordering; USD1 and unpriced holdings count as zero.
+    fn lamports(&self) -> u64 {
+        match self {
+            Valuation::Quoted {
+                units,
+                quote: QuoteToken::Sol,
+            }
+            | Valuation::Simulated(units) => *units,
+            _ => 0,
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            Valuation::Quoted { units, quote } => format!(
+                "{:.6} {}",
+                *units as f64 / 10u64.pow(quote.decimals()) as f64,
+                quote.symbol()
+            ),
+            Valuation::Simulated(lamports) => {
+                format!("~{:.6} SOL", *lamports as f64 / 1_000_000_000.0)
+            }
+            Valuation::Unsellable(reason) => format!("no sell ({reason})"),
+            Valuation::Unknown(reason) => format!("unknown ({reason})"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Holding {
+    pub(super) mint: Pubkey,
+    pub(super) amount: u64,
+    pub(super) session: bool,
+    pub(super) value: Valuation,
+}
+
+/// Every token the wallet holds, most valuable first, as last listed by
+/// `portfolio`. `portfolio sell <n>` refers to these rows.
+#[derive(Debug, Default)]
+pub(super) struct Portfolio {
+    holdings: Vec<Holding>,
+}
+
+impl Portfolio {
+    /// Lists the wallet's token accounts, one row per mint. Sessions are
+    /// valued from `sessions` (their quoted proceeds, if any); other tokens
+    /// through a simulated sell, like `sweep`.
+    pub(super) async fn fetch(
+        sweeper: &Sweeper,
+        sessions: &HashMap<Pubkey, Option<(u64, QuoteToken)>>,
+    ) -> Result<Self> {
+        let mut by_mint: BTreeMap<Pubkey, TokenAccount> = BTreeMap::new();
+        for account in sweeper.token_accounts().await? {
+            if account.amount == 0 {
+                continue;
+            }
+            by_mint
+                .entry(account.mint)
+                .and_modify(|held| held.amount = held.amount.saturating_add(account.amount))
+                .or_insert(account);
+        }
+        let mut holdings = Vec::with_capacity(by_mint.len());
+        for (mint, account) in by_mint {
+            let value = match sessions.get(&mint) {
+                Some(Some((units, quote))) => Valuation::Quoted {
+                    units: *units,
+                    quote: *quote,
+                },
+                Some(None) => Valuation::Unknown("no quote yet".to_string()),
+                None => valuation(sweeper, &account).await,
+            };
+            holdings.push(Holding {
+                mint,
+                amount: account.amount,
+                session: sessions.contains_key(&mint),
+                value,
+            });
+        }
+        Ok(Self::from_holdings(holdings))
+    }
+
+    fn from_holdings(mut holdings: Vec<Holding>) -> Self {
+        holdings.sort_by_key(|holding| Reverse(holding.value.lamports()));
+        Self { holdings }
+    }
+
+    /// The mint on row `n`, counting from 1.
+    pub(super) fn mint(&self, n: usize) -> Option<Pubkey> {
+        self.holdings
+            .get(n.checked_sub(1)?)
+            .map(|holding| holding.mint)
+    }
+
+    pub(super) fn render(&self) -> String {
+        if self.holdings.is_empty() {
+            return "the wallet holds no tokens\n".to_string();
+        }
+        let mut out = String::new();
+        for (n, holding) in self.holdings.iter().enumerate() {
+            let session = if holding.session { "  session" } else { "" };
+            let _ = writeln!(
+                out,
+                "{:>3}  {}  {:>20}  {}{session}",
+                n + 1,
+                holding.mint,
+                holding.amount,
+                holding.value.render()
+            );
+        }
+        let total: u64 = self
+            .holdings
+            .iter()
+            .map(|holding| holding.value.lamports())
+            .sum();
+        let _ = writeln!(
+            out,
+            "{} tokens, ~{:.6} SOL in total; `portfolio sell <n>` sells row n",
+            self.holdings.len(),
+            total as f64 / 1_000_000_000.0
+        );
+        out
+    }
+}
+
+async fn valuation(sweeper: &Sweeper, account: &TokenAccount) -> Valuation {
+    if account.mint == spl_token::native_mint::id() {
+        return Valuation::Quoted {
+            units: account.amount,
+            quote: QuoteToken::Sol,
+        };
+    }
+    if account.mint == usd1_mint() {
+        return Valuation::Quoted {
+            units: account.amount,
+            quote: QuoteToken::Usd1,
+        };
+    }
+    match sweeper.price(account).await {
+        Ok(Priced::Net(lamports)) => Valuation::Simulated(lamports),
+        Ok(Priced::Unsellable(reason)) => Valuation::Unsellable(reason),
+        Err(err) => Valuation::Unknown(format!("{err:#}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_numbered_by_value() {
+        let holding = |value| Holding {
+            mint: Pubkey::new_unique(),
+            amount: 1_000,
+            session: false,
+            value,
+        };
+        let usd1 = holding(Valuation::Quoted {
+            units: 2_500_000,
+            quote: QuoteToken::Usd1,
+        });
+        let small = holding(Valuation::Simulated(1_000_000));
+        let session = Holding {
+            session: true,
+            ..holding(Valuation::Quoted {
+                units: 500_000_000,
+                quote: QuoteToken::Sol,
+            })
+        };
+        let portfolio =
+            Portfolio::from_holdings(vec![usd1.clone(), small.clone(), session.clone()]);
+
+        assert_eq!(portfolio.mint(1), Some(session.mint));
+        assert_eq!(portfolio.mint(2), Some(small.mint));
+        assert_eq!(portfolio.mint(3), Some(usd1.mint));
+        assert_eq!(portfolio.mint(0), None);
+        assert_eq!(portfolio.mint(4), None);
+
+        let rendered = portfolio.render();
+        assert!(rendered.contains(&format!("  1  {}", session.mint)));
+        assert!(rendered.contains("1000  0.500000 SOL  session\n"));
+        assert!(rendered.contains("1000  2.500000 USD1\n"));
+        assert!(rendered
+            .ends_with("3 tokens, ~0.501000 SOL in total; `portfolio sell <n>` sells row n\n"));
+    }
+}
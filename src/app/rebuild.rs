@@ -0,0 +1,214 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use lasersell_sdk::exit_api::{BuildBuyTxRequest, BuildSellTxRequest, ExitApiClient, SellOutput};
+use lasersell_sdk::stream::proto::MarketContextMsg;
+use tracing::debug;
+
+use crate::config::SellOutputPreference;
+use crate::market::context_from_msg::quote_mint_from_msg;
+use crate::market::USD1_MINT;
+use crate::network::exit_api::{note_outcome, routed};
+
+/// Which request produced a sell tx, so hedges can be judged by how often
+/// they win.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum TxSource {
+    Stream,
+    ExitApi,
+    /// The duplicate exit API request fired by a hedge.
+    ExitApiHedge,
+}
+
+impl TxSource {
+    pub(super) fn label(self) -> &'static str {
+        match self {
+            Self::Stream => "stream",
+            Self::ExitApi => "exit_api",
+            Self::ExitApiHedge => "exit_api_hedge",
+        }
+    }
+}
+
+/// A sell tx, whether a hedge request was fired for it and which request
+/// it came from.
+pub(super) struct Built {
+    pub(super) tx: String,
+    pub(super) hedged: bool,
+    pub(super) source: TxSource,
+}
+
+/// Where an auto sell's refreshed txs come from: the exit API, or the
+/// stream with `request` sent to the exit API as the hedge.
+pub(super) struct RefreshRoute {
+    pub(super) rebuilder: Arc<SellRebuilder>,
+    /// Kept at the slippage of the latest refresh.
+    pub(super) request: BuildSellTxRequest,
+    pub(super) via_stream: bool,
+}
+
+/// Builds sell transactions through the exit API, for sells the stream
+/// cannot produce as configured (e.g. a forced output asset), and the
+/// console's manual buys.
+pub(super) struct SellRebuilder {
+    pub(super) client: ExitApiClient,
+    pub(super) user_pubkey: String,
+    pub(super) send_mode: String,
+    pub(super) tip_lamports: Option<u64>,
+}
+
+impl SellRebuilder {
+    pub(super) fn request(
+        &self,
+        mint: String,
+        amount_tokens: u64,
+        output: SellOutput,
+        slippage_bps: u16,
+        market_context: Option<MarketContextMsg>,
+    ) -> BuildSellTxRequest {
+        BuildSellTxRequest {
+            mint,
+            user_pubkey: self.user_pubkey.clone(),
+            amount_tokens,
+            output,
+            slippage_bps,
+            market_context,
+            send_mode: Some(self.send_mode.clone()),
+            tip_lamports: self.tip_lamports,
+            ..Default::default()
+        }
+    }
+
+    pub(super) async fn build(&self, request: &BuildSellTxRequest) -> Result<String> {
+        let response = routed(&self.client).build_sell_tx(request).await;
+        note_outcome(response.as_ref().err());
+        let response = response.context("build sell tx via exit API")?;
+        let tx = response.tx.trim();
+        if tx.is_empty() {
+            return Err(anyhow!("exit API returned an empty sell tx"));
+        }
+        Ok(tx.to_string())
+    }
+
+    /// A buy of `lamports` worth of `mint`, paid in SOL.
+    pub(super) async fn build_buy(
+        &self,
+        mint: String,
+        lamports: u64,
+        slippage_bps: u16,
+    ) -> Result<String> {
+        let request = BuildBuyTxRequest {
+            mint,
+            user_pubkey: self.user_pubkey.clone(),
+            amount_in_total: Some(lamports),
+            slippage_bps,
+            send_mode: Some(self.send_mode.clone()),
+            tip_lamports: self.tip_lamports,
+            ..Default::default()
+        };
+        let response = routed(&self.client).build_buy_tx(&request).await;
+        note_outcome(response.as_ref().err());
+        let response = response.context("build buy tx via exit API")?;
+        let tx = response.tx.trim();
+        if tx.is_empty() {
+            return Err(anyhow!("exit API returned an empty buy tx"));
+        }
+        Ok(tx.to_string())
+    }
+
+    /// Like [`Self::build`], but fires a duplicate request if the first has
+    /// not answered after `hedge_ms` and takes whichever lands first.
+    pub(super) async fn build_hedged(
+        &self,
+        request: &BuildSellTxRequest,
+        hedge_ms: u64,
+    ) -> Result<Built> {
+        let built = |tx, hedged, source| Built { tx, hedged, source };
+        if hedge_ms == 0 {
+            return Ok(built(self.build(request).await?, false, TxSource::ExitApi));
+        }
+        let primary = self.build(request);
+        tokio::pin!(primary);
+        tokio::select! {
+            result = &mut primary => return Ok(built(result?, false, TxSource::ExitApi)),
+            _ = tokio::time::sleep(Duration::from_millis(hedge_ms)) => {}
+        }
+        debug!(event = "app_exit_api_request_hedged", hedge_ms);
+        let hedge = self.build(request);
+        tokio::pin!(hedge);
+        tokio::select! {
+            result = &mut primary => match result {
+                Ok(tx) => Ok(built(tx, true, TxSource::ExitApi)),
+                Err(_) => Ok(built(hedge.await?, true, TxSource::ExitApiHedge)),
+            },
+            result = &mut hedge => match result {
+                Ok(tx) => Ok(built(tx, true, TxSource::ExitApiHedge)),
+                Err(_) => Ok(built(primary.await?, true, TxSource::ExitApi)),
+            },
+        }
+    }
+}
+
+/// The output asset to force on a sell, or `None` to keep the stream's tx.
+pub(super) fn resolve_sell_output(
+    preference: SellOutputPreference,
+    market_context: Option<&MarketContextMsg>,
+) -> Option<SellOutput> {
+    match preference {
+        SellOutputPreference::Auto => None,
+        SellOutputPreference::Sol => Some(SellOutput::Sol),
+        SellOutputPreference::Usd1 => Some(SellOutput::Usd1),
+        SellOutputPreference::Quote => {
+            let quote = market_context.and_then(quote_mint_from_msg);
+            Some(if quote == Some(USD1_MINT) {
+                SellOutput::Usd1
+            } else {
+                SellOutput::Sol
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lasersell_sdk::stream::proto::{MarketTypeMsg, RaydiumCpmmContextMsg};
+
+    use super::*;
+
+    fn cpmm_context(quote_mint: &str) -> MarketContextMsg {
+        MarketContextMsg {
+            market_type: MarketTypeMsg::RaydiumCpmm,
+            pumpfun: None,
+            pumpswap: None,
+            meteora_dbc: None,
+            meteora_damm_v2: None,
+            raydium_launchpad: None,
+            raydium_cpmm: Some(RaydiumCpmmContextMsg {
+                pool: "pool".to_string(),
+                config: "config".to_string(),
+                quote_mint: quote_mint.to_string(),
+                user_quote_account: "ata".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn quote_preference_follows_pool_quote_mint() {
+        let usd1_pool = cpmm_context(USD1_MINT);
+        let sol_pool = cpmm_context("So11111111111111111111111111111111111111112");
+        assert_eq!(
+            resolve_sell_output(SellOutputPreference::Quote, Some(&usd1_pool)),
+            Some(SellOutput::Usd1)
+        );
+        assert_eq!(
+            resolve_sell_output(SellOutputPreference::Quote, Some(&sol_pool)),
+            Some(SellOutput::Sol)
+        );
+        assert_eq!(
+            resolve_sell_output(SellOutputPreference::Quote, None),
+            Some(SellOutput::Sol)
+        );
+        assert_eq!(resolve_sell_output(SellOutputPreference::Auto, Some(&usd1_pool)), None);
+    }
+}
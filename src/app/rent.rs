@@ -0,0 +1,142 @@
+use std::fmt::Write as _;
+
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::network::{rpc_batch, RpcPriority, RpcScheduler};
+
+pub(super) const TOKEN_2022_PROGRAM: Pubkey =
+    Pubkey::from_str_const(crate::tx::policy::TOKEN_2022_PROGRAM);
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// SOL held as rent by the wallet's token accounts, and the part that
+/// closing empty accounts would hand back.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct RentReport {
+    pub(super) accounts: usize,
+    pub(super) locked_lamports: u64,
+    pub(super) empty_accounts: usize,
+    pub(super) reclaimable_lamports: u64,
+}
+
+impl RentReport {
+    /// Adds the accounts from one `getTokenAccountsByOwner` result.
+    fn add_accounts(&mut self, result: &serde_json::Value) -> Result<()> {
+        let accounts = result
+            .get("value")
+            .and_then(|value| value.as_array())
+            .ok_or_else(|| anyhow!("token accounts missing"))?;
+        for entry in accounts {
+            let lamports = entry
+                .pointer("/account/lamports")
+                .and_then(|lamports| lamports.as_u64())
+                .ok_or_else(|| anyhow!("token account lamports missing"))?;
+            let amount = entry
+                .pointer("/account/data/parsed/info/tokenAmount/amount")
+                .and_then(|amount| amount.as_str())
+                .and_then(|amount| amount.parse::<u64>().ok())
+                .ok_or_else(|| anyhow!("token account amount missing"))?;
+            self.accounts += 1;
+            self.locked_lamports += lamports;
+            if amount == 0 {
+                self.empty_accounts += 1;
+                self.reclaimable_lamports += lamports;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn render(&self) -> String {
+        let sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL;
+        let mut out = format!(
+            "rent locked   {:.6} SOL across {} token accounts\n",
+            sol(self.locked_lamports),
+            self.accounts
+        );
+        let _ = writeln!(
+            out,
+            "reclaimable   {:.6} SOL by closing {} empty accounts",
+            sol(self.reclaimable_lamports),
+            self.empty_accounts
+        );
+        out
+    }
+}
+
+/// Reads every SPL Token and Token-2022 account the wallet owns, both
+/// programs in one batched round trip.
+pub(super) async fn fetch_rent_report(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    rpc_scheduler: &RpcScheduler,
+    owner: &Pubkey,
+) -> Result<RentReport> {
+    let calls = token_accounts_calls(owner);
+    // Providers bill each call in a batch, so each takes a scheduler slot.
+    for _ in &calls {
+        rpc_scheduler.acquire(RpcPriority::Background).await;
+    }
+    let results = rpc_batch(client, rpc_url, &calls).await?;
+    let mut report = RentReport::default();
+    for result in &results {
+        report.add_accounts(result)?;
+    }
+    Ok(report)
+}
+
+/// `getTokenAccountsByOwner` for SPL Token then Token-2022, for `rpc_batch`.
+pub(super) fn token_accounts_calls(owner: &Pubkey) -> [(&'static str, serde_json::Value); 2] {
+    [spl_token::id(), TOKEN_2022_PROGRAM].map(|program| {
+        (
+            "getTokenAccountsByOwner",
+            serde_json::json!([
+                owner.to_string(),
+                { "programId": program.to_string() },
+                { "commitment": "confirmed", "encoding": "jsonParsed" }
+            ]),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(lamports: u64, amount: &str) -> serde_json::Value {
+        serde_json::json!({
+            "pubkey": Pubkey::new_unique().to_string(),
+            "account": {
+                "lamports": lamports,
+                "data": { "parsed": { "info": { "tokenAmount": { "amount": amount } } } }
+            }
+        })
+    }
+
+    #[test]
+    fn counts_empty_accounts_as_reclaimable() {
+        let mut report = RentReport::default();
+        report
+            .add_accounts(&serde_json::json!({
+                "value": [account(2_039_280, "0"), account(2_039_280, "1500")]
+            }))
+            .unwrap();
+        report
+            .add_accounts(&serde_json::json!({ "value": [account(2_074_080, "0")] }))
+            .unwrap();
+        assert_eq!(
+            report,
+            RentReport {
+                accounts: 3,
+                locked_lamports: 6_152_640,
+                empty_accounts: 2,
+                reclaimable_lamports: 4_113_360,
+            }
+        );
+        assert!(report
+            .render()
+            .contains("0.004113 SOL by closing 2 empty accounts"));
+        assert!(report
+            .add_accounts(&serde_json::json!({ "value": [{ "account": {} }] }))
+            .is_err());
+    }
+}
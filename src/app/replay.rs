@@ -0,0 +1,376 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::BufRead;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use lasersell_sdk::stream::proto::ServerMessage;
+
+use crate::config::StrategyConfig;
+use crate::market::context_from_msg::market_context_from_msg;
+use crate::market::QuoteToken;
+
+/// How one position left the market, in the recording or in the replay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Exit {
+    reason: String,
+    profit_units: i64,
+    held_ms: u64,
+}
+
+#[derive(Debug)]
+struct ReplayPosition {
+    mint: String,
+    entry_units: u64,
+    quote: QuoteToken,
+    opened_at_ms: Option<u64>,
+    peak_profit_units: i64,
+    last_profit_units: Option<i64>,
+    recorded: Option<Exit>,
+    simulated: Option<Exit>,
+}
+
+/// Backtests a strategy against a recording made with `run --record-stream`.
+/// The strategy is evaluated locally on every PnL sample and an exit fills
+/// at that sample's profit: nothing is signed or sent, so slippage, fees
+/// and landing delay are not modelled.
+#[derive(Debug)]
+pub(super) struct Replay {
+    strategy: StrategyConfig,
+    positions: BTreeMap<u64, ReplayPosition>,
+    messages: usize,
+    skipped_lines: usize,
+}
+
+impl Replay {
+    pub(super) fn new(strategy: StrategyConfig) -> Self {
+        Self {
+            strategy,
+            positions: BTreeMap::new(),
+            messages: 0,
+            skipped_lines: 0,
+        }
+    }
+
+    /// Feeds one recorded line; lines that are not a stream message are
+    /// counted and skipped.
+    pub(super) fn feed_line(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+        match serde_json::from_str::<ServerMessage>(line) {
+            Ok(msg) => self.feed(msg),
+            Err(_) => self.skipped_lines += 1,
+        }
+    }
+
+    fn feed(&mut self, msg: ServerMessage) {
+        self.messages += 1;
+        match msg {
+            ServerMessage::PositionOpened {
+                position_id,
+                mint,
+                entry_quote_units,
+                market_context,
+                opened_at_ms,
+                ..
+            } => {
+                let quote = market_context
+                    .as_ref()
+                    .map_or(QuoteToken::Sol, |ctx| market_context_from_msg(ctx).quote);
+                self.positions.entry(position_id).or_insert(ReplayPosition {
+                    mint,
+                    entry_units: entry_quote_units,
+                    quote,
+                    opened_at_ms,
+                    peak_profit_units: 0,
+                    last_profit_units: None,
+                    recorded: None,
+                    simulated: None,
+                });
+            }
+            ServerMessage::PnlUpdate {
+                position_id,
+                profit_units,
+                server_time_ms,
+                ..
+            } => self.sample(position_id, profit_units, server_time_ms),
+            ServerMessage::ExitSignalWithTx {
+                position_id,
+                profit_units,
+                reason,
+                triggered_at_ms,
+                ..
+            } => {
+                if let Some(position) = self.positions.get_mut(&position_id) {
+                    let opened = *position.opened_at_ms.get_or_insert(triggered_at_ms);
+                    position.recorded.get_or_insert(Exit {
+                        reason,
+                        profit_units,
+                        held_ms: triggered_at_ms.saturating_sub(opened),
+                    });
+                }
+                self.sample(position_id, profit_units, triggered_at_ms);
+            }
+            _ => {}
+        }
+    }
+
+    fn sample(&mut self, position_id: u64, profit_units: i64, at_ms: u64) {
+        let Some(position) = self.positions.get_mut(&position_id) else {
+            return;
+        };
+        position.last_profit_units = Some(profit_units);
+        if position.simulated.is_some() {
+            return;
+        }
+        let held_ms = at_ms.saturating_sub(*position.opened_at_ms.get_or_insert(at_ms));
+        position.peak_profit_units = position.peak_profit_units.max(profit_units);
+        let strategy = self
+            .strategy
+            .tightened(self.strategy.tighten_step_at(held_ms / 1_000));
+        if let Some(reason) = exit_reason(
+            &strategy,
+            position.entry_units,
+            profit_units,
+            position.peak_profit_units,
+            held_ms,
+        ) {
+            position.simulated = Some(Exit {
+                reason: reason.to_string(),
+                profit_units,
+                held_ms,
+            });
+        }
+    }
+
+    /// Per-position outcomes, recorded against simulated, and totals per
+    /// quote token. Positions the strategy never exited are marked to their
+    /// last PnL sample.
+    pub(super) fn render(&self) -> String {
+        let mut out = format!(
+            "replayed {} messages, {} positions",
+            self.messages,
+            self.positions.len()
+        );
+        if self.skipped_lines > 0 {
+            let _ = write!(out, " ({} unreadable lines skipped)", self.skipped_lines);
+        }
+        let _ = writeln!(
+            out,
+            "\nstrategy: target {}%, stop {}%, trail {}%, deadline {}s",
+            self.strategy.target_profit.percent_value(),
+            self.strategy.stop_loss.percent_value(),
+            self.strategy.trailing_stop.percent_value(),
+            self.strategy.deadline_timeout_sec
+        );
+        out.push_str(
+            "not simulated: take-profit levels, breakeven trail, graduation exits, slippage and fees\n",
+        );
+        if self.positions.is_empty() {
+            return out;
+        }
+        let _ = writeln!(
+            out,
+            "\n{:<12} {:>24} {:>24} {:>9}",
+            "mint", "recorded", "simulated", "held"
+        );
+        // quote -> (recorded sum, simulated sum, simulated wins, positions)
+        let mut totals: BTreeMap<&'static str, (i64, i64, usize, usize)> = BTreeMap::new();
+        for position in self.positions.values() {
+            let Some(last) = position.last_profit_units else {
+                continue;
+            };
+            let amount = |units: i64| format_units(units, position.quote);
+            let recorded = position.recorded.as_ref().map_or_else(
+                || "-".to_string(),
+                |exit| format!("{} {}", exit.reason, amount(exit.profit_units)),
+            );
+            let (simulated, simulated_units, held) = match &position.simulated {
+                Some(exit) => (
+                    format!("{} {}", exit.reason, amount(exit.profit_units)),
+                    exit.profit_units,
+                    format!("{:.1}s", exit.held_ms as f64 / 1_000.0),
+                ),
+                None => (format!("open {}", amount(last)), last, "-".to_string()),
+            };
+            let _ = writeln!(
+                out,
+                "{:<12} {:>24} {:>24} {:>9}",
+                short_mint(&position.mint),
+                recorded,
+                simulated,
+                held
+            );
+            let total = totals.entry(position.quote.symbol()).or_default();
+            total.0 += position
+                .recorded
+                .as_ref()
+                .map_or(0, |exit| exit.profit_units);
+            total.1 += simulated_units;
+            total.2 += usize::from(simulated_units > 0);
+            total.3 += 1;
+        }
+        out.push('\n');
+        for (symbol, (recorded, simulated, wins, count)) in totals {
+            let quote = if symbol == QuoteToken::Usd1.symbol() {
+                QuoteToken::Usd1
+            } else {
+                QuoteToken::Sol
+            };
+            let _ = writeln!(
+                out,
+                "{symbol:<5} recorded {}  simulated {}  ({wins}/{count} profitable)",
+                format_units(recorded, quote),
+                format_units(simulated, quote)
+            );
+        }
+        out
+    }
+}
+
+/// The first exit `strategy` takes at this sample. Percentages are of the
+/// entry cost; the trailing stop measures the drop from the peak profit.
+pub(super) fn exit_reason(
+    strategy: &StrategyConfig,
+    entry_units: u64,
+    profit_units: i64,
+    peak_profit_units: i64,
+    held_ms: u64,
+) -> Option<&'static str> {
+    let of_entry = |pct: f64| entry_units as f64 * pct / 100.0;
+    let profit = profit_units as f64;
+    if entry_units > 0 {
+        let target = strategy.target_profit.percent_value();
+        if target > 0.0 && profit >= of_entry(target) {
+            return Some("target");
+        }
+        let stop = strategy.stop_loss.percent_value();
+        if stop > 0.0 && -profit >= of_entry(stop) {
+            return Some("stop_loss");
+        }
+        let trail = strategy.trailing_stop.percent_value();
+        if trail > 0.0
+            && peak_profit_units > 0
+            && (peak_profit_units - profit_units) as f64 >= of_entry(trail)
+        {
+            return Some("trailing_stop");
+        }
+    }
+    let deadline_ms = strategy.deadline_timeout_sec.saturating_mul(1_000);
+    (deadline_ms > 0 && held_ms >= deadline_ms).then_some("timeout")
+}
+
+fn format_units(units: i64, quote: QuoteToken) -> String {
+    let scale = 10f64.powi(quote.decimals() as i32);
+    format!("{:+.4} {}", units as f64 / scale, quote.symbol())
+}
+
+fn short_mint(mint: &str) -> String {
+    if mint.len() > 10 {
+        format!("{}…{}", &mint[..4], &mint[mint.len() - 4..])
+    } else {
+        mint.to_string()
+    }
+}
+
+/// `lasersell replay <file>`: prints the backtest of `strategy` against a recording.
+pub fn run_replay_cli(path: &Path, strategy: StrategyConfig) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("open stream recording {}", path.display()))?;
+    let mut replay = Replay::new(strategy);
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("read stream recording {}", path.display()))?;
+        replay.feed_line(&line);
+    }
+    print!("{}", replay.render());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StrategyAmount;
+
+    fn strategy() -> StrategyConfig {
+        StrategyConfig {
+            target_profit: StrategyAmount::Percent(50.0),
+            stop_loss: StrategyAmount::Percent(20.0),
+            trailing_stop: StrategyAmount::Percent(0.0),
+            deadline_timeout_sec: 60,
+            sell_on_graduation: false,
+            take_profit_levels: Vec::new(),
+            liquidity_guard: false,
+            breakeven_trail: StrategyAmount::Percent(0.0),
+            tighten_after_sec: Vec::new(),
+            max_hold_sec: 0,
+        }
+    }
+
+    fn opened(position_id: u64) -> String {
+        serde_json::json!({
+            "type": "position_opened",
+            "position_id": position_id,
+            "wallet_pubkey": "wallet",
+            "mint": format!("Mint{position_id}xxxxxxxxxx"),
+            "token_account": "ata",
+            "tokens": 1_000,
+            "entry_quote_units": 1_000_000_000u64,
+            "opened_at_ms": 10_000,
+            "slot": 1
+        })
+        .to_string()
+    }
+
+    fn pnl(position_id: u64, profit_units: i64, at_ms: u64) -> String {
+        serde_json::json!({
+            "type": "pnl_update",
+            "position_id": position_id,
+            "profit_units": profit_units,
+            "proceeds_units": 0,
+            "server_time_ms": at_ms
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn exits_on_the_first_sample_past_a_threshold() {
+        let mut replay = Replay::new(strategy());
+        for line in [
+            opened(1),
+            pnl(1, 100_000_000, 11_000),
+            pnl(1, 600_000_000, 12_000),
+            pnl(1, 900_000_000, 13_000),
+            opened(2),
+            pnl(2, -250_000_000, 11_500),
+            opened(3),
+            pnl(3, 10_000_000, 75_000),
+            "not json".to_string(),
+        ] {
+            replay.feed_line(&line);
+        }
+        let exit = |id: u64| replay.positions[&id].simulated.clone().unwrap();
+        assert_eq!(exit(1).reason, "target");
+        assert_eq!(exit(1).profit_units, 600_000_000);
+        assert_eq!(exit(1).held_ms, 2_000);
+        assert_eq!(exit(2).reason, "stop_loss");
+        assert_eq!(exit(3).reason, "timeout");
+        assert_eq!(replay.skipped_lines, 1);
+        let report = replay.render();
+        assert!(
+            report.contains("SOL   recorded +0.0000 SOL  simulated +0.3600 SOL  (2/3 profitable)")
+        );
+    }
+
+    #[test]
+    fn trailing_stop_measures_the_drop_from_peak() {
+        let mut strategy = strategy();
+        strategy.target_profit = StrategyAmount::Percent(0.0);
+        strategy.trailing_stop = StrategyAmount::Percent(10.0);
+        assert_eq!(exit_reason(&strategy, 1_000, 250, 300, 0), None);
+        assert_eq!(
+            exit_reason(&strategy, 1_000, 200, 300, 0),
+            Some("trailing_stop")
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+use solana_sdk::pubkey::Pubkey;
+use tracing::{debug, info, warn};
+
+use crate::events::{emit, AppEvent};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+/// Stored until the first balance poll; an unknown balance never blocks.
+const UNKNOWN: u64 = u64::MAX;
+
+/// `risk.min_sol_reserve`: sells need SOL for the network fee, priority fee
+/// and tip, so none is started while the polled balance is below the
+/// reserve. Without it they fail at send time with an opaque error.
+#[derive(Debug)]
+pub(super) struct FeeReserve {
+    min_lamports: u64,
+    lamports: AtomicU64,
+    /// Mints whose sell was refused since the balance dropped below the
+    /// reserve, so each is reported once.
+    refused: Mutex<HashSet<Pubkey>>,
+}
+
+impl FeeReserve {
+    pub(super) fn new(min_sol: f64) -> Self {
+        Self {
+            min_lamports: (min_sol * LAMPORTS_PER_SOL).round() as u64,
+            lamports: AtomicU64::new(UNKNOWN),
+            refused: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The latest polled balance. Crossing the reserve either way is logged.
+    pub(super) fn note_balance(&self, lamports: u64) {
+        let previous = self.lamports.swap(lamports, Ordering::Relaxed);
+        let (was_low, low) = (self.is_low(previous), self.is_low(lamports));
+        if low && !was_low {
+            warn!(
+                event = "sol_reserve_low",
+                balance_lamports = lamports,
+                min_sol_reserve_lamports = self.min_lamports,
+                "SOL balance is below risk.min_sol_reserve; no sell will start until the \
+                 wallet is topped up"
+            );
+        } else if was_low && !low {
+            info!(event = "sol_reserve_restored", balance_lamports = lamports);
+            self.refused.lock().clear();
+        }
+    }
+
+    /// Forgets the balance after `wallet use`; the next poll reads the new
+    /// wallet's.
+    pub(super) fn reset(&self) {
+        self.lamports.store(UNKNOWN, Ordering::Relaxed);
+        self.refused.lock().clear();
+    }
+
+    /// The polled balance, when it is below the reserve.
+    pub(super) fn shortfall(&self) -> Option<u64> {
+        let lamports = self.lamports.load(Ordering::Relaxed);
+        self.is_low(lamports).then_some(lamports)
+    }
+
+    /// Whether a sell of `mint` must not start. The first refusal for each
+    /// mint is reported as a session error, so it stays under
+    /// `notifications`; repeated exit signals only log at debug.
+    pub(super) fn blocks_sell(&self, mint: Pubkey) -> bool {
+        let Some(lamports) = self.shortfall() else {
+            return false;
+        };
+        if self.refused.lock().insert(mint) {
+            emit(AppEvent::SessionError {
+                mint,
+                error: format!(
+                    "sell not started: SOL balance {:.4} is below risk.min_sol_reserve {:.4}",
+                    lamports as f64 / LAMPORTS_PER_SOL,
+                    self.min_lamports as f64 / LAMPORTS_PER_SOL
+                ),
+            });
+        } else {
+            debug!(event = "sell_refused_low_sol", mint = %mint, balance_lamports = lamports);
+        }
+        true
+    }
+
+    /// The `sessions` line while the balance is below the reserve.
+    pub(super) fn status(&self) -> Option<String> {
+        let lamports = self.shortfall()?;
+        Some(format!(
+            "SOL balance {:.4} is below risk.min_sol_reserve {:.4}: sells will not start",
+            lamports as f64 / LAMPORTS_PER_SOL,
+            self.min_lamports as f64 / LAMPORTS_PER_SOL
+        ))
+    }
+
+    fn is_low(&self, lamports: u64) -> bool {
+        lamports != UNKNOWN && lamports < self.min_lamports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_sells_only_while_the_polled_balance_is_short() {
+        let reserve = FeeReserve::new(0.01);
+        let mint = Pubkey::new_unique();
+        assert!(!reserve.blocks_sell(mint), "unknown balance");
+
+        reserve.note_balance(5_000_000);
+        assert_eq!(reserve.shortfall(), Some(5_000_000));
+        assert!(reserve.blocks_sell(mint));
+        assert!(reserve.blocks_sell(mint));
+        assert_eq!(
+            reserve.status().as_deref(),
+            Some("SOL balance 0.0050 is below risk.min_sol_reserve 0.0100: sells will not start")
+        );
+
+        reserve.note_balance(10_000_000);
+        assert!(!reserve.blocks_sell(mint));
+        assert!(reserve.refused.lock().is_empty());
+
+        reserve.note_balance(0);
+        reserve.reset();
+        assert_eq!(reserve.shortfall(), None);
+        assert!(!FeeReserve::new(0.0).is_low(0), "0 disables the reserve");
+    }
+}
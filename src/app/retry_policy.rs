@@ -0,0 +1,219 @@
+use lasersell_sdk::tx::TxSubmitError;
+
+use crate::tx::onchain_error::{FailureKind, OnchainFailure};
+use crate::tx::preflight::BlockhashExpired;
+use crate::tx::SimulationRejected;
+
+/// Times one signed tx is sent again after send failures before a fresh one
+/// is requested instead.
+const MAX_RESENDS_PER_TX: usize = 2;
+pub(super) const RESEND_BACKOFF_MS: u64 = 100;
+
+/// What a failed sell attempt says about the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum FailureClass {
+    /// The tx never reached the network: transport or RPC errors on send.
+    Send,
+    /// The tx can no longer land as built (expired blockhash, migrated
+    /// curve, unusable payload), but nothing points at the price.
+    Stale,
+    /// The price likely moved past the slippage: decoded slippage errors,
+    /// failed simulations, unexplained on-chain failures and confirm
+    /// timeouts.
+    Slippage,
+    /// Retrying cannot help: the wallet lacks funds, or an account is gone
+    /// or frozen.
+    Fatal,
+}
+
+impl FailureClass {
+    pub(super) fn of(err: &anyhow::Error) -> Self {
+        if err.is::<BlockhashExpired>() {
+            return FailureClass::Stale;
+        }
+        if let Some(failure) = err.downcast_ref::<OnchainFailure>() {
+            return match failure.kind {
+                FailureKind::Slippage | FailureKind::Other => FailureClass::Slippage,
+                FailureKind::CurveComplete => FailureClass::Stale,
+                FailureKind::InsufficientFunds
+                | FailureKind::AccountMissing
+                | FailureKind::AccountFrozen => FailureClass::Fatal,
+            };
+        }
+        if err.chain().any(|cause| cause.is::<SimulationRejected>()) {
+            return FailureClass::Slippage;
+        }
+        match err
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<TxSubmitError>())
+        {
+            Some(TxSubmitError::ConfirmTimeout { .. } | TxSubmitError::TxFailed { .. }) => {
+                FailureClass::Slippage
+            }
+            Some(
+                TxSubmitError::RequestSend { .. }
+                | TxSubmitError::ResponseRead { .. }
+                | TxSubmitError::HttpStatus { .. }
+                | TxSubmitError::DecodeResponse { .. }
+                | TxSubmitError::RpcError { .. }
+                | TxSubmitError::MissingResult { .. },
+            ) => FailureClass::Send,
+            _ => FailureClass::Stale,
+        }
+    }
+
+    pub(super) fn label(self) -> &'static str {
+        match self {
+            FailureClass::Send => "send",
+            FailureClass::Stale => "stale",
+            FailureClass::Slippage => "slippage",
+            FailureClass::Fatal => "fatal",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum RetryAction {
+    /// Send the same signed tx again after a short pause.
+    Resend,
+    /// Ask for a rebuilt tx, widening slippage when `bump` is set.
+    Refresh {
+        bump: bool,
+    },
+    Abort,
+}
+
+/// Retry state for one sell: failures seen per class, and the refreshes and
+/// resends still allowed. Only refreshes count against `sell.max_retries`.
+#[derive(Debug)]
+pub(super) struct RetryPolicy {
+    max_refreshes: usize,
+    refreshes: usize,
+    resends_of_tx: usize,
+    counts: [usize; 4],
+}
+
+impl RetryPolicy {
+    pub(super) fn new(max_refreshes: usize) -> Self {
+        Self {
+            max_refreshes,
+            refreshes: 0,
+            resends_of_tx: 0,
+            counts: [0; 4],
+        }
+    }
+
+    /// Records a failure of `class` and decides what to do about it.
+    pub(super) fn next(&mut self, class: FailureClass) -> RetryAction {
+        self.counts[class as usize] += 1;
+        let bump = match class {
+            FailureClass::Fatal => return RetryAction::Abort,
+            FailureClass::Send if self.resends_of_tx < MAX_RESENDS_PER_TX => {
+                self.resends_of_tx += 1;
+                return RetryAction::Resend;
+            }
+            FailureClass::Send | FailureClass::Stale => false,
+            FailureClass::Slippage => true,
+        };
+        if self.refreshes >= self.max_refreshes {
+            return RetryAction::Abort;
+        }
+        self.refreshes += 1;
+        self.resends_of_tx = 0;
+        RetryAction::Refresh { bump }
+    }
+
+    /// Failures of `class` so far.
+    pub(super) fn count(&self, class: FailureClass) -> usize {
+        self.counts[class as usize]
+    }
+
+    /// Failure counts by class, e.g. `send=2 slippage=1`.
+    pub(super) fn summary(&self) -> String {
+        [
+            FailureClass::Send,
+            FailureClass::Stale,
+            FailureClass::Slippage,
+            FailureClass::Fatal,
+        ]
+        .into_iter()
+        .filter(|class| self.count(*class) > 0)
+        .map(|class| format!("{}={}", class.label(), self.count(class)))
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_failures_resend_before_refreshing() {
+        let mut policy = RetryPolicy::new(3);
+        assert_eq!(policy.next(FailureClass::Send), RetryAction::Resend);
+        assert_eq!(policy.next(FailureClass::Send), RetryAction::Resend);
+        assert_eq!(
+            policy.next(FailureClass::Send),
+            RetryAction::Refresh { bump: false }
+        );
+        // A fresh tx gets its own resends.
+        assert_eq!(policy.next(FailureClass::Send), RetryAction::Resend);
+        assert_eq!(policy.summary(), "send=4");
+    }
+
+    #[test]
+    fn only_slippage_bumps_and_refreshes_are_capped() {
+        let mut policy = RetryPolicy::new(2);
+        assert_eq!(
+            policy.next(FailureClass::Stale),
+            RetryAction::Refresh { bump: false }
+        );
+        assert_eq!(
+            policy.next(FailureClass::Slippage),
+            RetryAction::Refresh { bump: true }
+        );
+        assert_eq!(policy.next(FailureClass::Slippage), RetryAction::Abort);
+        assert_eq!(policy.summary(), "stale=1 slippage=2");
+    }
+
+    #[test]
+    fn fatal_failures_abort_at_once() {
+        let mut policy = RetryPolicy::new(3);
+        assert_eq!(policy.next(FailureClass::Fatal), RetryAction::Abort);
+    }
+
+    #[test]
+    fn classifies_decoded_onchain_failures() {
+        let failure = |kind| {
+            anyhow::Error::from(TxSubmitError::TxFailed {
+                signature: "sig".to_string(),
+                error: "{}".to_string(),
+            })
+            .context(OnchainFailure {
+                kind,
+                reason: String::new(),
+            })
+        };
+        assert_eq!(
+            FailureClass::of(&failure(FailureKind::Slippage)),
+            FailureClass::Slippage
+        );
+        assert_eq!(
+            FailureClass::of(&failure(FailureKind::InsufficientFunds)),
+            FailureClass::Fatal
+        );
+        assert_eq!(
+            FailureClass::of(&failure(FailureKind::AccountFrozen)),
+            FailureClass::Fatal
+        );
+        assert_eq!(
+            FailureClass::of(&failure(FailureKind::CurveComplete)),
+            FailureClass::Stale
+        );
+        let timeout = anyhow::Error::from(TxSubmitError::ConfirmTimeout {
+            signature: "sig".to_string(),
+        });
+        assert_eq!(FailureClass::of(&timeout), FailureClass::Slippage);
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use solana_sdk::pubkey::Pubkey;
+
+/// Time-based exits checked on the client: a `sell-at` time per mint and
+/// `strategy.max_hold_sec` for every position. Each fires a manual sell
+/// once; a position whose sell failed is not sold again by the schedule.
+#[derive(Debug, Default)]
+pub(super) struct SellSchedule {
+    at_ms: HashMap<Pubkey, u64>,
+    fired: HashSet<Pubkey>,
+}
+
+impl SellSchedule {
+    pub(super) fn set(&mut self, mint: Pubkey, at_ms: u64) {
+        self.at_ms.insert(mint, at_ms);
+        self.fired.remove(&mint);
+    }
+
+    /// Returns whether a `sell-at` time was set for `mint`.
+    pub(super) fn clear(&mut self, mint: &Pubkey) -> bool {
+        self.at_ms.remove(mint).is_some()
+    }
+
+    /// When `mint` is due to be sold: the earlier of its `sell-at` time and
+    /// `max_hold_sec` after it opened.
+    pub(super) fn due_at(
+        &self,
+        mint: &Pubkey,
+        opened_at_ms: Option<u64>,
+        max_hold_sec: u64,
+    ) -> Option<u64> {
+        let max_hold = opened_at_ms
+            .filter(|_| max_hold_sec > 0)
+            .map(|opened| opened.saturating_add(max_hold_sec.saturating_mul(1_000)));
+        match (self.at_ms.get(mint).copied(), max_hold) {
+            (Some(at), Some(hold)) => Some(at.min(hold)),
+            (at, hold) => at.or(hold),
+        }
+    }
+
+    /// The open positions, `(mint, opened_at_ms)`, due by `now_ms` that have
+    /// not fired yet. Schedules of positions no longer open are dropped.
+    pub(super) fn take_due(
+        &mut self,
+        now_ms: u64,
+        open: &[(Pubkey, Option<u64>)],
+        max_hold_sec: u64,
+    ) -> Vec<Pubkey> {
+        let is_open = |mint: &Pubkey| open.iter().any(|(open_mint, _)| open_mint == mint);
+        self.at_ms.retain(|mint, _| is_open(mint));
+        self.fired.retain(|mint| is_open(mint));
+        let due: Vec<Pubkey> = open
+            .iter()
+            .filter(|(mint, opened_at_ms)| {
+                !self.fired.contains(mint)
+                    && self
+                        .due_at(mint, *opened_at_ms, max_hold_sec)
+                        .is_some_and(|due| due <= now_ms)
+            })
+            .map(|(mint, _)| *mint)
+            .collect();
+        self.fired.extend(due.iter().copied());
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fires_once_at_the_earlier_of_sell_at_and_max_hold() {
+        let (scheduled, held) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut schedule = SellSchedule::default();
+        schedule.set(scheduled, 50_000);
+        let open = [(scheduled, Some(10_000)), (held, Some(20_000))];
+        assert_eq!(schedule.due_at(&scheduled, Some(10_000), 60), Some(50_000));
+        assert_eq!(schedule.due_at(&held, Some(20_000), 60), Some(80_000));
+        assert_eq!(schedule.due_at(&held, Some(20_000), 0), None);
+
+        assert!(schedule.take_due(49_999, &open, 60).is_empty());
+        assert_eq!(schedule.take_due(50_000, &open, 60), vec![scheduled]);
+        assert!(schedule.take_due(60_000, &open, 60).is_empty());
+        assert_eq!(schedule.take_due(80_000, &open, 60), vec![held]);
+
+        // Once the position closes its schedule goes with it.
+        schedule.set(held, 90_000);
+        assert!(schedule.take_due(90_000, &open[..1], 60).is_empty());
+        assert!(!schedule.clear(&held));
+    }
+}
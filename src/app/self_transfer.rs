@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::network::{rpc_result, RpcPriority, RpcScheduler};
+
+/// Recent token account transactions checked for the transfer.
+const RECENT_SIGNATURES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum TransferDirection {
+    /// Tokens arrived from an own wallet (position opened without a buy).
+    In,
+    /// Tokens left for an own wallet (position closed without our sell).
+    Out,
+}
+
+impl TransferDirection {
+    pub(super) fn label(self) -> &'static str {
+        match self {
+            TransferDirection::In => "in",
+            TransferDirection::Out => "out",
+        }
+    }
+}
+
+/// Looks through the latest transactions on `token_account` for `mint`
+/// moving between `wallet` and one of `own_wallets`. Returns the other
+/// wallet and the transfer signature.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn find_self_transfer(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    rpc_scheduler: &RpcScheduler,
+    token_account: &str,
+    mint: &Pubkey,
+    wallet: &Pubkey,
+    own_wallets: &[Pubkey],
+    direction: TransferDirection,
+) -> Result<Option<(Pubkey, String)>> {
+    rpc_scheduler.acquire(RpcPriority::Background).await;
+    let signatures = rpc_result(
+        client,
+        rpc_url,
+        "getSignaturesForAddress",
+        serde_json::json!([
+            token_account,
+            { "limit": RECENT_SIGNATURES, "commitment": "confirmed" }
+        ]),
+    )
+    .await?;
+    let signatures = signatures
+        .as_array()
+        .ok_or_else(|| anyhow!("signatures missing"))?
+        .iter()
+        .filter(|entry| entry.get("err").is_none_or(|err| err.is_null()))
+        .filter_map(|entry| entry.get("signature")?.as_str().map(str::to_string));
+    for signature in signatures {
+        rpc_scheduler.acquire(RpcPriority::Background).await;
+        let tx = rpc_result(
+            client,
+            rpc_url,
+            "getTransaction",
+            serde_json::json!([
+                signature,
+                {
+                    "encoding": "jsonParsed",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0
+                }
+            ]),
+        )
+        .await?;
+        if let Some(counterparty) = transfer_counterparty(&tx, mint, wallet, own_wallets, direction)
+        {
+            return Ok(Some((counterparty, signature)));
+        }
+    }
+    Ok(None)
+}
+
+/// Net change in `mint` balance per owner across one transaction.
+fn owner_deltas(tx: &serde_json::Value, mint: &str) -> HashMap<String, i128> {
+    let mut deltas = HashMap::new();
+    for (field, sign) in [("postTokenBalances", 1), ("preTokenBalances", -1)] {
+        let Some(balances) = tx
+            .pointer(&format!("/meta/{field}"))
+            .and_then(|v| v.as_array())
+        else {
+            continue;
+        };
+        for balance in balances {
+            if balance.get("mint").and_then(|m| m.as_str()) != Some(mint) {
+                continue;
+            }
+            let (Some(owner), Some(amount)) = (
+                balance.get("owner").and_then(|o| o.as_str()),
+                balance
+                    .pointer("/uiTokenAmount/amount")
+                    .and_then(|a| a.as_str())
+                    .and_then(|a| a.parse::<i128>().ok()),
+            ) else {
+                continue;
+            };
+            *deltas.entry(owner.to_string()).or_insert(0) += sign * amount;
+        }
+    }
+    deltas
+}
+
+fn transfer_counterparty(
+    tx: &serde_json::Value,
+    mint: &Pubkey,
+    wallet: &Pubkey,
+    own_wallets: &[Pubkey],
+    direction: TransferDirection,
+) -> Option<Pubkey> {
+    let deltas = owner_deltas(tx, &mint.to_string());
+    let ours = deltas.get(&wallet.to_string()).copied().unwrap_or(0);
+    let moved = match direction {
+        TransferDirection::In => ours > 0,
+        TransferDirection::Out => ours < 0,
+    };
+    if !moved {
+        return None;
+    }
+    own_wallets.iter().copied().find(|own| {
+        deltas
+            .get(&own.to_string())
+            .is_some_and(|delta| delta.signum() == -ours.signum())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(mint: &Pubkey, owner: &Pubkey, amount: u64) -> serde_json::Value {
+        serde_json::json!({
+            "mint": mint.to_string(),
+            "owner": owner.to_string(),
+            "uiTokenAmount": { "amount": amount.to_string() }
+        })
+    }
+
+    #[test]
+    fn matches_transfers_to_own_wallets_only() {
+        let (mint, wallet, own, stranger) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        // Closing transfer: our account is gone afterwards.
+        let to_own = serde_json::json!({ "meta": {
+            "preTokenBalances": [balance(&mint, &wallet, 500)],
+            "postTokenBalances": [balance(&mint, &own, 500)]
+        }});
+        let out = TransferDirection::Out;
+        assert_eq!(
+            transfer_counterparty(&to_own, &mint, &wallet, &[own], out),
+            Some(own)
+        );
+        assert_eq!(
+            transfer_counterparty(&to_own, &mint, &wallet, &[own], TransferDirection::In),
+            None
+        );
+        let to_stranger = serde_json::json!({ "meta": {
+            "preTokenBalances": [balance(&mint, &wallet, 500), balance(&mint, &stranger, 0)],
+            "postTokenBalances": [balance(&mint, &wallet, 0), balance(&mint, &stranger, 500)]
+        }});
+        assert_eq!(
+            transfer_counterparty(&to_stranger, &mint, &wallet, &[own], out),
+            None
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+/// Admission order for queued sells: stop losses first (deepest loss
+/// first), then everything else by largest profit. Ties keep arrival order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct SellPriority {
+    pub(super) stop_loss: bool,
+    pub(super) profit_units: i64,
+}
+
+impl SellPriority {
+    fn cmp_urgency(&self, other: &Self) -> Ordering {
+        match (self.stop_loss, other.stop_loss) {
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (true, true) => other.profit_units.cmp(&self.profit_units),
+            (false, false) => self.profit_units.cmp(&other.profit_units),
+        }
+    }
+}
+
+struct Waiter {
+    priority: SellPriority,
+    seq: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp_urgency(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    running: usize,
+    next_seq: u64,
+    waiting: BinaryHeap<Waiter>,
+}
+
+/// Caps concurrent sells and admits waiting ones by [`SellPriority`].
+pub(super) struct SellQueue {
+    limit: usize,
+    state: Mutex<QueueState>,
+}
+
+/// Held while a sell executes; dropping it admits the next waiter.
+pub(super) struct SellSlot {
+    queue: Arc<SellQueue>,
+}
+
+impl SellQueue {
+    pub(super) fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            state: Mutex::new(QueueState::default()),
+        }
+    }
+
+    pub(super) fn depth(&self) -> usize {
+        self.state.lock().waiting.len()
+    }
+
+    /// Takes a slot if one is free and nobody is waiting for it.
+    pub(super) fn try_acquire(self: &Arc<Self>) -> Option<SellSlot> {
+        let mut state = self.state.lock();
+        if state.running < self.limit && state.waiting.is_empty() {
+            state.running += 1;
+            return Some(SellSlot {
+                queue: self.clone(),
+            });
+        }
+        None
+    }
+
+    /// Waits for a slot, behind any higher-priority waiters.
+    pub(super) async fn acquire(self: &Arc<Self>, priority: SellPriority) -> SellSlot {
+        let rx = {
+            let mut state = self.state.lock();
+            if state.running < self.limit && state.waiting.is_empty() {
+                state.running += 1;
+                return SellSlot {
+                    queue: self.clone(),
+                };
+            }
+            let (wake, rx) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter {
+                priority,
+                seq,
+                wake,
+            });
+            rx
+        };
+        // The releasing slot hands its place over before waking us.
+        let _ = rx.await;
+        SellSlot {
+            queue: self.clone(),
+        }
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock();
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        state.running = state.running.saturating_sub(1);
+    }
+}
+
+impl Drop for SellSlot {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn priority(stop_loss: bool, profit_units: i64) -> SellPriority {
+        SellPriority {
+            stop_loss,
+            profit_units,
+        }
+    }
+
+    #[tokio::test]
+    async fn admits_stop_losses_then_largest_profit() {
+        let queue = Arc::new(SellQueue::new(1));
+        let running = queue.try_acquire().expect("first slot is free");
+        assert!(queue.try_acquire().is_none());
+
+        let (order_tx, mut order_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut handles = Vec::new();
+        for (label, p) in [
+            ("small_profit", priority(false, 10)),
+            ("shallow_stop", priority(true, -5)),
+            ("big_profit", priority(false, 500)),
+            ("deep_stop", priority(true, -80)),
+        ] {
+            let waiter_queue = queue.clone();
+            let order_tx = order_tx.clone();
+            handles.push(tokio::spawn(async move {
+                let _slot = waiter_queue.acquire(p).await;
+                order_tx.send(label).unwrap();
+            }));
+            while queue.depth() < handles.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+        drop(running);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        let mut order = Vec::new();
+        while let Ok(label) = order_rx.try_recv() {
+            order.push(label);
+        }
+        assert_eq!(
+            order,
+            vec!["deep_stop", "shallow_stop", "big_profit", "small_profit"]
+        );
+        assert!(queue.try_acquire().is_some());
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::Write as _;
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::market::QuoteToken;
+
+/// How often the console is reminded that the stream is still down.
+pub(super) const DEGRADED_REMINDER_SECS: u64 = 30;
+/// Upper bound of the stream client's reconnect backoff.
+const RECONNECT_BACKOFF_MAX_SECS: u64 = 2;
+
+pub(super) struct SessionRow {
+    pub(super) mint: Pubkey,
+    pub(super) tokens: u64,
+    pub(super) quote: QuoteToken,
+    /// `(at_ms, profit_units)` from the last PnL update.
+    pub(super) last_pnl: Option<(u64, i64)>,
+    /// When `sell-at` or `strategy.max_hold_sec` sells it.
+    pub(super) sell_at_ms: Option<u64>,
+    /// Summary of the position's own strategy, when it has one.
+    pub(super) strategy: Option<String>,
+    /// Market state from the stream: quoted proceeds, when the pool last
+    /// traded and its liquidity trend.
+    pub(super) quoted_proceeds: Option<u64>,
+    pub(super) last_trade_ms: Option<u64>,
+    pub(super) liquidity_trend: Option<String>,
+    /// Why sells of this mint might revert, from reading the mint account.
+    pub(super) mint_risks: Vec<String>,
+}
+
+/// Open sessions as printed by `sessions`. While the stream is down the
+/// quotes are last-known values, labelled with their age.
+pub(super) struct SessionsView {
+    pub(super) stream_down_since_ms: Option<u64>,
+    pub(super) rows: Vec<SessionRow>,
+}
+
+impl SessionsView {
+    pub(super) fn render(&self, now_ms: u64) -> String {
+        let mut out = String::new();
+        if let Some(since) = self.stream_down_since_ms {
+            let _ = writeln!(
+                out,
+                "STREAM DOWN for {}; reconnecting, next attempt within {RECONNECT_BACKOFF_MAX_SECS}s",
+                format_age(now_ms.saturating_sub(since))
+            );
+            out.push_str(
+                "quotes are last-known unless marked as exit API quotes; `sell <mint>` still sells \
+                 via the exit API\n",
+            );
+        }
+        if self.rows.is_empty() {
+            out.push_str("no open sessions\n");
+            return out;
+        }
+        for row in &self.rows {
+            let _ = write!(out, "{}  {} tokens", row.mint, row.tokens);
+            if let Some(at_ms) = row.sell_at_ms {
+                let _ = write!(
+                    out,
+                    "  sells in {}",
+                    format_age(at_ms.saturating_sub(now_ms))
+                );
+            }
+            match row.last_pnl {
+                Some((at_ms, profit_units)) => {
+                    let stale = match self.stream_down_since_ms {
+                        Some(since) if at_ms >= since => " (exit API quote)",
+                        Some(_) => " (stale)",
+                        None => "",
+                    };
+                    let _ = writeln!(
+                        out,
+                        "  pnl {} {}  {} ago{stale}",
+                        format_signed_units(profit_units, row.quote),
+                        row.quote.symbol(),
+                        format_age(now_ms.saturating_sub(at_ms))
+                    );
+                }
+                None => out.push_str("  no quote yet\n"),
+            }
+            row.render_market(&mut out, now_ms);
+            if let Some(strategy) = &row.strategy {
+                let _ = writeln!(out, "    strategy {strategy}");
+            }
+            if !row.mint_risks.is_empty() {
+                let _ = writeln!(out, "    warning {}", row.mint_risks.join(", "));
+            }
+        }
+        out
+    }
+}
+
+impl SessionRow {
+    fn render_market(&self, out: &mut String, now_ms: u64) {
+        if self.quoted_proceeds.is_none()
+            && self.last_trade_ms.is_none()
+            && self.liquidity_trend.is_none()
+        {
+            return;
+        }
+        out.push_str("    market");
+        if let Some(proceeds) = self.quoted_proceeds {
+            let _ = write!(
+                out,
+                "  sells for {} {}",
+                format_units(proceeds, self.quote),
+                self.quote.symbol()
+            );
+        }
+        if let Some(at_ms) = self.last_trade_ms {
+            let _ = write!(
+                out,
+                "  last trade {} ago",
+                format_age(now_ms.saturating_sub(at_ms))
+            );
+        }
+        if let Some(trend) = &self.liquidity_trend {
+            let _ = write!(out, "  liquidity {trend}");
+        }
+        out.push('\n');
+    }
+}
+
+fn format_age(ms: u64) -> String {
+    let secs = ms / 1_000;
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3_599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3_600, secs % 3_600 / 60),
+    }
+}
+
+fn format_signed_units(units: i64, quote: QuoteToken) -> String {
+    let sign = if units < 0 { "-" } else { "+" };
+    format!("{sign}{}", format_units(units.unsigned_abs(), quote))
+}
+
+fn format_units(units: u64, quote: QuoteToken) -> String {
+    let decimals = quote.decimals();
+    let scale = 10u64.pow(decimals);
+    format!(
+        "{}.{:0width$}",
+        units / scale,
+        units % scale,
+        width = decimals as usize
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_quotes_stale_while_the_stream_is_down() {
+        let mint = Pubkey::new_unique();
+        let view = |down: Option<u64>| SessionsView {
+            stream_down_since_ms: down,
+            rows: vec![SessionRow {
+                mint,
+                tokens: 1_000,
+                quote: QuoteToken::Sol,
+                last_pnl: Some((10_000, -250_000_000)),
+                sell_at_ms: Some(75_000),
+                strategy: Some("tp 10% sl off trail off graduation off".to_string()),
+                quoted_proceeds: Some(750_000_000),
+                last_trade_ms: Some(13_000),
+                liquidity_trend: Some("draining".to_string()),
+                mint_risks: Vec::new(),
+            }],
+        };
+        let live = view(None).render(15_000);
+        assert!(live.contains("1000 tokens  sells in 1m 0s  pnl -0.250000000 SOL  5s ago\n"));
+        assert!(live.contains(
+            "5s ago\n    market  sells for 0.750000000 SOL  last trade 2s ago  liquidity draining\n"
+        ));
+        assert!(live.ends_with("\n    strategy tp 10% sl off trail off graduation off\n"));
+        assert!(!live.contains("STREAM DOWN"));
+
+        let mut risky = view(None);
+        risky.rows[0].mint_risks = vec![
+            "freeze authority enabled".to_string(),
+            "transfer fee 500 bps".to_string(),
+        ];
+        assert!(risky.render(15_000).ends_with(
+            "graduation off\n    warning freeze authority enabled, transfer fee 500 bps\n"
+        ));
+
+        let degraded = view(Some(12_000)).render(85_000);
+        assert!(degraded.starts_with("STREAM DOWN for 1m 13s;"));
+        assert!(degraded.contains("1m 15s ago (stale)\n"));
+
+        let mut requoted = view(Some(12_000));
+        requoted.rows[0].last_pnl = Some((80_000, -300_000_000));
+        assert!(requoted
+            .render(85_000)
+            .contains("5s ago (exit API quote)\n"));
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use anyhow::{anyhow, Result};
+
+use crate::config::{Config, StrategyAmount, StrategyConfig};
+use crate::util::format::parse_percent_to_bps;
+
+/// The onboarding strategy and sell fields, editable from the console.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum SettingsField {
+    TargetProfit,
+    StopLoss,
+    TrailingStop,
+    DeadlineTimeout,
+    SlippageMax,
+    SellOnGraduation,
+}
+
+impl SettingsField {
+    const ALL: [Self; 6] = [
+        Self::TargetProfit,
+        Self::StopLoss,
+        Self::TrailingStop,
+        Self::DeadlineTimeout,
+        Self::SlippageMax,
+        Self::SellOnGraduation,
+    ];
+
+    pub(super) fn parse(name: &str) -> Result<Self> {
+        let name = name.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|field| field.key() == name || field.alias() == name)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|field| field.alias()).collect();
+                anyhow!("unknown setting {name}; one of {}", names.join(", "))
+            })
+    }
+
+    pub(super) fn key(self) -> &'static str {
+        match self {
+            Self::TargetProfit => "target_profit",
+            Self::StopLoss => "stop_loss",
+            Self::TrailingStop => "trailing_stop",
+            Self::DeadlineTimeout => "deadline_timeout",
+            Self::SlippageMax => "slippage_max",
+            Self::SellOnGraduation => "sell_on_graduation",
+        }
+    }
+
+    fn alias(self) -> &'static str {
+        match self {
+            Self::TargetProfit => "tp",
+            Self::StopLoss => "sl",
+            Self::TrailingStop => "trail",
+            Self::DeadlineTimeout => "deadline",
+            Self::SlippageMax => "slip",
+            Self::SellOnGraduation => "graduation",
+        }
+    }
+
+    /// The SDK keeps the deadline timer for the session it opened, so a new
+    /// deadline only takes effect on restart.
+    fn needs_restart(self) -> bool {
+        self == Self::DeadlineTimeout
+    }
+
+    fn get(self, cfg: &Config) -> String {
+        match self {
+            Self::TargetProfit => percent(&cfg.strategy.target_profit),
+            Self::StopLoss => percent(&cfg.strategy.stop_loss),
+            Self::TrailingStop => percent(&cfg.strategy.trailing_stop),
+            Self::DeadlineTimeout => match cfg.strategy.deadline_timeout_sec {
+                0 => "off".to_string(),
+                sec => format!("{sec}s"),
+            },
+            Self::SlippageMax => format!("{}%", f64::from(cfg.sell.slippage_max_bps) / 100.0),
+            Self::SellOnGraduation => on_off(cfg.strategy.sell_on_graduation).to_string(),
+        }
+    }
+
+    fn set(self, cfg: &mut Config, raw: &str) -> Result<()> {
+        let off = matches!(raw.to_ascii_lowercase().as_str(), "off" | "0");
+        match self {
+            Self::TargetProfit | Self::StopLoss | Self::TrailingStop => {
+                let amount = if off {
+                    StrategyAmount::Percent(0.0)
+                } else {
+                    StrategyAmount::parse_str(raw)?
+                };
+                if amount.percent_value() < 0.0 {
+                    return Err(anyhow!("{} must be >= 0", self.key()));
+                }
+                match self {
+                    Self::TargetProfit => cfg.strategy.target_profit = amount,
+                    Self::StopLoss => cfg.strategy.stop_loss = amount,
+                    _ => cfg.strategy.trailing_stop = amount,
+                }
+            }
+            Self::DeadlineTimeout => {
+                cfg.strategy.deadline_timeout_sec = if off {
+                    0
+                } else {
+                    raw.trim_end_matches('s')
+                        .parse()
+                        .map_err(|_| anyhow!("deadline_timeout must be seconds, got {raw}"))?
+                };
+            }
+            Self::SlippageMax => {
+                cfg.sell.slippage_max_bps = parse_percent_to_bps(raw, self.key())?;
+            }
+            Self::SellOnGraduation => {
+                cfg.strategy.sell_on_graduation = match raw.to_ascii_lowercase().as_str() {
+                    "on" | "true" | "yes" => true,
+                    "off" | "false" | "no" => false,
+                    _ => return Err(anyhow!("sell_on_graduation must be on or off, got {raw}")),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// `set <setting> <value> for <mint>`: `base` with this field changed,
+    /// checked like the config. Only the exits the stream evaluates per
+    /// position can be overridden; the deadline and slippage are global.
+    pub(super) fn override_strategy(
+        self,
+        current: &Config,
+        base: &StrategyConfig,
+        raw: &str,
+    ) -> Result<StrategyConfig> {
+        if matches!(self, Self::DeadlineTimeout | Self::SlippageMax) {
+            return Err(anyhow!("{} cannot be set for one position", self.key()));
+        }
+        let mut next = current.clone();
+        next.strategy = base.clone();
+        self.set(&mut next, raw.trim())?;
+        next.validate()?;
+        Ok(next.strategy)
+    }
+
+    /// Copies this field from `from`, leaving the rest of `to` alone.
+    pub(super) fn copy(self, from: &Config, to: &mut Config) {
+        match self {
+            Self::TargetProfit => to.strategy.target_profit = from.strategy.target_profit.clone(),
+            Self::StopLoss => to.strategy.stop_loss = from.strategy.stop_loss.clone(),
+            Self::TrailingStop => to.strategy.trailing_stop = from.strategy.trailing_stop.clone(),
+            Self::DeadlineTimeout => {
+                to.strategy.deadline_timeout_sec = from.strategy.deadline_timeout_sec
+            }
+            Self::SlippageMax => to.sell.slippage_max_bps = from.sell.slippage_max_bps,
+            Self::SellOnGraduation => {
+                to.strategy.sell_on_graduation = from.strategy.sell_on_graduation
+            }
+        }
+    }
+}
+
+fn percent(amount: &StrategyAmount) -> String {
+    let value = amount.percent_value();
+    if value == 0.0 {
+        "off".to_string()
+    } else {
+        format!("{value}%")
+    }
+}
+
+/// One line for a position's own strategy, as shown by `sessions`.
+pub(super) fn strategy_summary(strategy: &StrategyConfig) -> String {
+    format!(
+        "tp {} sl {} trail {} graduation {}",
+        percent(&strategy.target_profit),
+        percent(&strategy.stop_loss),
+        percent(&strategy.trailing_stop),
+        on_off(strategy.sell_on_graduation)
+    )
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Settings staged with `set` and not yet applied. Every change is checked
+/// against the whole config as it is staged, so `settings apply` cannot
+/// fail validation.
+pub(super) struct SettingsDraft {
+    draft: Config,
+    changed: BTreeSet<SettingsField>,
+}
+
+impl SettingsDraft {
+    pub(super) fn new(current: &Config) -> Self {
+        Self {
+            draft: current.clone(),
+            changed: BTreeSet::new(),
+        }
+    }
+
+    /// Stages one value and returns its before and after, or why it was
+    /// refused.
+    pub(super) fn set(&mut self, field: SettingsField, raw: &str) -> Result<(String, String)> {
+        let mut next = self.draft.clone();
+        field.set(&mut next, raw.trim())?;
+        next.validate()?;
+        let before = field.get(&self.draft);
+        self.draft = next;
+        self.changed.insert(field);
+        Ok((before, field.get(&self.draft)))
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    pub(super) fn render(&self, current: &Config) -> String {
+        let mut out = format!("{:<32} {:<10} pending\n", "setting", "current");
+        for field in SettingsField::ALL {
+            let now = field.get(current);
+            let pending = field.get(&self.draft);
+            let pending = if pending == now {
+                "-".to_string()
+            } else {
+                pending
+            };
+            let _ = writeln!(
+                out,
+                "{:<32} {now:<10} {pending:<10}{}",
+                format!("{} ({})", field.key(), field.alias()),
+                if field.needs_restart() {
+                    " applies on restart"
+                } else {
+                    ""
+                }
+            );
+        }
+        out.push_str(if self.is_empty() {
+            "`set <setting> <value>` stages a change\n"
+        } else {
+            "`settings apply` to use and save the pending values, `settings cancel` to drop them\n"
+        });
+        out
+    }
+
+    pub(super) fn into_parts(self) -> (Config, BTreeSet<SettingsField>) {
+        (self.draft, self.changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        serde_yaml::from_str(
+            "account:\n  keypair_path: k.json\n  rpc_url: https://rpc.example.com\n  \
+             api_key: key\nstrategy:\n  target_profit: \"50%\"\n  stop_loss: \"10%\"\n  \
+             deadline_timeout: 0\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn stages_valid_values_and_refuses_invalid_ones() {
+        let current = config();
+        let mut draft = SettingsDraft::new(&current);
+        let tp = SettingsField::parse("tp").unwrap();
+        assert_eq!(
+            draft.set(tp, "6%").unwrap(),
+            ("50%".to_string(), "6%".to_string())
+        );
+        draft
+            .set(SettingsField::parse("slippage_max").unwrap(), "25%")
+            .unwrap();
+        assert!(draft.set(tp, "six").is_err());
+        assert!(SettingsField::parse("tip").is_err());
+
+        // Turning every exit off fails the config's own validation.
+        draft.set(tp, "off").unwrap();
+        assert!(draft.set(SettingsField::StopLoss, "off").is_err());
+
+        let (staged, changed) = draft.into_parts();
+        assert_eq!(staged.sell.slippage_max_bps, 2_500);
+        assert_eq!(staged.strategy.stop_loss.percent_value(), 10.0);
+        assert_eq!(
+            changed.into_iter().collect::<Vec<_>>(),
+            [SettingsField::TargetProfit, SettingsField::SlippageMax]
+        );
+    }
+
+    #[test]
+    fn overrides_one_position_without_touching_the_config() {
+        let current = config();
+        let tp = SettingsField::parse("tp").unwrap();
+        let strategy = tp
+            .override_strategy(&current, &current.strategy, "10%")
+            .unwrap();
+        assert_eq!(strategy.target_profit.percent_value(), 10.0);
+        assert_eq!(current.strategy.target_profit.percent_value(), 50.0);
+        assert_eq!(
+            strategy_summary(&strategy),
+            "tp 10% sl 10% trail off graduation off"
+        );
+        assert!(SettingsField::SlippageMax
+            .override_strategy(&current, &strategy, "20%")
+            .is_err());
+        let no_stop = SettingsField::StopLoss
+            .override_strategy(&current, &strategy, "off")
+            .unwrap();
+        assert!(tp.override_strategy(&current, &no_stop, "off").is_err());
+    }
+}
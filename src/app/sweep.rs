@@ -0,0 +1,564 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use lasersell_sdk::exit_api::{ExitApiError, SellOutput};
+use lasersell_sdk::tx::SendTarget;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use tracing::{info, warn};
+
+use super::rebuild::SellRebuilder;
+use super::rent::{token_accounts_calls, TOKEN_2022_PROGRAM};
+use crate::config::{Config, SweepConfig};
+use crate::market::usd1_mint;
+use crate::network::{rpc_batch, rpc_result, RpcPriority, RpcScheduler};
+use crate::tx::audit::Purpose;
+use crate::tx::policy::Intent;
+use crate::tx::senders::{self, SellSender};
+use crate::tx::{
+    fetch_latest_blockhash, send_tx, sign_local_tx, sign_unsigned_tx, simulate_lamports_after,
+    SimulationRejected,
+};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+/// Accounts burned and closed per transaction, well inside the size limit.
+const CLOSE_BATCH: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct TokenAccount {
+    pub(super) address: Pubkey,
+    pub(super) mint: Pubkey,
+    pub(super) program: Pubkey,
+    pub(super) amount: u64,
+    pub(super) decimals: u8,
+    pub(super) lamports: u64,
+    pub(super) frozen: bool,
+}
+
+/// Reads a `jsonParsed` `getTokenAccountsByOwner` result. Each account's
+/// program is the owner it names, or `program` when it names none.
+pub(super) fn parse_token_accounts(
+    result: &serde_json::Value,
+    program: Pubkey,
+) -> Result<Vec<TokenAccount>> {
+    let accounts = result
+        .get("value")
+        .and_then(|value| value.as_array())
+        .ok_or_else(|| anyhow!("token accounts missing"))?;
+    accounts
+        .iter()
+        .map(|entry| {
+            let pubkey = |pointer: &str| {
+                entry
+                    .pointer(pointer)
+                    .and_then(|value| value.as_str())
+                    .and_then(|value| Pubkey::from_str(value).ok())
+                    .ok_or_else(|| anyhow!("token account {pointer} missing"))
+            };
+            let info = "/account/data/parsed/info";
+            Ok(TokenAccount {
+                address: pubkey("/pubkey")?,
+                mint: pubkey(&format!("{info}/mint"))?,
+                program: pubkey("/account/owner").unwrap_or(program),
+                amount: entry
+                    .pointer(&format!("{info}/tokenAmount/amount"))
+                    .and_then(|amount| amount.as_str())
+                    .and_then(|amount| amount.parse().ok())
+                    .ok_or_else(|| anyhow!("token account amount missing"))?,
+                decimals: entry
+                    .pointer(&format!("{info}/tokenAmount/decimals"))
+                    .and_then(|decimals| decimals.as_u64())
+                    .and_then(|decimals| u8::try_from(decimals).ok())
+                    .ok_or_else(|| anyhow!("token account decimals missing"))?,
+                lamports: entry
+                    .pointer("/account/lamports")
+                    .and_then(|lamports| lamports.as_u64())
+                    .ok_or_else(|| anyhow!("token account lamports missing"))?,
+                frozen: entry
+                    .pointer(&format!("{info}/state"))
+                    .and_then(|state| state.as_str())
+                    == Some("frozen"),
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum SweepAction {
+    /// Already empty; closing it returns its rent.
+    Close,
+    /// Dust a sell still nets something for.
+    Sell { net_lamports: u64 },
+    /// Burned, then closed for its rent.
+    Burn { reason: String },
+    /// Left alone.
+    Keep { reason: String },
+}
+
+/// What pricing one account with a simulated full sell to SOL found.
+#[derive(Debug)]
+pub(super) enum Priced {
+    /// Lamports the wallet would gain, after fees and tips.
+    Net(u64),
+    /// The exit API has no route, or the sell would fail on-chain.
+    Unsellable(String),
+}
+
+fn classify(account: &TokenAccount, priced: Result<Priced>, cfg: &SweepConfig) -> SweepAction {
+    if account.frozen {
+        return SweepAction::Keep {
+            reason: "frozen by the mint".to_string(),
+        };
+    }
+    if account.amount == 0 {
+        return SweepAction::Close;
+    }
+    let dust_lamports = (cfg.dust_value_sol * LAMPORTS_PER_SOL) as u64;
+    match priced {
+        Ok(Priced::Net(net)) if net >= dust_lamports => SweepAction::Keep {
+            reason: format!("worth {:.6} SOL, above sweep.dust_value_sol", sol(net)),
+        },
+        Ok(Priced::Net(0)) => SweepAction::Burn {
+            reason: "a sell would not cover its fee".to_string(),
+        },
+        Ok(Priced::Net(net_lamports)) => SweepAction::Sell { net_lamports },
+        Ok(Priced::Unsellable(reason)) if cfg.burn_unsellable => SweepAction::Burn {
+            reason: format!("no sell: {reason}"),
+        },
+        Ok(Priced::Unsellable(reason)) => SweepAction::Keep {
+            reason: format!("no sell ({reason}); sweep.burn_unsellable burns these"),
+        },
+        Err(err) => SweepAction::Keep {
+            reason: format!("could not price: {err:#}"),
+        },
+    }
+}
+
+fn sol(lamports: u64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL
+}
+
+pub(super) struct SweepPlan {
+    items: Vec<(TokenAccount, SweepAction)>,
+}
+
+impl SweepPlan {
+    fn acting(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|(_, action)| !matches!(action, SweepAction::Keep { .. }))
+            .count()
+    }
+
+    pub(super) fn render(&self) -> String {
+        if self.items.is_empty() {
+            return "no token accounts to sweep\n".to_string();
+        }
+        let mut out = String::new();
+        let mut rent = 0;
+        for (account, action) in &self.items {
+            let what = match action {
+                SweepAction::Close => "close".to_string(),
+                SweepAction::Sell { net_lamports } => {
+                    format!("sell for ~{:.6} SOL, then close", sol(*net_lamports))
+                }
+                SweepAction::Burn { reason } => format!("burn and close ({reason})"),
+                SweepAction::Keep { reason } => format!("keep ({reason})"),
+            };
+            if !matches!(action, SweepAction::Keep { .. }) {
+                rent += account.lamports;
+            }
+            let _ = writeln!(out, "{}  {:>20}  {what}", account.mint, account.amount);
+        }
+        let _ = writeln!(
+            out,
+            "{} of {} accounts to sweep; closing them returns {:.6} SOL of rent",
+            self.acting(),
+            self.items.len(),
+            sol(rent)
+        );
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct SweepReport {
+    sold: usize,
+    sell_proceeds_lamports: u64,
+    burned: usize,
+    closed: usize,
+    reclaimed_lamports: u64,
+    failed: Vec<String>,
+}
+
+impl SweepReport {
+    pub(super) fn render(&self) -> String {
+        let mut out = format!(
+            "sold {} (~{:.6} SOL), burned {}, closed {} accounts for {:.6} SOL of rent\n",
+            self.sold,
+            sol(self.sell_proceeds_lamports),
+            self.burned,
+            self.closed,
+            sol(self.reclaimed_lamports)
+        );
+        for failure in &self.failed {
+            let _ = writeln!(out, "failed: {failure}");
+        }
+        out
+    }
+}
+
+/// Finds the wallet's dust token accounts and sells, burns or closes them.
+pub(super) struct Sweeper {
+    pub(super) rpc_http: reqwest::Client,
+    pub(super) rpc_url: String,
+    pub(super) rpc_scheduler: Arc<RpcScheduler>,
+    pub(super) send_target: Arc<dyn SellSender>,
+    pub(super) sell_rebuilder: Arc<SellRebuilder>,
+    pub(super) keypair: Keypair,
+    pub(super) config: SweepConfig,
+    pub(super) slippage_bps: u16,
+    pub(super) confirm_timeout: Duration,
+}
+
+impl Sweeper {
+    pub(super) async fn token_accounts(&self) -> Result<Vec<TokenAccount>> {
+        let calls = token_accounts_calls(&self.keypair.pubkey());
+        for _ in &calls {
+            self.rpc_scheduler.acquire(RpcPriority::Background).await;
+        }
+        let results = rpc_batch(&self.rpc_http, &self.rpc_url, &calls).await?;
+        let mut accounts = Vec::new();
+        for (result, program) in results.iter().zip([spl_token::id(), TOKEN_2022_PROGRAM]) {
+            accounts.extend(parse_token_accounts(result, program)?);
+        }
+        Ok(accounts)
+    }
+
+    /// Every token account except USD1, wrapped SOL and the mints in `skip`
+    /// (open sessions), each with what the sweep would do to it. Nothing is
+    /// signed or sent.
+    pub(super) async fn plan(&self, skip: &HashSet<Pubkey>) -> Result<SweepPlan> {
+        let usd1 = usd1_mint();
+        let mut items = Vec::new();
+        for account in self.token_accounts().await? {
+            if skip.contains(&account.mint)
+                || account.mint == usd1
+                || account.mint == spl_token::native_mint::id()
+            {
+                continue;
+            }
+            let priced = if account.amount == 0 || account.frozen {
+                Ok(Priced::Net(0))
+            } else {
+                self.price(&account).await
+            };
+            let action = classify(&account, priced, &self.config);
+            items.push((account, action));
+        }
+        Ok(SweepPlan { items })
+    }
+
+    /// A full sell of `amount` to SOL as the exit API builds it, unsigned.
+    async fn unsigned_sell_tx(&self, mint: &Pubkey, amount: u64) -> Result<String> {
+        let request = self.sell_rebuilder.request(
+            mint.to_string(),
+            amount,
+            SellOutput::Sol,
+            self.slippage_bps,
+            None,
+        );
+        self.sell_rebuilder.build(&request).await
+    }
+
+    pub(super) async fn price(&self, account: &TokenAccount) -> Result<Priced> {
+        self.quote(&account.mint, account.amount).await
+    }
+
+    /// What selling `amount` of `mint` to SOL would net, from simulating
+    /// the unsigned sell. The wallet key is not used.
+    pub(super) async fn quote(&self, mint: &Pubkey, amount: u64) -> Result<Priced> {
+        let tx = match self.unsigned_sell_tx(mint, amount).await {
+            Ok(tx) => tx,
+            Err(err) => {
+                return match err.downcast_ref::<ExitApiError>() {
+                    Some(api_err) if !api_err.is_retryable() => {
+                        Ok(Priced::Unsellable(api_err.to_string()))
+                    }
+                    _ => Err(err),
+                }
+            }
+        };
+        let wallet = self.keypair.pubkey().to_string();
+        self.rpc_scheduler.acquire(RpcPriority::Background).await;
+        let before = rpc_result(
+            &self.rpc_http,
+            &self.rpc_url,
+            "getBalance",
+            serde_json::json!([wallet, { "commitment": "processed" }]),
+        )
+        .await?
+        .get("value")
+        .and_then(|value| value.as_u64())
+        .ok_or_else(|| anyhow!("getBalance result missing"))?;
+        self.rpc_scheduler.acquire(RpcPriority::Background).await;
+        match simulate_lamports_after(&self.rpc_http, &self.rpc_url, &tx, &wallet).await {
+            Ok(after) => Ok(Priced::Net(after.saturating_sub(before))),
+            Err(err) => match err.downcast_ref::<SimulationRejected>() {
+                Some(rejected) => Ok(Priced::Unsellable(rejected.to_string())),
+                None => Err(err),
+            },
+        }
+    }
+
+    /// Carries out `plan`: sells first, then burns and closes in batches.
+    /// Accounts a sell emptied are closed too, unless the sell already did.
+    pub(super) async fn execute(&self, plan: SweepPlan) -> SweepReport {
+        let mut report = SweepReport::default();
+        let mut to_close = Vec::new();
+        let mut sold = HashSet::new();
+        for (account, action) in plan.items {
+            match action {
+                SweepAction::Sell { net_lamports } => match self.sell(&account).await {
+                    Ok(signature) => {
+                        info!(event = "sweep_sold", mint = %account.mint, signature);
+                        report.sold += 1;
+                        report.sell_proceeds_lamports += net_lamports;
+                        sold.insert(account.address);
+                    }
+                    Err(err) => report
+                        .failed
+                        .push(format!("sell {}: {err:#}", account.mint)),
+                },
+                SweepAction::Close | SweepAction::Burn { .. } => to_close.push(account),
+                SweepAction::Keep { .. } => {}
+            }
+        }
+        if !sold.is_empty() {
+            match self.token_accounts().await {
+                Ok(accounts) => to_close.extend(
+                    accounts
+                        .into_iter()
+                        .filter(|account| sold.contains(&account.address) && account.amount == 0),
+                ),
+                Err(err) => report
+                    .failed
+                    .push(format!("re-read sold accounts: {err:#}")),
+            }
+        }
+        for batch in to_close.chunks(CLOSE_BATCH) {
+            match self.burn_and_close(batch).await {
+                Ok(signature) => {
+                    info!(event = "sweep_closed", accounts = batch.len(), signature);
+                    report.burned += batch.iter().filter(|account| account.amount > 0).count();
+                    report.closed += batch.len();
+                    report.reclaimed_lamports +=
+                        batch.iter().map(|account| account.lamports).sum::<u64>();
+                }
+                Err(err) => {
+                    let mints: Vec<String> = batch
+                        .iter()
+                        .map(|account| account.mint.to_string())
+                        .collect();
+                    report
+                        .failed
+                        .push(format!("close {}: {err:#}", mints.join(", ")));
+                }
+            }
+        }
+        if !report.failed.is_empty() {
+            warn!(event = "sweep_partial", failures = report.failed.len());
+        }
+        info!(
+            event = "sweep_completed",
+            sold = report.sold,
+            burned = report.burned,
+            closed = report.closed,
+            reclaimed_lamports = report.reclaimed_lamports
+        );
+        report
+    }
+
+    /// Signs and sends a fresh sell; only `execute`, after the user has
+    /// confirmed the plan, gets here.
+    async fn sell(&self, account: &TokenAccount) -> Result<String> {
+        let unsigned = self.unsigned_sell_tx(&account.mint, account.amount).await?;
+        let tx = sign_unsigned_tx(
+            &unsigned,
+            &self.keypair,
+            Intent::Sell,
+            Purpose::new("dust_sell", Some(&account.mint)),
+        )?;
+        send_tx(
+            &self.rpc_http,
+            &self.rpc_url,
+            &tx,
+            self.send_target.as_ref(),
+            self.confirm_timeout,
+            &mut None,
+        )
+        .await
+    }
+
+    /// One transaction that burns what is left in each account and closes
+    /// it, returning the rent to the wallet. Token-2022 shares SPL Token's
+    /// burn and close encoding, so only the program id differs.
+    async fn burn_and_close(&self, accounts: &[TokenAccount]) -> Result<String> {
+        let owner = self.keypair.pubkey();
+        let mut instructions: Vec<Instruction> = Vec::with_capacity(accounts.len() * 2);
+        for account in accounts {
+            if account.amount > 0 {
+                let mut burn = spl_token::instruction::burn(
+                    &spl_token::id(),
+                    &account.address,
+                    &account.mint,
+                    &owner,
+                    &[],
+                    account.amount,
+                )?;
+                burn.program_id = account.program;
+                instructions.push(burn);
+            }
+            let mut close = spl_token::instruction::close_account(
+                &spl_token::id(),
+                &account.address,
+                &owner,
+                &owner,
+                &[],
+            )?;
+            close.program_id = account.program;
+            instructions.push(close);
+        }
+        self.rpc_scheduler.acquire(RpcPriority::Background).await;
+        let blockhash = fetch_latest_blockhash(&self.rpc_http, &self.rpc_url).await?;
+        let tx = sign_local_tx(
+            &instructions,
+            &self.keypair,
+            blockhash,
+            Purpose::new("burn_close", None),
+        );
+        // Nothing here races anyone, so it goes through the RPC rather than a
+        // tipped sender.
+        send_tx(
+            &self.rpc_http,
+            &self.rpc_url,
+            &tx,
+            &SendTarget::Rpc {
+                url: self.rpc_url.clone(),
+            },
+            self.confirm_timeout,
+            &mut None,
+        )
+        .await
+    }
+}
+
+/// `lasersell sweep`: shows the plan, asks, then sweeps and exits.
+pub async fn run_sweep_cli(cfg: Config, keypair: Keypair) -> Result<()> {
+    let sell = cfg.effective_sell();
+    let sweeper = Sweeper {
+        rpc_http: cfg
+            .http_client_builder()?
+            .connect_timeout(cfg.rpc_connect_timeout())
+            .timeout(cfg.rpc_request_timeout())
+            .build()?,
+        rpc_url: cfg.http_rpc_url(),
+        rpc_scheduler: Arc::new(RpcScheduler::new(cfg.rpc_requests_per_sec())),
+        send_target: senders::from_config(&cfg)?,
+        sell_rebuilder: Arc::new(super::sell_rebuilder_for(&cfg, keypair.pubkey())?),
+        keypair,
+        config: cfg.sweep.clone(),
+        slippage_bps: sell.slippage_max_bps,
+        confirm_timeout: Duration::from_secs(sell.confirm_timeout_sec),
+    };
+    // A running daemon's open sessions are not visible from here; anything
+    // below the dust threshold is swept regardless.
+    let plan = sweeper.plan(&HashSet::new()).await?;
+    print!("{}", plan.render());
+    if plan.acting() == 0 {
+        return Ok(());
+    }
+    if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        return Err(anyhow!("lasersell sweep asks before sending; run it in a terminal"));
+    }
+    let proceed = cliclack::confirm("Sweep these accounts? Burns cannot be undone.")
+        .initial_value(false)
+        .interact()
+        .unwrap_or(false);
+    if proceed {
+        print!("{}", sweeper.execute(plan).await.render());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_accounts_and_classifies_dust() {
+        let mint = Pubkey::new_unique();
+        let result = serde_json::json!({ "value": [{
+            "pubkey": Pubkey::new_unique().to_string(),
+            "account": {
+                "lamports": 2_039_280,
+                "data": { "parsed": { "info": {
+                    "mint": mint.to_string(),
+                    "state": "initialized",
+                    "tokenAmount": { "amount": "1500", "decimals": 6 }
+                } } }
+            }
+        }] });
+        let account = parse_token_accounts(&result, spl_token::id())
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            (account.mint, account.amount, account.frozen),
+            (mint, 1_500, false)
+        );
+
+        let cfg = SweepConfig::default();
+        let priced = |net| Ok(Priced::Net(net));
+        assert_eq!(
+            classify(&account, priced(1_000_000), &cfg),
+            SweepAction::Sell {
+                net_lamports: 1_000_000
+            }
+        );
+        assert!(matches!(
+            classify(&account, priced(5_000_000), &cfg),
+            SweepAction::Keep { .. }
+        ));
+        assert!(matches!(
+            classify(&account, priced(0), &cfg),
+            SweepAction::Burn { .. }
+        ));
+        let unsellable = || Ok(Priced::Unsellable("no route".to_string()));
+        assert!(matches!(
+            classify(&account, unsellable(), &cfg),
+            SweepAction::Keep { .. }
+        ));
+        let burning = SweepConfig {
+            burn_unsellable: true,
+            ..SweepConfig::default()
+        };
+        assert!(matches!(
+            classify(&account, unsellable(), &burning),
+            SweepAction::Burn { .. }
+        ));
+        assert!(matches!(
+            classify(&account, Err(anyhow!("timeout")), &burning),
+            SweepAction::Keep { .. }
+        ));
+        let empty = TokenAccount {
+            amount: 0,
+            ..account
+        };
+        assert_eq!(classify(&empty, priced(0), &cfg), SweepAction::Close);
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use parking_lot::Mutex;
+use solana_sdk::pubkey::Pubkey;
+
+const TRACKED_SESSIONS: usize = 200;
+const ENTRIES_PER_SESSION: usize = 100;
+
+/// One step of a sell, in the order the engine took it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum TimelineEntry {
+    SignalReceived {
+        reason: String,
+        profit_units: i64,
+    },
+    /// Another exit signal arrived while the sell was running; its tx
+    /// replaces the one being retried.
+    SignalRefreshed,
+    Queued {
+        depth: usize,
+    },
+    Attempt {
+        attempt: usize,
+        slippage_bps: u16,
+        resend: bool,
+    },
+    Failed {
+        attempt: usize,
+        class: &'static str,
+        error: String,
+    },
+    RefreshRequested {
+        slippage_bps: u16,
+    },
+    Refreshed {
+        latency_ms: u64,
+        hedged: bool,
+        source: &'static str,
+    },
+    Confirmed {
+        signature: String,
+    },
+    GaveUp {
+        error: String,
+    },
+}
+
+impl std::fmt::Display for TimelineEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelineEntry::SignalReceived {
+                reason,
+                profit_units,
+            } => write!(f, "exit signal: {reason}, profit {profit_units}"),
+            TimelineEntry::SignalRefreshed => f.write_str("newer exit signal replaced the tx"),
+            TimelineEntry::Queued { depth } => {
+                write!(f, "queued behind other sells (depth {depth})")
+            }
+            TimelineEntry::Attempt {
+                attempt,
+                slippage_bps,
+                resend,
+            } => {
+                let resend = if *resend {
+                    ", resending the same tx"
+                } else {
+                    ""
+                };
+                write!(f, "attempt {attempt} at {slippage_bps} bps{resend}")
+            }
+            TimelineEntry::Failed {
+                attempt,
+                class,
+                error,
+            } => write!(f, "attempt {attempt} failed ({class}): {error}"),
+            TimelineEntry::RefreshRequested { slippage_bps } => {
+                write!(f, "requested a fresh tx at {slippage_bps} bps")
+            }
+            TimelineEntry::Refreshed {
+                latency_ms,
+                hedged,
+                source,
+            } => {
+                let hedged = if *hedged { ", hedged" } else { "" };
+                write!(f, "fresh tx from {source} arrived in {latency_ms} ms{hedged}")
+            }
+            TimelineEntry::Confirmed { signature } => write!(f, "confirmed {signature}"),
+            TimelineEntry::GaveUp { error } => write!(f, "gave up: {error}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SellTimeline {
+    position_id: u64,
+    started_at_ms: u64,
+    entries: Vec<(u64, TimelineEntry)>,
+    finished: bool,
+}
+
+/// The decision and attempt timeline of the latest sell per mint, kept by
+/// the engine for `why <mint>`.
+#[derive(Debug, Default)]
+pub(super) struct SellTimelines {
+    sessions: Mutex<HashMap<Pubkey, SellTimeline>>,
+}
+
+impl SellTimelines {
+    /// Starts recording a sell of `position_id`, replacing any earlier
+    /// finished sell of the same mint.
+    pub(super) fn begin(&self, mint: Pubkey, position_id: u64, now_ms: u64) {
+        let mut sessions = self.sessions.lock();
+        if let Some(timeline) = sessions.get(&mint) {
+            if timeline.position_id == position_id && !timeline.finished {
+                return;
+            }
+        } else if sessions.len() >= TRACKED_SESSIONS {
+            let oldest = sessions
+                .iter()
+                .min_by_key(|(_, timeline)| timeline.started_at_ms)
+                .map(|(mint, _)| *mint);
+            if let Some(oldest) = oldest {
+                sessions.remove(&oldest);
+            }
+        }
+        sessions.insert(
+            mint,
+            SellTimeline {
+                position_id,
+                started_at_ms: now_ms,
+                entries: Vec::new(),
+                finished: false,
+            },
+        );
+    }
+
+    /// Appends `entry` to the mint's sell; ignored when none was begun.
+    pub(super) fn note(&self, mint: &Pubkey, now_ms: u64, entry: TimelineEntry) {
+        let mut sessions = self.sessions.lock();
+        let Some(timeline) = sessions.get_mut(mint) else {
+            return;
+        };
+        if matches!(
+            entry,
+            TimelineEntry::Confirmed { .. } | TimelineEntry::GaveUp { .. }
+        ) {
+            timeline.finished = true;
+        }
+        if timeline.entries.len() >= ENTRIES_PER_SESSION {
+            timeline.entries.remove(0);
+        }
+        timeline.entries.push((now_ms, entry));
+    }
+
+    /// Printable timeline for `mint`, or `None` if it has not been sold.
+    pub(super) fn render(&self, mint: &Pubkey) -> Option<String> {
+        let sessions = self.sessions.lock();
+        let timeline = sessions.get(mint)?;
+        let status = if timeline.finished {
+            "finished"
+        } else {
+            "in progress"
+        };
+        let mut out = format!(
+            "sell of {mint} (position {}, {status})\n",
+            timeline.position_id
+        );
+        for (at_ms, entry) in &timeline.entries {
+            let offset = at_ms.saturating_sub(timeline.started_at_ms) as f64 / 1_000.0;
+            let _ = writeln!(out, "  +{offset:>7.3}s  {entry}");
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_one_sell_and_restarts_after_it_finishes() {
+        let timelines = SellTimelines::default();
+        let mint = Pubkey::new_unique();
+        timelines.begin(mint, 7, 1_000);
+        timelines.note(
+            &mint,
+            1_000,
+            TimelineEntry::SignalReceived {
+                reason: "target".to_string(),
+                profit_units: 42,
+            },
+        );
+        // A second signal for the running sell keeps its timeline.
+        timelines.begin(mint, 7, 1_100);
+        timelines.note(
+            &mint,
+            1_400,
+            TimelineEntry::Failed {
+                attempt: 1,
+                class: "slippage",
+                error: "pump.fun slippage".to_string(),
+            },
+        );
+        timelines.note(
+            &mint,
+            2_250,
+            TimelineEntry::Confirmed {
+                signature: "5ig".to_string(),
+            },
+        );
+        let rendered = timelines.render(&mint).unwrap();
+        assert!(rendered.starts_with(&format!("sell of {mint} (position 7, finished)\n")));
+        assert!(rendered.contains("  +  0.400s  attempt 1 failed (slippage): pump.fun slippage\n"));
+        assert!(rendered.ends_with("  +  1.250s  confirmed 5ig\n"));
+
+        timelines.begin(mint, 7, 5_000);
+        assert_eq!(
+            timelines.render(&mint).unwrap(),
+            format!("sell of {mint} (position 7, in progress)\n")
+        );
+        assert!(timelines.render(&Pubkey::new_unique()).is_none());
+    }
+}
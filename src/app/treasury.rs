@@ -0,0 +1,187 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use lasersell_sdk::tx::SendTarget;
+use parking_lot::{Mutex, RwLock as ParkingRwLock};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_system_interface::instruction as system_instruction;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::events::{emit, AppEvent};
+use crate::journal::{Journal, TradeRecord};
+use crate::network::{RpcPriority, RpcScheduler};
+use crate::tx::audit::Purpose;
+use crate::tx::{fetch_latest_blockhash, send_tx, sign_local_tx};
+use crate::util::clock::now_unix_ms;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+/// Left behind for the transfer's own signature fee.
+const TRANSFER_FEE_LAMPORTS: u64 = 5_000;
+/// Smaller excesses wait for the next poll rather than paying a fee each.
+const MIN_SWEEP_LAMPORTS: u64 = 10_000_000;
+/// Pause after a failed sweep, so a transfer that confirms late is seen in
+/// the balance before another is sent.
+const RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Lamports to move out of a wallet holding `balance` so that `keep` stays
+/// after the fee, or `None` when the excess is too small to bother.
+fn excess_lamports(balance: u64, keep: u64) -> Option<u64> {
+    let excess = balance
+        .checked_sub(keep)?
+        .checked_sub(TRANSFER_FEE_LAMPORTS)?;
+    (excess >= MIN_SWEEP_LAMPORTS).then_some(excess)
+}
+
+/// `treasury.sweep_to`: moves SOL above `treasury.threshold_sol` from the
+/// signing wallet to a cold wallet after each balance poll.
+pub(super) struct TreasurySweeper {
+    to: Pubkey,
+    keep_lamports: u64,
+    /// The signing wallet; follows `wallet use`.
+    signer: ParkingRwLock<[u8; 64]>,
+    journal: Option<Arc<Journal>>,
+    confirm_timeout: Duration,
+    paused_until: Mutex<Option<Instant>>,
+}
+
+impl TreasurySweeper {
+    /// `None` unless `treasury.sweep_to` is set.
+    pub(super) fn from_config(
+        cfg: &Config,
+        keypair: &Keypair,
+        journal: Option<Arc<Journal>>,
+    ) -> Result<Option<Self>> {
+        let Some(to) = cfg.treasury.sweep_to.as_deref() else {
+            return Ok(None);
+        };
+        let to = Pubkey::from_str(to.trim()).context("treasury.sweep_to")?;
+        Ok(Some(Self {
+            to,
+            keep_lamports: (cfg.treasury.threshold_sol * LAMPORTS_PER_SOL).round() as u64,
+            signer: ParkingRwLock::new(keypair.to_bytes()),
+            journal,
+            confirm_timeout: Duration::from_secs(cfg.effective_sell().confirm_timeout_sec),
+            paused_until: Mutex::new(None),
+        }))
+    }
+
+    pub(super) fn set_signer(&self, keypair: &Keypair) {
+        *self.signer.write() = keypair.to_bytes();
+    }
+
+    /// Sweeps the excess over the threshold when `wallet` holds `lamports`.
+    /// Nothing is sent when the signer has moved on from `wallet` or
+    /// `wallet` is the cold wallet itself.
+    pub(super) async fn after_balance(
+        &self,
+        rpc_http: &reqwest::Client,
+        rpc_url: &str,
+        rpc_scheduler: &RpcScheduler,
+        wallet: Pubkey,
+        lamports: u64,
+    ) {
+        let Some(amount) = excess_lamports(lamports, self.keep_lamports) else {
+            return;
+        };
+        if self
+            .paused_until
+            .lock()
+            .is_some_and(|until| Instant::now() < until)
+        {
+            return;
+        }
+        let Ok(keypair) = Keypair::try_from(&self.signer.read()[..]) else {
+            return;
+        };
+        if keypair.pubkey() != wallet || wallet == self.to {
+            return;
+        }
+        rpc_scheduler.acquire(RpcPriority::Background).await;
+        match self.transfer(rpc_http, rpc_url, &keypair, amount).await {
+            Ok(signature) => {
+                if let Some(journal) = self.journal.as_ref() {
+                    let record = TradeRecord {
+                        closed_at_ms: now_unix_ms(),
+                        mint: spl_token::native_mint::id().to_string(),
+                        reason: "treasury_sweep".to_string(),
+                        signature: signature.clone(),
+                        fee_lamports: Some(TRANSFER_FEE_LAMPORTS),
+                        self_transfer: true,
+                        swept_lamports: Some(amount),
+                        ..TradeRecord::default()
+                    };
+                    if let Err(err) = journal.append(&record) {
+                        warn!(event = "journal_write_failed", error = %err);
+                    }
+                }
+                emit(AppEvent::TreasurySwept {
+                    to: self.to,
+                    lamports: amount,
+                    signature,
+                });
+            }
+            Err(err) => {
+                *self.paused_until.lock() = Some(Instant::now() + RETRY_AFTER);
+                warn!(
+                    event = "treasury_sweep_failed",
+                    to = %self.to,
+                    lamports = amount,
+                    retry_in_sec = RETRY_AFTER.as_secs(),
+                    error = %format!("{err:#}")
+                );
+            }
+        }
+    }
+
+    async fn transfer(
+        &self,
+        rpc_http: &reqwest::Client,
+        rpc_url: &str,
+        keypair: &Keypair,
+        lamports: u64,
+    ) -> Result<String> {
+        let from = keypair.pubkey();
+        let blockhash = fetch_latest_blockhash(rpc_http, rpc_url).await?;
+        let tx = sign_local_tx(
+            &[system_instruction::transfer(&from, &self.to, lamports)],
+            keypair,
+            blockhash,
+            Purpose::new("treasury_sweep", None),
+        );
+        send_tx(
+            rpc_http,
+            rpc_url,
+            &tx,
+            &SendTarget::Rpc {
+                url: rpc_url.to_string(),
+            },
+            self.confirm_timeout,
+            &mut None,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweeps_only_what_the_threshold_and_fee_leave() {
+        let keep = 1_000_000_000;
+        assert_eq!(excess_lamports(keep - 1, keep), None);
+        assert_eq!(excess_lamports(keep + TRANSFER_FEE_LAMPORTS, keep), None);
+        assert_eq!(
+            excess_lamports(keep + TRANSFER_FEE_LAMPORTS + MIN_SWEEP_LAMPORTS - 1, keep),
+            None
+        );
+        assert_eq!(
+            excess_lamports(2_500_000_000, keep),
+            Some(1_500_000_000 - TRANSFER_FEE_LAMPORTS)
+        );
+    }
+}
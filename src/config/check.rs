@@ -0,0 +1,327 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::{reject_removed_yaml_fields, Config};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One finding from `doctor`, pointing at the offending line when
+/// it can be found.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// 1-based line and column.
+    pub location: Option<(usize, usize)>,
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            location: None,
+            hint: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(message)
+        }
+    }
+
+    fn at(mut self, location: Option<(usize, usize)>) -> Self {
+        self.location = location;
+        self
+    }
+}
+
+pub struct CheckReport {
+    pub diagnostics: Vec<Diagnostic>,
+    pub rendered: String,
+}
+
+impl CheckReport {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+/// Validates a config file the same way startup does, then lints it for
+/// settings that load but cannot do what they say.
+pub fn check_file(path: &Path) -> Result<CheckReport> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("read config file {}", path.display()))?;
+    let diagnostics = check_str(&raw);
+    let rendered = render(&path.display().to_string(), &raw, &diagnostics);
+    Ok(CheckReport {
+        diagnostics,
+        rendered,
+    })
+}
+
+fn check_str(raw: &str) -> Vec<Diagnostic> {
+    if let Err(err) = reject_removed_yaml_fields(raw) {
+        return vec![located(raw, err.to_string())];
+    }
+    let mut cfg: Config = match serde_yaml::from_str(raw) {
+        Ok(cfg) => cfg,
+        Err(err) => return vec![parse_diagnostic(&err)],
+    };
+    cfg.apply_env_overrides();
+    let mut diagnostics = Vec::new();
+    if let Err(err) = cfg.validate() {
+        diagnostics.push(located(raw, err.to_string()));
+    }
+    diagnostics.extend(lint(&cfg).into_iter().map(|(field, message)| {
+        Diagnostic::warning(format!("{field} {message}")).at(locate_key(raw, field))
+    }));
+    diagnostics
+}
+
+/// Settings that load fine but are almost certainly a mistake.
+fn lint(cfg: &Config) -> Vec<(&'static str, String)> {
+    let mut findings = Vec::new();
+    if cfg.sell.slippage_pad_bps > cfg.sell.slippage_max_bps {
+        findings.push((
+            "sell.slippage_pad_bps",
+            format!(
+                "({}) is above sell.slippage_max_bps ({}); every sell starts at the cap",
+                cfg.sell.slippage_pad_bps, cfg.sell.slippage_max_bps
+            ),
+        ));
+    }
+    let ladder_pct: f64 = cfg
+        .strategy
+        .take_profit_levels
+        .iter()
+        .map(|level| level.sell_pct)
+        .sum();
+    if ladder_pct > 100.0 {
+        findings.push((
+            "strategy.take_profit_levels",
+            format!("sell {ladder_pct}% in total; later levels will find nothing left to sell"),
+        ));
+    }
+    if cfg.mirror.enabled && cfg.watch_wallets.iter().all(|wallet| !wallet.enabled) {
+        findings.push((
+            "mirror.enabled",
+            "is on but no enabled watch_wallets are configured".to_string(),
+        ));
+    }
+    findings
+}
+
+fn parse_diagnostic(err: &serde_yaml::Error) -> Diagnostic {
+    let full = err.to_string();
+    let message = match full.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => full,
+    };
+    let location = err
+        .location()
+        .map(|location| (location.line(), location.column()));
+    let (message, hint) = match suggest(&message) {
+        Some(hint) => (
+            message
+                .split_once(", expected")
+                .map_or(message.clone(), |(head, _)| head.to_string()),
+            Some(hint),
+        ),
+        None => (message, None),
+    };
+    Diagnostic {
+        hint,
+        ..Diagnostic::error(message).at(location)
+    }
+}
+
+/// Suggests a fix for serde's `unknown field`/`unknown variant` and
+/// `missing field` messages.
+fn suggest(message: &str) -> Option<String> {
+    let ticked = |text: &str| -> Vec<String> {
+        text.split('`')
+            .skip(1)
+            .step_by(2)
+            .map(str::to_string)
+            .collect()
+    };
+    if let Some(missing) = message
+        .split_once("missing field ")
+        .and_then(|(_, rest)| ticked(rest).into_iter().next())
+    {
+        return Some(format!("add `{missing}:` to this section"));
+    }
+    if !message.contains("unknown field") && !message.contains("unknown variant") {
+        return None;
+    }
+    let (found, expected) = message.split_once(", expected")?;
+    let found = ticked(found).pop()?;
+    let expected = ticked(expected);
+    let closest = expected
+        .iter()
+        .map(|candidate| (edit_distance(&found, candidate), candidate))
+        .min()
+        .filter(|(distance, _)| *distance <= (found.len() / 3).max(2));
+    Some(match closest {
+        Some((_, candidate)) => format!("did you mean `{candidate}`?"),
+        None if expected.is_empty() => "remove this key".to_string(),
+        None => format!("valid keys here: {}", expected.join(", ")),
+    })
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Validation errors start with the dotted field they are about.
+fn located(raw: &str, message: String) -> Diagnostic {
+    let field = message
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches([':', ',']);
+    let location = locate_key(raw, field);
+    Diagnostic::error(message).at(location)
+}
+
+/// Finds `a.b[0].c` in the YAML source by following indentation. List
+/// indexes are ignored, so the first matching item wins.
+fn locate_key(raw: &str, path: &str) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = raw.lines().collect();
+    let (mut from, mut parent_indent) = (0, None::<usize>);
+    let mut found = None;
+    for segment in path.split('.') {
+        let key = segment.split('[').next().unwrap_or(segment);
+        if key.is_empty() {
+            return None;
+        }
+        let mut hit = None;
+        for (index, line) in lines.iter().enumerate().skip(from) {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - trimmed.len();
+            let item = trimmed.starts_with("- ");
+            match parent_indent {
+                Some(parent) if indent < parent || (indent == parent && !item) => break,
+                None if indent > 0 => continue,
+                _ => {}
+            }
+            let body = trimmed.trim_start_matches("- ");
+            let key_indent = indent + (trimmed.len() - body.len());
+            if body
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+            {
+                hit = Some((index, key_indent));
+                break;
+            }
+        }
+        let (index, key_indent) = hit?;
+        found = Some((index + 1, key_indent + 1));
+        from = index + 1;
+        parent_indent = Some(key_indent);
+    }
+    found
+}
+
+fn render(path: &str, raw: &str, diagnostics: &[Diagnostic]) -> String {
+    let lines: Vec<&str> = raw.lines().collect();
+    let mut out = String::new();
+    for diagnostic in diagnostics {
+        let label = match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let _ = writeln!(out, "{label}: {}", diagnostic.message);
+        if let Some((line, column)) = diagnostic.location {
+            let gutter = " ".repeat(line.to_string().len());
+            let _ = writeln!(out, "{gutter}--> {path}:{line}:{column}");
+            if let Some(source) = lines.get(line.saturating_sub(1)) {
+                let _ = writeln!(out, "{gutter} |");
+                let _ = writeln!(out, "{line} | {source}");
+                let _ = writeln!(out, "{gutter} | {}^", " ".repeat(column.saturating_sub(1)));
+            }
+        }
+        if let Some(hint) = &diagnostic.hint {
+            let _ = writeln!(out, "  = help: {hint}");
+        }
+        out.push('\n');
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    if errors == 0 {
+        let _ = writeln!(out, "{path}: config OK ({warnings} warning(s))");
+    } else {
+        let _ = writeln!(out, "{path}: {errors} error(s), {warnings} warning(s)");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "account:
+  keypair_path: wallet.json
+  rpc_url: https://rpc.example.com
+  api_key: key
+strategy:
+  target_profit: \"50%\"
+  stop_loss: \"20%\"
+  deadline_timeout: 0
+";
+
+    #[test]
+    fn points_at_typos_and_nonsense() {
+        assert!(check_str(BASE).is_empty());
+
+        let typo = format!("{BASE}sell:\n  slipage_pad_bps: 100\n");
+        let found = check_str(&typo);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].location, Some((10, 3)));
+        assert_eq!(
+            found[0].hint.as_deref(),
+            Some("did you mean `slippage_pad_bps`?")
+        );
+        assert!(!found[0].message.contains("expected one of"));
+
+        let wide_stop = BASE.replace("\"20%\"", "\"150%\"");
+        let found = check_str(&wide_stop);
+        assert_eq!(found[0].severity, Severity::Error);
+        assert_eq!(found[0].location, Some((7, 3)));
+
+        let capped = format!("{BASE}sell:\n  slippage_pad_bps: 3000\n");
+        let found = check_str(&capped);
+        assert_eq!(found[0].severity, Severity::Warning);
+        assert!(render("config.yml", &capped, &found).contains("--> config.yml:10:3"));
+    }
+}
@@ -1,7 +1,9 @@
+use std::collections::{BTreeMap, HashSet};
 use std::env;
 use std::fs;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::path::Path;
+use std::str::FromStr;
 use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
@@ -13,7 +15,13 @@ use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
 
+use crate::util::logging::{LogCategory, LogRotation};
+use crate::util::theme::Rgb;
+
+pub mod check;
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub account: AccountConfig,
     pub strategy: StrategyConfig,
@@ -23,19 +31,605 @@ pub struct Config {
     pub watch_wallets: Vec<WatchWalletConfig>,
     #[serde(default)]
     pub mirror: MirrorConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+    #[serde(default)]
+    pub stream: StreamConfig,
+    #[serde(default)]
+    pub sweep: SweepConfig,
+    #[serde(default)]
+    pub treasury: TreasuryConfig,
+    #[serde(default)]
+    pub risk: RiskConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+    #[serde(default)]
+    pub exposure: ExposureConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub integration: IntegrationConfig,
+    #[serde(default)]
+    pub liveness: LivenessConfig,
+    #[serde(default)]
+    pub reports: ReportsConfig,
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub wallet: WalletConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// Other keystores `wallet use <name>` can switch signing to at runtime.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wallets: Vec<NamedWallet>,
+    /// Named stream/exit-API endpoint sets, selected with `account.profile`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, EndpointProfile>,
+}
+
+/// Console log verbosity. Category toggles only hide info/debug lines;
+/// warnings and errors are always shown.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoggingConfig {
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    #[serde(default = "default_true")]
+    pub balance: bool,
+    #[serde(default = "default_true")]
+    pub stream: bool,
+    #[serde(default = "default_true")]
+    pub sells: bool,
+    #[serde(default = "default_true")]
+    pub rpc: bool,
+    /// Rotate error.log / debug.log once they reach this size. 0 disables.
+    #[serde(default = "default_log_max_file_mb")]
+    pub max_file_mb: u64,
+    /// Rotate logs older than this many days. 0 disables.
+    #[serde(default = "default_log_max_age_days")]
+    pub max_age_days: u64,
+    /// Rotated copies kept per log.
+    #[serde(default = "default_log_retention")]
+    pub retention: usize,
+    /// Append every app event, scrubbed, to `events.ndjson` in the data
+    /// dir for `lasersell replay-ui`.
+    #[serde(default)]
+    pub event_journal: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: default_log_level(),
+            balance: true,
+            stream: true,
+            sells: true,
+            rpc: true,
+            max_file_mb: default_log_max_file_mb(),
+            max_age_days: default_log_max_age_days(),
+            retention: default_log_retention(),
+            event_journal: false,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn disabled_categories(&self) -> Vec<LogCategory> {
+        [
+            (self.balance, LogCategory::Balance),
+            (self.stream, LogCategory::Stream),
+            (self.sells, LogCategory::Sells),
+            (self.rpc, LogCategory::Rpc),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, category)| (!enabled).then_some(category))
+        .collect()
+    }
+
+    pub fn rotation(&self) -> LogRotation {
+        LogRotation {
+            max_bytes: self.max_file_mb.saturating_mul(1024 * 1024),
+            max_age: (self.max_age_days > 0)
+                .then(|| Duration::from_secs(self.max_age_days.saturating_mul(86_400))),
+            retention: self.retention,
+        }
+    }
+}
+
+/// Local command run on sell completion, session errors and stream
+/// disconnects, with `LASERSELL_*` env vars describing the event.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationsConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// Local HTTP API that buy bots use to pre-register positions they are
+/// about to open. Disabled unless `listen` is set.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IntegrationConfig {
+    /// Loopback address to listen on, e.g. `127.0.0.1:7727`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+    /// When set, requests must send `Authorization: Bearer <token>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Heartbeat file rewritten from the engine loop, for watchdogs that need
+/// to know the loop is turning and not just that the process exists.
+/// Disabled unless `path` is set.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LivenessConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default = "default_liveness_interval_sec")]
+    pub interval_sec: u64,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            interval_sec: default_liveness_interval_sec(),
+        }
+    }
+}
+
+fn default_liveness_interval_sec() -> u64 {
+    10
+}
+
+/// Per-session JSON reports written when a session closes, for attaching
+/// to support issues.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Console colours. The builtin themes use the basic 16 colours; the
+/// `#rrggbb` overrides need a truecolor terminal.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UiConfig {
+    #[serde(default)]
+    pub theme: UiTheme,
+    /// Banners and headings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<Rgb>,
+    /// Warning and error lines on the console.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Rgb>,
+    /// Behind the `lasersell attach` view.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<Rgb>,
+    /// Linear, text-only output for screen readers and plain logs: no
+    /// colour, no box drawing or bar charts, and `attach` prints changed
+    /// lines instead of redrawing the screen.
+    #[serde(default)]
+    pub accessible: bool,
+    #[serde(default)]
+    pub keybindings: KeyBindings,
+}
+
+/// One-word console shortcuts for common commands; unset ones have none.
+/// Checked against the built-in commands when the daemon starts.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyBindings {
+    /// Holds console output, or prints what was held (`log pause|resume`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pause: Option<String>,
+    /// `sell`, with the same arguments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sell: Option<String>,
+    /// `show <mint>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// `ack`: marks the notifications read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ack: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UiTheme {
+    #[default]
+    Default,
+    /// Bold white accents and bold red warnings and errors.
+    HighContrast,
+    /// Plain text, for terminals and log collectors without ANSI colours.
+    NoColor,
+}
+
+/// One-flag tuning bundles. Applied when the engine starts, so the values a
+/// profile changes are never written back into the config file.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PerformanceConfig {
+    /// Trade safety margins for speed: no pre-send simulation, a 100ms
+    /// refresh hedge, 5s confirm windows and RPC connections kept warm.
+    #[serde(default)]
+    pub low_latency: bool,
+}
+
+/// How the RPC clients reach the network. The exit API and stream clients
+/// come from lasersell-sdk, which always connects directly.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` proxy, with
+    /// optional `user:pass@`. Unset connects directly and ignores the
+    /// `*_PROXY` environment variables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(default)]
+    pub ip_version: IpVersion,
+    /// TCP (and TLS) connect timeout for RPC calls.
+    #[serde(default = "default_rpc_connect_timeout_ms")]
+    pub rpc_connect_timeout_ms: u64,
+    /// Whole-request timeout for RPC calls. Slow or distant RPCs that time
+    /// out on `getBalance` need more than the default.
+    #[serde(default = "default_rpc_request_timeout_ms")]
+    pub rpc_request_timeout_ms: u64,
+    /// Extra attempts after a timeout, connect or HTTP error. JSON-RPC
+    /// errors are the node's answer and are not retried.
+    #[serde(default)]
+    pub rpc_retries: u32,
+    /// Connect timeout for `account.send_rpc_url`.
+    #[serde(default = "default_rpc_connect_timeout_ms")]
+    pub send_rpc_connect_timeout_ms: u64,
+    /// Whole-request timeout for `sendTransaction` on `account.send_rpc_url`.
+    #[serde(default = "default_rpc_request_timeout_ms")]
+    pub send_rpc_request_timeout_ms: u64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            ip_version: IpVersion::default(),
+            rpc_connect_timeout_ms: default_rpc_connect_timeout_ms(),
+            rpc_request_timeout_ms: default_rpc_request_timeout_ms(),
+            rpc_retries: 0,
+            send_rpc_connect_timeout_ms: default_rpc_connect_timeout_ms(),
+            send_rpc_request_timeout_ms: default_rpc_request_timeout_ms(),
+        }
+    }
+}
+
+fn default_rpc_connect_timeout_ms() -> u64 {
+    200
+}
+
+fn default_rpc_request_timeout_ms() -> u64 {
+    800
+}
+
+/// Bounds on the `network.rpc_*` settings.
+const RPC_CONNECT_TIMEOUT_MS: std::ops::RangeInclusive<u64> = 50..=10_000;
+const RPC_REQUEST_TIMEOUT_MS: std::ops::RangeInclusive<u64> = 100..=60_000;
+const MAX_RPC_RETRIES: u32 = 5;
+
+/// Address family for outgoing connections.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+    /// Whatever the hostname resolves to.
+    #[default]
+    Auto,
+    V4,
+    V6,
+}
+
+/// `sell.hedge_request_ms` and `sell.confirm_timeout_sec` under `low_latency`;
+/// lower values in the config are kept.
+const LOW_LATENCY_HEDGE_MS: u64 = 100;
+const LOW_LATENCY_CONFIRM_TIMEOUT_SEC: u64 = 5;
+
+/// How new keystores are encrypted. Existing keystores are read with the
+/// parameters stored in them.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WalletConfig {
+    #[serde(default)]
+    pub kdf: KdfConfig,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KdfAlgorithm {
+    #[default]
+    Argon2id,
+    Scrypt,
+}
+
+/// Passphrase key derivation. Argon2id needs `argon2_memory_kib` of RAM
+/// while unlocking; scrypt needs `128 * 8 * 2^scrypt_log_n` bytes.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KdfConfig {
+    #[serde(default)]
+    pub algorithm: KdfAlgorithm,
+    #[serde(default = "default_argon2_memory_kib")]
+    pub argon2_memory_kib: u32,
+    #[serde(default = "default_argon2_iterations")]
+    pub argon2_iterations: u32,
+    #[serde(default = "default_scrypt_log_n")]
+    pub scrypt_log_n: u8,
+}
+
+impl Default for KdfConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: KdfAlgorithm::default(),
+            argon2_memory_kib: default_argon2_memory_kib(),
+            argon2_iterations: default_argon2_iterations(),
+            scrypt_log_n: default_scrypt_log_n(),
+        }
+    }
+}
+
+fn default_argon2_memory_kib() -> u32 {
+    65_536
+}
+
+fn default_argon2_iterations() -> u32 {
+    3
+}
+
+/// 16 MiB.
+fn default_scrypt_log_n() -> u8 {
+    14
+}
+
+/// Soft caps on open exposure (sum of cost bases). Exceeding one only logs
+/// a warning; nothing is sold or blocked. 0 disables a cap.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExposureConfig {
+    /// Max share (%) of one quote token's exposure held in a single market type.
+    #[serde(default)]
+    pub max_market_type_pct: f64,
+    #[serde(default)]
+    pub max_sol: f64,
+    #[serde(default)]
+    pub max_usd1: f64,
+}
+
+/// Cleanup of sessions whose sell failed or whose balance went to zero.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionsConfig {
+    /// Seconds a session may stay stale before it is pruned (0 disables).
+    #[serde(default = "default_stale_ttl_sec")]
+    pub stale_ttl_sec: u64,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            stale_ttl_sec: default_stale_ttl_sec(),
+        }
+    }
+}
+
+/// Health checks on the stream connection.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamConfig {
+    /// Seconds without any stream message, while the stream reports itself
+    /// connected, before the session is replaced (0 disables). A ping goes
+    /// out at half this, so a quiet but healthy stream is not mistaken for a
+    /// stalled one.
+    #[serde(default = "default_stall_timeout_sec")]
+    pub stall_timeout_sec: u64,
+    /// Directory for zstd-compressed archives of every stream message, for
+    /// audit and debugging. Unset disables archiving.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_dir: Option<String>,
+    /// Uncompressed megabytes per archive file before a new one is started.
+    #[serde(default = "default_archive_rotate_mb")]
+    pub archive_rotate_mb: u64,
+    /// Second stream endpoint to hold a session on alongside the primary.
+    /// Exit signals from either are used, a duplicate of one already taken
+    /// from the other is dropped by position id, and everything else comes
+    /// from the backup only while the primary is down.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_url: Option<String>,
+    /// Seconds between exit API quotes of the open sessions while the stream
+    /// is down, which keep PnL and limits going (0, the default, disables).
+    #[serde(default)]
+    pub fallback_quote_sec: u64,
+    /// Also sell a session whose quote reaches the strategy's target, stop
+    /// loss, trailing stop or deadline, checked on the client.
+    #[serde(default)]
+    pub fallback_exits: bool,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            stall_timeout_sec: default_stall_timeout_sec(),
+            archive_dir: None,
+            archive_rotate_mb: default_archive_rotate_mb(),
+            backup_url: None,
+            fallback_quote_sec: 0,
+            fallback_exits: false,
+        }
+    }
+}
+
+/// What `sweep` treats as dust and what it may do with it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepConfig {
+    /// Token accounts whose simulated full sell nets less SOL than this are
+    /// dust.
+    #[serde(default = "default_dust_value_sol")]
+    pub dust_value_sol: f64,
+    /// Burn tokens the exit API cannot route a sell for, so their accounts
+    /// can be closed. Off by default: a missing route may be temporary.
+    #[serde(default)]
+    pub burn_unsellable: bool,
+}
+
+impl Default for SweepConfig {
+    fn default() -> Self {
+        Self {
+            dust_value_sol: default_dust_value_sol(),
+            burn_unsellable: false,
+        }
+    }
+}
+
+fn default_dust_value_sol() -> f64 {
+    0.005
+}
+
+/// Moves idle SOL to a cold wallet. Off until `sweep_to` is set.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TreasuryConfig {
+    /// Cold wallet that receives the SOL above `threshold_sol`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sweep_to: Option<String>,
+    /// SOL the signing wallet keeps for fees; a balance poll that finds more
+    /// sends the excess to `sweep_to`.
+    #[serde(default = "default_treasury_threshold_sol")]
+    pub threshold_sol: f64,
+}
+
+impl Default for TreasuryConfig {
+    fn default() -> Self {
+        Self {
+            sweep_to: None,
+            threshold_sol: default_treasury_threshold_sol(),
+        }
+    }
+}
+
+fn default_treasury_threshold_sol() -> f64 {
+    1.0
+}
+
+/// Checks made before a sell is started.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiskConfig {
+    /// SOL kept for sell fees and tips. While the polled balance is below
+    /// it, sells are refused with a warning; 0 disables the check.
+    #[serde(default = "default_min_sol_reserve")]
+    pub min_sol_reserve: f64,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            min_sol_reserve: default_min_sol_reserve(),
+        }
+    }
+}
+
+fn default_min_sol_reserve() -> f64 {
+    0.002
+}
+
+/// What the wallet key may sign.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecurityConfig {
+    /// Programs a server-built transaction may call. One that calls any
+    /// other program is not signed.
+    #[serde(default = "default_allowed_program_ids")]
+    pub allowed_program_ids: Vec<String>,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            allowed_program_ids: default_allowed_program_ids(),
+        }
+    }
+}
+
+fn default_allowed_program_ids() -> Vec<String> {
+    crate::tx::policy::DEFAULT_ALLOWED_PROGRAM_IDS
+        .iter()
+        .map(|program| program.to_string())
+        .collect()
+}
+
+fn default_archive_rotate_mb() -> u64 {
+    64
+}
+
+fn default_stall_timeout_sec() -> u64 {
+    30
+}
+
+fn default_stale_ttl_sec() -> u64 {
+    3_600
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_log_max_file_mb() -> u64 {
+    10
+}
+
+fn default_log_max_age_days() -> u64 {
+    7
+}
+
+fn default_log_retention() -> usize {
+    3
+}
+
+/// Alternate stream and exit-API endpoints, e.g. for staging.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointProfile {
+    pub stream_url: String,
+    pub exit_api_url: String,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct AccountConfig {
     pub keypair_path: String,
     #[serde(default)]
     pub local: bool,
+    /// Name of an entry in `profiles` to use instead of the built-in endpoints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
     #[serde(
         default = "default_secret_string",
         deserialize_with = "deserialize_secret_string",
         serialize_with = "serialize_secret_string"
     )]
     pub rpc_url: SecretString,
+    /// RPC endpoint for `sendTransaction` with `send_target: rpc`, on its own
+    /// client and timeouts. Empty sends through `rpc_url`, which keeps the
+    /// reads and confirmations either way.
+    #[serde(
+        default = "default_secret_string",
+        deserialize_with = "deserialize_secret_string",
+        serialize_with = "serialize_secret_string",
+        skip_serializing_if = "is_empty_secret"
+    )]
+    pub send_rpc_url: SecretString,
     #[serde(
         default = "default_secret_string",
         deserialize_with = "deserialize_secret_string",
@@ -53,9 +647,26 @@ pub struct AccountConfig {
         skip_serializing_if = "is_empty_secret"
     )]
     pub astralane_api_key: SecretString,
+    /// Requests/sec the RPC provider allows. Unset uses a conservative limit
+    /// for known public endpoints and no limit otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_max_rps: Option<f64>,
+    /// Other wallets you control. Token transfers to or from them are
+    /// journaled as self-transfers and left out of trade statistics.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub own_wallets: Vec<String>,
+    /// Exit API base URLs by region name. Requests go to the one with the
+    /// lowest latency measured at startup, measured again after repeated
+    /// timeouts. Empty uses the built-in endpoint.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub exit_api_regions: BTreeMap<String, String>,
+    /// Name of an entry in `exit_api_regions` to always use instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_api_region: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct StrategyConfig {
     pub target_profit: StrategyAmount,
     /// Positive amount; if percent, it's based on detected buy amount.
@@ -76,9 +687,39 @@ pub struct StrategyConfig {
     /// Trailing stop that activates once position breaks even.
     #[serde(default = "default_trailing_stop")]
     pub breakeven_trail: StrategyAmount,
+    /// Age-based schedule that lowers the target and tightens the stop the
+    /// longer a position is held. `stop_loss_schedule` is accepted for a
+    /// schedule of stop-loss steps only.
+    #[serde(
+        default,
+        alias = "stop_loss_schedule",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tighten_after_sec: Vec<TightenStep>,
+    /// Sell any position still open after this long, checked on the client
+    /// and independent of `deadline_timeout`. 0 disables.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub max_hold_sec: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// Once a position has been held `after_sec`, its target profit and/or stop
+/// loss are replaced by these values.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TightenStep {
+    pub after_sec: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_profit: Option<StrategyAmount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_loss: Option<StrategyAmount>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct TakeProfitLevel {
     pub profit_pct: f64,
     pub sell_pct: f64,
@@ -87,6 +728,18 @@ pub struct TakeProfitLevel {
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamedWallet {
+    pub name: String,
+    pub keypair_path: String,
+}
+
+/// Name `Config::wallet_choices` gives `account.keypair_path` when no
+/// `wallets` entry points at it.
+pub const DEFAULT_WALLET_NAME: &str = "default";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct WatchWalletConfig {
     pub pubkey: String,
     #[serde(default)]
@@ -101,6 +754,7 @@ pub struct WatchWalletConfig {
 fn default_true() -> bool { true }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct AutoBuyConfig {
     /// SOL amount to auto-buy (human-readable, e.g. 0.1 = 0.1 SOL).
     #[serde(default)]
@@ -111,6 +765,7 @@ pub struct AutoBuyConfig {
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields)]
 pub struct MirrorConfig {
     /// Master toggle for mirror trading (default: disabled).
     /// When false, no watch wallets are sent to the stream.
@@ -151,6 +806,7 @@ fn default_mirror_max_active_sol() -> f64 { 5.0 }
 fn default_mirror_buy_slippage() -> u16 { 2500 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct SellConfig {
     #[serde(default = "default_slippage_pad")]
     pub slippage_pad_bps: u16,
@@ -164,6 +820,121 @@ pub struct SellConfig {
     pub confirm_timeout_sec: u64,
     #[serde(default = "default_max_retries")]
     pub max_retries: usize,
+    /// After this many milliseconds without a refreshed sell tx, also send
+    /// the exit API a `build_sell_tx` and take whichever answers first; for
+    /// a sell already built by the exit API, a duplicate request. 0 disables
+    /// hedging. `sell_refreshed` names the `source` that won.
+    #[serde(default = "default_hedge_request_ms")]
+    pub hedge_request_ms: u64,
+    /// Run each signed sell through `simulateTransaction` and go straight to
+    /// a slippage-bumped refresh when it would fail.
+    #[serde(default)]
+    pub simulate_before_send: bool,
+    /// Check each sell's recent blockhash with `isBlockhashValid` before it
+    /// is signed, and refresh at once when it has expired.
+    #[serde(default = "default_true")]
+    pub check_blockhash: bool,
+    #[serde(default)]
+    pub output: SellOutputPreference,
+    /// Cap on sells executing at once; further exit signals wait their turn.
+    /// 0 means unlimited.
+    #[serde(default)]
+    pub max_concurrent_sells: usize,
+    /// Extra starting slippage while the chain is congested (half of it when
+    /// merely busy), still capped by `slippage_max_bps`. 0 disables.
+    #[serde(default)]
+    pub congestion_slippage_bump_bps: u16,
+    /// Skip an automatic SOL sell whose expected proceeds, less the base fee
+    /// and sender tip, come to less than this; the position stays open for
+    /// the next exit signal. Manual sells always go through. 0 disables.
+    #[serde(default)]
+    pub min_net_proceeds_lamports: u64,
+    /// Where signed sells are submitted, overriding `account.send_target`:
+    /// `helius_sender`, `astralane`, `rpc`, `jito`, `webhook` or `race`.
+    /// Read at startup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_target: Option<String>,
+    #[serde(default, skip_serializing_if = "SendersConfig::is_unset")]
+    pub senders: SendersConfig,
+}
+
+/// Options for the send targets that need them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SendersConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jito: Option<JitoSenderConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookSenderConfig>,
+    /// Targets `race` sends every sell to at once; the first to accept it
+    /// wins. The sell is built for the first one's tip.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub race: Vec<String>,
+}
+
+impl SendersConfig {
+    fn is_unset(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// A Jito block engine's `sendTransaction` endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct JitoSenderConfig {
+    #[serde(default = "default_jito_url")]
+    pub url: String,
+    /// Sent as `x-jito-auth` when the block engine issued one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+}
+
+impl Default for JitoSenderConfig {
+    fn default() -> Self {
+        Self {
+            url: default_jito_url(),
+            uuid: None,
+        }
+    }
+}
+
+fn default_jito_url() -> String {
+    "https://mainnet.block-engine.jito.wtf/api/v1/transactions".to_string()
+}
+
+/// Any HTTP endpoint that takes a JSON-RPC `sendTransaction` and answers
+/// with the signature, such as a self-hosted relay.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookSenderConfig {
+    pub url: String,
+    /// Extra request headers, e.g. `Authorization`. Values are redacted in
+    /// the logs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Send targets that `sell.send_target` accepts.
+const SEND_TARGETS: [&str; 6] = [
+    "helius_sender",
+    "astralane",
+    "rpc",
+    "jito",
+    "webhook",
+    "race",
+];
+
+/// Which asset sell proceeds are paid out in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SellOutputPreference {
+    /// Keep whatever the stream-built transaction pays out.
+    #[default]
+    Auto,
+    Sol,
+    Usd1,
+    /// The pool's quote token (USD1 for USD1 pools, SOL otherwise).
+    Quote,
 }
 
 impl Default for SellConfig {
@@ -175,6 +946,15 @@ impl Default for SellConfig {
             slippage_max_bps: default_slippage_max(),
             confirm_timeout_sec: default_confirm_timeout_sec(),
             max_retries: default_max_retries(),
+            hedge_request_ms: default_hedge_request_ms(),
+            simulate_before_send: false,
+            check_blockhash: true,
+            output: SellOutputPreference::Auto,
+            max_concurrent_sells: 0,
+            congestion_slippage_bump_bps: 0,
+            min_net_proceeds_lamports: 0,
+            send_target: None,
+            senders: SendersConfig::default(),
         }
     }
 }
@@ -199,6 +979,10 @@ fn default_max_retries() -> usize {
     2
 }
 
+fn default_hedge_request_ms() -> u64 {
+    250
+}
+
 pub const STREAM_ENDPOINT: &str = "wss://stream.lasersell.io/v1/ws";
 pub const EXIT_API_BASE_URL: &str = "https://api.lasersell.io";
 pub const LOCAL_STREAM_ENDPOINT: &str = "ws://localhost:8082/v1/ws";
@@ -269,6 +1053,60 @@ impl Config {
         Ok(cfg)
     }
 
+    /// Builds the config from `LASERSELL_<SECTION>_<KEY>` env vars alone, for
+    /// containers with no config file or writable volume. The per-field
+    /// overrides (`LASERSELL_RPC_URL`, ...) still apply on top.
+    pub fn from_env() -> Result<Self> {
+        let mut cfg = Self::from_env_vars(env::vars())?;
+        cfg.apply_env_overrides();
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    fn from_env_vars(vars: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut root = serde_yaml::Mapping::new();
+        for section in ["account", "strategy"] {
+            root.insert(section.into(), serde_yaml::Mapping::new().into());
+        }
+        for (name, raw) in vars {
+            let Some((section, key)) = name
+                .strip_prefix("LASERSELL_")
+                .and_then(|rest| rest.split_once('_'))
+            else {
+                continue;
+            };
+            let section = section.to_ascii_lowercase();
+            if !ENV_CONFIG_SECTIONS.contains(&section.as_str()) || raw.trim().is_empty() {
+                continue;
+            }
+            let entry = root
+                .entry(section.into())
+                .or_insert_with(|| serde_yaml::Mapping::new().into());
+            if let Some(fields) = entry.as_mapping_mut() {
+                fields.insert(key.to_ascii_lowercase().into(), env_config_value(&raw));
+            }
+        }
+        serde_yaml::from_value(root.into())
+            .map_err(|err| anyhow!("env config: {err} (set it as LASERSELL_<SECTION>_<KEY>)"))
+    }
+
+    /// `sell` with the `performance` profile applied.
+    pub fn effective_sell(&self) -> SellConfig {
+        let mut sell = self.sell.clone();
+        if self.performance.low_latency {
+            sell.simulate_before_send = false;
+            sell.check_blockhash = false;
+            sell.hedge_request_ms = match sell.hedge_request_ms {
+                0 => LOW_LATENCY_HEDGE_MS,
+                ms => ms.min(LOW_LATENCY_HEDGE_MS),
+            };
+            sell.confirm_timeout_sec = sell
+                .confirm_timeout_sec
+                .min(LOW_LATENCY_CONFIRM_TIMEOUT_SEC);
+        }
+        sell
+    }
+
     pub fn write_to_path(&self, path: &Path) -> Result<()> {
         let raw = serde_yaml::to_string(self).context("serialize config yaml")?;
         crate::util::fs_utils::atomic_write(path, raw.as_bytes(), Some(0o600))
@@ -276,6 +1114,23 @@ impl Config {
         Ok(())
     }
 
+    /// Rewrites some fields of the config file on disk. Starts from the file
+    /// itself rather than a loaded config, so env overrides are not written
+    /// back; the result must still load with them. Comments in the file are
+    /// not kept.
+    pub fn edit_file(path: &Path, edit: impl FnOnce(&mut Config)) -> Result<()> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("read config file {}", path.display()))?;
+        reject_removed_yaml_fields(&raw)?;
+        let mut cfg: Config = serde_yaml::from_str(&raw)
+            .with_context(|| format!("parse yaml config {}", path.display()))?;
+        edit(&mut cfg);
+        let mut loaded = cfg.clone();
+        loaded.apply_env_overrides();
+        loaded.validate()?;
+        cfg.write_to_path(path)
+    }
+
     fn apply_env_overrides(&mut self) {
         if let Some(path) = env_nonempty("LASERSELL_KEYPAIR_PATH") {
             self.account.keypair_path = path;
@@ -285,94 +1140,274 @@ impl Config {
         {
             self.account.rpc_url = SecretString::new(value);
         }
+        if let Some(value) = env_nonempty("LASERSELL_SEND_RPC_URL") {
+            self.account.send_rpc_url = SecretString::new(value);
+        }
         if let Some(value) = env_nonempty("LASERSELL_API_KEY") {
             self.account.api_key = SecretString::new(value);
         }
         if let Some(value) = env_nonempty("LASERSELL_SEND_TARGET") {
+            // Wins over sell.send_target too.
+            self.sell.send_target = None;
             self.account.send_target = Some(value);
         }
         if let Some(value) = env_nonempty("LASERSELL_ASTRALANE_API_KEY") {
             self.account.astralane_api_key = SecretString::new(value);
         }
+        if let Some(value) = env_nonempty("LASERSELL_PROFILE") {
+            self.account.profile = Some(value);
+        }
+        if let Some(value) = env_nonempty("LASERSELL_EXIT_API_REGION") {
+            self.account.exit_api_region = Some(value);
+        }
     }
 
     pub fn wallet_pubkey(&self, keypair: &Keypair) -> Result<Pubkey> {
         Ok(keypair.pubkey())
     }
 
+    /// Every wallet `wallet use` can pick: `account.keypair_path` first,
+    /// then the `wallets` entries.
+    pub fn wallet_choices(&self) -> Vec<NamedWallet> {
+        let account_path = self.account.keypair_path.trim();
+        let mut choices = Vec::with_capacity(self.wallets.len() + 1);
+        if !self
+            .wallets
+            .iter()
+            .any(|wallet| wallet.keypair_path.trim() == account_path)
+        {
+            choices.push(NamedWallet {
+                name: DEFAULT_WALLET_NAME.to_string(),
+                keypair_path: account_path.to_string(),
+            });
+        }
+        choices.extend(self.wallets.iter().cloned());
+        choices
+    }
+
     pub fn http_rpc_url(&self) -> String {
         self.account.rpc_url.expose_secret().trim().to_string()
     }
 
+    /// `account.send_rpc_url`, if set.
+    pub fn send_rpc_url(&self) -> Option<String> {
+        let url = self.account.send_rpc_url.expose_secret().trim();
+        (!url.is_empty()).then(|| url.to_string())
+    }
+
+    /// The endpoint profile selected by `account.profile`, if any.
+    pub fn active_profile(&self) -> Option<&EndpointProfile> {
+        self.profiles.get(self.account.profile.as_deref()?)
+    }
+
     pub fn stream_url(&self) -> String {
-        if self.account.local {
+        if let Some(profile) = self.active_profile() {
+            profile.stream_url.trim().to_string()
+        } else if self.account.local {
             LOCAL_STREAM_ENDPOINT.to_string()
         } else {
             STREAM_ENDPOINT.to_string()
         }
     }
 
+    /// `account.exit_api_regions`, with validation's trimming applied.
+    pub fn exit_api_regions(&self) -> Vec<(String, String)> {
+        self.account
+            .exit_api_regions
+            .iter()
+            .map(|(name, url)| (name.clone(), url.trim().trim_end_matches('/').to_string()))
+            .collect()
+    }
+
+    fn validate_exit_api_regions(&self) -> Result<()> {
+        let regions = &self.account.exit_api_regions;
+        if let Some(name) = self.account.exit_api_region.as_deref() {
+            if !regions.contains_key(name) {
+                return Err(anyhow!(
+                    "account.exit_api_region \"{name}\" is not in account.exit_api_regions"
+                ));
+            }
+        }
+        if regions.is_empty() {
+            return Ok(());
+        }
+        if self.account.profile.is_some() || self.account.local {
+            return Err(anyhow!(
+                "account.exit_api_regions cannot be combined with account.profile or local"
+            ));
+        }
+        for (name, url) in regions {
+            validate_rpc_url(&format!("account.exit_api_regions.{name}"), url.trim())?;
+        }
+        Ok(())
+    }
+
     pub fn exit_api_url(&self) -> String {
-        if self.account.local {
+        if let Some(profile) = self.active_profile() {
+            profile.exit_api_url.trim().trim_end_matches('/').to_string()
+        } else if self.account.local {
             LOCAL_EXIT_API_BASE_URL.to_string()
         } else {
             EXIT_API_BASE_URL.to_string()
         }
     }
 
-    /// Resolves which [`SendTarget`] to use for transaction submission.
-    ///
-    /// If `account.send_target` is set it takes precedence; otherwise
-    /// `local=true` maps to RPC and `local=false` to Helius Sender.
-    pub fn resolve_send_target(&self) -> Result<SendTarget> {
-        if let Some(target) = self.account.send_target.as_deref() {
-            match target {
-                "helius_sender" => Ok(SendTarget::HeliusSender),
-                "astralane" => {
-                    let key = self.account.astralane_api_key.expose_secret().trim();
-                    if key.is_empty() {
-                        return Err(anyhow!(
-                            "account.astralane_api_key is required when send_target is \"astralane\""
-                        ));
-                    }
-                    Ok(SendTarget::Astralane {
-                        api_key: key.to_string(),
-                        region: None,
-                    })
+    /// The send target in effect: `sell.send_target`, then
+    /// `account.send_target`. With neither set, `local` sends through the
+    /// RPC and anything else through Helius Sender.
+    pub fn send_target_name(&self) -> &str {
+        match self
+            .sell
+            .send_target
+            .as_deref()
+            .or(self.account.send_target.as_deref())
+        {
+            Some(target) => target.trim(),
+            None if self.account.local => "rpc",
+            None => "helius_sender",
+        }
+    }
+
+    /// Resolves a send target the SDK submits to itself (`helius_sender`,
+    /// `astralane` or `rpc`); `None` for the others.
+    pub fn resolve_send_target(&self, target: &str) -> Result<Option<SendTarget>> {
+        match target {
+            "helius_sender" => Ok(Some(SendTarget::HeliusSender)),
+            "astralane" => {
+                let key = self.account.astralane_api_key.expose_secret().trim();
+                if key.is_empty() {
+                    return Err(anyhow!(
+                        "account.astralane_api_key is required when send_target is \"astralane\""
+                    ));
                 }
-                "rpc" => Ok(SendTarget::Rpc {
-                    url: self.http_rpc_url(),
-                }),
-                other => Err(anyhow!(
-                    "unknown send_target \"{other}\"; expected \"helius_sender\", \"astralane\", or \"rpc\""
-                )),
+                Ok(Some(SendTarget::Astralane {
+                    api_key: key.to_string(),
+                    region: None,
+                }))
             }
-        } else if self.account.local {
-            Ok(SendTarget::Rpc {
+            "rpc" => Ok(Some(SendTarget::Rpc {
                 url: self.http_rpc_url(),
-            })
-        } else {
-            Ok(SendTarget::HeliusSender)
+            })),
+            _ => Ok(None),
         }
     }
 
-    /// Returns the send mode string for exit API requests.
+    /// Returns the send mode string for exit API requests. Jito and webhook
+    /// sells are built as for `rpc`, without a sender tip, and `race` builds
+    /// for its first target.
     pub fn send_mode_str(&self) -> &str {
-        if let Some(target) = self.account.send_target.as_deref() {
-            target
-        } else if self.account.local {
-            "rpc"
-        } else {
-            "helius_sender"
+        let target = match self.send_target_name() {
+            "race" => self.sell.senders.race.first().map_or("rpc", |t| t.trim()),
+            target => target,
+        };
+        match target {
+            "jito" | "webhook" => "rpc",
+            target => target,
+        }
+    }
+
+    fn validate_send_target(&self) -> Result<()> {
+        let target = self.send_target_name();
+        if !SEND_TARGETS.contains(&target) {
+            return Err(anyhow!(
+                "unknown send_target \"{target}\"; expected one of {}",
+                SEND_TARGETS.join(", ")
+            ));
+        }
+        let mut targets = vec![target];
+        if target == "race" {
+            let race = &self.sell.senders.race;
+            if race.len() < 2 {
+                return Err(anyhow!(
+                    "sell.senders.race needs at least two targets for send_target \"race\""
+                ));
+            }
+            targets.clear();
+            for entry in race {
+                let entry = entry.trim();
+                if entry == "race" || !SEND_TARGETS.contains(&entry) {
+                    return Err(anyhow!("sell.senders.race: unknown target \"{entry}\""));
+                }
+                if targets.contains(&entry) {
+                    return Err(anyhow!("sell.senders.race lists \"{entry}\" twice"));
+                }
+                targets.push(entry);
+            }
+        }
+        for target in targets {
+            let url = match target {
+                "jito" => self
+                    .sell
+                    .senders
+                    .jito
+                    .as_ref()
+                    .map(|jito| jito.url.as_str()),
+                "webhook" => Some(
+                    self.sell
+                        .senders
+                        .webhook
+                        .as_ref()
+                        .map(|webhook| webhook.url.as_str())
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "sell.senders.webhook.url is required for send_target \"webhook\""
+                            )
+                        })?,
+                ),
+                other => {
+                    self.resolve_send_target(other)?;
+                    None
+                }
+            };
+            if let Some(url) = url {
+                let parsed = Url::parse(url.trim())
+                    .map_err(|_| anyhow!("sell.senders.{target}.url must be a valid URL"))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(anyhow!("sell.senders.{target}.url must use http or https"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Effective RPC rate limit, or `None` for unlimited.
+    pub fn rpc_requests_per_sec(&self) -> Option<f64> {
+        match self.account.rpc_max_rps {
+            Some(rps) if rps > 0.0 => Some(rps),
+            Some(_) => None,
+            None => is_public_rpc(&self.http_rpc_url()).then_some(PUBLIC_RPC_MAX_RPS),
         }
     }
 
+    /// A reqwest builder with `network.proxy_url` and `network.ip_version`
+    /// applied.
+    pub fn http_client_builder(&self) -> Result<reqwest::ClientBuilder> {
+        let builder = match self.network.proxy_url.as_deref() {
+            Some(url) => reqwest::Client::builder()
+                .proxy(reqwest::Proxy::all(url.trim()).context("network.proxy_url")?),
+            None => reqwest::Client::builder().no_proxy(),
+        };
+        Ok(match self.network.ip_version {
+            IpVersion::Auto => builder,
+            IpVersion::V4 => builder.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            IpVersion::V6 => builder.local_address(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        })
+    }
+
     pub fn rpc_connect_timeout(&self) -> Duration {
-        Duration::from_millis(200)
+        Duration::from_millis(self.network.rpc_connect_timeout_ms)
     }
 
     pub fn rpc_request_timeout(&self) -> Duration {
-        Duration::from_millis(800)
+        Duration::from_millis(self.network.rpc_request_timeout_ms)
+    }
+
+    pub fn send_rpc_connect_timeout(&self) -> Duration {
+        Duration::from_millis(self.network.send_rpc_connect_timeout_ms)
+    }
+
+    pub fn send_rpc_request_timeout(&self) -> Duration {
+        Duration::from_millis(self.network.send_rpc_request_timeout_ms)
     }
 
     pub fn exit_api_connect_timeout(&self) -> Duration {
@@ -391,29 +1426,29 @@ impl Config {
         if raw.is_empty() {
             return Err(anyhow!("account.rpc_url must not be empty"));
         }
-        let url = Url::parse(raw).map_err(|_| anyhow!("account.rpc_url must be a valid URL"))?;
-        match url.scheme() {
-            "https" => {}
-            "http" => {
-                let host = url
-                    .host_str()
-                    .ok_or_else(|| anyhow!("account.rpc_url host is missing"))?;
-                if !is_local_or_private_host(host) {
-                    return Err(anyhow!(
-                        "account.rpc_url http:// is allowed only for localhost/private endpoints"
-                    ));
-                }
-                if !config_warnings_suppressed() {
-                    eprintln!(
-                        "Warning: account.rpc_url uses http:// for local/private endpoint ({host}); use https:// in production."
-                    );
-                }
+        validate_rpc_url("account.rpc_url", raw)?;
+        if let Some(url) = self.send_rpc_url() {
+            validate_rpc_url("account.send_rpc_url", &url)?;
+            let target = self.send_target_name();
+            let sends_via_rpc = target == "rpc"
+                || (target == "race"
+                    && self.sell.senders.race.iter().any(|entry| entry.trim() == "rpc"));
+            if !sends_via_rpc {
+                return Err(anyhow!(
+                    "account.send_rpc_url is only used with send_target \"rpc\" or a race \
+                     including rpc, not \"{target}\"; remove it or change send_target"
+                ));
             }
-            _ => {
+        }
+        if let Some(name) = self.account.profile.as_deref() {
+            if self.account.local {
                 return Err(anyhow!(
-                    "account.rpc_url must start with https:// (or http:// for local/private endpoints)"
+                    "account.profile and account.local cannot both be set"
                 ));
             }
+            if !self.profiles.contains_key(name) {
+                return Err(anyhow!("account.profile \"{name}\" is not defined under profiles"));
+            }
         }
         let stream_url_value = self.stream_url();
         let stream_url = stream_url_value.trim();
@@ -422,7 +1457,9 @@ impl Config {
         }
         let stream = Url::parse(stream_url)
             .map_err(|_| anyhow!("internal stream endpoint must be a valid URL"))?;
-        if self.account.local {
+        if self.active_profile().is_some() {
+            validate_profile_url(&stream, "stream_url", "wss", "ws")?;
+        } else if self.account.local {
             if stream.scheme() != "ws" {
                 return Err(anyhow!(
                     "internal local stream endpoint must start with ws://"
@@ -442,21 +1479,12 @@ impl Config {
         if api_key.is_empty() {
             return Err(anyhow!("account.api_key must not be empty"));
         }
-        if self.account.send_target.as_deref() == Some("astralane")
-            && self
-                .account
-                .astralane_api_key
-                .expose_secret()
-                .trim()
-                .is_empty()
-        {
-            return Err(anyhow!(
-                "account.astralane_api_key is required when send_target is \"astralane\""
-            ));
-        }
+        self.validate_send_target()?;
         let exit_url = Url::parse(exit_api_url)
             .map_err(|_| anyhow!("internal exit-api endpoint must be a valid URL"))?;
-        if self.account.local {
+        if self.active_profile().is_some() {
+            validate_profile_url(&exit_url, "exit_api_url", "https", "http")?;
+        } else if self.account.local {
             if exit_url.scheme() != "http" {
                 return Err(anyhow!(
                     "internal local exit-api endpoint must start with http://"
@@ -467,12 +1495,25 @@ impl Config {
                 "internal production exit-api endpoint must start with https://"
             ));
         }
+        if !matches!(
+            self.logging.level.trim().to_ascii_lowercase().as_str(),
+            "trace" | "debug" | "info" | "warn" | "error"
+        ) {
+            return Err(anyhow!(
+                "logging.level must be one of trace, debug, info, warn, error"
+            ));
+        }
         let _ = self.strategy.target_profit_units(None)?;
         let _ = self.strategy.stop_loss_units(None)?;
         let _ = self.strategy.trailing_stop_units(None)?;
         let target_profit_pct = self.strategy.target_profit.percent_value();
         let stop_loss_pct = self.strategy.stop_loss.percent_value();
         let trailing_stop_pct = self.strategy.trailing_stop.percent_value();
+        if stop_loss_pct > 100.0 {
+            return Err(anyhow!(
+                "strategy.stop_loss above 100% can never trigger; a position cannot lose more than it cost"
+            ));
+        }
         let deadline_timeout_sec = self.strategy.deadline_timeout_sec;
         let has_take_profit_levels = !self.strategy.take_profit_levels.is_empty();
         if target_profit_pct <= 0.0
@@ -485,10 +1526,186 @@ impl Config {
                 "at least one of strategy.target_profit, strategy.stop_loss, strategy.trailing_stop, strategy.deadline_timeout, or strategy.take_profit_levels must be set"
             ));
         }
+        self.strategy.validate_tighten_schedule()?;
+        for (field, value) in [
+            ("exposure.max_market_type_pct", self.exposure.max_market_type_pct),
+            ("exposure.max_sol", self.exposure.max_sol),
+            ("exposure.max_usd1", self.exposure.max_usd1),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(anyhow!("{field} must be a non-negative number"));
+            }
+        }
+        self.validate_exit_api_regions()?;
+        for (index, wallet) in self.account.own_wallets.iter().enumerate() {
+            Pubkey::from_str(wallet.trim()).map_err(|_| {
+                anyhow!("account.own_wallets[{index}] is not a valid pubkey: {wallet}")
+            })?;
+        }
+        if let Some(listen) = self.integration.listen.as_deref() {
+            let addr: std::net::SocketAddr = listen.trim().parse().map_err(|_| {
+                anyhow!("integration.listen must be an address like 127.0.0.1:7727")
+            })?;
+            if !addr.ip().is_loopback() {
+                return Err(anyhow!(
+                    "integration.listen must be a loopback address; the API is local only"
+                ));
+            }
+        }
+        if let Some(proxy_url) = self.network.proxy_url.as_deref() {
+            let url = Url::parse(proxy_url.trim())
+                .map_err(|_| anyhow!("network.proxy_url must be a URL like socks5://host:1080"))?;
+            if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+                return Err(anyhow!(
+                    "network.proxy_url must use http, https, socks5 or socks5h"
+                ));
+            }
+        }
+        validate_rpc_timeouts(
+            "rpc",
+            self.network.rpc_connect_timeout_ms,
+            self.network.rpc_request_timeout_ms,
+        )?;
+        validate_rpc_timeouts(
+            "send_rpc",
+            self.network.send_rpc_connect_timeout_ms,
+            self.network.send_rpc_request_timeout_ms,
+        )?;
+        if self.network.rpc_retries > MAX_RPC_RETRIES {
+            return Err(anyhow!(
+                "network.rpc_retries must be at most {MAX_RPC_RETRIES}"
+            ));
+        }
+        if !self.sweep.dust_value_sol.is_finite() || self.sweep.dust_value_sol < 0.0 {
+            return Err(anyhow!("sweep.dust_value_sol must be a number >= 0"));
+        }
+        if let Some(sweep_to) = self.treasury.sweep_to.as_deref() {
+            Pubkey::from_str(sweep_to.trim()).map_err(|_| {
+                anyhow!("treasury.sweep_to is not a valid pubkey: {sweep_to}")
+            })?;
+        }
+        if !self.risk.min_sol_reserve.is_finite() || self.risk.min_sol_reserve < 0.0 {
+            return Err(anyhow!("risk.min_sol_reserve must be a number >= 0"));
+        }
+        if self.security.allowed_program_ids.is_empty() {
+            return Err(anyhow!("security.allowed_program_ids is empty; no sell could be signed"));
+        }
+        for (index, program) in self.security.allowed_program_ids.iter().enumerate() {
+            Pubkey::from_str(program.trim()).map_err(|_| {
+                anyhow!("security.allowed_program_ids[{index}] is not a valid pubkey: {program}")
+            })?;
+        }
+        if !self.treasury.threshold_sol.is_finite() || self.treasury.threshold_sol < 0.01 {
+            return Err(anyhow!(
+                "treasury.threshold_sol must be at least 0.01 to leave SOL for fees"
+            ));
+        }
+        if self.treasury.sweep_to.is_some()
+            && self.treasury.threshold_sol <= self.risk.min_sol_reserve
+        {
+            return Err(anyhow!(
+                "treasury.threshold_sol ({}) must be above risk.min_sol_reserve ({}); \
+                 sweeping down to it would leave too little SOL for any sell",
+                self.treasury.threshold_sol,
+                self.risk.min_sol_reserve
+            ));
+        }
+        if (1..10).contains(&self.stream.stall_timeout_sec) {
+            return Err(anyhow!(
+                "stream.stall_timeout_sec must be 0 (disabled) or at least 10"
+            ));
+        }
+        if (1..5).contains(&self.stream.fallback_quote_sec) {
+            return Err(anyhow!(
+                "stream.fallback_quote_sec must be 0 (disabled) or at least 5"
+            ));
+        }
+        if self.stream.fallback_exits && self.stream.fallback_quote_sec == 0 {
+            return Err(anyhow!("stream.fallback_exits needs stream.fallback_quote_sec"));
+        }
+        if self.stream.archive_dir.is_some() && self.stream.archive_rotate_mb == 0 {
+            return Err(anyhow!("stream.archive_rotate_mb must be at least 1"));
+        }
+        if self
+            .stream
+            .backup_url
+            .as_deref()
+            .is_some_and(|url| url.trim().is_empty())
+        {
+            return Err(anyhow!("stream.backup_url must not be empty"));
+        }
+        if self.liveness.path.is_some() && self.liveness.interval_sec == 0 {
+            return Err(anyhow!("liveness.interval_sec must be at least 1"));
+        }
+        let mut wallet_names = HashSet::new();
+        for wallet in &self.wallets {
+            let name = wallet.name.trim();
+            if name.is_empty() || wallet.keypair_path.trim().is_empty() {
+                return Err(anyhow!("wallets entries need a name and a keypair_path"));
+            }
+            if !wallet_names.insert(name) {
+                return Err(anyhow!("wallets name {name} is used twice"));
+            }
+        }
+        if wallet_names.contains(DEFAULT_WALLET_NAME)
+            && self.wallet_choices().len() > self.wallets.len()
+        {
+            return Err(anyhow!(
+                "wallets name {DEFAULT_WALLET_NAME} is taken by account.keypair_path; pick another"
+            ));
+        }
+        let kdf = &self.wallet.kdf;
+        if kdf.argon2_memory_kib < 8_192 {
+            return Err(anyhow!("wallet.kdf.argon2_memory_kib must be at least 8192 (8 MiB)"));
+        }
+        if kdf.argon2_iterations == 0 {
+            return Err(anyhow!("wallet.kdf.argon2_iterations must be at least 1"));
+        }
+        if !(10..=20).contains(&kdf.scrypt_log_n) {
+            return Err(anyhow!("wallet.kdf.scrypt_log_n must be between 10 and 20"));
+        }
         Ok(())
     }
 }
 
+/// Config sections that env-only mode reads from `LASERSELL_<SECTION>_<KEY>`.
+const ENV_CONFIG_SECTIONS: [&str; 19] = [
+    "account",
+    "strategy",
+    "sell",
+    "mirror",
+    "logging",
+    "sessions",
+    "stream",
+    "sweep",
+    "treasury",
+    "risk",
+    "security",
+    "exposure",
+    "notifications",
+    "integration",
+    "liveness",
+    "reports",
+    "performance",
+    "network",
+    "ui",
+];
+
+/// Numbers, booleans and `[...]`/`{...}` flow YAML keep their type; anything
+/// else (URLs, keys, `"50%"`) stays a string.
+fn env_config_value(raw: &str) -> serde_yaml::Value {
+    let trimmed = raw.trim();
+    match serde_yaml::from_str::<serde_yaml::Value>(trimmed) {
+        Ok(value @ (serde_yaml::Value::Bool(_) | serde_yaml::Value::Number(_))) => value,
+        Ok(value @ (serde_yaml::Value::Sequence(_) | serde_yaml::Value::Mapping(_)))
+            if trimmed.starts_with(['[', '{']) =>
+        {
+            value
+        }
+        _ => serde_yaml::Value::String(trimmed.to_string()),
+    }
+}
+
 fn reject_removed_yaml_fields(raw: &str) -> Result<()> {
     let parsed: serde_yaml::Value = serde_yaml::from_str(raw).context("parse yaml config")?;
     let Some(root) = parsed.as_mapping() else {
@@ -507,6 +1724,82 @@ fn reject_removed_yaml_fields(raw: &str) -> Result<()> {
     Ok(())
 }
 
+/// Profile endpoints must use the secure scheme unless they point at a
+/// local/private host, where the plaintext scheme is also accepted.
+fn validate_profile_url(url: &Url, field: &str, secure: &str, plaintext: &str) -> Result<()> {
+    let scheme = url.scheme();
+    if scheme == secure {
+        return Ok(());
+    }
+    let local_host = url.host_str().is_some_and(is_local_or_private_host);
+    if scheme == plaintext && local_host {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "profile {field} must start with {secure}:// ({plaintext}:// is allowed only for localhost/private endpoints)"
+    ))
+}
+
+const PUBLIC_RPC_MAX_RPS: f64 = 4.0;
+
+/// `https://`, or `http://` for a local or private host.
+fn validate_rpc_url(field: &str, raw: &str) -> Result<()> {
+    let url = Url::parse(raw).map_err(|_| anyhow!("{field} must be a valid URL"))?;
+    match url.scheme() {
+        "https" => Ok(()),
+        "http" => {
+            let host = url
+                .host_str()
+                .ok_or_else(|| anyhow!("{field} host is missing"))?;
+            if !is_local_or_private_host(host) {
+                return Err(anyhow!(
+                    "{field} http:// is allowed only for localhost/private endpoints"
+                ));
+            }
+            if !config_warnings_suppressed() {
+                eprintln!(
+                    "Warning: {field} uses http:// for local/private endpoint ({host}); use https:// in production."
+                );
+            }
+            Ok(())
+        }
+        _ => Err(anyhow!(
+            "{field} must start with https:// (or http:// for local/private endpoints)"
+        )),
+    }
+}
+
+/// Bounds `network.<prefix>_connect_timeout_ms` and
+/// `network.<prefix>_request_timeout_ms`.
+fn validate_rpc_timeouts(prefix: &str, connect_ms: u64, request_ms: u64) -> Result<()> {
+    if !RPC_CONNECT_TIMEOUT_MS.contains(&connect_ms) {
+        return Err(anyhow!(
+            "network.{prefix}_connect_timeout_ms must be between {} and {}",
+            RPC_CONNECT_TIMEOUT_MS.start(),
+            RPC_CONNECT_TIMEOUT_MS.end()
+        ));
+    }
+    if !RPC_REQUEST_TIMEOUT_MS.contains(&request_ms) {
+        return Err(anyhow!(
+            "network.{prefix}_request_timeout_ms must be between {} and {}",
+            RPC_REQUEST_TIMEOUT_MS.start(),
+            RPC_REQUEST_TIMEOUT_MS.end()
+        ));
+    }
+    if request_ms < connect_ms {
+        return Err(anyhow!(
+            "network.{prefix}_request_timeout_ms must be at least network.{prefix}_connect_timeout_ms"
+        ));
+    }
+    Ok(())
+}
+
+/// Free shared endpoints that throttle aggressively.
+pub fn is_public_rpc(rpc_url: &str) -> bool {
+    let url = rpc_url.trim();
+    url.contains("publicnode.com") || url.contains("api.mainnet-beta.solana.com")
+}
+
 fn is_local_or_private_host(host: &str) -> bool {
     let host = host.trim().to_ascii_lowercase();
     if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") {
@@ -544,6 +1837,69 @@ impl StrategyConfig {
         self.trailing_stop
             .to_base_units(buy_quote_amount, "strategy.trailing_stop")
     }
+
+    /// Number of `tighten_after_sec` steps reached after holding `held_sec`.
+    pub fn tighten_step_at(&self, held_sec: u64) -> usize {
+        self.tighten_after_sec
+            .iter()
+            .take_while(|step| step.after_sec <= held_sec)
+            .count()
+    }
+
+    /// This strategy with the first `steps` tightening steps applied.
+    pub fn tightened(&self, steps: usize) -> StrategyConfig {
+        let mut strategy = self.clone();
+        for step in self.tighten_after_sec.iter().take(steps) {
+            if let Some(target_profit) = &step.target_profit {
+                strategy.target_profit = target_profit.clone();
+            }
+            if let Some(stop_loss) = &step.stop_loss {
+                strategy.stop_loss = stop_loss.clone();
+            }
+        }
+        strategy
+    }
+
+    /// Steps must be in increasing `after_sec` order and may only lower the
+    /// target or tighten (or enable) the stop loss.
+    fn validate_tighten_schedule(&self) -> Result<()> {
+        let mut previous_after_sec = 0;
+        let mut target_profit_pct = self.target_profit.percent_value();
+        let mut stop_loss_pct = self.stop_loss.percent_value();
+        for (index, step) in self.tighten_after_sec.iter().enumerate() {
+            let field = format!("strategy.tighten_after_sec[{index}]");
+            if step.after_sec <= previous_after_sec {
+                return Err(anyhow!(
+                    "{field}.after_sec must be greater than the previous step's (and than 0)"
+                ));
+            }
+            previous_after_sec = step.after_sec;
+            if step.target_profit.is_none() && step.stop_loss.is_none() {
+                return Err(anyhow!("{field} must set target_profit or stop_loss"));
+            }
+            if let Some(target_profit) = &step.target_profit {
+                target_profit.to_base_units(None, &format!("{field}.target_profit"))?;
+                let pct = target_profit.percent_value();
+                if pct <= 0.0 || (target_profit_pct > 0.0 && pct > target_profit_pct) {
+                    return Err(anyhow!(
+                        "{field}.target_profit must be above 0% and not above the previous target"
+                    ));
+                }
+                target_profit_pct = pct;
+            }
+            if let Some(stop_loss) = &step.stop_loss {
+                stop_loss.to_base_units(None, &format!("{field}.stop_loss"))?;
+                let pct = stop_loss.percent_value();
+                if pct <= 0.0 || (stop_loss_pct > 0.0 && pct > stop_loss_pct) {
+                    return Err(anyhow!(
+                        "{field}.stop_loss must be above 0% and not wider than the previous stop"
+                    ));
+                }
+                stop_loss_pct = pct;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -661,3 +2017,295 @@ fn parse_strategy_amount_str(raw: &str) -> Result<StrategyAmount> {
         "strategy amount must be a percent string like \"10%\""
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_profile_url, Config, IpVersion, StrategyConfig};
+    use reqwest::Url;
+    use std::time::Duration;
+
+    #[test]
+    fn profile_urls_allow_plaintext_only_for_private_hosts() {
+        let check = |raw: &str| {
+            validate_profile_url(&Url::parse(raw).unwrap(), "stream_url", "wss", "ws").is_ok()
+        };
+        assert!(check("wss://stream.staging.example.com/v1/ws"));
+        assert!(check("ws://10.0.0.5:8082/v1/ws"));
+        assert!(!check("ws://stream.staging.example.com/v1/ws"));
+        assert!(!check("https://stream.staging.example.com/v1/ws"));
+    }
+
+    fn strategy_with_schedule(schedule: &str) -> StrategyConfig {
+        serde_yaml::from_str(&format!(
+            "target_profit: \"40%\"\nstop_loss: \"20%\"\ndeadline_timeout: 0\ntighten_after_sec:\n{schedule}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn tighten_schedule_applies_steps_by_age() {
+        let strategy = strategy_with_schedule(
+            "  - { after_sec: 300, target_profit: \"25%\" }\n  - { after_sec: 900, target_profit: \"10%\", stop_loss: \"5%\" }\n",
+        );
+        assert!(strategy.validate_tighten_schedule().is_ok());
+        assert_eq!(strategy.tighten_step_at(299), 0);
+        assert_eq!(strategy.tighten_step_at(300), 1);
+        assert_eq!(strategy.tighten_step_at(5_000), 2);
+        let first = strategy.tightened(1);
+        assert_eq!(first.target_profit.percent_value(), 25.0);
+        assert_eq!(first.stop_loss.percent_value(), 20.0);
+        let second = strategy.tightened(2);
+        assert_eq!(second.target_profit.percent_value(), 10.0);
+        assert_eq!(second.stop_loss.percent_value(), 5.0);
+    }
+
+    #[test]
+    fn stop_loss_schedule_is_a_tighten_schedule() {
+        let strategy: StrategyConfig = serde_yaml::from_str(
+            "target_profit: \"40%\"\nstop_loss: \"15%\"\ndeadline_timeout: 0\nstop_loss_schedule:\n  - { after_sec: 60, stop_loss: \"8%\" }\n",
+        )
+        .unwrap();
+        assert!(strategy.validate_tighten_schedule().is_ok());
+        assert_eq!(strategy.tighten_step_at(59), 0);
+        let tightened = strategy.tightened(strategy.tighten_step_at(60));
+        assert_eq!(tightened.stop_loss.percent_value(), 8.0);
+    }
+
+    #[test]
+    fn tighten_schedule_rejects_loosening_and_unordered_steps() {
+        let err = strategy_with_schedule("  - { after_sec: 300, stop_loss: \"30%\" }\n")
+            .validate_tighten_schedule()
+            .unwrap_err();
+        assert!(err.to_string().contains("strategy.tighten_after_sec[0].stop_loss"));
+        let err = strategy_with_schedule(
+            "  - { after_sec: 600, target_profit: \"30%\" }\n  - { after_sec: 600, target_profit: \"20%\" }\n",
+        )
+        .validate_tighten_schedule()
+        .unwrap_err();
+        assert!(err.to_string().contains("strategy.tighten_after_sec[1].after_sec"));
+    }
+
+    #[test]
+    fn env_only_config_types_values_and_rejects_typos() {
+        let vars = |extra: &[(&str, &str)]| {
+            let mut vars: Vec<(String, String)> = [
+                ("LASERSELL_ACCOUNT_KEYPAIR_PATH", "/secrets/wallet.json"),
+                ("LASERSELL_ACCOUNT_RPC_URL", "https://rpc.example.com/?key=a:b"),
+                ("LASERSELL_STRATEGY_TARGET_PROFIT", "50%"),
+                ("LASERSELL_STRATEGY_STOP_LOSS", "20%"),
+                ("LASERSELL_STRATEGY_DEADLINE_TIMEOUT", "120"),
+                ("LASERSELL_SELL_SLIPPAGE_MAX_BPS", "3000"),
+                ("LASERSELL_MIRROR_ENABLED", "true"),
+                ("LASERSELL_CONFIG_PATH", "/ignored.yml"),
+                ("HOME", "/root"),
+            ]
+            .iter()
+            .chain(extra)
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+            vars.sort();
+            vars
+        };
+        let cfg = Config::from_env_vars(vars(&[(
+            "LASERSELL_STRATEGY_TAKE_PROFIT_LEVELS",
+            "[{profit_pct: 50, sell_pct: 50}]",
+        )]))
+        .unwrap();
+        assert_eq!(cfg.account.keypair_path, "/secrets/wallet.json");
+        assert_eq!(cfg.http_rpc_url(), "https://rpc.example.com/?key=a:b");
+        assert_eq!(cfg.strategy.target_profit.percent_value(), 50.0);
+        assert_eq!(cfg.strategy.deadline_timeout_sec, 120);
+        assert_eq!(cfg.strategy.take_profit_levels.len(), 1);
+        assert_eq!(cfg.sell.slippage_max_bps, 3_000);
+        assert_eq!(cfg.sell.slippage_pad_bps, 2_000);
+        assert!(cfg.mirror.enabled);
+
+        let err = Config::from_env_vars(vars(&[("LASERSELL_SELL_SLIPAGE_MAX_BPS", "1")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown field `slipage_max_bps`"));
+    }
+
+    #[test]
+    fn low_latency_profile_tightens_sell_only_at_runtime() {
+        let raw = "account:
+  keypair_path: wallet.json
+  rpc_url: https://rpc.example.com
+strategy:
+  target_profit: \"50%\"
+  stop_loss: \"20%\"
+  deadline_timeout: 0
+sell:
+  simulate_before_send: true
+  hedge_request_ms: 50
+performance:
+  low_latency: true
+";
+        let cfg: Config = serde_yaml::from_str(raw).unwrap();
+        let sell = cfg.effective_sell();
+        assert!(!sell.simulate_before_send);
+        assert!(!sell.check_blockhash && cfg.sell.check_blockhash);
+        assert_eq!(sell.hedge_request_ms, 50);
+        assert_eq!(sell.confirm_timeout_sec, 5);
+        assert!(cfg.sell.simulate_before_send);
+        assert!(serde_yaml::to_string(&cfg).unwrap().contains("simulate_before_send: true"));
+    }
+
+    #[test]
+    fn wallet_choices_include_account_wallet_once() {
+        let with_wallets = |wallets: &str| -> Config {
+            serde_yaml::from_str(&format!(
+                "account:\n  keypair_path: main.json\n  rpc_url: https://rpc.example.com\n  \
+                 api_key: key\nstrategy:\n  target_profit: \"50%\"\n  stop_loss: \"20%\"\n  \
+                 deadline_timeout: 0\nwallets:\n{wallets}"
+            ))
+            .unwrap()
+        };
+        let cfg = with_wallets("  - { name: alt, keypair_path: alt.json }\n");
+        assert!(cfg.validate().is_ok());
+        let names: Vec<_> = cfg.wallet_choices().into_iter().map(|w| w.name).collect();
+        assert_eq!(names, ["default", "alt"]);
+
+        let cfg = with_wallets(
+            "  - { name: main, keypair_path: main.json }\n  - { name: alt, keypair_path: alt.json }\n",
+        );
+        let names: Vec<_> = cfg.wallet_choices().into_iter().map(|w| w.name).collect();
+        assert_eq!(names, ["main", "alt"]);
+
+        let err = with_wallets(
+            "  - { name: alt, keypair_path: a.json }\n  - { name: alt, keypair_path: b.json }\n",
+        )
+        .validate()
+        .unwrap_err();
+        assert!(err.to_string().contains("used twice"));
+        assert!(with_wallets("  - { name: default, keypair_path: alt.json }\n")
+            .validate()
+            .is_err());
+    }
+
+    fn with_network(network: &str) -> Config {
+        serde_yaml::from_str(&format!(
+            "account:\n  keypair_path: main.json\n  rpc_url: https://rpc.example.com\n  \
+             api_key: key\nstrategy:\n  target_profit: \"50%\"\n  stop_loss: \"20%\"\n  \
+             deadline_timeout: 0\nnetwork:\n{network}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn network_proxy_must_be_http_or_socks() {
+        let cfg = with_network("  proxy_url: socks5h://u:p@127.0.0.1:1080\n  ip_version: v4\n");
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.network.ip_version, IpVersion::V4);
+        assert!(cfg.http_client_builder().unwrap().build().is_ok());
+
+        let err = with_network("  proxy_url: ftp://127.0.0.1:21\n")
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("http, https, socks5 or socks5h"));
+        assert!(with_network("  proxy_url: not a url\n").validate().is_err());
+    }
+
+    #[test]
+    fn sell_send_target_overrides_account_and_checks_backends() {
+        let with_sell = |sell: &str| -> Config {
+            serde_yaml::from_str(&format!(
+                "account:\n  keypair_path: main.json\n  rpc_url: https://rpc.example.com\n  \
+                 api_key: key\n  send_target: astralane\n  astralane_api_key: k\nstrategy:\n  \
+                 target_profit: \"50%\"\n  stop_loss: \"20%\"\n  deadline_timeout: 0\nsell:\n{sell}"
+            ))
+            .unwrap()
+        };
+        let cfg = with_sell("  slippage_max_bps: 3000\n");
+        assert_eq!(cfg.send_target_name(), "astralane");
+        assert_eq!(cfg.send_mode_str(), "astralane");
+
+        let cfg = with_sell(
+            "  send_target: race\n  senders:\n    race: [jito, helius_sender]\n    \
+             jito: { uuid: secret }\n",
+        );
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.send_target_name(), "race");
+        assert_eq!(cfg.send_mode_str(), "rpc");
+        assert_eq!(
+            crate::tx::senders::from_config(&cfg).unwrap().label(),
+            "race"
+        );
+
+        for (sell, message) in [
+            ("  send_target: carrier_pigeon\n", "unknown send_target"),
+            (
+                "  send_target: webhook\n",
+                "sell.senders.webhook.url is required",
+            ),
+            (
+                "  send_target: race\n  senders:\n    race: [rpc]\n",
+                "at least two targets",
+            ),
+            (
+                "  send_target: race\n  senders:\n    race: [rpc, rpc]\n",
+                "lists \"rpc\" twice",
+            ),
+            (
+                "  send_target: webhook\n  senders:\n    webhook: { url: \"ftp://relay\" }\n",
+                "must use http or https",
+            ),
+        ] {
+            let err = with_sell(sell).validate().unwrap_err();
+            assert!(err.to_string().contains(message), "{sell}: {err}");
+        }
+    }
+
+    #[test]
+    fn rpc_timeouts_default_to_the_old_values_and_are_bounded() {
+        let cfg = with_network("  ip_version: auto\n");
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.rpc_connect_timeout(), Duration::from_millis(200));
+        assert_eq!(cfg.rpc_request_timeout(), Duration::from_millis(800));
+        assert_eq!(cfg.network.rpc_retries, 0);
+
+        let cfg = with_network("  rpc_request_timeout_ms: 3000\n  rpc_retries: 2\n");
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.rpc_request_timeout(), Duration::from_secs(3));
+
+        for bad in [
+            "  rpc_connect_timeout_ms: 10\n",
+            "  rpc_request_timeout_ms: 120000\n",
+            "  rpc_connect_timeout_ms: 2000\n  rpc_request_timeout_ms: 1000\n",
+            "  rpc_retries: 6\n",
+            "  send_rpc_request_timeout_ms: 50\n",
+        ] {
+            assert!(with_network(bad).validate().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn send_rpc_url_is_optional_and_validated_like_rpc_url() {
+        let mut cfg = with_network("  ip_version: auto\n");
+        assert_eq!(cfg.send_rpc_url(), None);
+        cfg.account.send_rpc_url =
+            secrecy::SecretString::new(" https://send.example.com ".to_string());
+        let err = cfg.validate().unwrap_err().to_string();
+        assert!(err.contains("only used with send_target \"rpc\""), "{err}");
+        cfg.account.send_target = Some("rpc".to_string());
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.send_rpc_url().as_deref(), Some("https://send.example.com"));
+        cfg.account.send_rpc_url =
+            secrecy::SecretString::new("http://send.example.com".to_string());
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn treasury_threshold_must_stay_above_the_fee_reserve() {
+        let mut cfg = with_network("  ip_version: auto\n");
+        cfg.treasury.threshold_sol = 0.05;
+        cfg.risk.min_sol_reserve = 0.1;
+        assert!(cfg.validate().is_ok(), "no sweep, so no conflict");
+        cfg.treasury.sweep_to = Some(solana_sdk::pubkey::Pubkey::new_unique().to_string());
+        let err = cfg.validate().unwrap_err().to_string();
+        assert!(err.contains("must be above risk.min_sol_reserve"), "{err}");
+        cfg.treasury.threshold_sol = 0.1;
+        assert!(cfg.validate().is_err());
+        cfg.treasury.threshold_sol = 0.5;
+        assert!(cfg.validate().is_ok());
+    }
+}
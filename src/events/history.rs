@@ -0,0 +1,516 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+
+use super::AppEvent;
+use crate::util::clock::now_unix_ms;
+
+const PNL_SAMPLES: usize = 120;
+const TIMELINE_ENTRIES: usize = 40;
+const TRACKED_MINTS: usize = 200;
+const SPARK_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const CHART_ROWS: usize = 5;
+const CHART_COLUMNS: usize = 60;
+
+/// Recent activity for one mint, kept so `show <mint>` can print it.
+#[derive(Debug, Default)]
+pub struct MintHistory {
+    updated_at_ms: u64,
+    cost_basis_units: Option<u64>,
+    /// `(at_ms, profit_units, proceeds_units)` samples from PnL updates.
+    pnl: VecDeque<(u64, i64, u64)>,
+    timeline: VecDeque<(u64, String)>,
+    signature: Option<String>,
+}
+
+impl MintHistory {
+    fn record(&mut self, now_ms: u64, event: &AppEvent) {
+        self.updated_at_ms = now_ms;
+        let entry = match event {
+            AppEvent::CostBasisSet {
+                cost_basis_lamports,
+                ..
+            } => {
+                self.cost_basis_units = Some(*cost_basis_lamports);
+                return;
+            }
+            AppEvent::PnlUpdate {
+                profit_lamports,
+                proceeds_lamports,
+                ..
+            } => {
+                if self.pnl.len() >= PNL_SAMPLES {
+                    self.pnl.pop_front();
+                }
+                self.pnl
+                    .push_back((now_ms, *profit_lamports, *proceeds_lamports));
+                return;
+            }
+            AppEvent::SellScheduled {
+                reason,
+                profit_lamports,
+                ..
+            } => format!("sell scheduled ({reason}, profit {profit_lamports})"),
+            AppEvent::SellAttempt {
+                attempt,
+                slippage_bps,
+                ..
+            } => format!("attempt {attempt} at {slippage_bps} bps"),
+            AppEvent::SellRetry {
+                attempt,
+                phase,
+                reason,
+                error,
+                ..
+            } => format!(
+                "attempt {attempt} failed during {phase}: {}",
+                reason.as_ref().unwrap_or(error)
+            ),
+            AppEvent::SellRefreshed {
+                latency_ms,
+                hedged,
+                source,
+                ..
+            } => {
+                let hedged = if *hedged { ", hedged" } else { "" };
+                format!("refreshed tx from {source} in {latency_ms} ms{hedged}")
+            }
+            AppEvent::SellComplete {
+                signature,
+                reason,
+                slippage_bps,
+                ..
+            } => {
+                self.signature = Some(signature.clone());
+                format!("sell complete ({reason}, {slippage_bps} bps)")
+            }
+            AppEvent::SellSettled {
+                slot,
+                fee_lamports,
+                priority_fee_lamports,
+                received_lamports,
+                received_usd1_units,
+                realized_pnl_units,
+                ..
+            } => {
+                let received = if *received_usd1_units > 0 {
+                    format!("{received_usd1_units} USD1 units")
+                } else {
+                    format!("{received_lamports} lamports")
+                };
+                let realized = realized_pnl_units
+                    .map(|pnl| format!(", realized {pnl}"))
+                    .unwrap_or_default();
+                format!(
+                    "settled in slot {slot}: received {received}, fee {fee_lamports} \
+                     ({priority_fee_lamports} priority){realized}"
+                )
+            }
+            AppEvent::SessionError { error, .. } => format!("error: {error}"),
+            _ => return,
+        };
+        if self.timeline.len() >= TIMELINE_ENTRIES {
+            self.timeline.pop_front();
+        }
+        self.timeline.push_back((now_ms, entry));
+    }
+
+    /// The `show <mint>` view; `accessible` leaves out the sparkline and
+    /// the chart and describes the proceeds in words instead.
+    fn render(&self, mint: &Pubkey, accessible: bool) -> String {
+        let mut out = format!("mint        {mint}\n");
+        if let Some(cost) = self.cost_basis_units {
+            let _ = writeln!(out, "cost basis  {cost}");
+        }
+        let profits: Vec<i64> = self.pnl.iter().map(|(_, profit, _)| *profit).collect();
+        if let (Some(min), Some(max), Some(&(_, last, proceeds))) =
+            (profits.iter().min(), profits.iter().max(), self.pnl.back())
+        {
+            if !accessible {
+                let _ = writeln!(out, "pnl         {}", sparkline(&profits));
+            }
+            let _ = writeln!(out, "profit      now {last}  min {min}  max {max}");
+            let first_proceeds = self.pnl.front().map_or(proceeds, |(_, _, p)| *p);
+            if accessible {
+                let _ = writeln!(
+                    out,
+                    "proceeds    from {first_proceeds} to {proceeds}{}",
+                    self.proceeds_moves()
+                );
+            } else {
+                let _ = writeln!(out, "proceeds    {first_proceeds} -> {proceeds}");
+                out.push_str(&self.proceeds_chart());
+            }
+        }
+        if let Some(&(start_ms, _)) = self.timeline.front() {
+            out.push_str("timeline\n");
+            for (at_ms, entry) in &self.timeline {
+                let offset = at_ms.saturating_sub(start_ms) as f64 / 1_000.0;
+                let _ = writeln!(out, "  +{offset:>7.3}s  {entry}");
+            }
+        }
+        if let Some(signature) = &self.signature {
+            let _ = writeln!(out, "signature   {signature}");
+        }
+        out
+    }
+
+    /// The quoted proceeds over the kept samples as a small bar chart, with
+    /// a `^` under the sample a sell was scheduled at and the largest move
+    /// between two updates, to tell a real move from a single bad quote.
+    fn proceeds_chart(&self) -> String {
+        if self.pnl.len() < 2 {
+            return String::new();
+        }
+        let samples: Vec<(u64, u64)> = self
+            .pnl
+            .iter()
+            .map(|(at_ms, _, proceeds)| (*at_ms, *proceeds))
+            .collect();
+        let columns = resample(&samples, CHART_COLUMNS);
+        let values: Vec<u64> = columns.iter().map(|(_, proceeds)| *proceeds).collect();
+        let (min, max) = (
+            values.iter().copied().min().unwrap_or(0),
+            values.iter().copied().max().unwrap_or(0),
+        );
+        let label_width = max.to_string().len();
+        let mut out = String::new();
+        for (row, line) in chart_rows(&values, min, max).iter().enumerate() {
+            let label = match row {
+                0 => max.to_string(),
+                _ if row == CHART_ROWS - 1 => min.to_string(),
+                _ => String::new(),
+            };
+            let _ = writeln!(out, "  {label:>label_width$} ┤{line}");
+        }
+        let scheduled_at = self
+            .timeline
+            .iter()
+            .find(|(_, entry)| entry.starts_with("sell scheduled"))
+            .map(|(at_ms, _)| *at_ms);
+        if let Some(column) =
+            scheduled_at.and_then(|at| columns.iter().position(|(column_ms, _)| *column_ms >= at))
+        {
+            let _ = writeln!(out, "  {:label_width$}  {:column$}^ sell scheduled", "", "");
+        }
+        let _ = writeln!(out, "  {}", proceeds_span(&samples));
+        out
+    }
+
+    /// The chart's summary in words: the span, the largest single update and
+    /// when a sell was scheduled.
+    fn proceeds_moves(&self) -> String {
+        let samples: Vec<(u64, u64)> = self
+            .pnl
+            .iter()
+            .map(|(at_ms, _, proceeds)| (*at_ms, *proceeds))
+            .collect();
+        let Some(&(start_ms, _)) = samples.first().filter(|_| samples.len() >= 2) else {
+            return String::new();
+        };
+        let mut out = format!(", {}", proceeds_span(&samples));
+        if let Some((at_ms, _)) = self
+            .timeline
+            .iter()
+            .find(|(_, entry)| entry.starts_with("sell scheduled"))
+        {
+            let after = at_ms.saturating_sub(start_ms) as f64 / 1_000.0;
+            let _ = write!(out, ", sell scheduled after {after:.1}s");
+        }
+        out
+    }
+
+    /// Everything kept for the mint as one JSON object, for session reports.
+    fn report(&self, mint: &Pubkey) -> serde_json::Value {
+        let pnl: Vec<_> = self
+            .pnl
+            .iter()
+            .map(|(at_ms, profit, proceeds)| {
+                json!({ "at_ms": at_ms, "profit_units": profit, "proceeds_units": proceeds })
+            })
+            .collect();
+        let events: Vec<_> = self
+            .timeline
+            .iter()
+            .map(|(at_ms, entry)| json!({ "at_ms": at_ms, "event": entry }))
+            .collect();
+        let final_pnl = self.pnl.back().map(|(at_ms, profit, proceeds)| {
+            json!({ "at_ms": at_ms, "profit_units": profit, "proceeds_units": proceeds })
+        });
+        json!({
+            "schema_version": super::EVENT_SCHEMA_VERSION,
+            "mint": mint.to_string(),
+            "cost_basis_units": self.cost_basis_units,
+            "signature": self.signature,
+            "final_pnl": final_pnl,
+            "events": events,
+            "pnl": pnl,
+        })
+    }
+}
+
+pub(super) fn event_mint(event: &AppEvent) -> Option<Pubkey> {
+    match event {
+        AppEvent::CostBasisSet { mint, .. }
+        | AppEvent::PnlUpdate { mint, .. }
+        | AppEvent::SellScheduled { mint, .. }
+        | AppEvent::SellAttempt { mint, .. }
+        | AppEvent::SellRetry { mint, .. }
+        | AppEvent::SellRefreshed { mint, .. }
+        | AppEvent::SellComplete { mint, .. }
+        | AppEvent::SellSettled { mint, .. }
+        | AppEvent::SessionError { mint, .. } => Some(*mint),
+        _ => None,
+    }
+}
+
+fn histories() -> &'static Mutex<HashMap<Pubkey, MintHistory>> {
+    static HISTORIES: OnceLock<Mutex<HashMap<Pubkey, MintHistory>>> = OnceLock::new();
+    HISTORIES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub(super) fn record(event: &AppEvent) {
+    let Some(mint) = event_mint(event) else {
+        return;
+    };
+    let mut histories = histories().lock();
+    if !histories.contains_key(&mint) && histories.len() >= TRACKED_MINTS {
+        let oldest = histories
+            .iter()
+            .min_by_key(|(_, history)| history.updated_at_ms)
+            .map(|(mint, _)| *mint);
+        if let Some(oldest) = oldest {
+            histories.remove(&oldest);
+        }
+    }
+    histories
+        .entry(mint)
+        .or_default()
+        .record(now_unix_ms(), event);
+}
+
+/// Printable drill-down for `mint`, or `None` if nothing was recorded.
+pub fn mint_detail(mint: &Pubkey) -> Option<String> {
+    histories()
+        .lock()
+        .get(mint)
+        .map(|history| history.render(mint, crate::util::theme::current().accessible()))
+}
+
+/// The recorded history of `mint` as a session report, or `None` if
+/// nothing was recorded.
+pub fn session_report(mint: &Pubkey) -> Option<serde_json::Value> {
+    histories()
+        .lock()
+        .get(mint)
+        .map(|history| history.report(mint))
+}
+
+/// Latest `(at_ms, profit_units)` PnL sample recorded for `mint`.
+pub fn last_pnl(mint: &Pubkey) -> Option<(u64, i64)> {
+    histories()
+        .lock()
+        .get(mint)
+        .and_then(|history| history.pnl.back())
+        .map(|&(at_ms, profit, _)| (at_ms, profit))
+}
+
+/// One eighth-height bar per value, scaled between the smallest and largest.
+pub(crate) fn sparkline(values: &[i64]) -> String {
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let span = (max - min) as f64;
+    values
+        .iter()
+        .map(|value| {
+            if span == 0.0 {
+                SPARK_BARS[3]
+            } else {
+                let scaled = (*value - min) as f64 / span * (SPARK_BARS.len() - 1) as f64;
+                SPARK_BARS[scaled.round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// `over <span>s` and the largest single update of proceeds `samples`.
+fn proceeds_span(samples: &[(u64, u64)]) -> String {
+    let span_ms = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => last.0.saturating_sub(first.0),
+        _ => 0,
+    };
+    let mut out = format!("over {:.1}s", span_ms as f64 / 1_000.0);
+    if let Some(step_pct) = largest_step_pct(samples) {
+        let _ = write!(out, ", largest single update {step_pct:+.1}%");
+    }
+    out
+}
+
+/// At most `columns` samples, each the last one of its stretch of `samples`.
+fn resample(samples: &[(u64, u64)], columns: usize) -> Vec<(u64, u64)> {
+    if samples.len() <= columns {
+        return samples.to_vec();
+    }
+    (1..=columns)
+        .map(|column| samples[column * samples.len() / columns - 1])
+        .collect()
+}
+
+/// `values` as `CHART_ROWS` lines of eighth-height bars, top row first.
+fn chart_rows(values: &[u64], min: u64, max: u64) -> Vec<String> {
+    let levels = (CHART_ROWS * SPARK_BARS.len()) as f64;
+    let heights: Vec<usize> = values
+        .iter()
+        .map(|value| {
+            if max == min {
+                CHART_ROWS * SPARK_BARS.len() / 2
+            } else {
+                ((value - min) as f64 / (max - min) as f64 * (levels - 1.0)).round() as usize + 1
+            }
+        })
+        .collect();
+    (0..CHART_ROWS)
+        .rev()
+        .map(|row| {
+            let floor = row * SPARK_BARS.len();
+            heights
+                .iter()
+                .map(|height| match height.saturating_sub(floor) {
+                    0 => ' ',
+                    filled => SPARK_BARS[filled.min(SPARK_BARS.len()) - 1],
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The biggest change in proceeds between two consecutive updates, as a
+/// percentage of the earlier one.
+fn largest_step_pct(samples: &[(u64, u64)]) -> Option<f64> {
+    samples
+        .windows(2)
+        .filter(|pair| pair[0].1 > 0)
+        .map(|pair| (pair[1].1 as f64 - pair[0].1 as f64) / pair[0].1 as f64 * 100.0)
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline_scales_between_min_and_max() {
+        assert_eq!(sparkline(&[-10, 0, 10]), "▁▅█");
+        assert_eq!(sparkline(&[5, 5]), "▄▄");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn chart_marks_the_sell_and_the_largest_jump() {
+        let mint = Pubkey::new_unique();
+        let mut history = MintHistory::default();
+        for (at_ms, proceeds) in [(0, 100), (1_000, 101), (2_000, 40), (3_000, 99)] {
+            history.record(
+                at_ms,
+                &AppEvent::PnlUpdate {
+                    mint,
+                    profit_lamports: proceeds as i64 - 100,
+                    proceeds_lamports: proceeds,
+                },
+            );
+        }
+        history.record(
+            2_000,
+            &AppEvent::SellScheduled {
+                mint,
+                reason: "stop_loss".to_string(),
+                profit_lamports: -60,
+            },
+        );
+        let chart = history.proceeds_chart();
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), CHART_ROWS + 2);
+        assert_eq!(lines[0], "  101 ┤▇█ ▇");
+        assert_eq!(lines[CHART_ROWS - 1], "   40 ┤██▁█");
+        assert_eq!(lines[CHART_ROWS], "         ^ sell scheduled");
+        assert_eq!(
+            lines[CHART_ROWS + 1],
+            "  over 3.0s, largest single update +147.5%"
+        );
+
+        let accessible = history.render(&mint, true);
+        assert!(accessible.contains(
+            "proceeds    from 100 to 99, over 3.0s, largest single update +147.5%, \
+             sell scheduled after 2.0s\n"
+        ));
+        assert!(!accessible.contains(['┤', '▁', '█']));
+
+        let many: Vec<(u64, u64)> = (0..120).map(|n| (n, n)).collect();
+        let columns = resample(&many, CHART_COLUMNS);
+        assert_eq!(columns.len(), CHART_COLUMNS);
+        assert_eq!(columns.last(), Some(&(119, 119)));
+    }
+
+    #[test]
+    fn render_includes_timeline_and_signature() {
+        let mint = Pubkey::new_unique();
+        let mut history = MintHistory::default();
+        history.record(
+            1_000,
+            &AppEvent::PnlUpdate {
+                mint,
+                profit_lamports: -5,
+                proceeds_lamports: 95,
+            },
+        );
+        history.record(
+            1_500,
+            &AppEvent::PnlUpdate {
+                mint,
+                profit_lamports: 20,
+                proceeds_lamports: 120,
+            },
+        );
+        history.record(
+            2_000,
+            &AppEvent::SellAttempt {
+                mint,
+                attempt: 1,
+                slippage_bps: 2500,
+            },
+        );
+        history.record(
+            3_250,
+            &AppEvent::SellComplete {
+                mint,
+                signature: "5igFull".to_string(),
+                reason: "target".to_string(),
+                slippage_bps: 2500,
+                trigger_slot: None,
+                confirm_slot: None,
+            },
+        );
+        let rendered = history.render(&mint, false);
+        assert!(rendered.contains("pnl         ▁█\n"));
+        assert!(rendered.contains("proceeds    95 -> 120\n"));
+        assert!(rendered.contains("  120 ┤ █\n"));
+        assert!(rendered.contains("   95 ┤▁█\n"));
+        assert!(rendered.contains("  over 0.5s, largest single update +26.3%\n"));
+        assert!(rendered.contains("  +  1.250s  sell complete (target, 2500 bps)\n"));
+        assert!(rendered.ends_with("signature   5igFull\n"));
+
+        let report = history.report(&mint);
+        assert_eq!(report["final_pnl"]["profit_units"], 20);
+        assert_eq!(report["events"][1]["at_ms"], 3_250);
+        assert_eq!(
+            report["events"][1]["event"],
+            "sell complete (target, 2500 bps)"
+        );
+        assert_eq!(report["signature"], "5igFull");
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+
+use super::AppEvent;
+use crate::util::clock::now_unix_ms;
+
+/// Unacknowledged notifications kept; older ones are dropped and counted.
+const MAX_NOTICES: usize = 100;
+
+/// Errors, stream disconnects, completed sells, treasury sweeps and refused
+/// signatures since the last `ack`, so they are not lost when the console
+/// scrolls past them.
+#[derive(Debug, Default)]
+struct Inbox {
+    notices: VecDeque<(u64, String)>,
+    dropped: usize,
+}
+
+impl Inbox {
+    fn record(&mut self, at_ms: u64, event: &AppEvent) {
+        let text = match event {
+            AppEvent::SellComplete {
+                mint,
+                signature,
+                reason,
+                ..
+            } => format!("sold {mint} ({reason}): {signature}"),
+            AppEvent::TreasurySwept {
+                to,
+                lamports,
+                signature,
+            } => format!(
+                "swept {:.4} SOL to {to}: {signature}",
+                *lamports as f64 / 1e9
+            ),
+            AppEvent::SigningRefused { reason, .. } => format!("refused to sign: {reason}"),
+            AppEvent::SessionError { mint, error } => format!("error on {mint}: {error}"),
+            AppEvent::SolanaWsStatus { connected: false } => "stream disconnected".to_string(),
+            _ => return,
+        };
+        if self.notices.len() >= MAX_NOTICES {
+            self.notices.pop_front();
+            self.dropped += 1;
+        }
+        self.notices.push_back((at_ms, text));
+    }
+
+    fn unread(&self) -> usize {
+        self.notices.len() + self.dropped
+    }
+
+    fn render(&self) -> String {
+        if self.unread() == 0 {
+            return "no unread notifications\n".to_string();
+        }
+        let mut out = String::new();
+        if self.dropped > 0 {
+            let _ = writeln!(out, "... {} older notifications dropped", self.dropped);
+        }
+        for (at_ms, text) in &self.notices {
+            let _ = writeln!(out, "{}  {text}", utc_time(*at_ms));
+        }
+        out.push_str("`ack` marks them read\n");
+        out
+    }
+}
+
+fn inbox() -> &'static Mutex<Inbox> {
+    static INBOX: OnceLock<Mutex<Inbox>> = OnceLock::new();
+    INBOX.get_or_init(|| Mutex::new(Inbox::default()))
+}
+
+pub(super) fn record(event: &AppEvent) {
+    inbox().lock().record(now_unix_ms(), event);
+}
+
+/// How many notifications are waiting for `ack`.
+pub fn unread() -> usize {
+    inbox().lock().unread()
+}
+
+/// The `notifications` listing, oldest first.
+pub fn render() -> String {
+    inbox().lock().render()
+}
+
+/// Marks every notification read; returns how many there were.
+pub fn acknowledge() -> usize {
+    let mut inbox = inbox().lock();
+    let count = inbox.unread();
+    *inbox = Inbox::default();
+    count
+}
+
+fn utc_time(at_ms: u64) -> String {
+    time::OffsetDateTime::from_unix_timestamp_nanos(i128::from(at_ms) * 1_000_000)
+        .ok()
+        .and_then(|at| {
+            at.format(time::macros::format_description!(
+                "[hour]:[minute]:[second]Z"
+            ))
+            .ok()
+        })
+        .unwrap_or_else(|| at_ms.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn keeps_notable_events_until_acknowledged() {
+        let mint = Pubkey::new_unique();
+        let mut inbox = Inbox::default();
+        inbox.record(0, &AppEvent::SolanaWsStatus { connected: true });
+        inbox.record(0, &AppEvent::Heartbeat);
+        assert_eq!(inbox.render(), "no unread notifications\n");
+
+        inbox.record(1_000, &AppEvent::SolanaWsStatus { connected: false });
+        inbox.record(
+            61_000,
+            &AppEvent::SessionError {
+                mint,
+                error: "sell failed".to_string(),
+            },
+        );
+        assert_eq!(inbox.unread(), 2);
+        assert_eq!(
+            inbox.render(),
+            format!(
+                "00:00:01Z  stream disconnected\n\
+                 00:01:01Z  error on {mint}: sell failed\n\
+                 `ack` marks them read\n"
+            )
+        );
+
+        for _ in 0..MAX_NOTICES {
+            inbox.record(0, &AppEvent::SolanaWsStatus { connected: false });
+        }
+        assert_eq!(inbox.unread(), MAX_NOTICES + 2);
+        assert!(inbox
+            .render()
+            .starts_with("... 2 older notifications dropped\n"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::{AppEvent, EVENT_SCHEMA_VERSION};
+use crate::util::clock::now_unix_ms;
+use crate::util::logging::{scrub_sensitive, LogRotation};
+
+/// Longest pause between two replayed events, whatever the speed, so a
+/// quiet hour in the journal does not stall the replay.
+const MAX_REPLAY_GAP: Duration = Duration::from_secs(5);
+
+static JOURNAL: OnceLock<Mutex<LineWriter<File>>> = OnceLock::new();
+static WRITE_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// One line of the event journal.
+#[derive(Serialize, Deserialize)]
+struct JournalLine {
+    schema_version: u32,
+    at_ms: u64,
+    #[serde(flatten)]
+    event: AppEvent,
+}
+
+/// Starts appending every event to `path` (`logging.event_journal`), one
+/// scrubbed JSON object per line, for `lasersell replay-ui`. The journal
+/// rotates at startup like the logs. Later calls are ignored.
+pub fn init(path: &Path, rotation: LogRotation) -> Result<()> {
+    if let Err(err) = rotation.rotate_if_needed(path) {
+        warn!(event = "event_journal_rotate_failed", path = %path.display(), error = %err);
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open event journal {}", path.display()))?;
+    if JOURNAL.set(Mutex::new(LineWriter::new(file))).is_ok() {
+        info!(event = "event_journal", path = %path.display());
+    }
+    Ok(())
+}
+
+fn journal_line(at_ms: u64, event: &AppEvent) -> Result<String> {
+    let line = serde_json::to_value(JournalLine {
+        schema_version: EVENT_SCHEMA_VERSION,
+        at_ms,
+        event: event.clone(),
+    })?;
+    Ok(scrub_sensitive(&line.to_string()))
+}
+
+/// Appends `event` to the journal, if one was started. Heartbeats are left
+/// out. The first failed write is logged.
+pub(super) fn record(event: &AppEvent) {
+    let Some(out) = JOURNAL.get() else {
+        return;
+    };
+    if matches!(event, AppEvent::Heartbeat) {
+        return;
+    }
+    let result =
+        journal_line(now_unix_ms(), event).and_then(|line| Ok(writeln!(out.lock(), "{line}")?));
+    if let Err(err) = result {
+        if !WRITE_FAILED.swap(true, Ordering::Relaxed) {
+            warn!(event = "event_journal_failed", error = %format!("{err:#}"));
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Result<(u64, AppEvent)> {
+    let parsed: JournalLine = serde_json::from_str(line)?;
+    Ok((parsed.at_ms, parsed.event))
+}
+
+/// `replay-ui`: plays the journal at `path` back through the console at
+/// `speed` times the recorded pace, then prints the `show <mint>` view of
+/// every mint it saw. Lines that do not parse are skipped.
+pub async fn replay(path: &Path, speed: f64) -> Result<()> {
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(anyhow!("--speed must be a positive number, got {speed}"));
+    }
+    let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut previous_ms = None;
+    let (mut replayed, mut skipped) = (0usize, 0usize);
+    let mut mints = BTreeSet::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("read {}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok((at_ms, event)) = parse_line(&line) else {
+            skipped += 1;
+            continue;
+        };
+        if let Some(previous) = previous_ms {
+            let gap = Duration::from_millis(at_ms.saturating_sub(previous));
+            tokio::time::sleep(gap.div_f64(speed).min(MAX_REPLAY_GAP)).await;
+        }
+        previous_ms = Some(at_ms);
+        if let Some(mint) = super::history::event_mint(&event) {
+            mints.insert(mint);
+        }
+        let _span = tracing::info_span!("replay", at = %recorded_time(at_ms)).entered();
+        super::replay(&event);
+        replayed += 1;
+    }
+    for mint in &mints {
+        if let Some(detail) = super::history::mint_detail(mint) {
+            println!("\n{detail}");
+        }
+    }
+    println!("replayed {replayed} events, skipped {skipped} unreadable lines");
+    Ok(())
+}
+
+/// When a journaled event happened, as RFC 3339 UTC.
+fn recorded_time(at_ms: u64) -> String {
+    time::OffsetDateTime::from_unix_timestamp_nanos(i128::from(at_ms) * 1_000_000)
+        .ok()
+        .and_then(|at| {
+            at.format(&time::format_description::well_known::Rfc3339)
+                .ok()
+        })
+        .unwrap_or_else(|| at_ms.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn lines_round_trip_with_base58_mints() {
+        let mint = Pubkey::new_unique();
+        let event = AppEvent::SellRetry {
+            mint,
+            attempt: 2,
+            elapsed_ms: 850,
+            phase: "confirm".to_string(),
+            class: "slippage".to_string(),
+            class_count: 1,
+            reason: None,
+            error: "slippage exceeded".to_string(),
+        };
+        let line = journal_line(1_700_000_000_000, &event).unwrap();
+        let raw: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(raw["event"], "sell_retry");
+        assert_eq!(raw["mint"], mint.to_string());
+        assert_eq!(raw["schema_version"], EVENT_SCHEMA_VERSION);
+
+        let (at_ms, parsed) = parse_line(&line).unwrap();
+        assert_eq!(at_ms, 1_700_000_000_000);
+        assert!(matches!(
+            parsed,
+            AppEvent::SellRetry { mint: parsed_mint, attempt: 2, .. } if parsed_mint == mint
+        ));
+        assert!(parse_line(r#"{"schema_version":1,"at_ms":1,"event":"newer_event"}"#).is_err());
+    }
+}
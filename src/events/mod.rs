@@ -1,8 +1,45 @@
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 
-/// Fire-and-forget event emission. In CLI mode events are logged via tracing.
+use crate::integration::ExpectedPosition;
+use crate::util::logging::ConsoleLogCommand;
+
+pub mod history;
+pub mod inbox;
+pub mod journal;
+pub mod notify;
+pub mod report;
+
+/// Version of the machine-readable output: the `notifications.command` env
+/// vars and `LASERSELL_EVENT_JSON`, the event journal, and the integration
+/// API's JSON bodies.
+/// Adding events or fields keeps the version; renaming or removing a field
+/// or event, or changing what a value means, bumps it.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Fire-and-forget event emission. In CLI mode events are logged via tracing,
+/// kept in a short per-mint history for `show <mint>` and session reports,
+/// listed by `notifications` until acknowledged when notable, passed to the
+/// `notifications.command` hook and appended to the event journal.
 pub fn emit(event: AppEvent) {
-    match &event {
+    history::record(&event);
+    inbox::record(&event);
+    notify::dispatch(&event);
+    report::dispatch(&event);
+    journal::record(&event);
+    log(&event);
+}
+
+/// What `replay-ui` does with a journaled event: its console line and the
+/// `show <mint>` history, without the hook, reports or journal.
+fn replay(event: &AppEvent) {
+    history::record(event);
+    log(event);
+}
+
+fn log(event: &AppEvent) {
+    match event {
         AppEvent::Startup { version, wallet_pubkey } => {
             tracing::info!(event = "startup", version = %version, wallet = %wallet_pubkey);
         }
@@ -33,11 +70,31 @@ pub fn emit(event: AppEvent) {
         AppEvent::SellAttempt { mint, attempt, slippage_bps } => {
             tracing::info!(event = "sell_attempt", mint = %mint, attempt, slippage_bps);
         }
-        AppEvent::SellRetry { mint, attempt, phase, error } => {
-            tracing::warn!(event = "sell_retry", mint = %mint, attempt, phase = %phase, error = %error);
+        AppEvent::SellRetry { mint, attempt, elapsed_ms, phase, class, class_count, reason, error } => {
+            tracing::warn!(event = "sell_retry", mint = %mint, attempt, elapsed_ms, phase = %phase, class = %class, class_count, reason = reason.as_deref(), error = %error);
         }
-        AppEvent::SellComplete { mint, signature, reason, slippage_bps } => {
-            tracing::info!(event = "sell_complete", mint = %mint, signature = %signature, reason = %reason, slippage_bps);
+        AppEvent::SellRefreshed { mint, attempt, latency_ms, hedged, source } => {
+            tracing::info!(event = "sell_refreshed", mint = %mint, attempt, latency_ms, hedged, source = %source);
+        }
+        AppEvent::SellComplete { mint, signature, reason, slippage_bps, trigger_slot, confirm_slot } => {
+            let slot_delta = crate::journal::slot_delta(*trigger_slot, *confirm_slot);
+            tracing::info!(event = "sell_complete", mint = %mint, signature = %signature, reason = %reason, slippage_bps, trigger_slot, confirm_slot, slot_delta);
+        }
+        AppEvent::SellSettled { mint, signature, slot, fee_lamports, priority_fee_lamports, received_lamports, received_usd1_units, realized_pnl_units } => {
+            tracing::info!(event = "sell_settled", mint = %mint, signature = %signature, slot, fee_lamports, priority_fee_lamports, received_lamports, received_usd1_units, realized_pnl_units);
+        }
+        AppEvent::TreasurySwept { to, lamports, signature } => {
+            tracing::info!(event = "treasury_swept", to = %to, lamports, signature = %signature);
+        }
+        AppEvent::SigningRefused { program, reason } => {
+            tracing::warn!(
+                event = "signing_refused",
+                program = program.as_deref().unwrap_or("-"),
+                reason = %reason
+            );
+        }
+        AppEvent::StrategyInsight { id, message } => {
+            tracing::info!(event = "strategy_insight", id = %id, message = %message);
         }
         AppEvent::SessionClosed { mint } => {
             tracing::info!(event = "session_closed", mint = %mint);
@@ -56,10 +113,14 @@ pub fn emit(event: AppEvent) {
     }
 }
 
-#[derive(Clone, Debug)]
+/// Serialized as `{"event": "<snake_case name>", ...fields}` in the event
+/// journal, with mints and pubkeys in base58.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
 pub enum AppEvent {
     Startup {
         version: String,
+        #[serde(with = "pubkey_str")]
         wallet_pubkey: Pubkey,
     },
     BalanceUpdate {
@@ -72,57 +133,256 @@ pub enum AppEvent {
         connected: bool,
     },
     MintDetected {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
     },
     SessionStarted {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
     },
     PositionTokensUpdated {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         tokens: u64,
     },
     CostBasisSet {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         cost_basis_lamports: u64,
     },
     PnlUpdate {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         profit_lamports: i64,
         proceeds_lamports: u64,
     },
     SellScheduled {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         reason: String,
         profit_lamports: i64,
     },
     SellAttempt {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         attempt: usize,
         slippage_bps: u16,
     },
+    /// A sell attempt failed and will be retried. `class` is the failure
+    /// class that picked the retry (send, stale, slippage) and `class_count`
+    /// how many failures of that class this sell has seen. `reason` is the
+    /// decoded on-chain error when the transaction landed and failed.
     SellRetry {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         attempt: usize,
+        elapsed_ms: u64,
         phase: String,
+        class: String,
+        class_count: usize,
+        reason: Option<String>,
         error: String,
     },
+    /// A refreshed sell tx arrived; `hedged` is true when the duplicate
+    /// request had to be sent before an answer came back.
+    SellRefreshed {
+        #[serde(with = "pubkey_str")]
+        mint: Pubkey,
+        attempt: usize,
+        latency_ms: u64,
+        hedged: bool,
+        /// Where the tx came from: `stream`, `exit_api` or `exit_api_hedge`.
+        source: String,
+    },
     SellComplete {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         signature: String,
         reason: String,
         slippage_bps: u16,
+        trigger_slot: Option<u64>,
+        confirm_slot: Option<u64>,
+    },
+    /// What a confirmed sell actually did to the wallet, read back from the
+    /// transaction. `realized_pnl_units` is the quote received less the
+    /// cost basis, when the cost basis is known.
+    SellSettled {
+        #[serde(with = "pubkey_str")]
+        mint: Pubkey,
+        signature: String,
+        slot: u64,
+        fee_lamports: u64,
+        priority_fee_lamports: u64,
+        received_lamports: i64,
+        received_usd1_units: i64,
+        realized_pnl_units: Option<i64>,
+    },
+    /// SOL above `treasury.threshold_sol` was moved to the cold wallet and
+    /// the transfer confirmed.
+    TreasurySwept {
+        #[serde(with = "pubkey_str")]
+        to: Pubkey,
+        lamports: u64,
+        signature: String,
+    },
+    /// The signing policy refused a server-built transaction. `program` is
+    /// set when it called one outside `security.allowed_program_ids`.
+    SigningRefused {
+        program: Option<String>,
+        reason: String,
+    },
+    /// Parameter suggestion derived from the trade journal.
+    StrategyInsight {
+        id: String,
+        message: String,
     },
     SessionClosed {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
     },
     SessionError {
+        #[serde(with = "pubkey_str")]
         mint: Pubkey,
         error: String,
     },
     Heartbeat,
 }
 
+mod pubkey_str {
+    use std::str::FromStr;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Pubkey::from_str(&raw).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum AppCommand {
     Quit,
+    /// Prune every stale session now instead of waiting for its TTL.
+    PruneSessions,
+    /// Print open sessions with their last quote, flagged stale while the
+    /// stream is down.
+    ShowSessions,
+    /// Print p50/p95 of trigger-to-send and trigger-to-confirm over recent
+    /// sells, split by stage.
+    ShowMetrics,
+    /// Print network, priority and tip fees from the trade journal.
+    ShowFees,
+    /// Print the editable settings with any staged changes.
+    ShowSettings,
+    /// Stage one setting; it is validated now and used on `ApplySettings`.
+    SetSetting {
+        field: String,
+        value: String,
+    },
+    /// Change one exit of an open position for this session only.
+    SetPositionSetting {
+        mint: Pubkey,
+        field: String,
+        value: String,
+    },
+    /// Use the staged settings and save them to the config file.
+    ApplySettings,
+    /// Drop the staged settings.
+    CancelSettings,
+    /// Print the configured wallets and which one is signing.
+    ShowWallets,
+    /// Unlock the named wallet and sign with it from now on. The passphrase
+    /// is read at the console when the keystore needs one.
+    UseWallet {
+        name: String,
+        passphrase: Option<SecretString>,
+    },
+    /// Print open cost basis by quote token and market type.
+    ShowExposure,
+    /// Print SOL locked as token account rent and what closing empty
+    /// accounts would reclaim.
+    ShowRent,
+    /// Print what a dust sweep would sell, burn and close; with `execute`,
+    /// do it.
+    Sweep { execute: bool },
+    /// List every token the wallet holds with an estimated value.
+    ShowPortfolio,
+    /// Sell the holding on row `n` of the last `portfolio` listing.
+    SellHolding { n: usize },
+    /// Print the latest chain congestion reading.
+    ShowCongestion,
+    /// Print log file sizes and the last error.
+    ShowLogs,
+    /// Errors, stream disconnects and completed sells not yet acknowledged.
+    ShowNotifications,
+    AckNotifications,
+    /// A buy bot registered a position it is about to open.
+    ExpectPosition(ExpectedPosition),
+    /// Print the recorded PnL history and sell timeline for a mint.
+    ShowMint {
+        mint: Pubkey,
+    },
+    /// Print every step of the latest sell of a mint: the signal, each
+    /// attempt and its slippage, failures, refreshes and the outcome.
+    Why {
+        mint: Pubkey,
+    },
+    /// Adjust what the console log shows.
+    ConsoleLog(ConsoleLogCommand),
+    /// Sell a position, or any token the wallet holds, now, optionally only
+    /// part of it and at a one-off slippage that may exceed
+    /// `sell.slippage_max_bps`.
+    Sell {
+        mint: Pubkey,
+        amount: Option<SellAmount>,
+        slippage_bps: Option<u16>,
+    },
+    /// Arm a client-side limit sell at `profit_pct`, or disarm it when unset.
+    SetLimit {
+        mint: Pubkey,
+        profit_pct: Option<f64>,
+    },
+    /// Print the armed limit sells.
+    ShowLimits,
+    /// Print the current strategy insights.
+    ShowInsights,
+    /// Hide a strategy insight, across restarts too.
+    DismissInsight {
+        id: String,
+    },
+    /// Sell a position at a set time, or cancel that when unset.
+    SellAt {
+        mint: Pubkey,
+        when: Option<SellTime>,
+    },
+    /// Buy `lamports` worth of a token through the exit API; the stream
+    /// opens the session once the tokens arrive.
+    Buy {
+        mint: Pubkey,
+        lamports: u64,
+        slippage_bps: Option<u16>,
+    },
+}
+
+/// When a `sell-at` exit fires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SellTime {
+    /// This many seconds from when the command is entered.
+    AfterSec(u64),
+    /// At this Unix time, in milliseconds.
+    AtMs(u64),
+}
+
+/// How much of a holding a manual `sell` sells; all of it when unset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SellAmount {
+    /// Percent of the tokens held, above 0 and at most 100.
+    Percent(f64),
+    /// Tokens in whole units, scaled by the mint's decimals.
+    Tokens(f64),
 }
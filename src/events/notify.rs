@@ -0,0 +1,167 @@
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use super::{AppEvent, EVENT_SCHEMA_VERSION};
+
+static COMMAND: OnceLock<String> = OnceLock::new();
+
+/// Sets the `notifications.command` hook. Later calls are ignored.
+pub fn init(command: Option<String>) {
+    if let Some(command) = command.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()) {
+        let _ = COMMAND.set(command);
+    }
+}
+
+/// `LASERSELL_*` variables for the events that trigger the hook, plus the
+/// schema version and the same fields as one JSON object.
+fn notification_env(event: &AppEvent) -> Option<Vec<(&'static str, String)>> {
+    let mut env = match event {
+        AppEvent::SellComplete {
+            mint,
+            signature,
+            reason,
+            slippage_bps,
+            ..
+        } => vec![
+            ("LASERSELL_EVENT", "sell_complete".to_string()),
+            ("LASERSELL_MINT", mint.to_string()),
+            ("LASERSELL_SIGNATURE", signature.clone()),
+            ("LASERSELL_REASON", reason.clone()),
+            ("LASERSELL_SLIPPAGE_BPS", slippage_bps.to_string()),
+        ],
+        AppEvent::SellSettled {
+            mint,
+            signature,
+            fee_lamports,
+            priority_fee_lamports,
+            received_lamports,
+            received_usd1_units,
+            realized_pnl_units,
+            ..
+        } => {
+            let mut env = vec![
+                ("LASERSELL_EVENT", "sell_settled".to_string()),
+                ("LASERSELL_MINT", mint.to_string()),
+                ("LASERSELL_SIGNATURE", signature.clone()),
+                ("LASERSELL_FEE_LAMPORTS", fee_lamports.to_string()),
+                ("LASERSELL_PRIORITY_FEE_LAMPORTS", priority_fee_lamports.to_string()),
+                ("LASERSELL_RECEIVED_LAMPORTS", received_lamports.to_string()),
+                ("LASERSELL_RECEIVED_USD1_UNITS", received_usd1_units.to_string()),
+            ];
+            if let Some(pnl) = realized_pnl_units {
+                env.push(("LASERSELL_REALIZED_PNL_UNITS", pnl.to_string()));
+            }
+            env
+        }
+        AppEvent::TreasurySwept {
+            to,
+            lamports,
+            signature,
+        } => vec![
+            ("LASERSELL_EVENT", "treasury_swept".to_string()),
+            ("LASERSELL_TO", to.to_string()),
+            ("LASERSELL_LAMPORTS", lamports.to_string()),
+            ("LASERSELL_SIGNATURE", signature.clone()),
+        ],
+        AppEvent::SigningRefused { program, reason } => {
+            let mut env = vec![
+                ("LASERSELL_EVENT", "signing_refused".to_string()),
+                ("LASERSELL_REASON", reason.clone()),
+            ];
+            if let Some(program) = program {
+                env.push(("LASERSELL_PROGRAM", program.clone()));
+            }
+            env
+        }
+        AppEvent::SessionError { mint, error } => vec![
+            ("LASERSELL_EVENT", "session_error".to_string()),
+            ("LASERSELL_MINT", mint.to_string()),
+            ("LASERSELL_ERROR", error.clone()),
+        ],
+        AppEvent::SolanaWsStatus { connected: false } => {
+            vec![("LASERSELL_EVENT", "stream_disconnected".to_string())]
+        }
+        _ => return None,
+    };
+    let mut payload = serde_json::Map::new();
+    payload.insert("schema_version".to_string(), EVENT_SCHEMA_VERSION.into());
+    for (name, value) in &env {
+        let key = name.trim_start_matches("LASERSELL_").to_ascii_lowercase();
+        payload.insert(key, value.clone().into());
+    }
+    env.push(("LASERSELL_SCHEMA_VERSION", EVENT_SCHEMA_VERSION.to_string()));
+    env.push((
+        "LASERSELL_EVENT_JSON",
+        serde_json::Value::Object(payload).to_string(),
+    ));
+    Some(env)
+}
+
+/// Runs the hook for `event` in the background, if one is configured.
+pub(super) fn dispatch(event: &AppEvent) {
+    let Some(command) = COMMAND.get() else {
+        return;
+    };
+    let Some(env) = notification_env(event) else {
+        return;
+    };
+    let mut process = shell_command(command);
+    process
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    match process.spawn() {
+        // Reap the child off the caller's thread so slow hooks never stall a sell.
+        Ok(mut child) => {
+            std::thread::spawn(move || {
+                if let Ok(status) = child.wait() {
+                    if !status.success() {
+                        tracing::warn!(event = "notification_command_failed", status = %status);
+                    }
+                }
+            });
+        }
+        Err(err) => tracing::warn!(event = "notification_command_failed", error = %err),
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut process = Command::new("sh");
+    process.arg("-c").arg(command);
+    process
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut process = Command::new("cmd");
+    process.arg("/C").arg(command);
+    process
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn env_describes_only_hooked_events() {
+        let mint = Pubkey::new_unique();
+        let env = notification_env(&AppEvent::SessionError {
+            mint,
+            error: "boom".to_string(),
+        })
+        .unwrap();
+        assert_eq!(env[0], ("LASERSELL_EVENT", "session_error".to_string()));
+        assert_eq!(env[1], ("LASERSELL_MINT", mint.to_string()));
+        assert_eq!(env[3], ("LASERSELL_SCHEMA_VERSION", "1".to_string()));
+        let payload: serde_json::Value = serde_json::from_str(&env[4].1).unwrap();
+        assert_eq!(payload["schema_version"], 1);
+        assert_eq!(payload["event"], "session_error");
+        assert_eq!(payload["error"], "boom");
+        assert!(notification_env(&AppEvent::SolanaWsStatus { connected: false }).is_some());
+        assert!(notification_env(&AppEvent::SolanaWsStatus { connected: true }).is_none());
+        assert!(notification_env(&AppEvent::Heartbeat).is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use solana_sdk::pubkey::Pubkey;
+use time::OffsetDateTime;
+
+use super::AppEvent;
+use crate::util::clock::now_unix_ms;
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Turns on per-session reports under `dir` (`reports.enabled`). Later
+/// calls are ignored.
+pub fn init(dir: PathBuf) {
+    let _ = DIR.set(dir);
+}
+
+/// Writes the closed session's history to `<dir>/<date>/<mint>.json`, if
+/// reports are on. A sell settled after the close is not in it.
+pub(super) fn dispatch(event: &AppEvent) {
+    let (Some(dir), AppEvent::SessionClosed { mint }) = (DIR.get(), event) else {
+        return;
+    };
+    let Some(report) = super::history::session_report(mint) else {
+        return;
+    };
+    let path = report_path(dir, mint, now_unix_ms());
+    match write_report(&path, &report) {
+        Ok(()) => {
+            tracing::debug!(event = "session_report_written", mint = %mint, path = %path.display())
+        }
+        Err(err) => {
+            tracing::warn!(event = "session_report_failed", mint = %mint, error = %format!("{err:#}"))
+        }
+    }
+}
+
+/// `<dir>/<UTC date>/<mint>.json`; a later session of the same mint that
+/// day replaces the report.
+fn report_path(dir: &Path, mint: &Pubkey, now_ms: u64) -> PathBuf {
+    let date = OffsetDateTime::from_unix_timestamp_nanos(i128::from(now_ms) * 1_000_000)
+        .ok()
+        .and_then(|at| {
+            at.format(time::macros::format_description!("[year]-[month]-[day]"))
+                .ok()
+        })
+        .unwrap_or_else(|| "undated".to_string());
+    dir.join(date).join(format!("{mint}.json"))
+}
+
+fn write_report(path: &Path, report: &serde_json::Value) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("create report dir {}", parent.display()))?;
+    }
+    let raw = serde_json::to_vec_pretty(report).context("serialize session report")?;
+    crate::util::fs_utils::atomic_write(path, &raw, Some(0o600))
+        .with_context(|| format!("write session report {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reports_go_under_the_utc_date() {
+        let dir = tempdir().unwrap();
+        let mint = Pubkey::new_unique();
+        let path = report_path(dir.path(), &mint, 1_700_000_000_000);
+        assert_eq!(
+            path,
+            dir.path().join("2023-11-14").join(format!("{mint}.json"))
+        );
+        write_report(&path, &serde_json::json!({ "mint": mint.to_string() })).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(written["mint"], mint.to_string());
+    }
+}
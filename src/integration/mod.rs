@@ -0,0 +1,412 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::config::{IntegrationConfig, StrategyAmount, StrategyConfig};
+use crate::events::{AppCommand, EVENT_SCHEMA_VERSION};
+
+/// How long a registration waits for its buy to land before it is dropped.
+pub const EXPECTED_POSITION_TTL_SECS: u64 = 900;
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const CONNECTION_TIMEOUT_SECS: u64 = 5;
+
+/// Strategy a bot wants for one position. Unset fields keep the
+/// configured strategy.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedStrategy {
+    #[serde(default)]
+    pub target_profit: Option<StrategyAmount>,
+    #[serde(default)]
+    pub stop_loss: Option<StrategyAmount>,
+    #[serde(default)]
+    pub trailing_stop: Option<StrategyAmount>,
+}
+
+impl ExpectedStrategy {
+    pub fn apply_to(&self, base: &StrategyConfig) -> StrategyConfig {
+        let mut strategy = base.clone();
+        if let Some(target_profit) = &self.target_profit {
+            strategy.target_profit = target_profit.clone();
+        }
+        if let Some(stop_loss) = &self.stop_loss {
+            strategy.stop_loss = stop_loss.clone();
+        }
+        if let Some(trailing_stop) = &self.trailing_stop {
+            strategy.trailing_stop = trailing_stop.clone();
+        }
+        strategy
+    }
+}
+
+/// A position a buy bot is about to open.
+#[derive(Clone, Debug)]
+pub struct ExpectedPosition {
+    pub mint: Pubkey,
+    pub expected_tokens: Option<u64>,
+    /// Quote token base units (lamports for SOL pools) the buy spends.
+    pub cost_basis_units: Option<u64>,
+    pub strategy: Option<ExpectedStrategy>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegisterRequest {
+    mint: String,
+    #[serde(default)]
+    expected_tokens: Option<u64>,
+    #[serde(default)]
+    cost_basis_units: Option<u64>,
+    #[serde(default)]
+    strategy: Option<ExpectedStrategy>,
+}
+
+struct Request<'a> {
+    method: &'a str,
+    path: &'a str,
+    authorization: Option<&'a str>,
+    origin: Option<&'a str>,
+    content_type: Option<&'a str>,
+    body: &'a [u8],
+}
+
+struct Response {
+    status: u16,
+    body: serde_json::Value,
+    registered: Option<ExpectedPosition>,
+}
+
+impl Response {
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+            registered: None,
+        }
+    }
+}
+
+/// Routes one request:
+///
+/// ```text
+/// GET  /v1/health              -> 200 {"status":"ok"}
+/// POST /v1/expected-positions  -> 202 {"status":"registered",...}
+///      {"mint": "...", "expected_tokens": 1000000, "cost_basis_units": 500000000,
+///       "strategy": {"target_profit": "50%", "stop_loss": "20%", "trailing_stop": "10%"}}
+/// ```
+///
+/// Browsers send `Origin` on cross-site requests and cannot POST
+/// `application/json` without a preflight this server never answers, so
+/// requests with an `Origin` or another content type are refused: a web
+/// page cannot register positions even when no token is set.
+fn handle_request(request: &Request<'_>, token: Option<&str>) -> Response {
+    if request.origin.is_some() {
+        return Response::error(403, "browser requests are not accepted");
+    }
+    if let Some(token) = token {
+        let presented = request
+            .authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        if !presented.is_some_and(|presented| constant_time_eq(presented, token)) {
+            return Response::error(401, "missing or wrong bearer token");
+        }
+    }
+    match (request.method, request.path) {
+        ("GET", "/v1/health") => Response {
+            status: 200,
+            body: json!({ "status": "ok" }),
+            registered: None,
+        },
+        ("POST", "/v1/expected-positions") => {
+            let json = request.content_type.is_some_and(|value| {
+                value
+                    .split(';')
+                    .next()
+                    .is_some_and(|media| media.trim().eq_ignore_ascii_case("application/json"))
+            });
+            if !json {
+                return Response::error(415, "content-type must be application/json");
+            }
+            let parsed: RegisterRequest = match serde_json::from_slice(request.body) {
+                Ok(parsed) => parsed,
+                Err(err) => return Response::error(400, format!("invalid body: {err}")),
+            };
+            let Ok(mint) = Pubkey::from_str(parsed.mint.trim()) else {
+                return Response::error(400, format!("invalid mint {}", parsed.mint));
+            };
+            Response {
+                status: 202,
+                body: json!({
+                    "status": "registered",
+                    "mint": mint.to_string(),
+                    "expires_in_sec": EXPECTED_POSITION_TTL_SECS,
+                }),
+                registered: Some(ExpectedPosition {
+                    mint,
+                    expected_tokens: parsed.expected_tokens,
+                    cost_basis_units: parsed.cost_basis_units,
+                    strategy: parsed.strategy,
+                }),
+            }
+        }
+        (_, "/v1/health" | "/v1/expected-positions") => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+/// Compares without stopping at the first differing byte, so response
+/// timing does not reveal how much of a guessed token was right.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Starts the local API when `integration.listen` is set. Registrations are
+/// forwarded to the app as [`AppCommand::ExpectPosition`].
+pub async fn spawn(cfg: &IntegrationConfig, cmd_tx: mpsc::UnboundedSender<AppCommand>) {
+    let Some(listen) = cfg.listen.as_deref().map(str::trim) else {
+        return;
+    };
+    let listener = match TcpListener::bind(listen).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            warn!(event = "integration_api_unavailable", listen, error = %err);
+            return;
+        }
+    };
+    info!(event = "integration_api_listening", listen);
+    let token: Option<Arc<str>> = cfg
+        .token
+        .as_deref()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(Arc::from);
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    debug!(event = "integration_api_accept_failed", error = %err);
+                    continue;
+                }
+            };
+            let token = token.clone();
+            let cmd_tx = cmd_tx.clone();
+            tokio::spawn(async move {
+                let served = tokio::time::timeout(
+                    Duration::from_secs(CONNECTION_TIMEOUT_SECS),
+                    serve_connection(stream, token.as_deref(), &cmd_tx),
+                )
+                .await;
+                match served {
+                    Ok(Err(err)) => debug!(event = "integration_api_request_failed", error = %err),
+                    Err(_) => debug!(event = "integration_api_request_timeout"),
+                    Ok(Ok(())) => {}
+                }
+            });
+        }
+    });
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    token: Option<&str>,
+    cmd_tx: &mpsc::UnboundedSender<AppCommand>,
+) -> Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEADER_BYTES {
+            return write_response(&mut stream, &Response::error(431, "headers too large")).await;
+        }
+        let read = stream.read(&mut chunk).await.context("read request")?;
+        if read == 0 {
+            return Err(anyhow!("connection closed before headers"));
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (method, path) = (
+        request_line.next().unwrap_or_default(),
+        request_line.next().unwrap_or_default(),
+    );
+    let (mut content_length, mut authorization) = (0usize, None);
+    let (mut origin, mut content_type) = (None, None);
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().context("invalid content-length")?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.trim());
+        } else if name.eq_ignore_ascii_case("origin") {
+            origin = Some(value.trim());
+        } else if name.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.trim());
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return write_response(&mut stream, &Response::error(413, "body too large")).await;
+    }
+    let mut body = buf.split_off(header_end);
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await.context("read body")?;
+        if read == 0 {
+            return Err(anyhow!("connection closed before body"));
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    let response = handle_request(
+        &Request {
+            method,
+            path,
+            authorization,
+            origin,
+            content_type,
+            body: &body,
+        },
+        token,
+    );
+    if let Some(position) = response.registered.clone() {
+        let _ = cmd_tx.send(AppCommand::ExpectPosition(position));
+    }
+    write_response(&mut stream, &response).await
+}
+
+async fn write_response(stream: &mut TcpStream, response: &Response) -> Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "Request Header Fields Too Large",
+    };
+    let mut body = response.body.clone();
+    if let Some(fields) = body.as_object_mut() {
+        fields.insert("schema_version".to_string(), EVENT_SCHEMA_VERSION.into());
+    }
+    let body = body.to_string();
+    let raw = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        body.len()
+    );
+    stream
+        .write_all(raw.as_bytes())
+        .await
+        .context("write response")?;
+    stream.shutdown().await.ok();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "So11111111111111111111111111111111111111112";
+
+    fn post(body: &str, authorization: Option<&str>, token: Option<&str>) -> Response {
+        handle_request(
+            &Request {
+                method: "POST",
+                path: "/v1/expected-positions",
+                authorization,
+                origin: None,
+                content_type: Some("application/json"),
+                body: body.as_bytes(),
+            },
+            token,
+        )
+    }
+
+    #[test]
+    fn registers_expected_positions() {
+        let body = format!(
+            r#"{{"mint":"{MINT}","cost_basis_units":500000000,"strategy":{{"stop_loss":"15%"}}}}"#
+        );
+        let response = post(&body, Some("Bearer s3cret"), Some("s3cret"));
+        assert_eq!(response.status, 202);
+        let position = response.registered.expect("registered");
+        assert_eq!(position.mint.to_string(), MINT);
+        assert_eq!(position.cost_basis_units, Some(500_000_000));
+        let base: StrategyConfig = serde_yaml::from_str(
+            "target_profit: \"50%\"\nstop_loss: \"30%\"\ndeadline_timeout: 0\n",
+        )
+        .unwrap();
+        let strategy = position.strategy.expect("strategy").apply_to(&base);
+        assert_eq!(strategy.stop_loss.percent_value(), 15.0);
+        assert_eq!(strategy.target_profit.percent_value(), 50.0);
+
+        assert_eq!(post(&body, None, Some("s3cret")).status, 401);
+        assert_eq!(post(r#"{"mint":"nope"}"#, None, None).status, 400);
+        assert_eq!(post(r#"{"mint":"x","extra":1}"#, None, None).status, 400);
+        let wrong_method = handle_request(
+            &Request {
+                method: "GET",
+                path: "/v1/expected-positions",
+                authorization: None,
+                origin: None,
+                content_type: None,
+                body: b"",
+            },
+            None,
+        );
+        assert_eq!(wrong_method.status, 405);
+    }
+
+    #[test]
+    fn refuses_what_a_web_page_can_send() {
+        let body = format!(r#"{{"mint":"{MINT}","strategy":{{"stop_loss":"99%"}}}}"#);
+        let request = |origin, content_type| {
+            handle_request(
+                &Request {
+                    method: "POST",
+                    path: "/v1/expected-positions",
+                    authorization: None,
+                    origin,
+                    content_type,
+                    body: body.as_bytes(),
+                },
+                None,
+            )
+            .status
+        };
+        assert_eq!(request(None, Some("application/json; charset=utf-8")), 202);
+        assert_eq!(request(None, Some("text/plain")), 415);
+        assert_eq!(request(None, None), 415);
+        assert_eq!(
+            request(Some("https://evil.example"), Some("application/json")),
+            403
+        );
+        assert!(constant_time_eq("s3cret", "s3cret"));
+        assert!(!constant_time_eq("s3cret", "s3creT"));
+        assert!(!constant_time_eq("s3cret", "s3cre"));
+    }
+}
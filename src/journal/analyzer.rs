@@ -0,0 +1,147 @@
+use crate::config::StrategyConfig;
+
+use super::TradeRecord;
+
+/// Fewer closed trades than this and suggestions are mostly noise.
+const MIN_SAMPLES: usize = 5;
+/// Only the most recent closed trades are judged, so suggestions follow
+/// how the strategy trades now rather than months of older settings.
+pub const WINDOW: usize = 200;
+const QUICK_STOP_MS: u64 = 10_000;
+const QUICK_STOP_SHARE: f64 = 0.6;
+const TIMEOUT_SHARE: f64 = 0.6;
+const EXECUTION_DRAG_PCT: f64 = 2.0;
+
+/// A parameter suggestion derived from journal history. `id` is stable so
+/// the insight can be dismissed for good.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Insight {
+    pub id: &'static str,
+    pub message: String,
+}
+
+pub fn analyze(records: &[TradeRecord], strategy: &StrategyConfig) -> Vec<Insight> {
+    let mut insights = Vec::new();
+    let records: Vec<TradeRecord> = records
+        .iter()
+        .filter(|record| !record.self_transfer)
+        .cloned()
+        .collect();
+    if records.len() < MIN_SAMPLES {
+        return insights;
+    }
+
+    let stop_loss_pct = strategy.stop_loss.percent_value();
+    let stops: Vec<&TradeRecord> = records.iter().filter(|r| r.reason == "stop_loss").collect();
+    if stop_loss_pct > 0.0 && stops.len() >= MIN_SAMPLES {
+        let quick = stops
+            .iter()
+            .filter(|r| r.held_ms.is_some_and(|held| held <= QUICK_STOP_MS))
+            .count();
+        let share = quick as f64 / stops.len() as f64;
+        if share >= QUICK_STOP_SHARE {
+            let widened = (stop_loss_pct * 1.2).ceil();
+            insights.push(Insight {
+                id: "stop_loss_too_tight",
+                message: format!(
+                    "your stop loss triggers {:.0}% of the time within {}s — consider widening to {widened:.0}%",
+                    share * 100.0,
+                    QUICK_STOP_MS / 1000
+                ),
+            });
+        }
+    }
+
+    let timeouts = records.iter().filter(|r| r.reason == "timeout").count();
+    let timeout_share = timeouts as f64 / records.len() as f64;
+    if strategy.deadline_timeout_sec > 0 && timeout_share >= TIMEOUT_SHARE {
+        insights.push(Insight {
+            id: "deadline_dominates",
+            message: format!(
+                "{:.0}% of exits hit the {}s deadline — consider a longer deadline or a lower target profit",
+                timeout_share * 100.0,
+                strategy.deadline_timeout_sec
+            ),
+        });
+    }
+
+    let drags: Vec<f64> = records
+        .iter()
+        .filter_map(|r| {
+            let cost = r.cost_basis_units.filter(|cost| *cost > 0)?;
+            Some(r.execution_pnl_units? as f64 / cost as f64 * 100.0)
+        })
+        .collect();
+    if drags.len() >= MIN_SAMPLES {
+        let avg = drags.iter().sum::<f64>() / drags.len() as f64;
+        if avg <= -EXECUTION_DRAG_PCT {
+            insights.push(Insight {
+                id: "execution_drag",
+                message: format!(
+                    "execution costs average {:.1}% of entry per trade — consider a faster send target or a lower slippage pad",
+                    -avg
+                ),
+            });
+        }
+    }
+
+    insights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StrategyAmount;
+
+    fn strategy(stop_loss_pct: f64, deadline_timeout_sec: u64) -> StrategyConfig {
+        StrategyConfig {
+            target_profit: StrategyAmount::Percent(20.0),
+            stop_loss: StrategyAmount::Percent(stop_loss_pct),
+            trailing_stop: StrategyAmount::Percent(0.0),
+            deadline_timeout_sec,
+            sell_on_graduation: false,
+            take_profit_levels: Vec::new(),
+            liquidity_guard: false,
+            breakeven_trail: StrategyAmount::Percent(0.0),
+            tighten_after_sec: Vec::new(),
+            max_hold_sec: 0,
+        }
+    }
+
+    fn record(reason: &str, held_ms: u64) -> TradeRecord {
+        TradeRecord {
+            reason: reason.to_string(),
+            held_ms: Some(held_ms),
+            ..TradeRecord::default()
+        }
+    }
+
+    #[test]
+    fn quick_stop_losses_suggest_wider_stop() {
+        let mut records: Vec<TradeRecord> = (0..7).map(|_| record("stop_loss", 4_000)).collect();
+        records.extend((0..3).map(|_| record("stop_loss", 60_000)));
+        let insights = analyze(&records, &strategy(10.0, 0));
+        assert_eq!(insights.len(), 1);
+        assert_eq!(insights[0].id, "stop_loss_too_tight");
+        assert_eq!(
+            insights[0].message,
+            "your stop loss triggers 70% of the time within 10s — consider widening to 12%"
+        );
+    }
+
+    #[test]
+    fn small_history_yields_nothing() {
+        let records: Vec<TradeRecord> = (0..4).map(|_| record("stop_loss", 1_000)).collect();
+        assert!(analyze(&records, &strategy(10.0, 30)).is_empty());
+    }
+
+    #[test]
+    fn self_transfers_are_not_counted() {
+        let mut records: Vec<TradeRecord> = (0..4).map(|_| record("timeout", 30_000)).collect();
+        records.extend((0..6).map(|_| TradeRecord {
+            self_transfer: true,
+            ..record("timeout", 30_000)
+        }));
+        assert!(analyze(&records, &strategy(10.0, 30)).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt::Write as _;
+
+use super::TradeRecord;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+const DAY_MS: u64 = 86_400_000;
+
+/// Network fees, priority fees and tips over a set of journal records.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FeeTotals {
+    pub sells: usize,
+    /// Sells whose fee was read back from the transaction.
+    pub with_fees: usize,
+    pub base_lamports: u64,
+    pub priority_lamports: u64,
+    pub tip_lamports: u64,
+    /// Realized PnL of the SOL-paid sells with fees, fees already taken out.
+    pub sol_realized_lamports: i64,
+    /// Fees and tips of those same sells.
+    pub sol_fee_lamports: u64,
+}
+
+impl FeeTotals {
+    pub fn from_records<'a>(records: impl IntoIterator<Item = &'a TradeRecord>) -> Self {
+        let mut totals = Self::default();
+        for record in records.into_iter().filter(|record| !record.self_transfer) {
+            totals.sells += 1;
+            let Some(fee) = record.fee_lamports else {
+                continue;
+            };
+            let priority = record.priority_fee_lamports.unwrap_or(0).min(fee);
+            let tip = record.tip_lamports.unwrap_or(0);
+            totals.with_fees += 1;
+            totals.base_lamports += fee - priority;
+            totals.priority_lamports += priority;
+            totals.tip_lamports += tip;
+            if let (false, Some(proceeds), Some(cost)) = (
+                record.paid_in_usd1,
+                record.proceeds_units,
+                record.cost_basis_units,
+            ) {
+                totals.sol_realized_lamports += proceeds - cost as i64;
+                totals.sol_fee_lamports += fee + tip;
+            }
+        }
+        totals
+    }
+
+    fn total_lamports(&self) -> u64 {
+        self.base_lamports + self.priority_lamports + self.tip_lamports
+    }
+
+    /// Fees as a share of what the SOL-paid sells would have made without
+    /// them. `None` when that gross is not positive.
+    pub fn share_of_gross_pct(&self) -> Option<f64> {
+        let gross = self.sol_realized_lamports + self.sol_fee_lamports as i64;
+        (gross > 0).then(|| self.sol_fee_lamports as f64 / gross as f64 * 100.0)
+    }
+}
+
+/// The `fees` console report: all time and the last 24 hours.
+pub fn render(records: &[TradeRecord], now_ms: u64) -> String {
+    let all = FeeTotals::from_records(records);
+    if all.with_fees == 0 {
+        return "no sells with fee data in the journal yet\n".to_string();
+    }
+    let day = FeeTotals::from_records(
+        records
+            .iter()
+            .filter(|record| record.closed_at_ms >= now_ms.saturating_sub(DAY_MS)),
+    );
+    let sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL;
+    let mut out = format!(
+        "{:<10} {:>6} {:>12} {:>12} {:>12} {:>12} {:>14} {:>10}\n",
+        "", "sells", "network", "priority", "tip", "total", "avg/sell", "of gross"
+    );
+    for (label, totals) in [("all time", &all), ("last 24h", &day)] {
+        let avg = totals.total_lamports() / totals.with_fees.max(1) as u64;
+        let share = totals
+            .share_of_gross_pct()
+            .map_or("-".to_string(), |pct| format!("{pct:.1}%"));
+        let _ = writeln!(
+            out,
+            "{label:<10} {:>6} {:>12.6} {:>12.6} {:>12.6} {:>12.6} {:>14} {share:>10}",
+            totals.with_fees,
+            sol(totals.base_lamports),
+            sol(totals.priority_lamports),
+            sol(totals.tip_lamports),
+            sol(totals.total_lamports()),
+            format!("{avg} lam"),
+        );
+    }
+    if all.with_fees < all.sells {
+        let _ = writeln!(
+            out,
+            "{} older sell(s) have no fee data",
+            all.sells - all.with_fees
+        );
+    }
+    out.push_str(
+        "amounts in SOL; \"of gross\" is fees and tips over SOL-paid PnL before them. \
+         Priority fees come from the exit API and the network; tips from account.tip_lamports\n",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_fees_and_relates_them_to_gross_pnl() {
+        let sell = |closed_at_ms, fee, priority, proceeds| TradeRecord {
+            closed_at_ms,
+            cost_basis_units: Some(1_000_000),
+            proceeds_units: Some(proceeds),
+            fee_lamports: Some(fee),
+            priority_fee_lamports: Some(priority),
+            tip_lamports: Some(10_000),
+            ..TradeRecord::default()
+        };
+        let records = vec![
+            sell(1_000, 105_000, 100_000, 1_385_000),
+            sell(DAY_MS * 2, 5_000, 0, 1_085_000),
+            TradeRecord::default(),
+        ];
+        let totals = FeeTotals::from_records(&records);
+        assert_eq!(totals.sells, 3);
+        assert_eq!(totals.with_fees, 2);
+        assert_eq!(totals.base_lamports, 10_000);
+        assert_eq!(totals.priority_lamports, 100_000);
+        assert_eq!(totals.tip_lamports, 20_000);
+        // 470k realized after 130k of fees and tips: 130 / 600 of gross.
+        assert_eq!(totals.sol_realized_lamports, 470_000);
+        assert!((totals.share_of_gross_pct().unwrap() - 21.666).abs() < 0.01);
+
+        let rendered = render(&records, DAY_MS * 2 + 1);
+        assert!(rendered.contains("last 24h        1"));
+        assert!(rendered.contains("1 older sell(s) have no fee data"));
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt::Write as _;
+
+use time::OffsetDateTime;
+
+use super::TradeRecord;
+use crate::market::QuoteToken;
+
+/// The `history` report: the last `limit` sells in the journal, oldest
+/// first. PnL is realized (proceeds less cost basis) when both were
+/// recorded, otherwise the strategy's profit at the exit signal, marked `~`.
+pub fn render(records: &[TradeRecord], limit: usize) -> String {
+    if records.is_empty() {
+        return "no sells in the journal yet\n".to_string();
+    }
+    let shown = &records[records.len().saturating_sub(limit)..];
+    let mut out = format!(
+        "{:<19} {:<44} {:<14} {:>16}  {}\n",
+        "closed (UTC)", "mint", "reason", "pnl", "signature"
+    );
+    for record in shown {
+        let quote = if record.paid_in_usd1 {
+            QuoteToken::Usd1
+        } else {
+            QuoteToken::Sol
+        };
+        let (units, marker) = match (record.proceeds_units, record.cost_basis_units) {
+            (Some(proceeds), Some(cost)) => (proceeds - cost as i64, ""),
+            _ => (record.profit_units, "~"),
+        };
+        let pnl = match record.swept_lamports {
+            Some(lamports) => format!("swept {:.4} SOL", lamports as f64 / 1e9),
+            None => format!(
+                "{marker}{:+.4} {}",
+                units as f64 / 10f64.powi(quote.decimals() as i32),
+                quote.symbol()
+            ),
+        };
+        let reason = if record.swept_lamports.is_some() {
+            "treasury_sweep"
+        } else if record.self_transfer {
+            "self_transfer"
+        } else {
+            record.reason.as_str()
+        };
+        let _ = writeln!(
+            out,
+            "{:<19} {:<44} {:<14} {pnl:>16}  {}",
+            closed_at(record.closed_at_ms),
+            record.mint,
+            reason,
+            record.signature
+        );
+    }
+    if shown.len() < records.len() {
+        let _ = writeln!(
+            out,
+            "{} of {} sells; --limit shows more",
+            shown.len(),
+            records.len()
+        );
+    }
+    out
+}
+
+fn closed_at(ms: u64) -> String {
+    OffsetDateTime::from_unix_timestamp_nanos(i128::from(ms) * 1_000_000)
+        .ok()
+        .and_then(|at| {
+            at.format(time::macros::format_description!(
+                "[year]-[month]-[day] [hour]:[minute]:[second]"
+            ))
+            .ok()
+        })
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_the_latest_sells_with_realized_pnl() {
+        let sell = |closed_at_ms, mint: &str, proceeds| TradeRecord {
+            closed_at_ms,
+            mint: mint.to_string(),
+            reason: "target".to_string(),
+            signature: "5ig".to_string(),
+            profit_units: 40_000_000,
+            cost_basis_units: Some(100_000_000),
+            proceeds_units: proceeds,
+            ..TradeRecord::default()
+        };
+        let records = [
+            sell(1_700_000_000_000, "Old", Some(150_000_000)),
+            sell(1_700_000_060_000, "Realized", Some(150_000_000)),
+            sell(1_700_000_120_000, "Signal", None),
+        ];
+        let out = render(&records, 2);
+        assert!(!out.contains("Old"));
+        assert!(out.contains("2023-11-14 22:14:20 Realized"));
+        assert!(out.contains("+0.0500 SOL"));
+        assert!(out.contains("~+0.0400 SOL"));
+        assert!(out.ends_with("2 of 3 sells; --limit shows more\n"));
+
+        let sweep = TradeRecord {
+            reason: "treasury_sweep".to_string(),
+            self_transfer: true,
+            swept_lamports: Some(1_500_000_000),
+            ..TradeRecord::default()
+        };
+        let out = render(&[sweep], 10);
+        assert!(out.contains("treasury_sweep swept 1.5000 SOL"));
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// A position carried over from another tool, used to seed cost basis for
+/// bags the stream reports without an entry price.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedPosition {
+    pub mint: String,
+    pub tokens: u64,
+    pub cost_basis_units: u64,
+}
+
+/// Parses `mint,tokens,cost_basis_sol` rows. A header row, blank lines and
+/// `#` comments are skipped.
+pub fn parse_positions_csv(raw: &str) -> Result<Vec<ImportedPosition>> {
+    let mut positions = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if index == 0 && fields.first().is_some_and(|f| f.eq_ignore_ascii_case("mint")) {
+            continue;
+        }
+        let [mint, tokens, cost_basis] = fields[..] else {
+            return Err(anyhow!(
+                "line {line_no}: expected 3 columns (mint,tokens,cost_basis_sol), found {}",
+                fields.len()
+            ));
+        };
+        Pubkey::from_str(mint).map_err(|_| anyhow!("line {line_no}: invalid mint {mint}"))?;
+        let tokens: u64 = tokens
+            .parse()
+            .map_err(|_| anyhow!("line {line_no}: tokens must be a whole number of base units"))?;
+        let cost_basis_sol: f64 = cost_basis
+            .parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite() && *value >= 0.0)
+            .ok_or_else(|| anyhow!("line {line_no}: cost_basis_sol must be a non-negative number"))?;
+        positions.push(ImportedPosition {
+            mint: mint.to_string(),
+            tokens,
+            cost_basis_units: (cost_basis_sol * LAMPORTS_PER_SOL).round() as u64,
+        });
+    }
+    Ok(positions)
+}
+
+/// Imported positions keyed by mint, persisted as JSON next to the journal.
+#[derive(Clone, Debug)]
+pub struct ImportStore {
+    path: PathBuf,
+}
+
+impl ImportStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(crate::util::paths::default_imported_positions_path()?))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self) -> Result<BTreeMap<String, ImportedPosition>> {
+        match fs::read_to_string(&self.path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .with_context(|| format!("parse imported positions {}", self.path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(err) => Err(err)
+                .with_context(|| format!("read imported positions {}", self.path.display())),
+        }
+    }
+
+    /// Adds `positions`, replacing any earlier import for the same mint.
+    pub fn merge(&self, positions: Vec<ImportedPosition>) -> Result<usize> {
+        let mut existing = self.load()?;
+        for position in positions {
+            existing.insert(position.mint.clone(), position);
+        }
+        let raw = serde_json::to_vec_pretty(&existing).context("serialize imported positions")?;
+        crate::util::fs_utils::atomic_write(&self.path, &raw, Some(0o600))
+            .with_context(|| format!("write imported positions {}", self.path.display()))?;
+        Ok(existing.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const MINT_A: &str = "So11111111111111111111111111111111111111112";
+    const MINT_B: &str = "USD1ttGY1N17NEEHLmELoaybftRBUSErhqYiQzvEmuB";
+
+    #[test]
+    fn parses_rows_and_skips_header() {
+        let raw = format!("mint,tokens,cost_basis_sol\n# comment\n{MINT_A}, 1500, 0.25\n\n");
+        let positions = parse_positions_csv(&raw).unwrap();
+        assert_eq!(
+            positions,
+            vec![ImportedPosition {
+                mint: MINT_A.to_string(),
+                tokens: 1500,
+                cost_basis_units: 250_000_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_rows_with_line_numbers() {
+        let err = parse_positions_csv(&format!("{MINT_A},12\n")).unwrap_err();
+        assert!(err.to_string().starts_with("line 1:"));
+        let err = parse_positions_csv(&format!("{MINT_A},12,0.1\nnot-a-mint,1,1\n")).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid mint not-a-mint");
+    }
+
+    #[test]
+    fn merge_replaces_same_mint() {
+        let dir = tempdir().unwrap();
+        let store = ImportStore::new(dir.path().join("imported_positions.json"));
+        let position = |mint: &str, tokens| ImportedPosition {
+            mint: mint.to_string(),
+            tokens,
+            cost_basis_units: 1,
+        };
+        assert_eq!(store.merge(vec![position(MINT_A, 1), position(MINT_B, 2)]).unwrap(), 2);
+        assert_eq!(store.merge(vec![position(MINT_A, 5)]).unwrap(), 2);
+        assert_eq!(store.load().unwrap()[MINT_A].tokens, 5);
+    }
+}
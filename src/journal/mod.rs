@@ -0,0 +1,246 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+pub mod analyzer;
+pub mod fees;
+pub mod history;
+pub mod imports;
+
+/// One completed trade, appended to the journal as a single NDJSON line.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub closed_at_ms: u64,
+    pub mint: String,
+    pub position_id: u64,
+    pub reason: String,
+    pub signature: String,
+    pub slippage_bps: u16,
+    /// Strategy-reported profit when the exit signal fired (quote units).
+    pub profit_units: i64,
+    /// Slot observed when the exit signal was received.
+    #[serde(default)]
+    pub trigger_slot: Option<u64>,
+    /// Slot the sell transaction confirmed in.
+    #[serde(default)]
+    pub confirm_slot: Option<u64>,
+    /// Wall-clock time from exit signal to confirmation.
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    /// Quote units paid to open the position.
+    #[serde(default)]
+    pub cost_basis_units: Option<u64>,
+    /// Net quote units the sell actually returned to the wallet.
+    #[serde(default)]
+    pub proceeds_units: Option<i64>,
+    /// Realized PnL minus `profit_units`: what slippage, fees and landing
+    /// delay added or cost relative to the trigger price.
+    #[serde(default)]
+    pub execution_pnl_units: Option<i64>,
+    /// Network fee the sell paid, priority fee included.
+    #[serde(default)]
+    pub fee_lamports: Option<u64>,
+    /// The priority part of `fee_lamports`.
+    #[serde(default)]
+    pub priority_fee_lamports: Option<u64>,
+    /// Sender tip paid on top of the network fee.
+    #[serde(default)]
+    pub tip_lamports: Option<u64>,
+    /// The sell paid out in USD1, so the quote units are USD1 rather than
+    /// lamports.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paid_in_usd1: bool,
+    /// Time from position open to exit signal, when the open was observed.
+    #[serde(default)]
+    pub held_ms: Option<u64>,
+    /// Tokens moved to or from one of `account.own_wallets` rather than
+    /// traded; excluded from trade statistics.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub self_transfer: bool,
+    /// SOL sent to `treasury.sweep_to`; such records are self-transfers
+    /// with no token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swept_lamports: Option<u64>,
+}
+
+pub fn slot_delta(trigger_slot: Option<u64>, confirm_slot: Option<u64>) -> Option<u64> {
+    Some(confirm_slot?.saturating_sub(trigger_slot?))
+}
+
+/// Splits realized PnL into the part the strategy saw at trigger time
+/// (`signal_pnl_units`) and the remainder introduced by execution.
+pub fn execution_pnl(
+    cost_basis_units: Option<u64>,
+    signal_pnl_units: i64,
+    proceeds_units: Option<i64>,
+) -> Option<i64> {
+    let realized = proceeds_units? - cost_basis_units? as i64;
+    Some(realized - signal_pnl_units)
+}
+
+/// Append-only trade journal stored as newline-delimited JSON.
+#[derive(Clone, Debug)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(crate::util::paths::default_journal_path()?))
+    }
+
+    pub fn append(&self, record: &TradeRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("create dir {}", parent.display()))?;
+        }
+        let mut line = serde_json::to_string(record).context("serialize journal record")?;
+        line.push('\n');
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(&self.path)
+            .with_context(|| format!("open journal {}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("write journal {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Reads every record, skipping lines that no longer parse. A missing
+    /// journal reads as empty.
+    pub fn read_all(&self) -> Result<Vec<TradeRecord>> {
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("open journal {}", self.path.display()))
+            }
+        };
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.with_context(|| format!("read journal {}", self.path.display()))?;
+            if let Ok(record) = serde_json::from_str(&line) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Reads the records appended since byte `offset` and returns them with
+    /// the offset to pass next time. A line still being written is left
+    /// for the next call; a journal shorter than `offset` was replaced and
+    /// is read from the start.
+    pub fn read_from(&self, offset: u64) -> Result<(Vec<TradeRecord>, u64)> {
+        let mut file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+            Err(err) => {
+                return Err(err).with_context(|| format!("open journal {}", self.path.display()))
+            }
+        };
+        let len = file
+            .metadata()
+            .with_context(|| format!("stat journal {}", self.path.display()))?
+            .len();
+        let offset = if len < offset { 0 } else { offset };
+        file.seek(SeekFrom::Start(offset))
+            .with_context(|| format!("seek journal {}", self.path.display()))?;
+        let mut raw = Vec::new();
+        file.read_to_end(&mut raw)
+            .with_context(|| format!("read journal {}", self.path.display()))?;
+        let complete = raw.iter().rposition(|byte| *byte == b'\n').map_or(0, |end| end + 1);
+        let records = raw[..complete]
+            .split(|byte| *byte == b'\n')
+            .filter_map(|line| serde_json::from_slice(line).ok())
+            .collect();
+        Ok((records, offset + complete as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn append_and_read_roundtrip() {
+        let dir = tempdir().unwrap();
+        let journal = Journal::new(dir.path().join("journal.ndjson"));
+        let record = TradeRecord {
+            closed_at_ms: 1_700_000_000_000,
+            mint: "mint".to_string(),
+            position_id: 7,
+            reason: "target".to_string(),
+            signature: "sig".to_string(),
+            slippage_bps: 2000,
+            profit_units: 12_345,
+            trigger_slot: Some(100),
+            confirm_slot: Some(103),
+            elapsed_ms: 900,
+            ..TradeRecord::default()
+        };
+        journal.append(&record).unwrap();
+        journal.append(&record).unwrap();
+        let records = journal.read_all().unwrap();
+        assert_eq!(records, vec![record.clone(), record]);
+        assert_eq!(slot_delta(records[0].trigger_slot, records[0].confirm_slot), Some(3));
+    }
+
+    #[test]
+    fn read_all_skips_bad_lines_and_missing_file() {
+        let dir = tempdir().unwrap();
+        let journal = Journal::new(dir.path().join("journal.ndjson"));
+        assert!(journal.read_all().unwrap().is_empty());
+        fs::write(dir.path().join("journal.ndjson"), "not json\n{\"closed_at_ms\":1}\n").unwrap();
+        assert!(journal.read_all().unwrap().is_empty());
+        journal.append(&TradeRecord::default()).unwrap();
+        assert_eq!(journal.read_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn read_from_returns_only_new_complete_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("journal.ndjson");
+        let journal = Journal::new(path.clone());
+        assert_eq!(journal.read_from(0).unwrap(), (Vec::new(), 0));
+        journal.append(&TradeRecord::default()).unwrap();
+        let (records, offset) = journal.read_from(0).unwrap();
+        assert_eq!(records.len(), 1);
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"closed_at_ms\":1,").unwrap();
+        assert_eq!(journal.read_from(offset).unwrap(), (Vec::new(), offset));
+
+        fs::write(&path, "").unwrap();
+        journal.append(&TradeRecord::default()).unwrap();
+        let (records, _) = journal.read_from(offset * 2).unwrap();
+        assert_eq!(records.len(), 1, "a replaced journal is read from the start");
+    }
+
+    #[test]
+    fn slot_delta_requires_both_slots() {
+        assert_eq!(slot_delta(Some(10), None), None);
+        assert_eq!(slot_delta(None, Some(10)), None);
+        assert_eq!(slot_delta(Some(12), Some(10)), Some(0));
+    }
+
+    #[test]
+    fn execution_pnl_is_realized_minus_signal() {
+        // Bought for 1000, trigger said +200, wallet received 1150.
+        assert_eq!(execution_pnl(Some(1_000), 200, Some(1_150)), Some(-50));
+        assert_eq!(execution_pnl(None, 200, Some(1_150)), None);
+        assert_eq!(execution_pnl(Some(1_000), 200, None), None);
+    }
+}
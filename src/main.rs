@@ -6,10 +6,8 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
-use lasersell_sdk::exit_api::{
-    BuildSellTxRequest, ExitApiClient, ExitApiClientOptions, SellOutput,
-};
+use clap::{Parser, Subcommand};
+use lasersell_sdk::exit_api::{BuildSellTxRequest, SellOutput};
 use lasersell_sdk::stream::client::{StreamClient as SdkStreamClient, StreamConfigure};
 use lasersell_sdk::stream::proto::{ServerMessage, StrategyConfigMsg};
 use secrecy::{ExposeSecret, SecretString};
@@ -23,6 +21,8 @@ use zeroize::Zeroizing;
 mod app;
 mod config;
 mod events;
+mod integration;
+mod journal;
 mod market;
 mod network;
 mod onboarding;
@@ -40,19 +40,77 @@ fn main() -> Result<()> {
 
 async fn async_main() -> Result<()> {
     let cli = parse_cli_args()?;
-    if cli.export_private_key {
-        export_private_key(&cli)?;
-        return Ok(());
+    if let Some(profile) = cli.profile.as_deref() {
+        util::paths::set_profile(profile);
     }
-    if cli.smoke {
-        match run_smoke_mode(&cli.config_path).await {
-            Ok(()) => println!("SMOKE OK"),
-            Err(failure) => {
-                eprintln!("SMOKE FAIL {}", failure.step);
+    match &cli.mode {
+        CliMode::ExportKey { .. } => return export_private_key(&cli),
+        CliMode::ChangePassphrase => return change_passphrase(&cli),
+        CliMode::RotateWallet => return rotate_wallet(&cli),
+        CliMode::OfflineSign { input } => return offline_sign(&cli, input),
+        CliMode::ImportPositions { csv } => return import_positions(csv),
+        CliMode::InspectArchive { file } => {
+            print!("{}", network::stream_archive::summarize(file)?);
+            return Ok(());
+        }
+        CliMode::History { limit } => return print_history(*limit),
+        CliMode::AuditVerify { file } => {
+            let path = match file {
+                Some(file) => file.clone(),
+                None => util::paths::default_tx_audit_path()?,
+            };
+            match tx::audit::verify(&path) {
+                Ok(summary) => print!("{summary}"),
+                Err(err) => {
+                    eprintln!("audit FAILED: {err:#}");
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        CliMode::SupportBundle { output, offline } => {
+            return write_support_bundle(&cli, output.as_deref(), *offline).await;
+        }
+        CliMode::SelfUpdate => return util::self_update::run().await,
+        CliMode::Attach => {
+            // Only for `ui`; attach runs without a config.
+            let config = cli_config_path(&cli).map(|path| config::Config::load_from_path(&path));
+            if let Some(Ok(cfg)) = config {
+                util::theme::init(&cfg.ui);
+            }
+            return app::run_attach_cli(&util::paths::attach_socket_path()?).await;
+        }
+        CliMode::ReplayUi { journal, speed } => {
+            tracing_subscriber::fmt()
+                .with_writer(util::logging::ConsoleWriter::default)
+                .with_ansi(util::theme::current().ansi())
+                .init();
+            return events::journal::replay(journal, *speed).await;
+        }
+        CliMode::Doctor { offline } => {
+            let report = config::check::check_file(&cli.config_path)?;
+            print!("{}", report.rendered);
+            if report.has_errors() {
                 std::process::exit(1);
             }
+            if !offline {
+                run_smoke_cli(&cli.config_path).await;
+            }
+            return Ok(());
+        }
+        CliMode::Smoke => {
+            run_smoke_cli(&cli.config_path).await;
+            return Ok(());
         }
-        return Ok(());
+        CliMode::Replay { recording } => {
+            let cfg = if cli.env_config {
+                config::Config::from_env()?
+            } else {
+                config::Config::load_from_path(&cli.config_path)?
+            };
+            return app::run_replay_cli(recording, cfg.strategy);
+        }
+        CliMode::Run { .. } | CliMode::Setup { .. } | CliMode::Sweep => {}
     }
 
     // Kick off version check in the background immediately — before wallet
@@ -66,20 +124,31 @@ async fn async_main() -> Result<()> {
             util::support::with_support_hint(format!("Failed to create data dir: {err}"))
         );
     }
-    let (cfg, keypair): (config::Config, solana_sdk::signature::Keypair) = if cli.setup {
+    if let CliMode::Setup {
+        section: Some(section),
+    } = cli.mode
+    {
+        return onboarding::run_setup_section(&config_path, section);
+    }
+    let setup = matches!(cli.mode, CliMode::Setup { .. });
+    let (cfg, keypair): (config::Config, solana_sdk::signature::Keypair) = if setup {
         onboarding::run_onboarding(&config_path)?
     } else {
-        if !config_path.exists() {
+        if !cli.env_config && !config_path.exists() {
             if std::io::stdin().is_terminal() {
                 onboarding::run_onboarding(&config_path)?
             } else {
                 return Err(anyhow!(
-                    "config file {} not found; run --setup in an interactive terminal",
+                    "config file {} not found; run `lasersell setup` in an interactive terminal",
                     config_path.display()
                 ));
             }
         } else {
-            let cfg = config::Config::load_from_path(&config_path)?;
+            let cfg = if cli.env_config {
+                config::Config::from_env()?
+            } else {
+                config::Config::load_from_path(&config_path)?
+            };
             let keypair_path = PathBuf::from(&cfg.account.keypair_path);
             let wallet_kind = wallet::detect_wallet_file_kind(&keypair_path)?;
             let keypair = match wallet_kind {
@@ -93,7 +162,8 @@ async fn async_main() -> Result<()> {
                     let keypair = wallet::load_keypair_from_path(&keypair_path, || {
                         Err(anyhow!("passphrase not required"))
                     })?;
-                    if std::io::stdin().is_terminal() {
+                    // Env-only mode has no config file to point at the keystore.
+                    if std::io::stdin().is_terminal() && !cli.env_config {
                         let migrate = cliclack::confirm(
                             "Plaintext keypair detected. Encrypt this wallet now?",
                         )
@@ -104,10 +174,12 @@ async fn async_main() -> Result<()> {
                             let passphrase = prompt_new_passphrase()?;
                             let keystore_path = wallet::default_keystore_path(&keypair_path);
                             let mut cfg = cfg.clone();
+                            let kdf = cfg.wallet.kdf.clone();
                             wallet::migrate_plaintext_to_keystore(
                                 &keypair_path,
                                 &keystore_path,
                                 passphrase,
+                                &kdf,
                                 |path| {
                                     cfg.account.keypair_path = path.to_string_lossy().to_string();
                                     cfg.write_to_path(&config_path)?;
@@ -117,7 +189,7 @@ async fn async_main() -> Result<()> {
                         }
                     } else {
                         eprintln!(
-                            "Warning: plaintext keypair file in use ({}). Run --setup to migrate.",
+                            "Warning: plaintext keypair file in use ({}). Run `lasersell setup` to migrate.",
                             keypair_path.display()
                         );
                     }
@@ -128,45 +200,249 @@ async fn async_main() -> Result<()> {
         }
     };
 
+    util::theme::init(&cfg.ui);
     // Collect the update check result.
     let update_available = update_check_handle.await.ok().flatten();
     if let Some(ref update) = update_available {
         util::update_check::print_update_banner(update);
     }
 
-    util::logging::init_redactions(vec![
-        cfg.account.rpc_url.expose_secret().to_string(),
-        cfg.account.api_key.expose_secret().to_string(),
-    ]);
+    util::logging::init_redactions(config_redactions(&cfg));
 
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
         if cli.debug {
             EnvFilter::new("info,lasersell=debug,lasersell_sdk=debug,lasersell_sdk::stream::client=trace")
         } else {
-            EnvFilter::new("info")
+            EnvFilter::new(cfg.logging.level.trim().to_ascii_lowercase())
         }
     });
-    let _debug_log_guard = init_tracing(cli.debug, filter);
+    let category_filter = util::logging::CategoryFilter::new(cfg.logging.disabled_categories());
+    let _debug_log_guard =
+        init_tracing(cli.debug, filter, category_filter, cfg.logging.rotation());
+    util::warn_dedup::spawn_summaries();
+    network::unknown_messages::spawn_summaries();
+    network::rpc::init_retries(cfg.network.rpc_retries);
+    tx::audit::init(&util::paths::default_tx_audit_path()?)?;
+    tx::policy::init(cfg.account.tip_lamports, &cfg.security.allowed_program_ids);
+    network::exit_api::init_regions(&cfg).await?;
+    if cli.mode == CliMode::Sweep {
+        return app::run_sweep_cli(cfg, keypair).await;
+    }
     let wallet_pubkey = cfg.wallet_pubkey(&keypair)?;
 
+    events::notify::init(cfg.notifications.command.clone());
+    util::update_check::spawn_daily_check();
+    if cfg.reports.enabled {
+        events::report::init(util::paths::default_reports_dir()?);
+    }
+    if cfg.logging.event_journal {
+        events::journal::init(
+            &util::paths::default_event_journal_path()?,
+            cfg.logging.rotation(),
+        )?;
+    }
+    if let CliMode::Run {
+        record_stream: Some(path),
+    } = &cli.mode
+    {
+        network::stream_recorder::init(path)?;
+    }
+    if let Some(dir) = cfg.stream.archive_dir.as_deref() {
+        network::stream_archive::init(Path::new(dir), cfg.stream.archive_rotate_mb)?;
+    }
     events::emit(events::AppEvent::Startup {
         version: env!("CARGO_PKG_VERSION").to_string(),
         wallet_pubkey,
     });
 
-    // Install Ctrl+C handler for graceful shutdown.
+    // Console commands on stdin and the buy-bot API, plus a Ctrl+C handler for graceful shutdown.
     let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel();
+    let shortcuts = app::Shortcuts::new(&cfg.ui.keybindings)?;
+    app::spawn_stdin_reader(shutdown_tx.clone(), shortcuts);
+    integration::spawn(&cfg.integration, shutdown_tx.clone()).await;
     tokio::spawn(async move {
         if tokio::signal::ctrl_c().await.is_ok() {
             let _ = shutdown_tx.send(events::AppCommand::Quit);
         }
     });
 
-    app::run(cfg, keypair, Some(shutdown_rx)).await
+    let config_path = (!cli.env_config).then_some(config_path);
+    let result = app::run(cfg, keypair, config_path, Some(shutdown_rx)).await;
+    network::stream_archive::finish();
+    result
 }
 
 fn export_private_key(cli: &CliArgs) -> Result<()> {
-    let keystore_path = resolve_export_private_key_path(cli)?;
+    let keypair = load_keystore_for_cli(cli)?;
+    let bytes = Zeroizing::new(keypair.to_bytes());
+    let b58 = Zeroizing::new(bs58::encode(bytes.as_ref()).into_string());
+    let mut out = std::io::stdout();
+    out.write_all(b58.as_bytes())?;
+    out.write_all(b"\n")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Config values the logs must never show.
+fn config_redactions(cfg: &config::Config) -> Vec<String> {
+    let mut redactions = vec![
+        cfg.account.rpc_url.expose_secret().to_string(),
+        cfg.account.api_key.expose_secret().to_string(),
+        cfg.account.send_rpc_url.expose_secret().to_string(),
+    ];
+    redactions.extend(cfg.integration.token.clone());
+    redactions.extend(cfg.network.proxy_url.clone());
+    if let Some(jito) = &cfg.sell.senders.jito {
+        redactions.extend(jito.uuid.clone());
+    }
+    if let Some(webhook) = &cfg.sell.senders.webhook {
+        redactions.extend(webhook.headers.values().cloned());
+    }
+    redactions
+}
+
+/// `support-bundle`: what support asks for first, in one file. The config
+/// is included only if it loads, since its secrets are known only then.
+async fn write_support_bundle(cli: &CliArgs, output: Option<&Path>, offline: bool) -> Result<()> {
+    const REPORTS: usize = 20;
+    let now = time::OffsetDateTime::now_utc();
+    let mut bundle = util::support_bundle::SupportBundle::default();
+    bundle.add(
+        "version.txt",
+        format!(
+            "lasersell {}\nos {} {}\ncreated {}\n",
+            env!("CARGO_PKG_VERSION"),
+            env::consts::OS,
+            env::consts::ARCH,
+            now.format(&time::format_description::well_known::Rfc3339)?
+        ),
+    );
+
+    let loaded = if cli.env_config {
+        config::Config::from_env().map(|cfg| (cfg, None))
+    } else {
+        config::Config::load_from_path(&cli.config_path).and_then(|cfg| {
+            let raw = fs::read_to_string(&cli.config_path)
+                .with_context(|| format!("read config {}", cli.config_path.display()))?;
+            Ok((cfg, Some(raw)))
+        })
+    };
+    match loaded {
+        Ok((cfg, raw)) => {
+            util::logging::init_redactions(config_redactions(&cfg));
+            let raw = match raw {
+                Some(raw) => raw,
+                None => serde_yaml::to_string(&cfg).context("serialize config")?,
+            };
+            bundle.add("config.yml", util::logging::scrub_sensitive(&raw));
+        }
+        Err(err) => bundle.add(
+            "config-error.txt",
+            util::logging::scrub_sensitive(&format!("{err:#}\n")),
+        ),
+    }
+    if !cli.env_config {
+        if let Ok(report) = config::check::check_file(&cli.config_path) {
+            bundle.add(
+                "doctor.txt",
+                util::logging::scrub_sensitive(&report.rendered),
+            );
+        }
+    }
+
+    bundle.add_error_logs(&util::paths::default_error_log_path()?);
+    bundle.add_reports(&util::paths::default_reports_dir()?, REPORTS);
+    let smoke = if offline {
+        "skipped (--offline)\n".to_string()
+    } else if cli.env_config {
+        "skipped (--env-config)\n".to_string()
+    } else {
+        match run_smoke_mode(&cli.config_path).await {
+            Ok(()) => "SMOKE OK\n".to_string(),
+            Err(failure) => format!("SMOKE FAIL {}\n", failure.step),
+        }
+    };
+    bundle.add("smoke.txt", smoke);
+
+    let path = match output {
+        Some(path) => path.to_path_buf(),
+        None => PathBuf::from(format!(
+            "lasersell-support-{}.tar.zst",
+            now.format(time::macros::format_description!(
+                "[year][month][day]-[hour][minute][second]"
+            ))?
+        )),
+    };
+    bundle.write(&path, now.unix_timestamp().max(0) as u64)?;
+    for name in bundle.names() {
+        println!("  {name}");
+    }
+    println!(
+        "Wrote {}. Attach it in discord.gg/lasersell; secrets are redacted, but look it over first.",
+        path.display()
+    );
+    Ok(())
+}
+
+/// `history`: the latest sells in the trade journal, newest last.
+fn print_history(limit: usize) -> Result<()> {
+    let records = journal::Journal::open_default()?.read_all()?;
+    print!("{}", journal::history::render(&records, limit));
+    Ok(())
+}
+
+fn import_positions(csv_path: &Path) -> Result<()> {
+    let raw = fs::read_to_string(csv_path)
+        .with_context(|| format!("read positions csv {}", csv_path.display()))?;
+    let positions = journal::imports::parse_positions_csv(&raw)?;
+    if positions.is_empty() {
+        return Err(anyhow!("no positions found in {}", csv_path.display()));
+    }
+    let imported = positions.len();
+    util::paths::ensure_data_dir_exists()?;
+    let store = journal::imports::ImportStore::open_default()?;
+    let total = store.merge(positions)?;
+    println!(
+        "Imported {imported} positions into {} ({total} total). They apply on the next daemon start.",
+        store.path().display()
+    );
+    Ok(())
+}
+
+/// Signs an unsigned transaction with the keystore and prints the signed
+/// base64. Touches only local files, so it works on an air-gapped machine.
+fn offline_sign(cli: &CliArgs, input: &Path) -> Result<()> {
+    let raw = if input.as_os_str() == "-" {
+        std::io::read_to_string(std::io::stdin()).context("read unsigned tx from stdin")?
+    } else {
+        fs::read_to_string(input)
+            .with_context(|| format!("read unsigned tx {}", input.display()))?
+    };
+    let unsigned_tx_b64 = raw.trim();
+    if unsigned_tx_b64.is_empty() {
+        return Err(anyhow!("unsigned tx input is empty"));
+    }
+    let keypair = load_keystore_for_cli(cli)?;
+    // The user built this one; the signing policy is for server-built sells.
+    let signed = lasersell_sdk::tx::sign_unsigned_tx(unsigned_tx_b64, &keypair)?;
+    util::paths::ensure_data_dir_exists()?;
+    tx::audit::init(&util::paths::default_tx_audit_path()?)?;
+    tx::audit::record(&signed, tx::audit::Purpose::new("offline_sign", None));
+    let signed_b64 = lasersell_sdk::tx::encode_signed_tx(&signed)?;
+    let mut out = std::io::stdout();
+    out.write_all(signed_b64.as_bytes())?;
+    out.write_all(b"\n")?;
+    out.flush()?;
+    Ok(())
+}
+
+fn load_keystore_for_cli(cli: &CliArgs) -> Result<solana_sdk::signature::Keypair> {
+    let keystore_path = encrypted_keystore_path_for_cli(cli)?;
+    wallet::load_keypair_from_path(&keystore_path, || read_passphrase_cli(None))
+}
+
+fn encrypted_keystore_path_for_cli(cli: &CliArgs) -> Result<PathBuf> {
+    let keystore_path = resolve_keystore_path(cli)?;
     if !keystore_path.is_file() {
         return Err(anyhow!(
             "keystore file {} not found",
@@ -176,22 +452,77 @@ fn export_private_key(cli: &CliArgs) -> Result<()> {
     let wallet_kind = wallet::detect_wallet_file_kind(&keystore_path)?;
     if wallet_kind != wallet::WalletFileKind::EncryptedKeystore {
         return Err(anyhow!(
-            "wallet file {} is plaintext JSON; run --setup to migrate",
+            "wallet file {} is plaintext JSON; run `lasersell setup` to migrate",
             keystore_path.display()
         ));
     }
-    let keypair = wallet::load_keypair_from_path(&keystore_path, || read_passphrase_cli(None))?;
-    let bytes = Zeroizing::new(keypair.to_bytes());
-    let b58 = Zeroizing::new(bs58::encode(bytes.as_ref()).into_string());
-    let mut out = std::io::stdout();
-    out.write_all(b58.as_bytes())?;
-    out.write_all(b"\n")?;
-    out.flush()?;
+    Ok(keystore_path)
+}
+
+fn change_passphrase(cli: &CliArgs) -> Result<()> {
+    let keystore_path = encrypted_keystore_path_for_cli(cli)?;
+    let pubkey = wallet::read_keystore_pubkey(&keystore_path).ok();
+    let current = read_passphrase_cli(pubkey.as_deref())?;
+    let new = prompt_new_passphrase()?;
+    let kdf = kdf_for_cli(cli);
+    let pubkey = wallet::change_keystore_passphrase(&keystore_path, current, &new, &kdf)?;
+    println!(
+        "Re-encrypted {} ({pubkey}) with the new passphrase.",
+        keystore_path.display()
+    );
     Ok(())
 }
 
-fn resolve_export_private_key_path(cli: &CliArgs) -> Result<PathBuf> {
-    if let Some(path) = cli.export_private_key_path.as_ref() {
+fn rotate_wallet(cli: &CliArgs) -> Result<()> {
+    let keystore_path = encrypted_keystore_path_for_cli(cli)?;
+    let pubkey = wallet::read_keystore_pubkey(&keystore_path).ok();
+    let passphrase = read_passphrase_cli(pubkey.as_deref())?;
+    let rotated = wallet::rotate_keystore(&keystore_path, passphrase, &kdf_for_cli(cli))?;
+    let new_path = rotated.keystore_path.to_string_lossy().to_string();
+    println!("Old wallet: {}", rotated.old_pubkey);
+    println!("New wallet: {}", rotated.new_pubkey);
+    println!("New keystore: {new_path} (same passphrase)");
+
+    if env::var("LASERSELL_KEYPAIR_PATH").is_ok_and(|value| !value.trim().is_empty()) {
+        println!("LASERSELL_KEYPAIR_PATH is set; point it at {new_path} to use the new wallet.");
+    } else if let Some(config_path) = cli_config_path(cli) {
+        config::Config::edit_file(&config_path, |cfg| {
+            cfg.account.keypair_path = new_path.clone();
+        })?;
+        println!("{} now uses the new keystore.", config_path.display());
+    } else {
+        println!("Set account.keypair_path to {new_path} to use the new wallet.");
+    }
+    println!(
+        "The old keystore at {} is unchanged. Move funds off the old wallet before retiring it.",
+        keystore_path.display()
+    );
+    Ok(())
+}
+
+/// The config file the wallet commands read, when there is one.
+fn cli_config_path(cli: &CliArgs) -> Option<PathBuf> {
+    if cli.config_path.as_os_str().is_empty() {
+        default_config_path().ok().filter(|path| path.is_file())
+    } else {
+        Some(cli.config_path.clone())
+    }
+}
+
+/// `wallet.kdf` from the config file, or the defaults without one.
+fn kdf_for_cli(cli: &CliArgs) -> config::KdfConfig {
+    cli_config_path(cli)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|raw| serde_yaml::from_str::<ExportConfig>(&raw).ok())
+        .map(|cfg| cfg.wallet.kdf)
+        .unwrap_or_default()
+}
+
+fn resolve_keystore_path(cli: &CliArgs) -> Result<PathBuf> {
+    if let CliMode::ExportKey {
+        keystore: Some(path),
+    } = &cli.mode
+    {
         return Ok(path.clone());
     }
     if let Ok(value) = env::var("LASERSELL_KEYPAIR_PATH") {
@@ -276,12 +607,73 @@ fn prompt_new_passphrase() -> Result<SecretString> {
 
 #[derive(Clone, Debug)]
 struct CliArgs {
+    /// Empty when the mode reads a config file only if one is named.
     config_path: PathBuf,
+    /// `--profile`; its data files live apart from other profiles'.
+    profile: Option<String>,
     debug: bool,
-    setup: bool,
-    smoke: bool,
-    export_private_key: bool,
-    export_private_key_path: Option<PathBuf>,
+    env_config: bool,
+    mode: CliMode,
+}
+
+/// What one invocation does, from its subcommand or a legacy mode flag.
+#[derive(Clone, Debug, PartialEq)]
+enum CliMode {
+    /// The exit daemon; the default without a subcommand.
+    Run { record_stream: Option<PathBuf> },
+    /// Onboarding, then the daemon; with a section, only that part of
+    /// onboarding against the existing config.
+    Setup {
+        section: Option<onboarding::SetupSection>,
+    },
+    Smoke,
+    /// The config check, then the smoke check unless `offline`.
+    Doctor { offline: bool },
+    ExportKey { keystore: Option<PathBuf> },
+    History { limit: usize },
+    /// Checks the signed-transaction audit log, the default one unless
+    /// `file` is given.
+    AuditVerify { file: Option<PathBuf> },
+    Sweep,
+    OfflineSign { input: PathBuf },
+    ChangePassphrase,
+    RotateWallet,
+    Replay { recording: PathBuf },
+    ImportPositions { csv: PathBuf },
+    InspectArchive { file: PathBuf },
+    /// Logs, redacted config, version and smoke result in one archive; no
+    /// smoke check when `offline`.
+    SupportBundle {
+        output: Option<PathBuf>,
+        offline: bool,
+    },
+    SelfUpdate,
+    /// A read-only view of the daemon running on this machine.
+    Attach,
+    /// Plays a `logging.event_journal` file back at `speed` times the
+    /// recorded pace.
+    ReplayUi { journal: PathBuf, speed: f64 },
+}
+
+impl CliMode {
+    /// Modes that read a config file only when one is named, so a missing
+    /// default config is not an error for them.
+    fn config_optional(&self) -> bool {
+        matches!(
+            self,
+            CliMode::ExportKey { .. }
+                | CliMode::History { .. }
+                | CliMode::AuditVerify { .. }
+                | CliMode::OfflineSign { .. }
+                | CliMode::ChangePassphrase
+                | CliMode::RotateWallet
+                | CliMode::ImportPositions { .. }
+                | CliMode::InspectArchive { .. }
+                | CliMode::SelfUpdate
+                | CliMode::Attach
+                | CliMode::ReplayUi { .. }
+        )
+    }
 }
 
 #[derive(Clone, Debug, Parser)]
@@ -295,22 +687,206 @@ struct RawCliArgs {
         short = 'f',
         long = "config",
         value_name = "path",
-        env = "LASERSELL_CONFIG_PATH"
+        global = true,
+        help = "Config file [default: LASERSELL_CONFIG_PATH, else ~/.lasersell/config.yml]"
     )]
     config_path: Option<PathBuf>,
-    #[arg(long = "debug", help = "Write debug-level logs to debug.log")]
+    #[arg(
+        long = "profile",
+        value_name = "name",
+        global = true,
+        help = "Use the config at ~/.lasersell/profiles/<name>.yml and keep the journal \
+                and sell state under ~/.lasersell/profiles/<name>/"
+    )]
+    profile: Option<String>,
+    #[arg(long = "debug", global = true, help = "Write debug-level logs to debug.log")]
     debug: bool,
-    #[arg(long = "setup")]
-    setup: bool,
-    #[arg(long = "smoke")]
+    #[arg(
+        long = "env-config",
+        global = true,
+        help = "Build the config from LASERSELL_<SECTION>_<KEY> env vars; no config file is read"
+    )]
+    env_config: bool,
+    #[command(subcommand)]
+    command: Option<RawCommand>,
+
+    // Mode flags from before the subcommands, kept as hidden aliases.
+    #[arg(long = "setup", value_name = "section", num_args = 0..=1, hide = true)]
+    setup: Option<Option<onboarding::SetupSection>>,
+    #[arg(long = "smoke", hide = true)]
     smoke: bool,
-    #[arg(long = "export-private-key", value_name = "path", num_args = 0..=1)]
+    #[arg(long = "check-config", hide = true)]
+    check_config: bool,
+    #[arg(long = "export-private-key", value_name = "path", num_args = 0..=1, hide = true)]
     export_private_key: Option<Option<PathBuf>>,
+    #[arg(long = "offline-sign", value_name = "unsigned.b64", hide = true)]
+    offline_sign: Option<PathBuf>,
+    #[arg(long = "change-passphrase", hide = true)]
+    change_passphrase: bool,
+    #[arg(long = "rotate-wallet", hide = true)]
+    rotate_wallet: bool,
+    #[arg(long = "sweep", hide = true)]
+    sweep: bool,
+    #[arg(long = "attach", hide = true)]
+    attach: bool,
+    #[arg(long = "replay", value_name = "file", hide = true)]
+    replay: Option<PathBuf>,
+    #[arg(long = "replay-ui", value_name = "journal", hide = true)]
+    replay_ui: Option<PathBuf>,
+    #[arg(long = "record-stream", value_name = "file", hide = true)]
+    record_stream: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum RawCommand {
+    /// Start the exit daemon (the default).
+    Run {
+        /// Append every stream message to this file as JSON lines, for
+        /// `replay`.
+        #[arg(long = "record-stream", value_name = "file")]
+        record_stream: Option<PathBuf>,
+    },
+    /// Run the setup wizard, then the daemon; or re-run only its
+    /// credentials, strategy or wallet part.
+    Setup {
+        #[arg(value_name = "section")]
+        section: Option<onboarding::SetupSection>,
+    },
+    /// Health check: connect, verify, exit.
+    Smoke,
+    /// Validate the config file and point at problems, then run the smoke
+    /// check.
+    Doctor {
+        /// Only validate the config; no network access.
+        #[arg(long = "offline")]
+        offline: bool,
+    },
+    /// Print the base58 private key of the keystore to stdout.
+    ExportKey {
+        #[arg(value_name = "keystore")]
+        keystore: Option<PathBuf>,
+    },
+    /// Print the latest sells from the trade journal.
+    History {
+        #[arg(long = "limit", value_name = "n", default_value_t = 20)]
+        limit: usize,
+    },
+    /// Work with the log of every transaction signed with the wallet key.
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    /// Sell, burn or close dust token accounts after showing the plan.
+    Sweep,
+    /// Sign an unsigned base64 tx with the keystore and print it; no network
+    /// access ('-' reads stdin).
+    OfflineSign {
+        #[arg(value_name = "unsigned.b64")]
+        input: PathBuf,
+    },
+    /// Re-encrypt the keystore with a new passphrase; asks for the current
+    /// one.
+    ChangePassphrase,
+    /// Generate a new wallet into a new keystore and point the config at it.
+    RotateWallet,
+    /// Replay a `run --record-stream` recording against the config's
+    /// strategy and print the PnL report.
+    Replay {
+        #[arg(value_name = "file")]
+        recording: PathBuf,
+    },
+    /// Seed cost basis for positions opened with another tool from a
+    /// mint,tokens,cost_basis_sol CSV.
+    ImportPositions {
+        #[arg(value_name = "csv")]
+        csv: PathBuf,
+    },
+    /// Summarize the positions and exit signals in a stream.archive_dir
+    /// archive.
+    InspectArchive {
+        #[arg(value_name = "file")]
+        file: PathBuf,
+    },
+    /// Collect the scrubbed error log, the config with secrets redacted,
+    /// version/OS info, recent session reports and the smoke check result
+    /// into one .tar.zst to attach to a support request.
+    SupportBundle {
+        /// Where to write the bundle; defaults to
+        /// lasersell-support-<time>.tar.zst in the current directory.
+        #[arg(long = "output", short = 'o', value_name = "file")]
+        output: Option<PathBuf>,
+        /// Skip the smoke check; no network access.
+        #[arg(long = "offline")]
+        offline: bool,
+    },
+    /// Download the latest release, verify its signature and replace this
+    /// binary with it.
+    SelfUpdate,
+    /// Watch the daemon running on this machine from another terminal,
+    /// read-only: its wallet, stream state and sessions.
+    Attach,
+    /// Play a `logging.event_journal` file back through the console, then
+    /// print the `show <mint>` view of each mint in it.
+    ReplayUi {
+        #[arg(value_name = "journal")]
+        journal: PathBuf,
+        /// Multiple of the recorded pace; pauses are capped at 5s.
+        #[arg(long = "speed", value_name = "x", default_value_t = 1.0)]
+        speed: f64,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum AuditAction {
+    /// Recompute the hash chain and check every entry's signatures; exits
+    /// with status 1 at the first entry that was edited, removed or reordered.
+    Verify {
+        /// The log to check; defaults to ~/.lasersell/tx_audit.ndjson.
+        #[arg(long = "file", value_name = "path")]
+        file: Option<PathBuf>,
+    },
+}
+
+impl RawCommand {
+    fn into_mode(self) -> (&'static str, CliMode) {
+        match self {
+            RawCommand::Run { record_stream } => ("run", CliMode::Run { record_stream }),
+            RawCommand::Setup { section } => ("setup", CliMode::Setup { section }),
+            RawCommand::Smoke => ("smoke", CliMode::Smoke),
+            RawCommand::Doctor { offline } => ("doctor", CliMode::Doctor { offline }),
+            RawCommand::ExportKey { keystore } => ("export-key", CliMode::ExportKey { keystore }),
+            RawCommand::History { limit } => ("history", CliMode::History { limit }),
+            RawCommand::Audit {
+                action: AuditAction::Verify { file },
+            } => ("audit verify", CliMode::AuditVerify { file }),
+            RawCommand::Sweep => ("sweep", CliMode::Sweep),
+            RawCommand::OfflineSign { input } => ("offline-sign", CliMode::OfflineSign { input }),
+            RawCommand::ChangePassphrase => ("change-passphrase", CliMode::ChangePassphrase),
+            RawCommand::RotateWallet => ("rotate-wallet", CliMode::RotateWallet),
+            RawCommand::Replay { recording } => ("replay", CliMode::Replay { recording }),
+            RawCommand::ImportPositions { csv } => {
+                ("import-positions", CliMode::ImportPositions { csv })
+            }
+            RawCommand::InspectArchive { file } => {
+                ("inspect-archive", CliMode::InspectArchive { file })
+            }
+            RawCommand::SupportBundle { output, offline } => {
+                ("support-bundle", CliMode::SupportBundle { output, offline })
+            }
+            RawCommand::SelfUpdate => ("self-update", CliMode::SelfUpdate),
+            RawCommand::Attach => ("attach", CliMode::Attach),
+            RawCommand::ReplayUi { journal, speed } => {
+                ("replay-ui", CliMode::ReplayUi { journal, speed })
+            }
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct ExportConfig {
     account: ExportAccount,
+    #[serde(default)]
+    wallet: config::WalletConfig,
 }
 
 #[derive(Debug, Deserialize)]
@@ -332,54 +908,116 @@ where
     normalize_cli_args(raw)
 }
 
-fn normalize_cli_args(raw: RawCliArgs) -> Result<CliArgs> {
-    let export_private_key = raw.export_private_key.is_some();
-    let export_private_key_path = raw.export_private_key.flatten();
-    if export_private_key_path
-        .as_ref()
-        .map(|path| path.as_os_str().is_empty())
-        .unwrap_or(false)
-    {
-        return Err(anyhow!("--export-private-key requires a path after '='"));
+/// Picks the one mode from the subcommand and the legacy mode flags, then
+/// resolves the config path for it.
+fn normalize_cli_args(mut raw: RawCliArgs) -> Result<CliArgs> {
+    let mut modes = Vec::new();
+    if let Some(command) = raw.command.take() {
+        modes.push(command.into_mode());
     }
-    if raw.smoke && raw.setup {
-        return Err(anyhow!("--smoke cannot be combined with --setup"));
+    if let Some(input) = raw.offline_sign.take() {
+        modes.push(("--offline-sign", CliMode::OfflineSign { input }));
     }
-    if raw.smoke && export_private_key {
-        return Err(anyhow!(
-            "--smoke cannot be combined with --export-private-key"
-        ));
+    if raw.smoke {
+        modes.push(("--smoke", CliMode::Smoke));
+    }
+    if let Some(section) = raw.setup.take() {
+        modes.push(("--setup", CliMode::Setup { section }));
+    }
+    if raw.check_config {
+        modes.push(("--check-config", CliMode::Doctor { offline: true }));
+    }
+    if let Some(keystore) = raw.export_private_key.take() {
+        if keystore
+            .as_ref()
+            .is_some_and(|path| path.as_os_str().is_empty())
+        {
+            return Err(anyhow!("--export-private-key requires a path after '='"));
+        }
+        modes.push(("--export-private-key", CliMode::ExportKey { keystore }));
+    }
+    if raw.change_passphrase {
+        modes.push(("--change-passphrase", CliMode::ChangePassphrase));
+    }
+    if raw.rotate_wallet {
+        modes.push(("--rotate-wallet", CliMode::RotateWallet));
+    }
+    if raw.sweep {
+        modes.push(("--sweep", CliMode::Sweep));
+    }
+    if raw.attach {
+        modes.push(("--attach", CliMode::Attach));
+    }
+    if let Some(recording) = raw.replay.take() {
+        modes.push(("--replay", CliMode::Replay { recording }));
+    }
+    if let Some(journal) = raw.replay_ui.take() {
+        modes.push(("--replay-ui", CliMode::ReplayUi { journal, speed: 1.0 }));
+    }
+    if let [(first, _), (second, _), ..] = modes.as_slice() {
+        return Err(anyhow!("{first} cannot be combined with {second}"));
+    }
+    let (name, mut mode) = modes.pop().unwrap_or((
+        "run",
+        CliMode::Run {
+            record_stream: None,
+        },
+    ));
+    if let Some(path) = raw.record_stream.take() {
+        let CliMode::Run { record_stream } = &mut mode else {
+            return Err(anyhow!("--record-stream cannot be combined with {name}"));
+        };
+        record_stream.get_or_insert(path);
     }
-    if export_private_key {
-        if raw.setup {
-            return Err(anyhow!(
-                "--export-private-key cannot be combined with --setup"
-            ));
-        }
-        return Ok(CliArgs {
-            config_path: raw.config_path.unwrap_or_default(),
-            debug: raw.debug,
-            setup: raw.setup,
-            smoke: raw.smoke,
-            export_private_key,
-            export_private_key_path,
-        });
+    if raw.env_config && matches!(mode, CliMode::Setup { .. } | CliMode::Doctor { .. }) {
+        return Err(anyhow!("--env-config cannot be combined with {name}"));
+    }
+    // LASERSELL_CONFIG_PATH is only the default, so `--profile` overrides it.
+    if let Some(profile) = raw.profile.as_deref() {
+        if raw.config_path.is_some() {
+            return Err(anyhow!("--profile cannot be combined with -f/--config"));
+        }
+        if raw.env_config {
+            return Err(anyhow!("--profile cannot be combined with --env-config"));
+        }
+        let setup = matches!(mode, CliMode::Setup { .. });
+        raw.config_path = Some(resolve_profile(profile, setup)?);
     }
     let config_path = match raw.config_path {
         Some(path) => path,
+        None if mode.config_optional() => PathBuf::new(),
+        // Env-only mode never reads a config file, so the home dir may not exist.
+        None if raw.env_config => PathBuf::new(),
         None => default_config_path()?,
     };
-
     Ok(CliArgs {
         config_path,
+        profile: raw.profile,
         debug: raw.debug,
-        setup: raw.setup,
-        smoke: raw.smoke,
-        export_private_key,
-        export_private_key_path,
+        env_config: raw.env_config,
+        mode,
     })
 }
 
+/// The config file behind `--profile <name>`. Only `setup` may create one,
+/// so a typo does not walk into onboarding.
+fn resolve_profile(name: &str, setup: bool) -> Result<PathBuf> {
+    let path = util::paths::profile_config_path(name)?;
+    if setup || path.is_file() {
+        return Ok(path);
+    }
+    let known = util::paths::list_profiles();
+    let known = if known.is_empty() {
+        "none yet".to_string()
+    } else {
+        known.join(", ")
+    };
+    Err(anyhow!(
+        "profile {name} not found at {} (profiles: {known}); create it with `lasersell --profile {name} setup`",
+        path.display()
+    ))
+}
+
 fn default_config_path() -> Result<PathBuf> {
     if let Ok(value) = env::var("LASERSELL_CONFIG_PATH") {
         if !value.trim().is_empty() {
@@ -393,46 +1031,331 @@ fn default_config_path() -> Result<PathBuf> {
 mod cli_tests {
     use super::*;
 
+    fn parse_mode<const N: usize>(args: [&str; N]) -> CliMode {
+        parse_cli_args_from(args).expect("parse cli args").mode
+    }
+
     #[test]
-    fn parse_export_private_key_without_path() {
-        let cli =
-            parse_cli_args_from(["lasersell", "--export-private-key"]).expect("parse cli args");
-        assert!(cli.export_private_key);
-        assert!(cli.export_private_key_path.is_none());
-        assert!(cli.config_path.as_os_str().is_empty());
+    fn parse_defaults_to_run() {
+        let cli = parse_cli_args_from(["lasersell", "-f", "/tmp/cfg.yml"]).expect("parse cli args");
+        assert_eq!(
+            cli.mode,
+            CliMode::Run {
+                record_stream: None
+            }
+        );
+        let cli = parse_cli_args_from(["lasersell", "run", "-f", "/tmp/cfg.yml", "--debug"])
+            .expect("parse cli args");
+        assert!(cli.debug);
+        assert_eq!(cli.config_path, PathBuf::from("/tmp/cfg.yml"));
     }
 
     #[test]
-    fn parse_export_private_key_with_path() {
-        let cli = parse_cli_args_from([
-            "lasersell",
-            "--export-private-key",
-            "/tmp/wallet.keystore.json",
-        ])
-        .expect("parse cli args");
-        assert!(cli.export_private_key);
+    fn parse_export_key_without_path() {
+        for args in [
+            &["lasersell", "export-key"][..],
+            &["lasersell", "--export-private-key"][..],
+        ] {
+            let cli = parse_cli_args_from(args.iter().copied()).expect("parse cli args");
+            assert_eq!(cli.mode, CliMode::ExportKey { keystore: None });
+            assert!(cli.config_path.as_os_str().is_empty());
+        }
+    }
+
+    #[test]
+    fn parse_export_key_with_path() {
+        let keystore = Some(PathBuf::from("/tmp/wallet.keystore.json"));
         assert_eq!(
-            cli.export_private_key_path,
-            Some(PathBuf::from("/tmp/wallet.keystore.json"))
+            parse_mode(["lasersell", "export-key", "/tmp/wallet.keystore.json"]),
+            CliMode::ExportKey {
+                keystore: keystore.clone()
+            }
+        );
+        assert_eq!(
+            parse_mode([
+                "lasersell",
+                "--export-private-key",
+                "/tmp/wallet.keystore.json",
+            ]),
+            CliMode::ExportKey { keystore }
         );
     }
 
     #[test]
-    fn parse_rejects_smoke_setup_combo() {
+    fn parse_rejects_two_modes() {
         let err =
             parse_cli_args_from(["lasersell", "--smoke", "--setup"]).expect_err("should fail");
         assert!(err
             .to_string()
             .contains("--smoke cannot be combined with --setup"));
+        let err = parse_cli_args_from(["lasersell", "--smoke", "--export-private-key"])
+            .expect_err("should fail");
+        assert!(err
+            .to_string()
+            .contains("--smoke cannot be combined with --export-private-key"));
+        let err = parse_cli_args_from(["lasersell", "--offline-sign", "-", "--smoke"])
+            .expect_err("should fail");
+        assert!(err.to_string().contains("--offline-sign cannot be combined"));
+        let err = parse_cli_args_from(["lasersell", "--smoke", "inspect-archive", "a"])
+            .expect_err("should fail");
+        assert!(err.to_string().contains("inspect-archive cannot be combined"));
+        let err =
+            parse_cli_args_from(["lasersell", "--sweep", "doctor"]).expect_err("should fail");
+        assert!(err
+            .to_string()
+            .contains("doctor cannot be combined with --sweep"));
     }
 
     #[test]
-    fn parse_rejects_smoke_export_combo() {
-        let err = parse_cli_args_from(["lasersell", "--smoke", "--export-private-key"])
+    fn parse_setup_with_and_without_section() {
+        for args in [&["lasersell", "setup"][..], &["lasersell", "--setup"][..]] {
+            let cli = parse_cli_args_from(args.iter().copied()).expect("parse cli args");
+            assert_eq!(cli.mode, CliMode::Setup { section: None });
+        }
+        let strategy = CliMode::Setup {
+            section: Some(onboarding::SetupSection::Strategy),
+        };
+        assert_eq!(parse_mode(["lasersell", "setup", "strategy"]), strategy);
+        assert_eq!(parse_mode(["lasersell", "--setup", "strategy"]), strategy);
+        assert!(parse_cli_args_from(["lasersell", "--setup", "everything"]).is_err());
+        assert!(parse_cli_args_from(["lasersell", "setup", "--env-config"]).is_err());
+    }
+
+    #[test]
+    fn parse_doctor_and_check_config() {
+        assert_eq!(
+            parse_mode(["lasersell", "-f", "/tmp/cfg.yml", "doctor"]),
+            CliMode::Doctor { offline: false }
+        );
+        assert_eq!(
+            parse_mode(["lasersell", "-f", "/tmp/cfg.yml", "--check-config"]),
+            CliMode::Doctor { offline: true }
+        );
+        assert_eq!(
+            parse_mode(["lasersell", "-f", "/tmp/cfg.yml", "smoke"]),
+            CliMode::Smoke
+        );
+    }
+
+    #[test]
+    fn parse_history_limit() {
+        let cli = parse_cli_args_from(["lasersell", "history"]).expect("parse cli args");
+        assert_eq!(cli.mode, CliMode::History { limit: 20 });
+        assert!(cli.config_path.as_os_str().is_empty());
+        assert_eq!(
+            parse_mode(["lasersell", "history", "--limit", "5"]),
+            CliMode::History { limit: 5 }
+        );
+    }
+
+    #[test]
+    fn parse_support_bundle() {
+        assert_eq!(
+            parse_mode(["lasersell", "-f", "/tmp/cfg.yml", "support-bundle"]),
+            CliMode::SupportBundle {
+                output: None,
+                offline: false
+            }
+        );
+        assert_eq!(
+            parse_mode([
+                "lasersell",
+                "-f",
+                "/tmp/cfg.yml",
+                "support-bundle",
+                "--offline",
+                "-o",
+                "/tmp/b.tar.zst"
+            ]),
+            CliMode::SupportBundle {
+                output: Some(PathBuf::from("/tmp/b.tar.zst")),
+                offline: true
+            }
+        );
+    }
+
+    #[test]
+    fn parse_attach_needs_no_config() {
+        assert_eq!(parse_mode(["lasersell", "attach"]), CliMode::Attach);
+        assert_eq!(parse_mode(["lasersell", "--attach"]), CliMode::Attach);
+        assert!(CliMode::Attach.config_optional());
+        assert!(parse_cli_args_from(["lasersell", "--attach", "--sweep"]).is_err());
+    }
+
+    #[test]
+    fn parse_replay_ui_speed() {
+        assert_eq!(
+            parse_mode(["lasersell", "replay-ui", "events.ndjson", "--speed", "4"]),
+            CliMode::ReplayUi {
+                journal: PathBuf::from("events.ndjson"),
+                speed: 4.0,
+            }
+        );
+        assert_eq!(
+            parse_mode(["lasersell", "--replay-ui", "events.ndjson"]),
+            CliMode::ReplayUi {
+                journal: PathBuf::from("events.ndjson"),
+                speed: 1.0,
+            }
+        );
+        assert!(CliMode::ReplayUi {
+            journal: PathBuf::new(),
+            speed: 1.0,
+        }
+        .config_optional());
+    }
+
+    #[test]
+    fn parse_wallet_maintenance_modes_stand_alone() {
+        assert_eq!(
+            parse_mode(["lasersell", "--rotate-wallet"]),
+            CliMode::RotateWallet
+        );
+        assert_eq!(
+            parse_mode(["lasersell", "change-passphrase"]),
+            CliMode::ChangePassphrase
+        );
+        let err = parse_cli_args_from(["lasersell", "--change-passphrase", "--rotate-wallet"])
             .expect_err("should fail");
+        assert!(err.to_string().contains("cannot be combined"));
+        assert!(parse_cli_args_from(["lasersell", "--change-passphrase", "--smoke"]).is_err());
+    }
+
+    #[test]
+    fn parse_sweep_runs_against_the_config() {
+        let cli = parse_cli_args_from(["lasersell", "-f", "/tmp/cfg.yml", "--sweep"])
+            .expect("parse cli args");
+        assert_eq!(cli.mode, CliMode::Sweep);
+        assert_eq!(cli.config_path, PathBuf::from("/tmp/cfg.yml"));
+        assert_eq!(
+            parse_mode(["lasersell", "sweep", "-f", "/tmp/cfg.yml"]),
+            CliMode::Sweep
+        );
+        assert!(parse_cli_args_from(["lasersell", "--sweep", "--smoke"]).is_err());
+        assert!(parse_cli_args_from(["lasersell", "--sweep", "--rotate-wallet"]).is_err());
+    }
+
+    #[test]
+    fn parse_replay_and_record_stream() {
+        let recording = PathBuf::from("/tmp/stream.ndjson");
+        let replay = CliMode::Replay {
+            recording: recording.clone(),
+        };
+        assert_eq!(
+            parse_mode(["lasersell", "-f", "/tmp/cfg.yml", "replay", "/tmp/stream.ndjson"]),
+            replay
+        );
+        assert_eq!(
+            parse_mode(["lasersell", "-f", "/tmp/cfg.yml", "--replay", "/tmp/stream.ndjson"]),
+            replay
+        );
+        let run = CliMode::Run {
+            record_stream: Some(recording),
+        };
+        assert_eq!(
+            parse_mode([
+                "lasersell",
+                "-f",
+                "/tmp/cfg.yml",
+                "run",
+                "--record-stream",
+                "/tmp/stream.ndjson",
+            ]),
+            run
+        );
+        assert_eq!(
+            parse_mode([
+                "lasersell",
+                "-f",
+                "/tmp/cfg.yml",
+                "--record-stream",
+                "/tmp/stream.ndjson",
+            ]),
+            run
+        );
+        assert!(parse_cli_args_from(["lasersell", "--replay", "a", "--record-stream", "b"]).is_err());
+        assert!(parse_cli_args_from(["lasersell", "--replay", "a", "--smoke"]).is_err());
+        assert!(parse_cli_args_from(["lasersell", "--record-stream", "a", "--sweep"]).is_err());
+    }
+
+    #[test]
+    fn parse_profile_maps_to_the_profiles_dir() {
+        let cli = parse_cli_args_from(["lasersell", "--profile", "devnet", "setup"])
+            .expect("parse cli args");
+        assert_eq!(
+            cli.config_path,
+            util::paths::profile_config_path("devnet").unwrap()
+        );
+        assert!(cli.config_path.ends_with("profiles/devnet.yml"));
+        let err = parse_cli_args_from(["lasersell", "--profile", "no-such-profile-here"])
+            .expect_err("missing profile");
         assert!(err
             .to_string()
-            .contains("--smoke cannot be combined with --export-private-key"));
+            .contains("lasersell --profile no-such-profile-here setup"));
+        assert!(parse_cli_args_from(["lasersell", "--profile", "../x", "--setup"]).is_err());
+        assert!(
+            parse_cli_args_from(["lasersell", "--profile", "a", "-f", "/tmp/cfg.yml"]).is_err()
+        );
+        assert_eq!(cli.profile.as_deref(), Some("devnet"));
+        assert_eq!(parse_cli_args_from(["lasersell", "history"]).unwrap().profile, None);
+    }
+
+    #[test]
+    fn parse_offline_sign_skips_default_config() {
+        for args in [
+            &["lasersell", "offline-sign", "/tmp/unsigned.b64"][..],
+            &["lasersell", "--offline-sign", "/tmp/unsigned.b64"][..],
+        ] {
+            let cli = parse_cli_args_from(args.iter().copied()).expect("parse cli args");
+            assert_eq!(
+                cli.mode,
+                CliMode::OfflineSign {
+                    input: PathBuf::from("/tmp/unsigned.b64")
+                }
+            );
+            assert!(cli.config_path.as_os_str().is_empty());
+        }
+    }
+
+    #[test]
+    fn parse_audit_verify_skips_default_config() {
+        let cli = parse_cli_args_from(["lasersell", "audit", "verify"]).expect("parse cli args");
+        assert_eq!(cli.mode, CliMode::AuditVerify { file: None });
+        assert!(cli.config_path.as_os_str().is_empty());
+        assert_eq!(
+            parse_mode(["lasersell", "audit", "verify", "--file", "old.ndjson"]),
+            CliMode::AuditVerify {
+                file: Some(PathBuf::from("old.ndjson"))
+            }
+        );
+        assert!(parse_cli_args_from(["lasersell", "audit"]).is_err());
+    }
+
+    #[test]
+    fn parse_import_and_inspect_subcommands() {
+        assert_eq!(
+            parse_mode(["lasersell", "import-positions", "bags.csv"]),
+            CliMode::ImportPositions {
+                csv: PathBuf::from("bags.csv")
+            }
+        );
+        assert_eq!(
+            parse_mode(["lasersell", "inspect-archive", "s.ndjson.zst"]),
+            CliMode::InspectArchive {
+                file: PathBuf::from("s.ndjson.zst")
+            }
+        );
+    }
+}
+
+/// Prints `SMOKE OK`, or `SMOKE FAIL <step>` and exits with status 1.
+async fn run_smoke_cli(config_path: &Path) {
+    match run_smoke_mode(config_path).await {
+        Ok(()) => println!("SMOKE OK"),
+        Err(failure) => {
+            eprintln!("SMOKE FAIL {}", failure.step);
+            std::process::exit(1);
+        }
     }
 }
 
@@ -460,13 +1383,19 @@ async fn run_smoke_mode(config_path: &Path) -> std::result::Result<(), SmokeFail
     }
     let cfg = cfg_result.map_err(|_| SmokeFailure::new("config"))?;
     smoke_stream_check(&cfg).await?;
+    network::exit_api::init_regions(&cfg)
+        .await
+        .map_err(|_| SmokeFailure::new("exit_api_regions"))?;
     smoke_exit_api_check(&cfg).await?;
     Ok(())
 }
 
 async fn smoke_stream_check(cfg: &config::Config) -> std::result::Result<(), SmokeFailure> {
-    let stream_client =
+    let mut stream_client =
         SdkStreamClient::new(cfg.account.api_key.clone()).with_local_mode(cfg.account.local);
+    if cfg.active_profile().is_some() {
+        stream_client = stream_client.with_endpoint(cfg.stream_url());
+    }
     let strategy = StrategyConfigMsg {
         target_profit_pct: cfg.strategy.target_profit.percent_value(),
         stop_loss_pct: cfg.strategy.stop_loss.percent_value(),
@@ -498,15 +1427,9 @@ async fn smoke_stream_check(cfg: &config::Config) -> std::result::Result<(), Smo
 }
 
 async fn smoke_exit_api_check(cfg: &config::Config) -> std::result::Result<(), SmokeFailure> {
-    let options = ExitApiClientOptions {
-        connect_timeout: cfg.exit_api_connect_timeout(),
-        attempt_timeout: cfg.exit_api_request_timeout(),
-        ..ExitApiClientOptions::default()
-    };
-    let exit_api =
-        ExitApiClient::with_options(optional_api_key_for_smoke(&cfg.account.api_key), options)
-            .map(|client| client.with_local_mode(cfg.account.local))
-            .map_err(|_| SmokeFailure::new("exit_api_client"))?;
+    let exit_api = network::exit_api::exit_api_client(cfg)
+        .map_err(|_| SmokeFailure::new("exit_api_client"))?;
+    let exit_api = network::exit_api::routed(&exit_api);
 
     let request = BuildSellTxRequest {
         mint: SMOKE_MINT.to_string(),
@@ -527,17 +1450,11 @@ async fn smoke_exit_api_check(cfg: &config::Config) -> std::result::Result<(), S
     Ok(())
 }
 
-fn optional_api_key_for_smoke(api_key: &SecretString) -> Option<SecretString> {
-    let trimmed = api_key.expose_secret().trim();
-    if trimmed.is_empty() {
-        return None;
-    }
-    Some(SecretString::new(trimmed.to_string()))
-}
-
 fn init_tracing(
     debug: bool,
     filter: EnvFilter,
+    category_filter: util::logging::CategoryFilter,
+    rotation: util::logging::LogRotation,
 ) -> Option<tracing_appender::non_blocking::WorkerGuard> {
     let error_log_path = match util::paths::default_error_log_path() {
         Ok(path) => Some(path),
@@ -578,12 +1495,23 @@ fn init_tracing(
         }
     }
 
+    for path in error_log_path.iter().chain(debug_log_path.iter()) {
+        if let Err(err) = rotation.rotate_if_needed(path) {
+            eprintln!("Failed to rotate log {}: {err}", path.display());
+        }
+    }
+
     install_error_log_panic_hook(error_log_path.clone(), debug_log_path.clone());
 
     let error_file_layer = tracing_subscriber::fmt::layer()
         .with_writer({
             let error_log_path = error_log_path.clone();
             move || {
+                // The debug log's appender holds its file open, so it only
+                // rotates at startup; the error log reopens per line.
+                if let Some(path) = error_log_path.as_ref() {
+                    let _ = rotation.rotate_if_needed(path);
+                }
                 let writer: Box<dyn Write + Send> = match error_log_path.as_ref() {
                     Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
                         Ok(file) => Box::new(file),
@@ -632,9 +1560,16 @@ fn init_tracing(
 
     // CLI mode: always log to stderr
     tracing_subscriber::registry()
+        .with(network::unknown_messages::layer())
+        .with(util::warn_dedup::layer())
         .with(error_file_layer)
         .with(debug_file_layer)
-        .with(tracing_subscriber::fmt::layer())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(util::logging::ConsoleWriter::default)
+                .with_ansi(util::theme::current().ansi())
+                .with_filter(category_filter),
+        )
         .with(filter)
         .init();
 
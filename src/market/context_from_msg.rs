@@ -1,6 +1,6 @@
 use lasersell_sdk::stream::proto::{MarketContextMsg, MarketTypeMsg};
 
-use crate::market::{MarketContext, MarketType};
+use crate::market::{MarketContext, MarketType, QuoteToken, USD1_MINT};
 
 pub fn market_context_from_msg(msg: &MarketContextMsg) -> MarketContext {
     let market_type = match msg.market_type {
@@ -11,7 +11,24 @@ pub fn market_context_from_msg(msg: &MarketContextMsg) -> MarketContext {
         MarketTypeMsg::RaydiumLaunchpad => MarketType::RaydiumLaunchpad,
         MarketTypeMsg::RaydiumCpmm => MarketType::RaydiumCpmm,
     };
-    MarketContext { market_type }
+    let quote = if quote_mint_from_msg(msg) == Some(USD1_MINT) {
+        QuoteToken::Usd1
+    } else {
+        QuoteToken::Sol
+    };
+    MarketContext { market_type, quote }
+}
+
+/// Quote mint of the pool, for the market types whose context carries it.
+pub fn quote_mint_from_msg(msg: &MarketContextMsg) -> Option<&str> {
+    match msg.market_type {
+        MarketTypeMsg::MeteoraDbc => msg.meteora_dbc.as_ref().map(|c| c.quote_mint.as_str()),
+        MarketTypeMsg::RaydiumLaunchpad => {
+            msg.raydium_launchpad.as_ref().map(|c| c.quote_mint.as_str())
+        }
+        MarketTypeMsg::RaydiumCpmm => msg.raydium_cpmm.as_ref().map(|c| c.quote_mint.as_str()),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
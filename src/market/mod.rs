@@ -10,7 +10,7 @@ pub fn usd1_mint() -> Pubkey {
     Pubkey::from_str(USD1_MINT).expect("USD1_MINT invalid")
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MarketType {
     #[serde(alias = "pumpfun")]
@@ -23,9 +23,47 @@ pub enum MarketType {
     RaydiumCpmm,
 }
 
+impl MarketType {
+    pub fn label(self) -> &'static str {
+        match self {
+            MarketType::PumpFun => "pump_fun",
+            MarketType::MeteoraDbc => "meteora_dbc",
+            MarketType::PumpSwap => "pump_swap",
+            MarketType::MeteoraDammV2 => "meteora_damm_v2",
+            MarketType::RaydiumLaunchpad => "raydium_launchpad",
+            MarketType::RaydiumCpmm => "raydium_cpmm",
+        }
+    }
+}
+
+/// Asset a pool is quoted in, and so the unit of its cost basis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QuoteToken {
+    #[default]
+    Sol,
+    Usd1,
+}
+
+impl QuoteToken {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            QuoteToken::Sol => "SOL",
+            QuoteToken::Usd1 => "USD1",
+        }
+    }
+
+    pub fn decimals(self) -> u32 {
+        match self {
+            QuoteToken::Sol => 9,
+            QuoteToken::Usd1 => 6,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct MarketContext {
     pub market_type: MarketType,
+    pub quote: QuoteToken,
 }
 
 #[cfg(test)]
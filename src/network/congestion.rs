@@ -0,0 +1,239 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use tracing::{debug, info, warn};
+
+use super::{rpc_result, RpcPriority, RpcScheduler};
+
+const SAMPLE_INTERVAL_SECS: u64 = 30;
+const PERFORMANCE_SAMPLES: u64 = 5;
+const SLOT_TIME_MS: f64 = 400.0;
+/// Median per-slot minimum priority fee (micro-lamports per CU).
+const BUSY_FEE: u64 = 10_000;
+const CONGESTED_FEE: u64 = 100_000;
+const BUSY_SKIPPED_PCT: f64 = 10.0;
+const CONGESTED_SKIPPED_PCT: f64 = 25.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CongestionLevel {
+    Calm,
+    Busy,
+    Congested,
+}
+
+impl CongestionLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            CongestionLevel::Calm => "calm",
+            CongestionLevel::Busy => "busy",
+            CongestionLevel::Congested => "congested",
+        }
+    }
+
+    /// Share of `full_bump_bps` added to a sell's starting slippage.
+    pub fn slippage_bump_bps(self, full_bump_bps: u16) -> u16 {
+        match self {
+            CongestionLevel::Calm => 0,
+            CongestionLevel::Busy => full_bump_bps / 2,
+            CongestionLevel::Congested => full_bump_bps,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CongestionReading {
+    pub median_fee: u64,
+    pub skipped_slot_pct: f64,
+    pub level: CongestionLevel,
+}
+
+impl CongestionReading {
+    /// Builds a reading from `getRecentPrioritizationFees` and
+    /// `getRecentPerformanceSamples` results.
+    fn from_rpc(fees: &serde_json::Value, performance: &serde_json::Value) -> Result<Self> {
+        let mut fees: Vec<u64> = fees
+            .as_array()
+            .ok_or_else(|| anyhow!("prioritization fees missing"))?
+            .iter()
+            .filter_map(|entry| entry.get("prioritizationFee")?.as_u64())
+            .collect();
+        fees.sort_unstable();
+        let median_fee = fees.get(fees.len() / 2).copied().unwrap_or(0);
+
+        let (mut slots, mut period_secs) = (0u64, 0u64);
+        for sample in performance
+            .as_array()
+            .ok_or_else(|| anyhow!("performance samples missing"))?
+        {
+            slots += sample.get("numSlots").and_then(|v| v.as_u64()).unwrap_or(0);
+            period_secs += sample
+                .get("samplePeriodSecs")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+        }
+        let expected_slots = period_secs as f64 * 1_000.0 / SLOT_TIME_MS;
+        let skipped_slot_pct = if expected_slots > 0.0 {
+            ((1.0 - slots as f64 / expected_slots) * 100.0).max(0.0)
+        } else {
+            0.0
+        };
+
+        let level = if median_fee >= CONGESTED_FEE || skipped_slot_pct >= CONGESTED_SKIPPED_PCT {
+            CongestionLevel::Congested
+        } else if median_fee >= BUSY_FEE || skipped_slot_pct >= BUSY_SKIPPED_PCT {
+            CongestionLevel::Busy
+        } else {
+            CongestionLevel::Calm
+        };
+        Ok(Self {
+            median_fee,
+            skipped_slot_pct,
+            level,
+        })
+    }
+
+    pub fn render(&self, slippage_bump_bps: u16) -> String {
+        let gauge = match self.level {
+            CongestionLevel::Calm => "[#  ]",
+            CongestionLevel::Busy => "[## ]",
+            CongestionLevel::Congested => "[###]",
+        };
+        let mut out = format!(
+            "congestion  {gauge} {}  fee {} µlamports/CU  skipped slots {:.1}%\n",
+            self.level.label(),
+            self.median_fee,
+            self.skipped_slot_pct
+        );
+        let hint = match (self.level, self.level.slippage_bump_bps(slippage_bump_bps)) {
+            (CongestionLevel::Calm, _) => "sells use the configured slippage".to_string(),
+            (_, 0) => {
+                "sells may need retries; set sell.congestion_slippage_bump_bps to start higher"
+                    .to_string()
+            }
+            (_, bump) => format!("sells start {bump} bps above slippage_pad_bps"),
+        };
+        let _ = writeln!(out, "hint        {hint}");
+        out
+    }
+}
+
+/// Latest congestion reading, refreshed in the background.
+#[derive(Default)]
+pub struct CongestionMonitor {
+    latest: Mutex<Option<CongestionReading>>,
+}
+
+impl CongestionMonitor {
+    pub fn latest(&self) -> Option<CongestionReading> {
+        self.latest.lock().clone()
+    }
+
+    /// Current level; `Calm` until the first sample lands.
+    pub fn level(&self) -> CongestionLevel {
+        self.latest
+            .lock()
+            .as_ref()
+            .map_or(CongestionLevel::Calm, |reading| reading.level)
+    }
+
+    /// Samples the chain every 30s, skipping rounds while a sell runs.
+    pub fn spawn(
+        self: &Arc<Self>,
+        client: reqwest::Client,
+        rpc_url: String,
+        rpc_scheduler: Arc<RpcScheduler>,
+    ) {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(SAMPLE_INTERVAL_SECS));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if rpc_scheduler.sell_active() {
+                    debug!(event = "congestion_sample_deferred");
+                    continue;
+                }
+                match sample(&client, &rpc_url, &rpc_scheduler).await {
+                    Ok(reading) => monitor.store(reading),
+                    Err(err) if super::rpc_health().is_degraded() => {
+                        debug!(event = "congestion_sample_failed", error = %err)
+                    }
+                    Err(err) => warn!(event = "congestion_sample_failed", error = %err),
+                }
+            }
+        });
+    }
+
+    fn store(&self, reading: CongestionReading) {
+        let previous = self.latest.lock().replace(reading.clone());
+        if previous.map(|previous| previous.level) != Some(reading.level) {
+            info!(
+                event = "congestion_changed",
+                level = reading.level.label(),
+                median_fee = reading.median_fee,
+                skipped_slot_pct = format!("{:.1}", reading.skipped_slot_pct)
+            );
+        }
+    }
+}
+
+async fn sample(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    rpc_scheduler: &RpcScheduler,
+) -> Result<CongestionReading> {
+    rpc_scheduler.acquire(RpcPriority::Background).await;
+    let fees = rpc_result(
+        client,
+        rpc_url,
+        "getRecentPrioritizationFees",
+        serde_json::json!([]),
+    )
+    .await?;
+    rpc_scheduler.acquire(RpcPriority::Background).await;
+    let performance = rpc_result(
+        client,
+        rpc_url,
+        "getRecentPerformanceSamples",
+        serde_json::json!([PERFORMANCE_SAMPLES]),
+    )
+    .await?;
+    CongestionReading::from_rpc(&fees, &performance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fees(values: &[u64]) -> serde_json::Value {
+        values
+            .iter()
+            .enumerate()
+            .map(|(slot, fee)| serde_json::json!({ "slot": slot, "prioritizationFee": fee }))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_by_fee_and_skipped_slots() {
+        let full = serde_json::json!([{ "numSlots": 150, "samplePeriodSecs": 60 }]);
+        let calm = CongestionReading::from_rpc(&fees(&[0, 0, 5_000]), &full).unwrap();
+        assert_eq!(calm.level, CongestionLevel::Calm);
+        assert_eq!(calm.skipped_slot_pct, 0.0);
+
+        let pricey = CongestionReading::from_rpc(&fees(&[0, 20_000, 200_000]), &full).unwrap();
+        assert_eq!(pricey.median_fee, 20_000);
+        assert_eq!(pricey.level, CongestionLevel::Busy);
+
+        let skipping = serde_json::json!([
+            { "numSlots": 100, "samplePeriodSecs": 60 },
+            { "numSlots": 110, "samplePeriodSecs": 60 }
+        ]);
+        let reading = CongestionReading::from_rpc(&fees(&[]), &skipping).unwrap();
+        assert_eq!(reading.level, CongestionLevel::Congested);
+        assert!(reading.render(400).contains("sells start 400 bps above"));
+        assert_eq!(CongestionLevel::Busy.slippage_bump_bps(400), 200);
+    }
+}
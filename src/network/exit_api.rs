@@ -0,0 +1,243 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use lasersell_sdk::exit_api::{ExitApiClient, ExitApiClientOptions, ExitApiError};
+use parking_lot::RwLock;
+use secrecy::{ExposeSecret, SecretString};
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Consecutive timed-out requests that send the regions through another
+/// latency probe.
+const REPROBE_AFTER_TIMEOUTS: u32 = 3;
+/// Requests timed per region; the fastest counts.
+const PROBE_ROUNDS: usize = 3;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+static REGIONS: OnceLock<Regions> = OnceLock::new();
+
+/// `account.exit_api_regions`: the region requests go to now, and what it
+/// takes to pick another.
+struct Regions {
+    urls: Vec<(String, String)>,
+    /// Index into `urls` of the region in use.
+    selected: RwLock<usize>,
+    /// `account.exit_api_region` is set; timeouts never move off it.
+    pinned: bool,
+    timeouts: AtomicU32,
+    probing: AtomicBool,
+    http: reqwest::Client,
+}
+
+impl Regions {
+    fn selected_url(&self) -> &str {
+        &self.urls[*self.selected.read()].1
+    }
+
+    /// Counts a request's outcome; true when it was the timeout that
+    /// should start a new probe.
+    fn note(&self, timed_out: bool) -> bool {
+        if !timed_out {
+            self.timeouts.store(0, Ordering::Relaxed);
+            return false;
+        }
+        let timeouts = self.timeouts.fetch_add(1, Ordering::Relaxed) + 1;
+        !self.pinned
+            && timeouts >= REPROBE_AFTER_TIMEOUTS
+            && !self.probing.swap(true, Ordering::SeqCst)
+    }
+
+    async fn reprobe(&self) {
+        let previous = *self.selected.read();
+        if let Some(index) = probe(&self.http, &self.urls).await {
+            *self.selected.write() = index;
+            if index != previous {
+                warn!(
+                    event = "exit_api_region_switched",
+                    from = %self.urls[previous].0,
+                    to = %self.urls[index].0
+                );
+            }
+        }
+        self.timeouts.store(0, Ordering::Relaxed);
+        self.probing.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Exit API client honouring the configured timeouts, local mode and
+/// endpoint profile or region.
+pub fn exit_api_client(cfg: &Config) -> Result<ExitApiClient> {
+    let options = ExitApiClientOptions {
+        connect_timeout: cfg.exit_api_connect_timeout(),
+        attempt_timeout: cfg.exit_api_request_timeout(),
+        ..ExitApiClientOptions::default()
+    };
+    let api_key = cfg.account.api_key.expose_secret().trim();
+    let api_key = (!api_key.is_empty()).then(|| SecretString::new(api_key.to_string()));
+    let client = ExitApiClient::with_options(api_key, options)
+        .context("build LaserSell exit API client")?
+        .with_local_mode(cfg.account.local);
+    Ok(if cfg.active_profile().is_some() {
+        client.with_base_url(cfg.exit_api_url())
+    } else {
+        client
+    })
+}
+
+/// `client` pointed at the region selected now, which a re-probe may have
+/// changed since it was built.
+pub fn routed(client: &ExitApiClient) -> Cow<'_, ExitApiClient> {
+    match REGIONS.get() {
+        Some(regions) => Cow::Owned(client.clone().with_base_url(regions.selected_url())),
+        None => Cow::Borrowed(client),
+    }
+}
+
+/// Records how a request through [`routed`] went. After
+/// `REPROBE_AFTER_TIMEOUTS` timeouts in a row the regions are probed again
+/// in the background.
+pub fn note_outcome(error: Option<&ExitApiError>) {
+    let Some(regions) = REGIONS.get() else {
+        return;
+    };
+    let timed_out = matches!(error, Some(ExitApiError::Transport(err)) if err.is_timeout());
+    if regions.note(timed_out) {
+        warn!(
+            event = "exit_api_region_reprobe",
+            region = %regions.urls[*regions.selected.read()].0,
+            timeouts = REPROBE_AFTER_TIMEOUTS
+        );
+        tokio::spawn(regions.reprobe());
+    }
+}
+
+/// Picks the exit API region at startup: `account.exit_api_region`, or the
+/// lowest latency among `account.exit_api_regions`. Does nothing when no
+/// regions are configured. Later calls are ignored.
+pub async fn init_regions(cfg: &Config) -> Result<()> {
+    let urls = cfg.exit_api_regions();
+    if urls.is_empty() || REGIONS.get().is_some() {
+        return Ok(());
+    }
+    let http = cfg
+        .http_client_builder()?
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .context("build exit API probe client")?;
+    let pinned = cfg.account.exit_api_region.as_deref();
+    let selected = match pinned {
+        Some(name) => urls.iter().position(|(region, _)| region == name),
+        None => probe(&http, &urls).await,
+    }
+    .unwrap_or(0);
+    info!(
+        event = "exit_api_region_selected",
+        region = %urls[selected].0,
+        url = %urls[selected].1,
+        pinned = pinned.is_some()
+    );
+    let _ = REGIONS.set(Regions {
+        urls,
+        selected: RwLock::new(selected),
+        pinned: pinned.is_some(),
+        timeouts: AtomicU32::new(0),
+        probing: AtomicBool::new(false),
+        http,
+    });
+    Ok(())
+}
+
+/// Times every region at once, logs each latency and returns the fastest,
+/// or `None` when none answered.
+async fn probe(http: &reqwest::Client, urls: &[(String, String)]) -> Option<usize> {
+    let handles: Vec<_> = urls
+        .iter()
+        .map(|(_, url)| tokio::spawn(latency(http.clone(), url.clone())))
+        .collect();
+    let mut latencies = Vec::with_capacity(handles.len());
+    for ((region, url), handle) in urls.iter().zip(handles) {
+        let latency = handle.await.ok().flatten();
+        match latency {
+            Some(latency) => info!(
+                event = "exit_api_region_latency",
+                region = %region,
+                url = %url,
+                latency_ms = latency.as_millis() as u64
+            ),
+            None => warn!(
+                event = "exit_api_region_unreachable",
+                region = %region,
+                url = %url
+            ),
+        }
+        latencies.push(latency);
+    }
+    fastest(&latencies)
+}
+
+/// The quickest of a few requests to `url`. Any HTTP response counts; only
+/// a failed connection or a timeout does not.
+async fn latency(http: reqwest::Client, url: String) -> Option<Duration> {
+    let mut best: Option<Duration> = None;
+    for _ in 0..PROBE_ROUNDS {
+        let started = Instant::now();
+        if http.get(&url).send().await.is_ok() {
+            let elapsed = started.elapsed();
+            best = Some(best.map_or(elapsed, |best| best.min(elapsed)));
+        }
+    }
+    best
+}
+
+fn fastest(latencies: &[Option<Duration>]) -> Option<usize> {
+    latencies
+        .iter()
+        .enumerate()
+        .filter_map(|(index, latency)| latency.map(|latency| (index, latency)))
+        .min_by_key(|(_, latency)| *latency)
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(pinned: bool) -> Regions {
+        Regions {
+            urls: vec![("us".to_string(), "https://us.example".to_string())],
+            selected: RwLock::new(0),
+            pinned,
+            timeouts: AtomicU32::new(0),
+            probing: AtomicBool::new(false),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    #[test]
+    fn picks_the_fastest_answering_region() {
+        let ms = Duration::from_millis;
+        assert_eq!(fastest(&[Some(ms(80)), None, Some(ms(30))]), Some(2));
+        assert_eq!(fastest(&[None, None]), None);
+    }
+
+    #[test]
+    fn reprobes_after_consecutive_timeouts_only() {
+        let auto = regions(false);
+        assert!(!auto.note(true));
+        assert!(!auto.note(true));
+        assert!(!auto.note(false), "a success resets the count");
+        assert!(!auto.note(true));
+        assert!(!auto.note(true));
+        assert!(auto.note(true));
+        assert!(!auto.note(true), "one probe at a time");
+
+        let pinned = regions(true);
+        for _ in 0..REPROBE_AFTER_TIMEOUTS * 2 {
+            assert!(!pinned.note(true));
+        }
+    }
+}
@@ -1,5 +1,16 @@
+pub mod congestion;
+pub mod exit_api;
 pub mod rpc;
+pub mod rpc_health;
+pub mod rpc_scheduler;
 pub mod stream_client;
+pub mod stream_archive;
+pub mod stream_recorder;
+pub mod stream_stall;
+pub mod unknown_messages;
 
+pub use congestion::CongestionMonitor;
 pub use rpc::*;
+pub use rpc_health::rpc_health;
+pub use rpc_scheduler::{RpcPriority, RpcScheduler};
 pub use stream_client::*;
@@ -1,31 +1,141 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Instant;
+
 use anyhow::{anyhow, Context, Result};
 use reqwest::Client;
 use serde_json::{json, Value};
+use tracing::debug;
 
+use super::rpc_health::rpc_health;
 use crate::util::logging::redact_url;
 
+static RPC_RETRIES: AtomicU32 = AtomicU32::new(0);
+
+/// Sets `network.rpc_retries`: extra attempts for a call that failed in
+/// transport. Called once at startup.
+pub fn init_retries(retries: u32) {
+    RPC_RETRIES.store(retries, Ordering::Relaxed);
+}
+
 pub async fn rpc_call(client: &Client, url: &str, method: &str, params: Value) -> Result<Value> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+    let body = post_with_retries(client, url, method, &request).await?;
+    let parsed: Value = serde_json::from_str(&body).context("decode rpc response")?;
+    if let Some(err) = parsed.get("error") {
+        return Err(anyhow!("RPC error: {}", err));
+    }
+    Ok(parsed)
+}
+
+/// Sends `calls` as one JSON-RPC batch, a single HTTP round trip, and
+/// returns each call's `result` in order. Any call answering with an error
+/// fails the batch.
+pub async fn rpc_batch(client: &Client, url: &str, calls: &[(&str, Value)]) -> Result<Vec<Value>> {
+    let request: Vec<Value> = calls
+        .iter()
+        .enumerate()
+        .map(|(id, (method, params))| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            })
+        })
+        .collect();
+    let label = calls
+        .iter()
+        .map(|(method, _)| *method)
+        .collect::<Vec<_>>()
+        .join(",");
+    let body = post_with_retries(client, url, &label, &Value::Array(request)).await?;
+    let parsed: Value = serde_json::from_str(&body).context("decode rpc batch response")?;
+    batch_results(parsed, calls)
+}
+
+/// Orders a batch response by id; nodes may answer in any order.
+fn batch_results(parsed: Value, calls: &[(&str, Value)]) -> Result<Vec<Value>> {
+    let Value::Array(responses) = parsed else {
+        // A node without batch support answers with one error object.
+        let err = parsed.get("error").cloned().unwrap_or(parsed);
+        return Err(anyhow!("RPC batch rejected: {}", err));
+    };
+    let mut results = vec![None; calls.len()];
+    for mut response in responses {
+        let Some(id) = response
+            .get("id")
+            .and_then(Value::as_u64)
+            .and_then(|id| usize::try_from(id).ok())
+            .filter(|id| *id < calls.len())
+        else {
+            continue;
+        };
+        let method = calls[id].0;
+        if let Some(err) = response.get("error") {
+            return Err(anyhow!("RPC error in {method}: {}", err));
+        }
+        let result = response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| anyhow!("rpc response missing result for {method}"))?;
+        results[id] = Some(result);
+    }
+    results
+        .into_iter()
+        .zip(calls)
+        .map(|(result, (method, _))| {
+            result.ok_or_else(|| anyhow!("rpc batch response missing {method}"))
+        })
+        .collect()
+}
+
+/// Posts `request`, retrying transport failures up to `network.rpc_retries`
+/// times. Only transport failures are retried and count against RPC health;
+/// a JSON-RPC error still means the node answered.
+async fn post_with_retries(
+    client: &Client,
+    url: &str,
+    method: &str,
+    request: &Value,
+) -> Result<String> {
+    let retries = RPC_RETRIES.load(Ordering::Relaxed);
+    let started = Instant::now();
+    let mut attempt = 0;
+    loop {
+        match post_rpc(client, url, method, request).await {
+            Ok(body) => {
+                rpc_health().record_success(started.elapsed());
+                return Ok(body);
+            }
+            Err(err) if attempt < retries => {
+                attempt += 1;
+                debug!(event = "rpc_retry", method, attempt, error = %err);
+            }
+            Err(err) => {
+                rpc_health().record_failure(method, &err);
+                return Err(err);
+            }
+        }
+    }
+}
+
+async fn post_rpc(client: &Client, url: &str, method: &str, request: &Value) -> Result<String> {
     let endpoint = redact_url(url);
-    let resp = client
-        .post(url)
-        .json(&json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": method,
-            "params": params,
-        }))
-        .send()
-        .await
-        .map_err(|err| {
-            let kind = if err.is_timeout() {
-                "timeout"
-            } else if err.is_connect() {
-                "connect"
-            } else {
-                "send"
-            };
-            anyhow!("rpc request {method} failed ({kind}) to {endpoint}")
-        })?;
+    let resp = client.post(url).json(request).send().await.map_err(|err| {
+        let kind = if err.is_timeout() {
+            "timeout"
+        } else if err.is_connect() {
+            "connect"
+        } else {
+            "send"
+        };
+        anyhow!("rpc request {method} failed ({kind}) to {endpoint}")
+    })?;
 
     let status = resp.status();
     let body = resp.text().await.map_err(|err| {
@@ -35,11 +145,7 @@ pub async fn rpc_call(client: &Client, url: &str, method: &str, params: Value) -
     if !status.is_success() {
         return Err(anyhow!("RPC HTTP {} for {}", status, method));
     }
-    let parsed: Value = serde_json::from_str(&body).context("decode rpc response")?;
-    if let Some(err) = parsed.get("error") {
-        return Err(anyhow!("RPC error: {}", err));
-    }
-    Ok(parsed)
+    Ok(body)
 }
 
 pub async fn rpc_result(client: &Client, url: &str, method: &str, params: Value) -> Result<Value> {
@@ -49,3 +155,36 @@ pub async fn rpc_result(client: &Client, url: &str, method: &str, params: Value)
         .cloned()
         .ok_or_else(|| anyhow!("rpc response missing result"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_results_follow_request_order() {
+        let calls = [("getBalance", json!([])), ("getSlot", json!([]))];
+        let results = batch_results(
+            json!([
+                { "jsonrpc": "2.0", "id": 1, "result": 42 },
+                { "jsonrpc": "2.0", "id": 0, "result": { "value": 7 } }
+            ]),
+            &calls,
+        )
+        .unwrap();
+        assert_eq!(results, [json!({ "value": 7 }), json!(42)]);
+
+        let err = batch_results(
+            json!([
+                { "jsonrpc": "2.0", "id": 0, "result": 1 },
+                { "jsonrpc": "2.0", "id": 1, "error": { "code": -32005 } }
+            ]),
+            &calls,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("RPC error in getSlot"));
+        let err = batch_results(json!([{ "id": 0, "result": 1 }]), &calls).unwrap_err();
+        assert!(err.to_string().contains("missing getSlot"));
+        let err = batch_results(json!({ "error": { "code": -32600 } }), &calls).unwrap_err();
+        assert!(err.to_string().contains("RPC batch rejected"));
+    }
+}
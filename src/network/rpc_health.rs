@@ -0,0 +1,100 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tracing::{info, warn};
+
+/// Consecutive transport failures (timeouts, connect errors, HTTP errors)
+/// before the RPC is reported degraded.
+const DEGRADED_AFTER_FAILURES: u32 = 5;
+
+#[derive(Default)]
+struct HealthState {
+    consecutive_failures: u32,
+    degraded: bool,
+}
+
+/// Health of the configured RPC across every call the daemon makes. A run of
+/// failures logs one `rpc_degraded` warning; callers stay quiet while it lasts.
+#[derive(Default)]
+pub struct RpcHealth {
+    state: Mutex<HealthState>,
+    /// Slowest call that succeeded since the last `take_slowest_ms`.
+    slowest_ms: Mutex<Option<u64>>,
+}
+
+impl RpcHealth {
+    /// Records a call the node answered after `latency`, including retries.
+    pub fn record_success(&self, latency: Duration) {
+        let latency_ms = latency.as_millis() as u64;
+        let mut slowest = self.slowest_ms.lock();
+        *slowest = Some(slowest.map_or(latency_ms, |ms| ms.max(latency_ms)));
+        drop(slowest);
+        let mut state = self.state.lock();
+        if state.degraded {
+            info!(
+                event = "rpc_recovered",
+                failed_calls = state.consecutive_failures,
+                "RPC recovered"
+            );
+        }
+        *state = HealthState::default();
+    }
+
+    pub fn record_failure(&self, method: &str, error: &anyhow::Error) {
+        let mut state = self.state.lock();
+        state.consecutive_failures += 1;
+        if !state.degraded && state.consecutive_failures >= DEGRADED_AFTER_FAILURES {
+            state.degraded = true;
+            warn!(
+                event = "rpc_degraded",
+                consecutive_failures = state.consecutive_failures,
+                method,
+                error = %error,
+                "RPC DEGRADED: balances, slots and sell confirmations may lag until it recovers"
+            );
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.state.lock().degraded
+    }
+
+    /// The slowest successful call since this was last taken, for the
+    /// `metrics` graphs; `None` if none succeeded.
+    pub fn take_slowest_ms(&self) -> Option<u64> {
+        self.slowest_ms.lock().take()
+    }
+}
+
+pub fn rpc_health() -> &'static RpcHealth {
+    static HEALTH: OnceLock<RpcHealth> = OnceLock::new();
+    HEALTH.get_or_init(RpcHealth::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degrades_after_a_run_of_failures_and_recovers() {
+        let health = RpcHealth::default();
+        let err = anyhow::anyhow!("timeout");
+        for _ in 0..DEGRADED_AFTER_FAILURES - 1 {
+            health.record_failure("getSlot", &err);
+        }
+        assert!(!health.is_degraded());
+        health.record_success(Duration::from_millis(40));
+        for _ in 0..DEGRADED_AFTER_FAILURES - 1 {
+            health.record_failure("getSlot", &err);
+        }
+        assert!(!health.is_degraded());
+        health.record_failure("getSlot", &err);
+        assert!(health.is_degraded());
+        health.record_success(Duration::from_millis(250));
+        health.record_success(Duration::from_millis(40));
+        assert!(!health.is_degraded());
+        assert_eq!(health.take_slowest_ms(), Some(250));
+        assert_eq!(health.take_slowest_ms(), None);
+    }
+}
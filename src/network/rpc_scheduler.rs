@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+/// How to prioritize an RPC call when the provider's rate limit is tight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpcPriority {
+    /// Calls on the exit path: always take the next free slot.
+    SellCritical,
+    /// Polling that can wait: backs off while a sell is running and only
+    /// uses slots nothing else has claimed.
+    Background,
+}
+
+struct SchedulerState {
+    next_free: Instant,
+    active_sells: usize,
+}
+
+/// Shares one requests/sec budget between the sell path and the balance
+/// pollers so polling cannot starve an exit.
+pub struct RpcScheduler {
+    spacing: Option<Duration>,
+    state: Mutex<SchedulerState>,
+}
+
+/// Held while a sell executes; background polling defers until it drops.
+pub struct SellWindow {
+    scheduler: Arc<RpcScheduler>,
+}
+
+impl RpcScheduler {
+    /// `requests_per_sec` of `None` (or non-positive) means unlimited.
+    pub fn new(requests_per_sec: Option<f64>) -> Self {
+        let spacing = requests_per_sec
+            .filter(|rps| rps.is_finite() && *rps > 0.0)
+            .map(|rps| Duration::from_secs_f64(1.0 / rps));
+        Self {
+            spacing,
+            state: Mutex::new(SchedulerState {
+                next_free: Instant::now(),
+                active_sells: 0,
+            }),
+        }
+    }
+
+    /// Waits until a request of `priority` may be sent.
+    pub async fn acquire(&self, priority: RpcPriority) {
+        let Some(spacing) = self.spacing else {
+            return;
+        };
+        loop {
+            let wait_until = {
+                let mut state = self.state.lock();
+                let now = Instant::now();
+                match priority {
+                    RpcPriority::SellCritical => {
+                        let slot = state.next_free.max(now);
+                        state.next_free = slot + spacing;
+                        if slot <= now {
+                            return;
+                        }
+                        tokio::time::sleep_until(slot)
+                    }
+                    RpcPriority::Background if state.next_free <= now => {
+                        state.next_free = now + spacing;
+                        return;
+                    }
+                    // Re-check once the slot frees up; a sell may claim it first.
+                    RpcPriority::Background => tokio::time::sleep_until(state.next_free),
+                }
+            };
+            wait_until.await;
+            if priority == RpcPriority::SellCritical {
+                return;
+            }
+        }
+    }
+
+    /// Marks a sell as running until the returned guard drops.
+    pub fn begin_sell(self: &Arc<Self>) -> SellWindow {
+        self.state.lock().active_sells += 1;
+        SellWindow {
+            scheduler: self.clone(),
+        }
+    }
+
+    /// Whether background work should skip this round.
+    pub fn sell_active(&self) -> bool {
+        self.state.lock().active_sells > 0
+    }
+}
+
+impl Drop for SellWindow {
+    fn drop(&mut self) {
+        let mut state = self.scheduler.state.lock();
+        state.active_sells = state.active_sells.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn sell_calls_jump_ahead_of_background_polling() {
+        let scheduler = Arc::new(RpcScheduler::new(Some(10.0)));
+        let started = Instant::now();
+        scheduler.acquire(RpcPriority::Background).await;
+
+        let background = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move {
+                scheduler.acquire(RpcPriority::Background).await;
+                started.elapsed()
+            })
+        };
+        tokio::task::yield_now().await;
+        scheduler.acquire(RpcPriority::SellCritical).await;
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+        assert_eq!(background.await.unwrap(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn sell_window_tracks_active_sells() {
+        let scheduler = Arc::new(RpcScheduler::new(None));
+        let first = scheduler.begin_sell();
+        let second = scheduler.begin_sell();
+        drop(first);
+        assert!(scheduler.sell_active());
+        drop(second);
+        assert!(!scheduler.sell_active());
+    }
+}
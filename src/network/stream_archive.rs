@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use lasersell_sdk::stream::proto::ServerMessage;
+use parking_lot::Mutex;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::util::logging::scrub_sensitive;
+
+const ZSTD_LEVEL: i32 = 3;
+
+static ARCHIVE: OnceLock<Mutex<Archive>> = OnceLock::new();
+
+struct Archive {
+    dir: PathBuf,
+    rotate_bytes: u64,
+    current: Option<ArchiveFile>,
+    /// Files opened by this process, so rotations within a second get
+    /// distinct names.
+    opened: u32,
+    failed: bool,
+}
+
+struct ArchiveFile {
+    path: PathBuf,
+    encoder: zstd::Encoder<'static, File>,
+    written: u64,
+}
+
+impl Archive {
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let full = self
+            .current
+            .as_ref()
+            .is_some_and(|file| file.written >= self.rotate_bytes);
+        if full {
+            self.close()?;
+        }
+        let file = match self.current.as_mut() {
+            Some(file) => file,
+            None => {
+                self.opened += 1;
+                self.current
+                    .insert(open_archive_file(&self.dir, self.opened)?)
+            }
+        };
+        writeln!(file.encoder, "{line}")
+            .with_context(|| format!("write stream archive {}", file.path.display()))?;
+        file.written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let Some(file) = self.current.take() else {
+            return Ok(());
+        };
+        file.encoder
+            .finish()
+            .and_then(|mut out| out.flush())
+            .with_context(|| format!("finish stream archive {}", file.path.display()))?;
+        Ok(())
+    }
+}
+
+fn open_archive_file(dir: &Path, seq: u32) -> Result<ArchiveFile> {
+    let stamp = time::OffsetDateTime::now_utc()
+        .format(time::macros::format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .context("format stream archive name")?;
+    let path = dir.join(format!("stream-{stamp}-{seq:04}.ndjson.zst"));
+    let file = OpenOptions::new()
+        .create_new(true)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("create stream archive {}", path.display()))?;
+    let encoder = zstd::Encoder::new(file, ZSTD_LEVEL)
+        .with_context(|| format!("start stream archive {}", path.display()))?;
+    info!(event = "stream_archive_opened", path = %path.display());
+    Ok(ArchiveFile {
+        path,
+        encoder,
+        written: 0,
+    })
+}
+
+/// Starts archiving every stream message into `dir`, a new file each
+/// `rotate_mb` uncompressed megabytes. Later calls are ignored.
+pub fn init(dir: &Path, rotate_mb: u64) -> Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("create stream archive dir {}", dir.display()))?;
+    let first = open_archive_file(dir, 1)?;
+    let _ = ARCHIVE.set(Mutex::new(Archive {
+        dir: dir.to_path_buf(),
+        rotate_bytes: rotate_mb.saturating_mul(1024 * 1024),
+        current: Some(first),
+        opened: 1,
+        failed: false,
+    }));
+    Ok(())
+}
+
+/// Appends `msg`, scrubbed, to the current archive. The first failure is
+/// logged and archiving stops; it never holds up the stream.
+pub(super) fn record(msg: &ServerMessage) {
+    let Some(archive) = ARCHIVE.get() else {
+        return;
+    };
+    let mut archive = archive.lock();
+    if archive.failed {
+        return;
+    }
+    let result = scrub_message(msg).and_then(|line| archive.write_line(&line));
+    if let Err(err) = result {
+        archive.failed = true;
+        archive.current = None;
+        warn!(event = "stream_archive_failed", error = %format!("{err:#}"));
+    }
+}
+
+/// Completes the current archive file so it decompresses cleanly. Called
+/// once on shutdown.
+pub fn finish() {
+    let Some(archive) = ARCHIVE.get() else {
+        return;
+    };
+    if let Err(err) = archive.lock().close() {
+        warn!(event = "stream_archive_failed", error = %format!("{err:#}"));
+    }
+}
+
+/// One JSON line for `msg` with signable tx payloads emptied and secrets
+/// redacted as in the logs. It still parses as a `ServerMessage`.
+fn scrub_message(msg: &ServerMessage) -> Result<String> {
+    let mut value = serde_json::to_value(msg).context("serialize stream message")?;
+    if let Some(tx) = value.get_mut("unsigned_tx_b64") {
+        *tx = Value::String(String::new());
+    }
+    Ok(scrub_sensitive(&value.to_string()))
+}
+
+#[derive(Debug, Default)]
+struct PositionSummary {
+    mint: String,
+    signals: Vec<String>,
+    closed: Option<String>,
+}
+
+/// `lasersell inspect-archive <file>`: message counts, and the positions
+/// and exit signals in a stream archive. An archive the daemon did not
+/// close is read up to where it was cut off.
+pub fn summarize(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("open archive {}", path.display()))?;
+    let decoder =
+        zstd::Decoder::new(file).with_context(|| format!("read archive {}", path.display()))?;
+    let mut by_type: BTreeMap<String, usize> = BTreeMap::new();
+    let mut reasons: BTreeMap<String, usize> = BTreeMap::new();
+    let mut positions: BTreeMap<u64, PositionSummary> = BTreeMap::new();
+    let mut messages = 0usize;
+    let mut unreadable = 0usize;
+    let mut truncated = false;
+    for line in BufReader::new(decoder).lines() {
+        let Ok(line) = line else {
+            truncated = true;
+            break;
+        };
+        let Ok(value) = serde_json::from_str::<Value>(&line) else {
+            unreadable += 1;
+            continue;
+        };
+        messages += 1;
+        let kind = value
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        *by_type.entry(kind.to_string()).or_default() += 1;
+        match serde_json::from_value::<ServerMessage>(value) {
+            Ok(ServerMessage::PositionOpened {
+                position_id, mint, ..
+            }) => {
+                positions.entry(position_id).or_default().mint = mint;
+            }
+            Ok(ServerMessage::ExitSignalWithTx {
+                position_id,
+                mint,
+                reason,
+                ..
+            }) => {
+                *reasons.entry(reason.clone()).or_default() += 1;
+                let position = positions.entry(position_id).or_default();
+                position.mint = mint;
+                position.signals.push(reason);
+            }
+            Ok(ServerMessage::PositionClosed {
+                position_id,
+                mint,
+                reason,
+                ..
+            }) => {
+                let position = positions.entry(position_id).or_default();
+                position.mint = mint;
+                position.closed = Some(reason);
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = format!("{}: {messages} messages", path.display());
+    if unreadable > 0 {
+        let _ = write!(out, ", {unreadable} unreadable lines");
+    }
+    if truncated {
+        out.push_str(" (cut off: the daemon did not close this archive)");
+    }
+    out.push('\n');
+    let counts = |map: &BTreeMap<String, usize>| {
+        map.iter()
+            .map(|(name, count)| format!("{name} {count}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let _ = writeln!(out, "by type: {}", counts(&by_type));
+    let closed = positions.values().filter(|p| p.closed.is_some()).count();
+    let _ = writeln!(out, "positions: {} seen, {closed} closed", positions.len());
+    let signals: usize = reasons.values().sum();
+    if signals > 0 {
+        let _ = writeln!(out, "exit signals: {signals} ({})", counts(&reasons));
+    } else {
+        out.push_str("exit signals: 0\n");
+    }
+    for (position_id, position) in &positions {
+        let signals = if position.signals.is_empty() {
+            "-".to_string()
+        } else {
+            position.signals.join(",")
+        };
+        let _ = writeln!(
+            out,
+            "  {position_id:>8}  {:<44}  signals {signals}  closed {}",
+            position.mint,
+            position.closed.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archives_rotate_and_summarize_after_scrubbing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut archive = Archive {
+            dir: dir.path().to_path_buf(),
+            rotate_bytes: 1,
+            current: None,
+            opened: 0,
+            failed: false,
+        };
+        let signal: ServerMessage = serde_json::from_value(serde_json::json!({
+            "type": "exit_signal_with_tx",
+            "session_id": 1,
+            "position_id": 7,
+            "wallet_pubkey": "wallet",
+            "mint": "Mint111",
+            "position_tokens": 10,
+            "profit_units": 5,
+            "reason": "target",
+            "triggered_at_ms": 1_000,
+            "unsigned_tx_b64": "AAAA"
+        }))
+        .unwrap();
+        let line = scrub_message(&signal).unwrap();
+        assert!(line.contains(r#""unsigned_tx_b64":"""#));
+        archive.write_line(&line).unwrap();
+        // Past rotate_bytes, so this starts a second file.
+        archive.write_line("not json").unwrap();
+        let second = archive.current.as_ref().unwrap().path.clone();
+        archive.close().unwrap();
+
+        let mut files: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1], second);
+        let first = &files[0];
+        let summary = summarize(first).unwrap();
+        assert!(summary.contains(": 1 messages\n"));
+        assert!(summary.contains("exit signals: 1 (target 1)\n"));
+        assert!(summary.contains("signals target  closed -"));
+        assert!(summarize(&second)
+            .unwrap()
+            .contains("0 messages, 1 unreadable lines"));
+    }
+}
@@ -1,17 +1,34 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result};
 use lasersell_sdk::exit_api::{ExitApiClient, ExitApiClientOptions};
 use lasersell_sdk::stream::client::{
-    StreamClient as SdkStreamClient, StreamConfigure, StreamSender,
+    StreamClient as SdkStreamClient, StreamConfigure, StreamConnectionStatus, StreamSender,
 };
 use lasersell_sdk::stream::proto::{
     MarketContextMsg, MirrorConfigMsg, ServerMessage, StrategyConfigMsg, WatchWalletEntryMsg,
 };
 use lasersell_sdk::stream::session::{StreamEvent as SdkStreamEvent, StreamSession};
+use parking_lot::RwLock;
 use secrecy::{ExposeSecret, SecretString};
 use solana_sdk::signature::Keypair;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
+use super::{stream_archive, stream_recorder};
+use super::stream_stall::{StallAction, StallWatchdog};
+use crate::config::EndpointProfile;
+use crate::util::clock::now_unix_ms;
+
+/// Wait between attempts to replace a stalled session.
+const STALL_RECONNECT_RETRY_SECS: u64 = 2;
+/// How long an exit signal taken from one connection makes the same
+/// position's signal from the other a duplicate.
+const EXIT_DEDUP_WINDOW: Duration = Duration::from_secs(60);
+
 #[derive(Clone)]
 pub struct StreamClient {
     sdk: SdkStreamClient,
@@ -24,19 +41,105 @@ pub struct StreamClient {
     tip_lamports: Option<u64>,
     watch_wallets: Vec<WatchWalletEntryMsg>,
     mirror_config: Option<MirrorConfigMsg>,
+    exit_api_url: Option<String>,
+    stall_timeout: Option<Duration>,
+    backup: Option<SdkStreamClient>,
 }
 
+/// Sends to the current stream session; the sender is swapped when a stalled
+/// session is replaced. With a backup connection, requests go to the
+/// backup while the primary is down and strategy changes go to both.
 #[derive(Clone, Debug)]
 pub struct StreamHandle {
-    sender: StreamSender,
+    sender: Arc<RwLock<StreamSender>>,
+    /// The backup session's sender, once it has connected.
+    backup: Option<Arc<RwLock<Option<StreamSender>>>>,
+    primary_connected: Arc<AtomicBool>,
+    /// Session strategy sent after connecting, so a replacement session
+    /// starts with it rather than the startup one.
+    strategy: Arc<RwLock<Option<StrategyConfigMsg>>>,
 }
 
 impl StreamHandle {
     pub fn request_exit_signal(&self, position_id: u64, slippage_bps: Option<u16>) -> Result<()> {
-        self.sender
+        self.active()
             .request_exit_signal(position_id, slippage_bps)
             .map_err(|err| anyhow::anyhow!("send request_exit_signal: {err}"))
     }
+
+    pub fn update_position_strategy(
+        &self,
+        position_id: u64,
+        strategy: StrategyConfigMsg,
+    ) -> Result<()> {
+        if let Some(backup) = self.backup_sender() {
+            let _ = backup.update_position_strategy(position_id, strategy.clone());
+        }
+        self.sender
+            .read()
+            .update_position_strategy(position_id, strategy)
+            .map_err(|err| anyhow::anyhow!("send update_position_strategy: {err}"))
+    }
+
+    /// Replaces the session-level strategy. Positions with their own
+    /// strategy keep it.
+    pub fn update_strategy(&self, strategy: StrategyConfigMsg) -> Result<()> {
+        *self.strategy.write() = Some(strategy.clone());
+        self.send_strategy(strategy)
+    }
+
+    /// Sends the strategy from `update_strategy` again; the SDK configures a
+    /// reconnected socket with the one it was opened with.
+    pub fn reapply_strategy(&self) -> Result<()> {
+        match self.strategy.read().clone() {
+            Some(strategy) => self.send_strategy(strategy),
+            None => Ok(()),
+        }
+    }
+
+    /// Asks the server for its time; the answer arrives as `ServerTime`.
+    pub fn ping(&self, client_time_ms: u64) -> Result<()> {
+        self.active()
+            .ping(client_time_ms)
+            .map_err(|err| anyhow::anyhow!("send ping: {err}"))
+    }
+
+    fn send_strategy(&self, strategy: StrategyConfigMsg) -> Result<()> {
+        if let Some(backup) = self.backup_sender() {
+            let _ = backup.update_strategy(strategy.clone());
+        }
+        self.sender
+            .read()
+            .update_strategy(strategy)
+            .map_err(|err| anyhow::anyhow!("send update_strategy: {err}"))
+    }
+
+    /// The primary session, or the backup while the primary is down.
+    fn active(&self) -> StreamSender {
+        if !self.primary_connected.load(Ordering::Relaxed) {
+            if let Some(backup) = self.backup_sender() {
+                return backup;
+            }
+        }
+        self.sender.read().clone()
+    }
+
+    fn backup_sender(&self) -> Option<StreamSender> {
+        self.backup.as_ref()?.read().clone()
+    }
+
+    fn set_sender(&self, conn: Conn, sender: StreamSender) {
+        match (conn, self.backup.as_ref()) {
+            (Conn::Backup, Some(backup)) => *backup.write() = Some(sender),
+            _ => *self.sender.write() = sender,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Conn {
+    Primary,
+    Backup,
 }
 
 #[derive(Debug, Clone)]
@@ -85,9 +188,25 @@ pub enum StreamEvent {
         profit_units: i64,
         proceeds_units: u64,
     },
+    /// A swap on the pool of an open position.
+    TradeTick {
+        mint: String,
+        price_quote: u64,
+        time_ms: u64,
+    },
+    /// Whether the pool of an open position is gaining or losing liquidity.
+    LiquidityTrend {
+        mint: String,
+        trend: String,
+    },
+    /// Server clock from a pong.
+    ServerTime {
+        server_time_ms: u64,
+    },
 }
 
 impl StreamClient {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         api_key: SecretString,
         local: bool,
@@ -110,7 +229,39 @@ impl StreamClient {
             tip_lamports,
             watch_wallets,
             mirror_config,
+            exit_api_url: None,
+            stall_timeout: None,
+            backup: None,
+        }
+    }
+
+    /// Holds a second session on `backup_url` for redundancy; see
+    /// `stream.backup_url`.
+    pub fn with_backup(mut self, backup_url: Option<&str>) -> Self {
+        self.backup = backup_url.map(|url| {
+            SdkStreamClient::new(self.api_key.clone())
+                .with_local_mode(self.local)
+                .with_endpoint(url.trim())
+        });
+        self
+    }
+
+    /// Replaces the session when nothing arrives for `stall_timeout_sec`
+    /// while the SDK still reports it connected. 0 disables.
+    pub fn with_stall_timeout(mut self, stall_timeout_sec: u64) -> Self {
+        self.stall_timeout =
+            (stall_timeout_sec > 0).then(|| Duration::from_secs(stall_timeout_sec));
+        self
+    }
+
+    /// Points both the stream and wallet registration at a configured
+    /// endpoint profile instead of the built-in endpoints.
+    pub fn with_profile(mut self, profile: Option<&EndpointProfile>) -> Self {
+        if let Some(profile) = profile {
+            self.sdk = self.sdk.with_endpoint(profile.stream_url.trim());
+            self.exit_api_url = Some(profile.exit_api_url.trim().to_string());
         }
+        self
     }
 
     pub async fn connect(
@@ -127,6 +278,10 @@ impl StreamClient {
             )
             .context("build LaserSell API client for wallet registration")?
             .with_local_mode(self.local);
+            let client = match self.exit_api_url.as_deref() {
+                Some(url) => client.with_base_url(url),
+                None => client,
+            };
             client
                 .register_wallet(&proof, None)
                 .await
@@ -141,36 +296,354 @@ impl StreamClient {
         configure.tip_lamports = self.tip_lamports;
         configure.watch_wallets = self.watch_wallets.clone();
         configure.mirror_config = self.mirror_config.clone();
-        let mut session = StreamSession::connect(&self.sdk, configure)
-            .await
-            .context("connect to stream server")?;
-        info!(event = "stream_client_authed");
-
-        // Enable priority lanes so exit signals are never delayed by PnL updates.
-        session.enable_lanes(64);
-
-        let sender = session.sender();
-        let stream_handle = StreamHandle { sender };
+        let session = open_session(&self.sdk, configure.clone()).await?;
+        let stream_handle = StreamHandle {
+            sender: Arc::new(RwLock::new(session.session.sender())),
+            backup: self.backup.as_ref().map(|_| Arc::new(RwLock::new(None))),
+            primary_connected: Arc::new(AtomicBool::new(true)),
+            strategy: Arc::new(RwLock::new(None)),
+        };
 
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         let _ = event_tx.send(StreamEvent::ConnectionStatus { connected: true });
+        let stall_timeout = self.stall_timeout;
+        let Some(backup_sdk) = self.backup.clone() else {
+            tokio::spawn(supervise(
+                Conn::Primary,
+                self.sdk.clone(),
+                configure,
+                Some(session),
+                stream_handle.clone(),
+                event_tx,
+                stall_timeout,
+            ));
+            return Ok((stream_handle, event_rx));
+        };
+
+        let (primary_tx, primary_rx) = mpsc::unbounded_channel();
+        let (backup_tx, backup_rx) = mpsc::unbounded_channel();
+        tokio::spawn(supervise(
+            Conn::Primary,
+            self.sdk.clone(),
+            configure.clone(),
+            Some(session),
+            stream_handle.clone(),
+            primary_tx,
+            stall_timeout,
+        ));
+        tokio::spawn(supervise(
+            Conn::Backup,
+            backup_sdk,
+            configure,
+            None,
+            stream_handle.clone(),
+            backup_tx,
+            stall_timeout,
+        ));
+        tokio::spawn(merge_connections(
+            primary_rx,
+            backup_rx,
+            event_tx,
+            stream_handle.clone(),
+        ));
+        Ok((stream_handle, event_rx))
+    }
+}
+
+/// Runs one connection's sessions, replacing a stalled one. A backup
+/// starts without a session and keeps trying until it has one.
+async fn supervise(
+    conn: Conn,
+    sdk: SdkStreamClient,
+    mut configure: StreamConfigure,
+    mut session: Option<OpenSession>,
+    handle: StreamHandle,
+    event_tx: mpsc::UnboundedSender<StreamEvent>,
+    stall_timeout: Option<Duration>,
+) {
+    let backup = conn == Conn::Backup;
+    let mut stalled = false;
+    loop {
+        let open = match session.take() {
+            Some(open) => open,
+            None => {
+                if let Some(strategy) = handle.strategy.read().clone() {
+                    configure.strategy = strategy;
+                }
+                let open = loop {
+                    match open_session(&sdk, configure.clone()).await {
+                        Ok(session) => break session,
+                        Err(err) => {
+                            let error = format!("{err:#}");
+                            if stalled {
+                                warn!(
+                                    event = "stream_stall_reconnect_failed",
+                                    backup,
+                                    error = %error
+                                );
+                            } else {
+                                warn!(event = "stream_backup_connect_failed", error = %error);
+                            }
+                            tokio::time::sleep(Duration::from_secs(STALL_RECONNECT_RETRY_SECS))
+                                .await;
+                        }
+                    }
+                };
+                handle.set_sender(conn, open.session.sender());
+                if stalled {
+                    info!(event = "stream_stall_reconnected", backup);
+                } else {
+                    info!(event = "stream_backup_connected");
+                }
+                if event_tx
+                    .send(StreamEvent::ConnectionStatus { connected: true })
+                    .is_err()
+                {
+                    return;
+                }
+                open
+            }
+        };
+        // Only the primary is recorded, so a replay does not see each
+        // message twice.
+        match run_session(open, &event_tx, stall_timeout, !backup).await {
+            SessionEnd::Closed => return,
+            SessionEnd::Stalled => stalled = true,
+        }
+    }
+}
+
+/// Feeds the engine from both connections as if they were one.
+async fn merge_connections(
+    mut primary_rx: mpsc::UnboundedReceiver<StreamEvent>,
+    mut backup_rx: mpsc::UnboundedReceiver<StreamEvent>,
+    event_tx: mpsc::UnboundedSender<StreamEvent>,
+    handle: StreamHandle,
+) {
+    let mut redundancy = Redundancy::default();
+    let (mut primary_open, mut backup_open) = (true, true);
+    while primary_open || backup_open {
+        let (conn, evt) = tokio::select! {
+            evt = primary_rx.recv(), if primary_open => match evt {
+                Some(evt) => (Conn::Primary, evt),
+                None => {
+                    primary_open = false;
+                    continue;
+                }
+            },
+            evt = backup_rx.recv(), if backup_open => match evt {
+                Some(evt) => (Conn::Backup, evt),
+                None => {
+                    backup_open = false;
+                    continue;
+                }
+            },
+        };
+        let routed = redundancy.route(conn, evt, Instant::now());
+        handle
+            .primary_connected
+            .store(redundancy.primary_connected(), Ordering::Relaxed);
+        match routed {
+            Routed::Forward(evt) => {
+                if event_tx.send(*evt).is_err() {
+                    return;
+                }
+            }
+            Routed::Rejoined => {
+                if let Err(err) = handle.reapply_strategy() {
+                    debug!(event = "stream_strategy_reapply_failed", error = %err);
+                }
+            }
+            Routed::Drop => {}
+        }
+    }
+}
+
+enum Routed {
+    Forward(Box<StreamEvent>),
+    /// A connection came up while the other carried the stream, so the
+    /// engine sees no reconnect; the strategy is reapplied here instead.
+    Rejoined,
+    Drop,
+}
+
+/// Connection priority and exit signal de-duplication for a primary and a
+/// backup stream.
+#[derive(Debug)]
+struct Redundancy {
+    connected: [bool; 2],
+    /// Exit signals taken recently, keyed like `CompletedSells` by position,
+    /// reason and position size so each take-profit level is its own
+    /// signal: the connection it came from and when.
+    exits: HashMap<(u64, String, u64), (Conn, Instant)>,
+}
+
+impl Default for Redundancy {
+    fn default() -> Self {
+        Self {
+            // The primary's first connect has already been reported.
+            connected: [true, false],
+            exits: HashMap::new(),
+        }
+    }
+}
+
+impl Redundancy {
+    fn primary_connected(&self) -> bool {
+        self.connected[0]
+    }
+
+    fn route(&mut self, conn: Conn, evt: StreamEvent, now: Instant) -> Routed {
+        match evt {
+            StreamEvent::ConnectionStatus { connected } => {
+                let was_up = self.connected.contains(&true);
+                self.connected[conn as usize] = connected;
+                let up = self.connected.contains(&true);
+                if up != was_up {
+                    Routed::Forward(Box::new(StreamEvent::ConnectionStatus { connected: up }))
+                } else if connected {
+                    Routed::Rejoined
+                } else {
+                    Routed::Drop
+                }
+            }
+            StreamEvent::ExitSignalWithTx {
+                position_id,
+                ref reason,
+                position_tokens,
+                ..
+            } => {
+                self.exits
+                    .retain(|_, (_, at)| now.duration_since(*at) < EXIT_DEDUP_WINDOW);
+                let key = (position_id, reason.clone(), position_tokens);
+                match self.exits.get(&key) {
+                    Some((taken_from, _)) if *taken_from != conn => {
+                        debug!(
+                            event = "stream_exit_signal_duplicate",
+                            position_id,
+                            reason = %key.1
+                        );
+                        Routed::Drop
+                    }
+                    _ => {
+                        self.exits.insert(key, (conn, now));
+                        Routed::Forward(Box::new(evt))
+                    }
+                }
+            }
+            evt if conn == Conn::Primary || !self.primary_connected() => {
+                Routed::Forward(Box::new(evt))
+            }
+            _ => Routed::Drop,
+        }
+    }
+}
+
+struct OpenSession {
+    session: StreamSession,
+    status_rx: Option<mpsc::UnboundedReceiver<StreamConnectionStatus>>,
+}
 
-        tokio::spawn(async move {
-            loop {
-                let Some(evt) = session.recv().await else {
+enum SessionEnd {
+    /// The session or the engine went away.
+    Closed,
+    /// The session went silent and was closed so it can be replaced.
+    Stalled,
+}
+
+async fn open_session(sdk: &SdkStreamClient, configure: StreamConfigure) -> Result<OpenSession> {
+    let mut session = StreamSession::connect(sdk, configure)
+        .await
+        .context("connect to stream server")?;
+    info!(event = "stream_client_authed");
+
+    // The SDK reconnects on its own; its status channel is the only way to
+    // see an outage, and lanes mode drops it, so take it first.
+    let status_rx = session.take_status_channel();
+    // Enable priority lanes so exit signals are never delayed by PnL updates.
+    session.enable_lanes(64);
+    Ok(OpenSession { session, status_rx })
+}
+
+/// Forwards one session's events and connection status until it ends or
+/// stalls.
+async fn run_session(
+    open: OpenSession,
+    event_tx: &mpsc::UnboundedSender<StreamEvent>,
+    stall_timeout: Option<Duration>,
+    record: bool,
+) -> SessionEnd {
+    let OpenSession {
+        mut session,
+        mut status_rx,
+    } = open;
+    let mut watchdog =
+        stall_timeout.map(|timeout| StallWatchdog::new(timeout, tokio::time::Instant::now()));
+    let mut stall_check = tokio::time::interval(Duration::from_secs(1));
+    stall_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            evt = session.recv() => {
+                let Some(evt) = evt else {
                     warn!(event = "stream_session_ended");
                     let _ = event_tx.send(StreamEvent::ConnectionStatus { connected: false });
-                    break;
+                    return SessionEnd::Closed;
                 };
+                if let Some(watchdog) = watchdog.as_mut() {
+                    watchdog.on_event(tokio::time::Instant::now());
+                }
+                if record {
+                    stream_recorder::record(sdk_event_message(&evt));
+                    stream_archive::record(sdk_event_message(&evt));
+                }
                 if let Some(mapped) = map_session_event(evt) {
                     if event_tx.send(mapped).is_err() {
-                        break;
+                        return SessionEnd::Closed;
                     }
                 }
             }
-        });
-
-        Ok((stream_handle, event_rx))
+            status = async {
+                match status_rx.as_mut() {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                let Some(status) = status else {
+                    status_rx = None;
+                    continue;
+                };
+                let connected = status == StreamConnectionStatus::Connected;
+                if let Some(watchdog) = watchdog.as_mut() {
+                    watchdog.on_status(connected, tokio::time::Instant::now());
+                }
+                if event_tx.send(StreamEvent::ConnectionStatus { connected }).is_err() {
+                    return SessionEnd::Closed;
+                }
+            }
+            _ = stall_check.tick(), if watchdog.is_some() => {
+                let Some(watchdog) = watchdog.as_mut() else {
+                    continue;
+                };
+                match watchdog.check(tokio::time::Instant::now()) {
+                    StallAction::None => {}
+                    StallAction::Ping => {
+                        if let Err(err) = session.sender().ping(now_unix_ms()) {
+                            debug!(event = "stream_stall_ping_failed", error = %err);
+                        }
+                    }
+                    StallAction::Stalled { idle } => {
+                        warn!(
+                            event = "stream_stalled",
+                            idle_sec = idle.as_secs(),
+                            "STREAM STALLED: connected but silent; forcing a reconnect"
+                        );
+                        let _ = event_tx.send(StreamEvent::ConnectionStatus { connected: false });
+                        session.close();
+                        return SessionEnd::Stalled;
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -189,6 +662,21 @@ fn sdk_event_label(evt: &SdkStreamEvent) -> &'static str {
     }
 }
 
+fn sdk_event_message(evt: &SdkStreamEvent) -> &ServerMessage {
+    match evt {
+        SdkStreamEvent::Message(message)
+        | SdkStreamEvent::PositionOpened { message, .. }
+        | SdkStreamEvent::PositionClosed { message, .. }
+        | SdkStreamEvent::ExitSignalWithTx { message, .. }
+        | SdkStreamEvent::PnlUpdate { message, .. }
+        | SdkStreamEvent::LiquiditySnapshot { message, .. }
+        | SdkStreamEvent::TradeTick { message, .. }
+        | SdkStreamEvent::MirrorBuySignal { message }
+        | SdkStreamEvent::MirrorBuyFailed { message }
+        | SdkStreamEvent::MirrorWalletAutoDisabled { message } => message,
+    }
+}
+
 fn map_session_event(evt: SdkStreamEvent) -> Option<StreamEvent> {
     info!(event = "stream_session_event_received", variant = sdk_event_label(&evt));
     match evt {
@@ -234,20 +722,46 @@ fn map_session_event(evt: SdkStreamEvent) -> Option<StreamEvent> {
             }
         }
         SdkStreamEvent::LiquiditySnapshot { handle, message } => {
-            if let (Some(h), ServerMessage::LiquiditySnapshot { liquidity_trend, bands, .. }) =
-                (&handle, &message)
-            {
-                info!(
-                    event = "liquidity_snapshot",
-                    position_id = h.position_id,
-                    mint = %h.mint,
-                    trend = %liquidity_trend,
-                    bands = bands.len(),
-                );
-            }
-            None
+            let (
+                Some(h),
+                ServerMessage::LiquiditySnapshot {
+                    liquidity_trend,
+                    bands,
+                    watched,
+                    ..
+                },
+            ) = (handle, message)
+            else {
+                return None;
+            };
+            info!(
+                event = "liquidity_snapshot",
+                position_id = h.position_id,
+                mint = %h.mint,
+                trend = %liquidity_trend,
+                bands = bands.len(),
+            );
+            (!watched).then_some(StreamEvent::LiquidityTrend {
+                mint: h.mint,
+                trend: liquidity_trend,
+            })
         }
-        SdkStreamEvent::TradeTick { .. } => None,
+        SdkStreamEvent::TradeTick { handle, message } => match (handle, message) {
+            (
+                Some(h),
+                ServerMessage::TradeTick {
+                    price_quote,
+                    time_ms,
+                    watched: false,
+                    ..
+                },
+            ) => Some(StreamEvent::TradeTick {
+                mint: h.mint,
+                price_quote,
+                time_ms,
+            }),
+            _ => None,
+        },
         SdkStreamEvent::MirrorBuySignal { message } => map_server_event(message),
         SdkStreamEvent::MirrorBuyFailed { message } => map_server_event(message),
         SdkStreamEvent::MirrorWalletAutoDisabled { message } => map_server_event(message),
@@ -328,7 +842,8 @@ fn map_server_event(msg: ServerMessage) -> Option<StreamEvent> {
             market_context,
             unsigned_tx_b64,
         }),
-        ServerMessage::HelloOk { .. } | ServerMessage::Pong { .. } => None,
+        ServerMessage::Pong { server_time_ms } => Some(StreamEvent::ServerTime { server_time_ms }),
+        ServerMessage::HelloOk { .. } => None,
         ServerMessage::PnlUpdate { .. } => None,
         ServerMessage::LiquiditySnapshot { position_id, liquidity_trend, bands, .. } => {
             info!(
@@ -374,3 +889,101 @@ fn map_server_event(msg: ServerMessage) -> Option<StreamEvent> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit_signal(position_id: u64) -> StreamEvent {
+        ladder_signal(position_id, "target_profit", 1)
+    }
+
+    fn ladder_signal(position_id: u64, reason: &str, position_tokens: u64) -> StreamEvent {
+        StreamEvent::ExitSignalWithTx {
+            position_id,
+            mint: "mint".to_string(),
+            token_program: None,
+            token_account: None,
+            position_tokens,
+            profit_units: 0,
+            reason: reason.to_string(),
+            triggered_at_ms: 0,
+            market_context: None,
+            unsigned_tx_b64: String::new(),
+        }
+    }
+
+    fn forwarded(routed: Routed) -> Option<StreamEvent> {
+        match routed {
+            Routed::Forward(evt) => Some(*evt),
+            Routed::Rejoined | Routed::Drop => None,
+        }
+    }
+
+    #[test]
+    fn exits_are_deduplicated_across_connections_only() {
+        let mut redundancy = Redundancy::default();
+        let start = Instant::now();
+        let route = |redundancy: &mut Redundancy, conn, evt, secs| {
+            forwarded(redundancy.route(conn, evt, start + Duration::from_secs(secs)))
+        };
+
+        assert!(route(&mut redundancy, Conn::Backup, exit_signal(7), 0).is_some());
+        assert!(route(&mut redundancy, Conn::Primary, exit_signal(7), 1).is_none());
+        // A second signal on the same connection is a new one, e.g. after a
+        // `request_exit_signal`.
+        assert!(route(&mut redundancy, Conn::Backup, exit_signal(7), 2).is_some());
+        assert!(route(&mut redundancy, Conn::Primary, exit_signal(8), 3).is_some());
+        assert!(route(&mut redundancy, Conn::Primary, exit_signal(7), 70).is_some());
+    }
+
+    #[test]
+    fn backup_exits_after_the_first_ladder_level_are_kept() {
+        let mut redundancy = Redundancy::default();
+        let now = Instant::now();
+        let mut route = |conn, evt| forwarded(redundancy.route(conn, evt, now)).is_some();
+
+        assert!(route(Conn::Primary, ladder_signal(7, "take_profit", 1_000)));
+        assert!(!route(Conn::Backup, ladder_signal(7, "take_profit", 1_000)));
+        // The primary drops after the first level; the backup's next level
+        // and stop loss for the same position still go through.
+        assert!(route(Conn::Backup, ladder_signal(7, "take_profit", 600)));
+        assert!(route(Conn::Backup, ladder_signal(7, "stop_loss", 300)));
+        assert!(!route(Conn::Primary, ladder_signal(7, "stop_loss", 300)));
+    }
+
+    #[test]
+    fn backup_carries_the_stream_only_while_the_primary_is_down() {
+        let mut redundancy = Redundancy::default();
+        let now = Instant::now();
+        let pnl = || StreamEvent::PnlUpdate {
+            mint: "mint".to_string(),
+            profit_units: 1,
+            proceeds_units: 2,
+        };
+        let status = |connected| StreamEvent::ConnectionStatus { connected };
+
+        assert!(matches!(
+            redundancy.route(Conn::Backup, status(true), now),
+            Routed::Rejoined
+        ));
+        assert!(forwarded(redundancy.route(Conn::Backup, pnl(), now)).is_none());
+        assert!(forwarded(redundancy.route(Conn::Primary, pnl(), now)).is_some());
+
+        // The primary dropping is no outage while the backup is up.
+        assert!(matches!(
+            redundancy.route(Conn::Primary, status(false), now),
+            Routed::Drop
+        ));
+        assert!(!redundancy.primary_connected());
+        assert!(forwarded(redundancy.route(Conn::Backup, pnl(), now)).is_some());
+        assert!(matches!(
+            forwarded(redundancy.route(Conn::Backup, status(false), now)),
+            Some(StreamEvent::ConnectionStatus { connected: false })
+        ));
+        assert!(matches!(
+            forwarded(redundancy.route(Conn::Primary, status(true), now)),
+            Some(StreamEvent::ConnectionStatus { connected: true })
+        ));
+    }
+}
@@ -0,0 +1,43 @@
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use lasersell_sdk::stream::proto::ServerMessage;
+use parking_lot::Mutex;
+use tracing::{info, warn};
+
+static RECORDER: OnceLock<Mutex<LineWriter<File>>> = OnceLock::new();
+static WRITE_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Starts appending every stream message to `path`, one JSON object per
+/// line, for `lasersell replay`. Later calls are ignored.
+pub fn init(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open stream recording {}", path.display()))?;
+    if RECORDER.set(Mutex::new(LineWriter::new(file))).is_ok() {
+        info!(event = "stream_recording", path = %path.display());
+    }
+    Ok(())
+}
+
+/// Appends `msg` to the recording, if one was started. The first failed
+/// write is logged; recording never holds up the stream.
+pub(super) fn record(msg: &ServerMessage) {
+    let Some(out) = RECORDER.get() else {
+        return;
+    };
+    let result = serde_json::to_string(msg)
+        .map_err(std::io::Error::from)
+        .and_then(|line| writeln!(out.lock(), "{line}"));
+    if let Err(err) = result {
+        if !WRITE_FAILED.swap(true, Ordering::Relaxed) {
+            warn!(event = "stream_recording_failed", error = %err);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StallAction {
+    None,
+    /// Quiet for half the timeout: ask for a pong so a healthy but idle
+    /// stream proves itself.
+    Ping,
+    /// Quiet for the whole timeout while reported connected.
+    Stalled {
+        idle: Duration,
+    },
+}
+
+/// Notices a stream that says it is connected but has stopped delivering.
+/// The SDK only reconnects when the socket errors, so a half-open
+/// connection would otherwise sit silent forever.
+pub struct StallWatchdog {
+    timeout: Duration,
+    last_event: Instant,
+    connected: bool,
+    pinged: bool,
+}
+
+impl StallWatchdog {
+    pub fn new(timeout: Duration, now: Instant) -> Self {
+        Self {
+            timeout,
+            last_event: now,
+            connected: true,
+            pinged: false,
+        }
+    }
+
+    pub fn on_event(&mut self, now: Instant) {
+        self.last_event = now;
+        self.pinged = false;
+    }
+
+    /// While the SDK is reconnecting silence is expected; the clock restarts
+    /// when it is back.
+    pub fn on_status(&mut self, connected: bool, now: Instant) {
+        self.connected = connected;
+        self.on_event(now);
+    }
+
+    pub fn check(&mut self, now: Instant) -> StallAction {
+        if !self.connected {
+            return StallAction::None;
+        }
+        let idle = now.saturating_duration_since(self.last_event);
+        if idle >= self.timeout {
+            StallAction::Stalled { idle }
+        } else if idle >= self.timeout / 2 && !self.pinged {
+            self.pinged = true;
+            StallAction::Ping
+        } else {
+            StallAction::None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pings_at_half_timeout_then_reports_stall() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut watchdog = StallWatchdog::new(Duration::from_secs(30), start);
+        assert_eq!(watchdog.check(at(14)), StallAction::None);
+        assert_eq!(watchdog.check(at(15)), StallAction::Ping);
+        assert_eq!(watchdog.check(at(20)), StallAction::None);
+
+        // The pong counts as traffic.
+        watchdog.on_event(at(21));
+        assert_eq!(watchdog.check(at(35)), StallAction::None);
+
+        watchdog.on_status(false, at(41));
+        assert_eq!(watchdog.check(at(200)), StallAction::None);
+        watchdog.on_status(true, at(200));
+        assert_eq!(watchdog.check(at(215)), StallAction::Ping);
+        assert_eq!(
+            watchdog.check(at(231)),
+            StallAction::Stalled {
+                idle: Duration::from_secs(31)
+            }
+        );
+    }
+}
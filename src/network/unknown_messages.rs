@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Logged by lasersell-sdk for every stream message it cannot parse into a
+/// `ServerMessage`, so newer server messages never reach `map_server_event`.
+const PARSE_ERROR_EVENT: &str = "stream_msg_parse_error";
+const SUMMARY_EVENT: &str = "stream_unknown_messages";
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Counts stream messages this client does not understand, by their `type`.
+/// The SDK's warning is kept for the first message of each type; later ones
+/// are only counted and summarized once a minute with an update hint.
+#[derive(Default)]
+pub struct UnknownMessages {
+    pending: Mutex<BTreeMap<String, u64>>,
+    seen: Mutex<HashSet<String>>,
+    total: AtomicU64,
+}
+
+impl UnknownMessages {
+    /// Counts one message; whether it is the first of its type.
+    fn record(&self, kind: &str) -> bool {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        *self.pending.lock().entry(kind.to_string()).or_default() += 1;
+        self.seen.lock().insert(kind.to_string())
+    }
+
+    /// Takes the counts since the last summary, by type.
+    fn drain(&self) -> BTreeMap<String, u64> {
+        std::mem::take(&mut *self.pending.lock())
+    }
+
+    /// Unrecognized messages since start.
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+}
+
+pub fn shared() -> &'static Arc<UnknownMessages> {
+    static UNKNOWN: OnceLock<Arc<UnknownMessages>> = OnceLock::new();
+    UNKNOWN.get_or_init(Arc::default)
+}
+
+/// The `type` tag of an unparseable message, or `<none>`.
+fn message_kind(raw: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(raw)
+        .ok()
+        .and_then(|value| value.get("type")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "<none>".to_string())
+}
+
+/// Registry layer that counts the SDK's parse errors and drops all but the
+/// first warning of each message type.
+pub struct UnknownMessagesLayer(Arc<UnknownMessages>);
+
+pub fn layer() -> UnknownMessagesLayer {
+    UnknownMessagesLayer(shared().clone())
+}
+
+impl<S: Subscriber> Layer<S> for UnknownMessagesLayer {
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        let mut visitor = ParseErrorVisitor::default();
+        event.record(&mut visitor);
+        if visitor.event.as_deref() != Some(PARSE_ERROR_EVENT) {
+            return true;
+        }
+        self.0.record(&message_kind(&visitor.raw_message))
+    }
+}
+
+#[derive(Default)]
+struct ParseErrorVisitor {
+    event: Option<String>,
+    raw_message: String,
+}
+
+impl Visit for ParseErrorVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "event" => self.event = Some(value.to_string()),
+            "raw_message" => self.raw_message = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "raw_message" {
+            let _ = write!(self.raw_message, "{value:?}");
+        }
+    }
+}
+
+/// Logs how many unrecognized messages arrived, once a minute while any do.
+pub fn spawn_summaries() {
+    let unknown = shared().clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SUMMARY_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let counts = unknown.drain();
+            if counts.is_empty() {
+                continue;
+            }
+            let count: u64 = counts.values().sum();
+            let kinds = counts
+                .iter()
+                .map(|(kind, n)| format!("{kind} x{n}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            tracing::warn!(
+                event = SUMMARY_EVENT,
+                count,
+                kinds = %kinds,
+                "server sent {count} unrecognized stream messages in the last 60s; this client \
+                 may be out of date, run `{}`",
+                crate::util::update_check::update_command()
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_first_message_of_a_type_is_logged() {
+        let unknown = UnknownMessages::default();
+        let kind = message_kind(r#"{"type":"unknown_test_kind","x":1}"#);
+        assert_eq!(kind, "unknown_test_kind");
+        assert_eq!(message_kind("not json"), "<none>");
+
+        assert!(unknown.record(&kind));
+        assert!(!unknown.record(&kind));
+        assert!(unknown.record("<none>"));
+        let drained = unknown.drain();
+        assert_eq!(drained.get("unknown_test_kind"), Some(&2));
+        assert_eq!(unknown.total(), 3);
+
+        // Already reported types stay quiet after the summary.
+        assert!(!unknown.record(&kind));
+        assert_eq!(unknown.drain().get("unknown_test_kind"), Some(&1));
+        assert!(unknown.drain().is_empty());
+    }
+}
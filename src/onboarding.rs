@@ -95,6 +95,7 @@ struct WalletSelection {
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 enum WalletPlan {
     ReuseExistingKeystore { path: PathBuf },
     NewWallet { selection: WalletSelection },
@@ -131,6 +132,18 @@ struct ConfigInputs {
     sell_on_graduation: bool,
 }
 
+/// A part of onboarding that `lasersell setup <section>` re-runs on its own,
+/// leaving the rest of the config file as it is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SetupSection {
+    /// RPC URL and LaserSell API key.
+    Credentials,
+    /// Exit strategy and slippage.
+    Strategy,
+    /// Create or import a wallet into a new keystore.
+    Wallet,
+}
+
 pub fn run_onboarding(config_path: &Path) -> Result<(Config, Keypair)> {
     match run_onboarding_inner(config_path) {
         Ok(result) => Ok(result),
@@ -334,7 +347,7 @@ fn run_onboarding_inner(config_path: &Path) -> Result<(Config, Keypair)> {
             .take()
             .ok_or_else(|| anyhow!("missing keystore passphrase"))?;
         let passphrase_secret = SecretString::new(passphrase.to_string());
-        wallet::write_keystore(&keystore_path, &keypair, &passphrase_secret)?;
+        wallet::write_keystore(&keystore_path, &keypair, &passphrase_secret, &config.wallet.kdf)?;
         config.write_to_path(&config_path)?;
         drop(passphrase_secret);
         passphrase.zeroize();
@@ -357,6 +370,124 @@ fn run_onboarding_inner(config_path: &Path) -> Result<(Config, Keypair)> {
     Ok((config, keypair))
 }
 
+pub fn run_setup_section(config_path: &Path, section: SetupSection) -> Result<()> {
+    match run_setup_section_inner(config_path, section) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            let _ = cliclack::outro_cancel("Setup cancelled.");
+            Err(err)
+        }
+    }
+}
+
+fn run_setup_section_inner(config_path: &Path, section: SetupSection) -> Result<()> {
+    if !config_path.is_file() {
+        return Err(anyhow!(
+            "config file {} not found; run `lasersell setup` for the full wizard first",
+            config_path.display()
+        ));
+    }
+    cliclack::clear_screen()?;
+    cliclack::intro("LaserSell Setup")?;
+
+    match section {
+        SetupSection::Credentials => {
+            cliclack::log::step("Credentials & Network")?;
+            let rpc_url = prompt_rpc_url()?;
+            let api_key = prompt_api_key()?;
+            Config::edit_file(config_path, |cfg| {
+                cfg.account.rpc_url = SecretString::new(rpc_url);
+                cfg.account.api_key = SecretString::new(api_key);
+            })?;
+        }
+        SetupSection::Strategy => {
+            cliclack::log::step("Strategy")?;
+            let inputs = prompt_strategy_inputs()?;
+            Config::edit_file(config_path, |cfg| apply_strategy_inputs(cfg, &inputs))?;
+        }
+        SetupSection::Wallet => {
+            cliclack::log::step("Wallet")?;
+            let keystore_path = setup_new_wallet(config_path)?;
+            Config::edit_file(config_path, |cfg| {
+                cfg.account.keypair_path = keystore_path.to_string_lossy().to_string();
+            })?;
+        }
+    }
+
+    cliclack::outro(format!(
+        "Updated {}. Restart lasersell to use it.",
+        config_path.display()
+    ))?;
+    Ok(())
+}
+
+fn apply_strategy_inputs(cfg: &mut Config, inputs: &StrategyInputs) {
+    cfg.strategy.target_profit = inputs.target_profit.clone();
+    cfg.strategy.stop_loss = inputs.stop_loss.clone();
+    cfg.strategy.trailing_stop = inputs.trailing_stop.clone();
+    cfg.strategy.deadline_timeout_sec = inputs.sell_timeout_sec;
+    cfg.strategy.sell_on_graduation = inputs.sell_on_graduation;
+    cfg.sell.slippage_max_bps = inputs.slippage_max_bps;
+}
+
+/// Writes a new or imported wallet to a keystore and returns its path. The
+/// current keystore is only replaced if the user confirms it.
+fn setup_new_wallet(config_path: &Path) -> Result<PathBuf> {
+    let selection = prompt_wallet()?;
+    let pubkey = selection.keypair.pubkey();
+
+    cliclack::log::step("Security")?;
+    cliclack::log::info("Set a password to encrypt your wallet.")?;
+    let mut passphrase = prompt_passphrase()?;
+
+    let current = find_existing_encrypted_keystore(config_path);
+    let default_path = default_keystore_path_for_config(config_path);
+    let suggested = if default_path.exists() {
+        default_path.with_file_name(format!("wallet-{}.keystore.json", &pubkey.to_string()[..8]))
+    } else {
+        default_path
+    };
+    let mut keystore_path = prompt_path("Keystore file location", &suggested)?;
+    while keystore_path.exists() {
+        let current_note = if current.as_deref() == Some(keystore_path.as_path()) {
+            " It holds the current wallet, which is lost without its seed phrase."
+        } else {
+            ""
+        };
+        let overwrite: bool = cliclack::confirm(format!(
+            "Keystore file {} exists.{current_note} Overwrite?",
+            keystore_path.display()
+        ))
+        .initial_value(false)
+        .interact()?;
+        if overwrite {
+            break;
+        }
+        keystore_path = prompt_path("Keystore file location", &keystore_path)?;
+    }
+
+    let kdf = Config::load_from_path(config_path)
+        .map(|cfg| cfg.wallet.kdf)
+        .unwrap_or_default();
+    let passphrase_secret = SecretString::new(passphrase.to_string());
+    wallet::write_keystore(&keystore_path, &selection.keypair, &passphrase_secret, &kdf)?;
+    drop(passphrase_secret);
+    passphrase.zeroize();
+    if let Some(path) = selection.delete_after_import {
+        if let Err(err) = fs::remove_file(&path) {
+            cliclack::log::warning(format!(
+                "Failed to delete keypair JSON file {}: {err}",
+                path.display()
+            ))?;
+        }
+    }
+    cliclack::log::success(format!(
+        "Wallet {pubkey} saved to {}",
+        keystore_path.display()
+    ))?;
+    Ok(keystore_path)
+}
+
 fn prompt_wallet() -> Result<WalletSelection> {
     let create_new: bool = cliclack::select("Wallet setup")
         .item(true, "Create a new wallet", "Generate a fresh keypair")
@@ -658,9 +789,7 @@ fn prompt_strategy_amount(prompt: &str, default: &str, allow_zero: bool) -> Resu
                 let effective = if input.trim().is_empty() { &default_clone } else { input };
                 match StrategyAmount::parse_str(effective) {
                     Ok(amount) => {
-                        let numeric = match amount {
-                            StrategyAmount::Percent(val) => val,
-                        };
+                        let StrategyAmount::Percent(numeric) = amount;
                         if !numeric.is_finite() {
                             return Err("Must be a finite number.");
                         }
@@ -760,11 +889,17 @@ fn build_config(inputs: &ConfigInputs, keystore_path: &Path) -> Result<Config> {
         account: AccountConfig {
             keypair_path: keystore_path.to_string_lossy().to_string(),
             local: inputs.local,
+            profile: None,
             rpc_url: SecretString::new(inputs.rpc_url.clone()),
+            send_rpc_url: SecretString::new(String::new()),
             api_key: SecretString::new(inputs.api_key.clone()),
             send_target: Some("helius_sender".to_string()),
             tip_lamports: Some(1_000_000),
             astralane_api_key: SecretString::new(String::new()),
+            rpc_max_rps: None,
+            own_wallets: Vec::new(),
+            exit_api_regions: Default::default(),
+            exit_api_region: None,
         },
         strategy: StrategyConfig {
             target_profit: inputs.target_profit.clone(),
@@ -775,6 +910,8 @@ fn build_config(inputs: &ConfigInputs, keystore_path: &Path) -> Result<Config> {
             take_profit_levels: Vec::new(),
             liquidity_guard: false,
             breakeven_trail: StrategyAmount::Percent(0.0),
+            tighten_after_sec: Vec::new(),
+            max_hold_sec: 0,
         },
         sell: SellConfig {
             slippage_max_bps: inputs.slippage_max_bps,
@@ -782,6 +919,24 @@ fn build_config(inputs: &ConfigInputs, keystore_path: &Path) -> Result<Config> {
         },
         watch_wallets: Vec::new(),
         mirror: Default::default(),
+        logging: Default::default(),
+        sessions: Default::default(),
+        exposure: Default::default(),
+        notifications: Default::default(),
+        integration: Default::default(),
+        liveness: Default::default(),
+        reports: Default::default(),
+        stream: Default::default(),
+        sweep: Default::default(),
+        treasury: Default::default(),
+        risk: Default::default(),
+        security: Default::default(),
+        performance: Default::default(),
+        network: Default::default(),
+        wallet: Default::default(),
+        ui: Default::default(),
+        wallets: Vec::new(),
+        profiles: Default::default(),
     })
 }
 
@@ -917,7 +1072,7 @@ mod tests {
         let keypair = Keypair::new();
         let passphrase = SecretString::new("test-passphrase".to_string());
 
-        wallet::write_keystore(&keystore_path, &keypair, &passphrase).unwrap();
+        wallet::write_keystore(&keystore_path, &keypair, &passphrase, &Default::default()).unwrap();
         fs::write(
             &config_path,
             "account:\n  keypair_path: custom.keystore.json\n",
@@ -928,6 +1083,40 @@ mod tests {
         assert_eq!(found, Some(keystore_path));
     }
 
+    #[test]
+    fn strategy_section_rewrites_only_strategy_fields() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.yml");
+        fs::write(
+            &config_path,
+            "account:\n  keypair_path: wallet.keystore.json\n  rpc_url: https://rpc.example.com\n  \
+             api_key: key\nstrategy:\n  target_profit: \"50%\"\n  stop_loss: \"10%\"\n  \
+             deadline_timeout: 0\nsell:\n  max_retries: 7\n",
+        )
+        .unwrap();
+        let inputs = StrategyInputs {
+            target_profit: StrategyAmount::Percent(6.0),
+            target_profit_enabled: true,
+            stop_loss: StrategyAmount::Percent(0.0),
+            stop_loss_enabled: false,
+            trailing_stop: StrategyAmount::Percent(5.0),
+            trailing_stop_enabled: true,
+            sell_timeout_sec: 45,
+            timeout_enabled: true,
+            slippage_max_bps: 2_000,
+            sell_on_graduation: true,
+        };
+
+        Config::edit_file(&config_path, |cfg| apply_strategy_inputs(cfg, &inputs)).unwrap();
+
+        let cfg = Config::load_from_path(&config_path).unwrap();
+        assert_eq!(cfg.strategy.target_profit.percent_value(), 6.0);
+        assert_eq!(cfg.strategy.deadline_timeout_sec, 45);
+        assert_eq!(cfg.sell.slippage_max_bps, 2_000);
+        assert_eq!(cfg.sell.max_retries, 7);
+        assert_eq!(cfg.account.keypair_path, "wallet.keystore.json");
+    }
+
     #[test]
     fn find_existing_keystore_falls_back_to_default() {
         let dir = tempdir().unwrap();
@@ -936,7 +1125,7 @@ mod tests {
         let keypair = Keypair::new();
         let passphrase = SecretString::new("test-passphrase".to_string());
 
-        wallet::write_keystore(&keystore_path, &keypair, &passphrase).unwrap();
+        wallet::write_keystore(&keystore_path, &keypair, &passphrase, &Default::default()).unwrap();
 
         let found = find_existing_encrypted_keystore(&config_path);
         assert_eq!(found, Some(keystore_path));
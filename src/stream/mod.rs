@@ -2,10 +2,16 @@ use parking_lot::RwLock;
 
 use crate::market::MarketType;
 
+/// What the stream last said about one open position's market.
 #[derive(Debug)]
 pub struct InMemoryMarketStreamState {
     market_type: MarketType,
     position_tokens: RwLock<Option<u64>>,
+    /// Proceeds of selling the whole position, from the last PnL update.
+    quoted_proceeds: RwLock<Option<u64>>,
+    /// `(time_ms, price_quote)` of the last swap seen on the pool.
+    last_trade: RwLock<Option<(u64, u64)>>,
+    liquidity_trend: RwLock<Option<String>>,
 }
 
 impl InMemoryMarketStreamState {
@@ -13,6 +19,9 @@ impl InMemoryMarketStreamState {
         Self {
             market_type,
             position_tokens: RwLock::new(None),
+            quoted_proceeds: RwLock::new(None),
+            last_trade: RwLock::new(None),
+            liquidity_trend: RwLock::new(None),
         }
     }
 
@@ -23,4 +32,33 @@ impl InMemoryMarketStreamState {
     pub fn set_position_tokens(&self, tokens: Option<u64>) {
         *self.position_tokens.write() = tokens;
     }
+
+    pub fn set_quoted_proceeds(&self, proceeds_units: u64) {
+        *self.quoted_proceeds.write() = Some(proceeds_units);
+    }
+
+    /// Quote units the whole position would sell for, per the server.
+    pub fn quote_sell_proceeds(&self) -> Option<u64> {
+        *self.quoted_proceeds.read()
+    }
+
+    /// Keeps the newer of the stored and the given trade.
+    pub fn record_trade(&self, time_ms: u64, price_quote: u64) {
+        let mut last = self.last_trade.write();
+        if last.is_none_or(|(at_ms, _)| at_ms <= time_ms) {
+            *last = Some((time_ms, price_quote));
+        }
+    }
+
+    pub fn last_trade(&self) -> Option<(u64, u64)> {
+        *self.last_trade.read()
+    }
+
+    pub fn set_liquidity_trend(&self, trend: String) {
+        *self.liquidity_trend.write() = Some(trend);
+    }
+
+    pub fn liquidity_trend(&self) -> Option<String> {
+        self.liquidity_trend.read().clone()
+    }
 }
@@ -0,0 +1,332 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use lasersell_sdk::tx::encode_signed_tx;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use tracing::{info, warn};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+use crate::util::clock::now_unix_ms;
+
+/// `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+static AUDIT: OnceLock<Mutex<AuditLog>> = OnceLock::new();
+static WRITE_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// What a transaction was signed for, as the audit log records it.
+#[derive(Clone, Copy, Debug)]
+pub struct Purpose<'a> {
+    pub label: &'a str,
+    pub mint: Option<&'a Pubkey>,
+}
+
+impl<'a> Purpose<'a> {
+    pub fn new(label: &'a str, mint: Option<&'a Pubkey>) -> Self {
+        Self { label, mint }
+    }
+}
+
+/// What each entry's hash covers: everything but the hash itself.
+#[derive(Serialize)]
+struct EntryBody<'a> {
+    seq: u64,
+    at_ms: u64,
+    purpose: &'a str,
+    mint: Option<&'a str>,
+    signature: &'a str,
+    tx_base64: &'a str,
+    prev_hash: &'a str,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Serialize, Deserialize)]
+struct AuditEntry {
+    seq: u64,
+    at_ms: u64,
+    purpose: String,
+    mint: Option<String>,
+    signature: String,
+    tx_base64: String,
+    prev_hash: String,
+    hash: String,
+}
+
+impl AuditEntry {
+    fn body(&self) -> EntryBody<'_> {
+        EntryBody {
+            seq: self.seq,
+            at_ms: self.at_ms,
+            purpose: &self.purpose,
+            mint: self.mint.as_deref(),
+            signature: &self.signature,
+            tx_base64: &self.tx_base64,
+            prev_hash: &self.prev_hash,
+        }
+    }
+}
+
+fn entry_hash(body: &EntryBody<'_>) -> Result<String> {
+    let json = serde_json::to_string(body).context("serialize audit entry")?;
+    Ok(hex(&Sha256::digest(json.as_bytes())))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The open log and the tail of its hash chain.
+struct AuditLog {
+    out: LineWriter<File>,
+    seq: u64,
+    prev_hash: String,
+    /// A resend of the same signed transaction is not logged again.
+    last_signature: Option<String>,
+}
+
+impl AuditLog {
+    fn append(
+        &mut self,
+        at_ms: u64,
+        tx: &VersionedTransaction,
+        purpose: Purpose<'_>,
+    ) -> Result<()> {
+        let signature = tx
+            .signatures
+            .first()
+            .map(ToString::to_string)
+            .ok_or_else(|| anyhow!("transaction has no signature"))?;
+        if self.last_signature.as_deref() == Some(signature.as_str()) {
+            return Ok(());
+        }
+        let mut entry = AuditEntry {
+            seq: self.seq + 1,
+            at_ms,
+            purpose: purpose.label.to_string(),
+            mint: purpose.mint.map(ToString::to_string),
+            signature,
+            tx_base64: encode_signed_tx(tx)?,
+            prev_hash: self.prev_hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry_hash(&entry.body())?;
+        writeln!(self.out, "{}", serde_json::to_string(&entry)?)?;
+        self.seq = entry.seq;
+        self.prev_hash = entry.hash;
+        self.last_signature = Some(entry.signature);
+        Ok(())
+    }
+}
+
+/// Opens the audit log at `path`, continuing the hash chain of what is
+/// already there. A line that is not an audit entry is an error rather
+/// than a gap in the chain. Later calls are ignored.
+pub fn init(path: &Path) -> Result<()> {
+    if AUDIT.get().is_some() {
+        return Ok(());
+    }
+    let (seq, prev_hash) = chain_tail(path)?;
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let file = options
+        .open(path)
+        .with_context(|| format!("open transaction audit log {}", path.display()))?;
+    let log = AuditLog {
+        out: LineWriter::new(file),
+        seq,
+        prev_hash,
+        last_signature: None,
+    };
+    if AUDIT.set(Mutex::new(log)).is_ok() {
+        info!(event = "tx_audit_log", path = %path.display(), entries = seq);
+    }
+    Ok(())
+}
+
+/// Appends `tx` to the audit log as it is signed, if the log was opened.
+/// A failed write is logged once and does not stop the signing.
+pub fn record(tx: &VersionedTransaction, purpose: Purpose<'_>) {
+    let Some(log) = AUDIT.get() else {
+        return;
+    };
+    if let Err(err) = log.lock().append(now_unix_ms(), tx, purpose) {
+        if !WRITE_FAILED.swap(true, Ordering::Relaxed) {
+            warn!(event = "tx_audit_write_failed", error = %format!("{err:#}"));
+        }
+    }
+}
+
+/// The last entry's seq and hash in the log at `path`, or the start of a
+/// new chain when there is no log yet.
+fn chain_tail(path: &Path) -> Result<(u64, String)> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok((0, GENESIS_HASH.to_string()))
+        }
+        Err(err) => return Err(err).with_context(|| format!("read {}", path.display())),
+    };
+    let mut tail = (0, GENESIS_HASH.to_string());
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("read {}", path.display()))?;
+        let entry: AuditEntry = serde_json::from_str(&line).map_err(|err| {
+            anyhow!(
+                "{} line {}: not an audit entry ({err}); run `lasersell audit verify` \
+                 and move the file aside to start a new log",
+                path.display(),
+                index + 1
+            )
+        })?;
+        tail = (entry.seq, entry.hash);
+    }
+    Ok(tail)
+}
+
+/// `lasersell audit verify`: recomputes the hash chain and checks that each
+/// entry's transaction carries its signature and that every signature in it
+/// is valid. The first broken entry is an error.
+pub fn verify(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut count = 0u64;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("read {}", path.display()))?;
+        let line_no = index + 1;
+        let entry: AuditEntry = serde_json::from_str(&line)
+            .map_err(|err| anyhow!("line {line_no}: not an audit entry: {err}"))?;
+        verify_entry(&entry, count + 1, &prev_hash)
+            .map_err(|err| anyhow!("line {line_no} (seq {}): {err}", entry.seq))?;
+        count = entry.seq;
+        prev_hash = entry.hash;
+    }
+    Ok(format!(
+        "{} ok: {count} signed transactions, hash chain intact\nlast hash {prev_hash}\n",
+        path.display()
+    ))
+}
+
+fn verify_entry(entry: &AuditEntry, seq: u64, prev_hash: &str) -> Result<()> {
+    if entry.seq != seq {
+        return Err(anyhow!(
+            "expected seq {seq}; an entry was removed or reordered"
+        ));
+    }
+    if entry.prev_hash != prev_hash {
+        return Err(anyhow!("prev_hash does not match the entry before it"));
+    }
+    if entry_hash(&entry.body())? != entry.hash {
+        return Err(anyhow!("hash does not match the entry; it was edited"));
+    }
+    let tx_bytes = base64::engine::general_purpose::STANDARD
+        .decode(&entry.tx_base64)
+        .context("tx_base64 is not base64")?;
+    let tx: VersionedTransaction =
+        bincode::deserialize(&tx_bytes).context("tx_base64 is not a transaction")?;
+    if tx.signatures.first().map(ToString::to_string).as_deref() != Some(&entry.signature) {
+        return Err(anyhow!("signature is not the transaction's"));
+    }
+    if !tx.verify_with_results().into_iter().all(|valid| valid) {
+        return Err(anyhow!("a transaction signature does not verify"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::message::Message;
+    use solana_sdk::signature::{Keypair, Signer};
+    use solana_sdk::transaction::Transaction;
+    use tempfile::tempdir;
+
+    fn signed_tx(keypair: &Keypair, blockhash: Hash) -> VersionedTransaction {
+        let message = Message::new(&[], Some(&keypair.pubkey()));
+        VersionedTransaction::from(Transaction::new(&[keypair], message, blockhash))
+    }
+
+    fn open(path: &Path) -> AuditLog {
+        AuditLog {
+            out: LineWriter::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .unwrap(),
+            ),
+            seq: 0,
+            prev_hash: GENESIS_HASH.to_string(),
+            last_signature: None,
+        }
+    }
+
+    #[test]
+    fn chain_verifies_until_an_entry_is_edited() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tx_audit.ndjson");
+        let keypair = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let mut log = open(&path);
+        let first = signed_tx(&keypair, Hash::new_unique());
+        log.append(1, &first, Purpose::new("sell", Some(&mint)))
+            .unwrap();
+        log.append(2, &first, Purpose::new("sell", Some(&mint)))
+            .unwrap();
+        log.append(
+            3,
+            &signed_tx(&keypair, Hash::new_unique()),
+            Purpose::new("buy", None),
+        )
+        .unwrap();
+        drop(log);
+        assert!(verify(&path).unwrap().contains("ok: 2 signed transactions"));
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, raw.replacen("\"sell\"", "\"buy\"", 1)).unwrap();
+        let err = verify(&path).unwrap_err().to_string();
+        assert!(
+            err.starts_with("line 1 (seq 1): hash does not match"),
+            "{err}"
+        );
+
+        let second = raw.lines().nth(1).unwrap();
+        std::fs::write(&path, format!("{second}\n")).unwrap();
+        let err = verify(&path).unwrap_err().to_string();
+        assert!(err.contains("expected seq 1"), "{err}");
+    }
+
+    #[test]
+    fn refuses_to_continue_past_an_unreadable_line() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tx_audit.ndjson");
+        assert_eq!(chain_tail(&path).unwrap().0, 0);
+        let mut log = open(&path);
+        log.append(
+            1,
+            &signed_tx(&Keypair::new(), Hash::new_unique()),
+            Purpose::new("sell", None),
+        )
+        .unwrap();
+        drop(log);
+        assert_eq!(chain_tail(&path).unwrap().0, 1);
+
+        let mut raw = std::fs::read_to_string(&path).unwrap();
+        raw.push_str("{\"seq\":2,\"at_ms\"\n");
+        std::fs::write(&path, raw).unwrap();
+        let err = chain_tail(&path).unwrap_err().to_string();
+        assert!(err.contains("line 2: not an audit entry"), "{err}");
+    }
+}
@@ -1,23 +1,499 @@
-use anyhow::Result;
-use lasersell_sdk::tx::{
-    confirm_signature_via_rpc, send_transaction, SendTarget,
-    sign_unsigned_tx as sdk_sign_unsigned_tx,
-};
-use solana_sdk::signature::Keypair;
-use solana_sdk::transaction::VersionedTransaction;
+use std::str::FromStr;
 
-pub fn sign_unsigned_tx(unsigned_tx_b64: &str, keypair: &Keypair) -> Result<VersionedTransaction> {
-    Ok(sdk_sign_unsigned_tx(unsigned_tx_b64, keypair)?)
+use anyhow::{anyhow, Result};
+use base64::Engine as _;
+use lasersell_sdk::tx::{confirm_signature_via_rpc, encode_signed_tx, TxSubmitError};
+use serde_json::Value;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+
+use crate::events::{emit, AppEvent};
+use crate::market::USD1_MINT;
+use crate::network::rpc_result;
+
+pub mod audit;
+pub mod onchain_error;
+pub mod policy;
+pub mod preflight;
+pub mod senders;
+
+use onchain_error::decode_tx_error;
+use senders::SellSender;
+
+/// Signs a transaction built by the exit API or carried by an exit signal,
+/// after [`policy::check`] accepts it for the signing wallet and `intent`,
+/// and logs it to the audit log as `purpose`.
+pub fn sign_unsigned_tx(
+    unsigned_tx_b64: &str,
+    keypair: &Keypair,
+    intent: policy::Intent,
+    purpose: audit::Purpose<'_>,
+) -> Result<VersionedTransaction> {
+    let unsigned = decode_unsigned_tx(unsigned_tx_b64)?;
+    if let Err(refused) = policy::check(&unsigned, &keypair.pubkey(), intent) {
+        emit(AppEvent::SigningRefused {
+            program: refused.program.map(|program| program.to_string()),
+            reason: refused.reason.clone(),
+        });
+        return Err(refused.into());
+    }
+    let signed = VersionedTransaction::try_new(unsigned.message, &[keypair])
+        .map_err(TxSubmitError::SignTx)?;
+    audit::record(&signed, purpose);
+    Ok(signed)
+}
+
+/// Decodes a base64 unsigned transaction as the exit API or stream sent it.
+pub fn decode_unsigned_tx(unsigned_tx_b64: &str) -> Result<VersionedTransaction> {
+    let raw = base64::engine::general_purpose::STANDARD
+        .decode(unsigned_tx_b64)
+        .map_err(TxSubmitError::DecodeUnsignedTx)?;
+    Ok(bincode::deserialize(&raw).map_err(TxSubmitError::DeserializeUnsignedTx)?)
 }
 
+/// Signs a transaction built here rather than by a server, paid by
+/// `keypair`, and logs it to the audit log as `purpose`.
+pub fn sign_local_tx(
+    instructions: &[Instruction],
+    keypair: &Keypair,
+    blockhash: Hash,
+    purpose: audit::Purpose<'_>,
+) -> VersionedTransaction {
+    let signed = VersionedTransaction::from(Transaction::new_signed_with_payer(
+        instructions,
+        Some(&keypair.pubkey()),
+        &[keypair],
+        blockhash,
+    ));
+    audit::record(&signed, purpose);
+    signed
+}
+
+/// Sends `tx` through `sender` and waits for
+/// confirmation. `first_sent_at` is set when a send
+/// is first accepted, so timing stays on the first attempt across retries.
+/// A sell that lands and fails carries its decoded
+/// [`onchain_error::OnchainFailure`] as context.
 pub async fn send_tx(
     http: &reqwest::Client,
     rpc_url: &str,
     tx: &VersionedTransaction,
-    send_target: &SendTarget,
+    sender: &dyn SellSender,
     confirm_timeout: std::time::Duration,
+    first_sent_at: &mut Option<tokio::time::Instant>,
 ) -> Result<String> {
-    let signature = send_transaction(http, send_target, tx).await?;
-    confirm_signature_via_rpc(http, rpc_url, &signature, confirm_timeout).await?;
-    Ok(signature)
+    let tx_b64 = encode_signed_tx(tx)?;
+    let signature = sender.send(http, &tx_b64).await?;
+    first_sent_at.get_or_insert_with(tokio::time::Instant::now);
+    match confirm_signature_via_rpc(http, rpc_url, &signature, confirm_timeout).await {
+        Ok(()) => Ok(signature),
+        Err(TxSubmitError::TxFailed { signature, error }) => {
+            let failure = decode_tx_error(&error, tx);
+            Err(anyhow::Error::from(TxSubmitError::TxFailed { signature, error }).context(failure))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The RPC node's simulation rejected a signed transaction.
+#[derive(Debug)]
+pub struct SimulationRejected {
+    pub error: String,
+}
+
+impl std::fmt::Display for SimulationRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "simulation rejected tx: {}", self.error)
+    }
+}
+
+impl std::error::Error for SimulationRejected {}
+
+/// Runs `tx` through `simulateTransaction`. Returns the simulation error when
+/// the transaction would fail on-chain, `None` when it would succeed.
+pub async fn simulate_tx(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    tx: &VersionedTransaction,
+) -> Result<Option<String>> {
+    let encoded = encode_signed_tx(tx)?;
+    let result = rpc_result(
+        http,
+        rpc_url,
+        "simulateTransaction",
+        serde_json::json!([
+            encoded,
+            { "encoding": "base64", "commitment": "processed", "sigVerify": false }
+        ]),
+    )
+    .await?;
+    Ok(simulation_error(&result))
+}
+
+fn simulation_error(result: &Value) -> Option<String> {
+    let value = result.get("value")?;
+    let err = value.get("err").filter(|err| !err.is_null())?;
+    let last_log = value
+        .get("logs")
+        .and_then(|logs| logs.as_array())
+        .and_then(|logs| logs.last())
+        .and_then(|log| log.as_str());
+    Some(match last_log {
+        Some(log) => format!("{err} ({log})"),
+        None => err.to_string(),
+    })
+}
+
+/// Simulates the exit API's unsigned `tx_b64` and returns the lamports
+/// `account` would hold afterwards. Nothing is signed: the node skips the
+/// signature check and swaps in a fresh blockhash, so what it is handed
+/// could never land. A transaction that would fail comes back as
+/// [`SimulationRejected`].
+pub async fn simulate_lamports_after(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    tx_b64: &str,
+    account: &str,
+) -> Result<u64> {
+    let result = rpc_result(
+        http,
+        rpc_url,
+        "simulateTransaction",
+        serde_json::json!([
+            tx_b64.trim(),
+            {
+                "encoding": "base64",
+                "commitment": "processed",
+                "sigVerify": false,
+                "replaceRecentBlockhash": true,
+                "accounts": { "encoding": "base64", "addresses": [account] }
+            }
+        ]),
+    )
+    .await?;
+    if let Some(error) = simulation_error(&result) {
+        return Err(SimulationRejected { error }.into());
+    }
+    result
+        .pointer("/value/accounts/0/lamports")
+        .and_then(|lamports| lamports.as_u64())
+        .ok_or_else(|| anyhow!("simulateTransaction result missing account lamports"))
+}
+
+pub async fn fetch_latest_blockhash(http: &reqwest::Client, rpc_url: &str) -> Result<Hash> {
+    let result = rpc_result(
+        http,
+        rpc_url,
+        "getLatestBlockhash",
+        serde_json::json!([{ "commitment": "confirmed" }]),
+    )
+    .await?;
+    result
+        .pointer("/value/blockhash")
+        .and_then(|hash| hash.as_str())
+        .and_then(|hash| Hash::from_str(hash).ok())
+        .ok_or_else(|| anyhow!("getLatestBlockhash result missing blockhash"))
+}
+
+/// Current processed slot according to the RPC node.
+pub async fn fetch_current_slot(http: &reqwest::Client, rpc_url: &str) -> Result<u64> {
+    let result = rpc_result(
+        http,
+        rpc_url,
+        "getSlot",
+        serde_json::json!([{ "commitment": "processed" }]),
+    )
+    .await?;
+    result.as_u64().ok_or_else(|| anyhow!("getSlot result missing"))
+}
+
+pub(crate) const BASE_FEE_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// What a confirmed sell actually did to the wallet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SellSettlement {
+    pub slot: u64,
+    /// Everything the transaction paid the network: signature fees plus the
+    /// priority fee.
+    pub fee_lamports: u64,
+    /// The part of `fee_lamports` above the base fee per signature.
+    pub priority_fee_lamports: u64,
+    /// SOL the wallet transferred out in the sell's own instructions: the
+    /// sender tip, outside the network fee.
+    pub tip_lamports: u64,
+    /// The wallet's lamport change, fees and tips included.
+    pub received_lamports: i64,
+    /// Lamports returned to the wallet by token accounts the sell closed:
+    /// their rent, and any SOL already wrapped in them, none of it proceeds.
+    pub reclaimed_lamports: u64,
+    /// The wallet's USD1 change in base units.
+    pub received_usd1_units: i64,
+    /// Net quote received: the wallet's USD1 increase when the sell paid out
+    /// in USD1, otherwise its lamport change (fees included) less
+    /// `reclaimed_lamports`.
+    pub quote_received_units: i64,
+}
+
+/// Looks up a confirmed sell and measures its effect on `wallet`. Returns
+/// `None` while the RPC node does not have the transaction yet.
+pub async fn fetch_sell_settlement(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    signature: &str,
+    wallet: &str,
+) -> Result<Option<SellSettlement>> {
+    let result = rpc_result(
+        http,
+        rpc_url,
+        "getTransaction",
+        serde_json::json!([
+            signature,
+            {
+                "encoding": "jsonParsed",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0
+            }
+        ]),
+    )
+    .await?;
+    if result.is_null() {
+        return Ok(None);
+    }
+    parse_sell_settlement(&result, wallet)
+        .map(Some)
+        .ok_or_else(|| anyhow!("getTransaction result missing balances"))
+}
+
+fn parse_sell_settlement(tx: &Value, wallet: &str) -> Option<SellSettlement> {
+    let slot = tx.get("slot")?.as_u64()?;
+    let meta = tx.get("meta")?;
+    let fee_lamports = meta.get("fee")?.as_u64()?;
+    let signatures = tx
+        .pointer("/transaction/signatures")
+        .and_then(|signatures| signatures.as_array())
+        .map_or(1, |signatures| signatures.len() as u64);
+    let priority_fee_lamports =
+        fee_lamports.saturating_sub(signatures * BASE_FEE_LAMPORTS_PER_SIGNATURE);
+    // The wallet signs and pays for its own sells, so it is normally account
+    // index 0; jsonParsed names the keys, so look it up when they are there.
+    let keys: Vec<&str> = tx
+        .pointer("/transaction/message/accountKeys")
+        .and_then(|keys| keys.as_array())
+        .into_iter()
+        .flatten()
+        .map(|key| {
+            key.as_str()
+                .or_else(|| key.get("pubkey")?.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    let index = keys.iter().position(|key| *key == wallet).unwrap_or(0);
+    let pre_balances = meta.get("preBalances")?;
+    let pre_lamports = pre_balances.get(index)?.as_u64()?;
+    let post_lamports = meta.get("postBalances")?.get(index)?.as_u64()?;
+    let received_lamports = post_lamports as i64 - pre_lamports as i64;
+    let tip_lamports = tx
+        .pointer("/transaction/message/instructions")
+        .and_then(|instructions| instructions.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|ix| {
+            ix.get("program").and_then(|p| p.as_str()) == Some("system")
+                && ix.pointer("/parsed/type").and_then(|t| t.as_str()) == Some("transfer")
+                && ix.pointer("/parsed/info/source").and_then(|s| s.as_str()) == Some(wallet)
+        })
+        .filter_map(|ix| ix.pointer("/parsed/info/lamports")?.as_u64())
+        .sum();
+    // Token accounts closed to the wallet, such as the sold token's account,
+    // hand back what they held before the sell; an account opened by the
+    // sell itself held nothing.
+    let inner = meta
+        .get("innerInstructions")
+        .and_then(|inner| inner.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|inner| inner.get("instructions")?.as_array())
+        .flatten();
+    let mut closed: Vec<&str> = tx
+        .pointer("/transaction/message/instructions")
+        .and_then(|instructions| instructions.as_array())
+        .into_iter()
+        .flatten()
+        .chain(inner)
+        .filter(|ix| {
+            matches!(
+                ix.get("program").and_then(|p| p.as_str()),
+                Some("spl-token" | "spl-token-2022")
+            ) && ix.pointer("/parsed/type").and_then(|t| t.as_str()) == Some("closeAccount")
+                && ix
+                    .pointer("/parsed/info/destination")
+                    .and_then(|d| d.as_str())
+                    == Some(wallet)
+        })
+        .filter_map(|ix| ix.pointer("/parsed/info/account")?.as_str())
+        .collect();
+    closed.sort_unstable();
+    closed.dedup();
+    let reclaimed_lamports = closed
+        .iter()
+        .filter_map(|account| keys.iter().position(|key| key == account))
+        .filter_map(|index| pre_balances.get(index)?.as_u64())
+        .sum::<u64>();
+    let received_usd1_units = usd1_balance(meta.get("postTokenBalances"), wallet)
+        - usd1_balance(meta.get("preTokenBalances"), wallet);
+    let quote_received_units = if received_usd1_units > 0 {
+        received_usd1_units
+    } else {
+        received_lamports - reclaimed_lamports as i64
+    };
+    Some(SellSettlement {
+        slot,
+        fee_lamports,
+        priority_fee_lamports,
+        tip_lamports,
+        received_lamports,
+        reclaimed_lamports,
+        received_usd1_units,
+        quote_received_units,
+    })
+}
+
+fn usd1_balance(balances: Option<&Value>, wallet: &str) -> i64 {
+    balances
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|entry| {
+            entry.get("mint").and_then(|m| m.as_str()) == Some(USD1_MINT)
+                && entry.get("owner").and_then(|o| o.as_str()) == Some(wallet)
+        })
+        .filter_map(|entry| {
+            entry
+                .get("uiTokenAmount")?
+                .get("amount")?
+                .as_str()?
+                .parse::<i64>()
+                .ok()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_error_reports_err_and_last_log() {
+        let ok = serde_json::json!({ "value": { "err": null, "logs": ["ok"] } });
+        assert_eq!(simulation_error(&ok), None);
+        let failed = serde_json::json!({
+            "value": {
+                "err": { "InstructionError": [2, { "Custom": 6001 }] },
+                "logs": ["Program log: start", "Program log: slippage exceeded"]
+            }
+        });
+        assert_eq!(
+            simulation_error(&failed).unwrap(),
+            r#"{"InstructionError":[2,{"Custom":6001}]} (Program log: slippage exceeded)"#
+        );
+    }
+
+    #[test]
+    fn settlement_uses_lamport_delta_for_sol_sells() {
+        let tx = serde_json::json!({
+            "slot": 321,
+            "transaction": {
+                "signatures": ["sig"],
+                "message": { "accountKeys": ["wallet", "tip"], "instructions": [{
+                    "program": "system",
+                    "parsed": { "type": "transfer", "info": {
+                        "source": "wallet", "destination": "tip", "lamports": 10_000
+                    } }
+                }] }
+            },
+            "meta": {
+                "fee": 125_000,
+                "preBalances": [1_000_000, 7],
+                "postBalances": [1_495_000, 7],
+                "preTokenBalances": [],
+                "postTokenBalances": []
+            }
+        });
+        let settlement = parse_sell_settlement(&tx, "wallet").unwrap();
+        assert_eq!(settlement.slot, 321);
+        assert_eq!(settlement.fee_lamports, 125_000);
+        assert_eq!(settlement.priority_fee_lamports, 120_000);
+        assert_eq!(settlement.tip_lamports, 10_000);
+        assert_eq!(settlement.quote_received_units, 495_000);
+    }
+
+    #[test]
+    fn settlement_leaves_out_rent_of_closed_token_accounts() {
+        let close = |account: &str| {
+            serde_json::json!({
+                "program": "spl-token",
+                "parsed": { "type": "closeAccount", "info": {
+                    "account": account, "destination": "wallet", "owner": "wallet"
+                } }
+            })
+        };
+        let tx = serde_json::json!({
+            "slot": 77,
+            "transaction": {
+                "signatures": ["sig"],
+                "message": {
+                    "accountKeys": ["wallet", "token_ata", "wsol_ata", "pool"],
+                    "instructions": [
+                        { "programId": "pool", "data": "swap" },
+                        close("token_ata")
+                    ]
+                }
+            },
+            "meta": {
+                "fee": 5000,
+                // The swap pays 0.5 SOL into a WSOL account it opened with
+                // the wallet's rent, which the pool's unwrap closes again.
+                "preBalances": [1_000_000_000, 2_039_280, 0, 9],
+                "postBalances": [1_502_034_280, 0, 0, 9],
+                "innerInstructions": [{ "index": 0, "instructions": [close("wsol_ata")] }],
+                "preTokenBalances": [],
+                "postTokenBalances": []
+            }
+        });
+        let settlement = parse_sell_settlement(&tx, "wallet").unwrap();
+        assert_eq!(settlement.received_lamports, 502_034_280);
+        assert_eq!(settlement.reclaimed_lamports, 2_039_280);
+        assert_eq!(settlement.quote_received_units, 499_995_000);
+    }
+
+    #[test]
+    fn settlement_prefers_usd1_increase() {
+        let usd1 = |amount: &str| {
+            serde_json::json!({
+                "mint": USD1_MINT,
+                "owner": "wallet",
+                "uiTokenAmount": { "amount": amount }
+            })
+        };
+        let tx = serde_json::json!({
+            "slot": 9,
+            "transaction": { "message": { "accountKeys": [
+                { "pubkey": "feepayer", "signer": true },
+                { "pubkey": "wallet", "signer": true }
+            ] } },
+            "meta": {
+                "fee": 5000,
+                "preBalances": [50_000, 1_000_000],
+                "postBalances": [40_000, 995_000],
+                "preTokenBalances": [usd1("100")],
+                "postTokenBalances": [usd1("2600")]
+            }
+        });
+        let settlement = parse_sell_settlement(&tx, "wallet").unwrap();
+        assert_eq!(settlement.quote_received_units, 2500);
+        assert_eq!(settlement.received_lamports, -5_000);
+        assert_eq!(settlement.priority_fee_lamports, 0);
+    }
 }
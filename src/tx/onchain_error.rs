@@ -0,0 +1,272 @@
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+
+use super::policy::TOKEN_2022_PROGRAM;
+
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const PUMP_FUN_PROGRAM: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+const PUMP_SWAP_PROGRAM: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+const RAYDIUM_CPMM_PROGRAM: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+/// Anchor's `AccountNotInitialized`, shared by every Anchor program.
+const ANCHOR_ACCOUNT_NOT_INITIALIZED: u64 = 3012;
+
+/// What kind of on-chain failure ended a sell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureKind {
+    /// The pool would pay out less than the minimum the sell allowed.
+    Slippage,
+    /// The wallet cannot cover the fee, rent or token amount.
+    InsufficientFunds,
+    /// An account the sell reads is closed or was never created.
+    AccountMissing,
+    /// The mint's freeze authority froze the wallet's token account.
+    AccountFrozen,
+    /// The bonding curve completed and the token moved to its AMM pool.
+    CurveComplete,
+    Other,
+}
+
+/// A sell that landed and failed, with the transaction error decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnchainFailure {
+    pub kind: FailureKind,
+    pub reason: String,
+}
+
+impl std::fmt::Display for OnchainFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+impl std::error::Error for OnchainFailure {}
+
+/// The wallet's token account for `mint` is frozen by the mint's freeze
+/// authority, so every sell fails until it is thawed.
+#[derive(Debug)]
+pub struct AccountFrozen {
+    pub mint: Pubkey,
+}
+
+impl std::fmt::Display for AccountFrozen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "account_frozen: the token account for {} is frozen by the mint; \
+             no sell can land until it is thawed",
+            self.mint
+        )
+    }
+}
+
+impl std::error::Error for AccountFrozen {}
+
+/// `err` as [`AccountFrozen`] when a sell of `mint` landed and failed on
+/// the frozen token account.
+pub fn account_frozen(err: &anyhow::Error, mint: Pubkey) -> Option<AccountFrozen> {
+    let failure = err.downcast_ref::<OnchainFailure>()?;
+    (failure.kind == FailureKind::AccountFrozen).then_some(AccountFrozen { mint })
+}
+
+/// Decodes the JSON transaction error of a failed `tx`. Custom program
+/// errors are looked up by the program that ran the failing instruction.
+pub fn decode_tx_error(error: &str, tx: &VersionedTransaction) -> OnchainFailure {
+    let Ok(value) = serde_json::from_str::<Value>(error) else {
+        return other(error.to_string());
+    };
+    let program_at = |index: usize| {
+        let keys = tx.message.static_account_keys();
+        tx.message
+            .instructions()
+            .get(index)
+            .and_then(|ix| keys.get(ix.program_id_index as usize))
+            .map(|program| program.to_string())
+    };
+    decode_error_value(&value, program_at)
+}
+
+fn decode_error_value(
+    value: &Value,
+    program_at: impl Fn(usize) -> Option<String>,
+) -> OnchainFailure {
+    if let Some(name) = value.as_str() {
+        return match name {
+            "InsufficientFundsForFee" => failure(
+                FailureKind::InsufficientFunds,
+                "wallet cannot pay the transaction fee",
+            ),
+            "AccountNotFound" => {
+                failure(FailureKind::AccountMissing, "fee payer account not found")
+            }
+            _ => other(name.to_string()),
+        };
+    }
+    if let Some(rent) = value.get("InsufficientFundsForRent") {
+        let account = rent
+            .get("account_index")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+        return failure(
+            FailureKind::InsufficientFunds,
+            &format!("account {account} would fall below rent exemption"),
+        );
+    }
+    let Some([index, ix_error]) = value
+        .get("InstructionError")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+    else {
+        return other(value.to_string());
+    };
+    let index = index.as_u64().unwrap_or_default() as usize;
+    if let Some(code) = ix_error.get("Custom").and_then(Value::as_u64) {
+        let program = program_at(index);
+        return match custom_error(program.as_deref(), code) {
+            Some((kind, name)) => failure(kind, &format!("instruction {index}: {name}")),
+            None => other(format!(
+                "instruction {index}: {} error {code} ({code:#x})",
+                program.as_deref().unwrap_or("unknown program")
+            )),
+        };
+    }
+    let name = ix_error
+        .as_str()
+        .map_or_else(|| ix_error.to_string(), str::to_string);
+    let kind = match name.as_str() {
+        "InsufficientFunds" => FailureKind::InsufficientFunds,
+        "UninitializedAccount" | "InvalidAccountData" => FailureKind::AccountMissing,
+        _ => FailureKind::Other,
+    };
+    failure(kind, &format!("instruction {index}: {name}"))
+}
+
+fn custom_error(program: Option<&str>, code: u64) -> Option<(FailureKind, &'static str)> {
+    let decoded = match (program?, code) {
+        (TOKEN_PROGRAM | TOKEN_2022_PROGRAM, 1) => {
+            (FailureKind::InsufficientFunds, "token balance too low")
+        }
+        (TOKEN_PROGRAM | TOKEN_2022_PROGRAM, 17) => {
+            (FailureKind::AccountFrozen, "token account is frozen")
+        }
+        (PUMP_FUN_PROGRAM, 6003) => (
+            FailureKind::Slippage,
+            "pump.fun slippage: too little SOL received",
+        ),
+        (PUMP_FUN_PROGRAM, 6005) => (
+            FailureKind::CurveComplete,
+            "pump.fun bonding curve complete",
+        ),
+        (PUMP_SWAP_PROGRAM, 6004) => (FailureKind::Slippage, "PumpSwap slippage exceeded"),
+        (RAYDIUM_CPMM_PROGRAM, 6005) => (FailureKind::Slippage, "Raydium CPMM slippage exceeded"),
+        (_, ANCHOR_ACCOUNT_NOT_INITIALIZED) => {
+            (FailureKind::AccountMissing, "account not initialized")
+        }
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+fn failure(kind: FailureKind, reason: &str) -> OnchainFailure {
+    OnchainFailure {
+        kind,
+        reason: reason.to_string(),
+    }
+}
+
+fn other(reason: String) -> OnchainFailure {
+    OnchainFailure {
+        kind: FailureKind::Other,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(error: &str, program: &str) -> OnchainFailure {
+        let value: Value = serde_json::from_str(error).unwrap();
+        decode_error_value(&value, |index| (index == 2).then(|| program.to_string()))
+    }
+
+    #[test]
+    fn decodes_program_slippage_codes() {
+        let pump = decode(
+            r#"{"InstructionError":[2,{"Custom":6003}]}"#,
+            PUMP_FUN_PROGRAM,
+        );
+        assert_eq!(pump.kind, FailureKind::Slippage);
+        assert_eq!(
+            pump.reason,
+            "instruction 2: pump.fun slippage: too little SOL received"
+        );
+        let cpmm = decode(
+            r#"{"InstructionError":[2,{"Custom":6005}]}"#,
+            RAYDIUM_CPMM_PROGRAM,
+        );
+        assert_eq!(cpmm.kind, FailureKind::Slippage);
+        // The same code means something else in another program.
+        let pump_6005 = decode(
+            r#"{"InstructionError":[2,{"Custom":6005}]}"#,
+            PUMP_FUN_PROGRAM,
+        );
+        assert_eq!(pump_6005.kind, FailureKind::CurveComplete);
+    }
+
+    #[test]
+    fn decodes_funds_and_missing_accounts() {
+        let token = decode(r#"{"InstructionError":[2,{"Custom":1}]}"#, TOKEN_PROGRAM);
+        assert_eq!(token.kind, FailureKind::InsufficientFunds);
+        let fee = decode(r#""InsufficientFundsForFee""#, TOKEN_PROGRAM);
+        assert_eq!(fee.kind, FailureKind::InsufficientFunds);
+        let builtin = decode(
+            r#"{"InstructionError":[0,"UninitializedAccount"]}"#,
+            TOKEN_PROGRAM,
+        );
+        assert_eq!(builtin.kind, FailureKind::AccountMissing);
+        assert_eq!(builtin.reason, "instruction 0: UninitializedAccount");
+    }
+
+    #[test]
+    fn decodes_token_2022_errors() {
+        let funds = decode(
+            r#"{"InstructionError":[2,{"Custom":1}]}"#,
+            TOKEN_2022_PROGRAM,
+        );
+        assert_eq!(funds.kind, FailureKind::InsufficientFunds);
+        let frozen = decode(
+            r#"{"InstructionError":[2,{"Custom":17}]}"#,
+            TOKEN_2022_PROGRAM,
+        );
+        assert_eq!(frozen.kind, FailureKind::AccountFrozen);
+        assert_eq!(frozen.reason, "instruction 2: token account is frozen");
+    }
+
+    #[test]
+    fn a_frozen_token_account_names_the_mint() {
+        let mint = Pubkey::new_unique();
+        let failed = |code: u64| {
+            let value = serde_json::json!({ "InstructionError": [2, { "Custom": code }] });
+            let failure = decode_error_value(&value, |_| Some(TOKEN_PROGRAM.to_string()));
+            anyhow::Error::msg("tx failed").context(failure)
+        };
+        let frozen = account_frozen(&failed(17), mint).expect("frozen");
+        assert!(frozen.to_string().contains(&mint.to_string()));
+        assert!(account_frozen(&failed(1), mint).is_none());
+    }
+
+    #[test]
+    fn unknown_codes_keep_program_and_hex_code() {
+        let unknown = decode(
+            r#"{"InstructionError":[2,{"Custom":6042}]}"#,
+            PUMP_SWAP_PROGRAM,
+        );
+        assert_eq!(unknown.kind, FailureKind::Other);
+        assert_eq!(
+            unknown.reason,
+            format!("instruction 2: {PUMP_SWAP_PROGRAM} error 6042 (0x179a)")
+        );
+    }
+}
@@ -0,0 +1,638 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+
+const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+const COMPUTE_BUDGET_PROGRAM: &str = "ComputeBudget111111111111111111111111111111";
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+const ASSOCIATED_TOKEN_PROGRAM: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const PUMP_FUN_PROGRAM: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+const PUMP_SWAP_PROGRAM: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+const METEORA_DBC_PROGRAM: &str = "dbcij3LWUppWqq96dh6gJWwBifmcGfLSB5D4DuSMaqN";
+const METEORA_DAMM_V2_PROGRAM: &str = "cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG";
+const RAYDIUM_LAUNCHPAD_PROGRAM: &str = "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj";
+const RAYDIUM_CPMM_PROGRAM: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+/// `security.allowed_program_ids` by default: the programs the exit API's
+/// sells and buys call, for the markets it supports.
+pub const DEFAULT_ALLOWED_PROGRAM_IDS: [&str; 11] = [
+    SYSTEM_PROGRAM,
+    COMPUTE_BUDGET_PROGRAM,
+    TOKEN_PROGRAM,
+    TOKEN_2022_PROGRAM,
+    ASSOCIATED_TOKEN_PROGRAM,
+    PUMP_FUN_PROGRAM,
+    PUMP_SWAP_PROGRAM,
+    METEORA_DBC_PROGRAM,
+    METEORA_DAMM_V2_PROGRAM,
+    RAYDIUM_LAUNCHPAD_PROGRAM,
+    RAYDIUM_CPMM_PROGRAM,
+];
+
+/// Tip allowance until `init` runs: `account.tip_lamports`' default.
+const DEFAULT_MAX_TIP_LAMPORTS: u64 = 1_000_000;
+/// Priority fee above which a transaction is refused, far beyond what any
+/// sell pays even under congestion.
+const MAX_PRIORITY_FEE_LAMPORTS: u64 = 100_000_000;
+/// Compute units an instruction gets without `SetComputeUnitLimit`.
+const DEFAULT_UNITS_PER_INSTRUCTION: u64 = 200_000;
+const MAX_COMPUTE_UNITS: u64 = 1_400_000;
+/// Rent of one token account: the most a sell may wrap into the wallet's
+/// WSOL account, and what a buy may wrap beyond what it spends.
+const TOKEN_ACCOUNT_RENT_LAMPORTS: u64 = 2_039_280;
+
+static POLICY: OnceLock<SignPolicy> = OnceLock::new();
+
+/// What a transaction from the exit API or the stream may do with the
+/// wallet key before it is signed: call only allowed programs, swap in the
+/// direction asked for, create, wrap and close the wallet's own token
+/// accounts, set its compute budget and pay the sender tip. Anything else
+/// is refused, so a compromised or buggy server cannot get a drain or an
+/// unasked-for buy signed.
+#[derive(Debug)]
+struct SignPolicy {
+    max_tip_lamports: u64,
+    programs: HashSet<Pubkey>,
+}
+
+impl SignPolicy {
+    fn new<S: AsRef<str>>(max_tip_lamports: u64, programs: &[S]) -> Self {
+        Self {
+            max_tip_lamports,
+            programs: programs
+                .iter()
+                .filter_map(|program| Pubkey::from_str(program.as_ref().trim()).ok())
+                .collect(),
+        }
+    }
+}
+
+/// Sets the tip allowance from `account.tip_lamports` and the programs from
+/// `security.allowed_program_ids`. Later calls are ignored.
+pub fn init(tip_lamports: Option<u64>, allowed_program_ids: &[String]) {
+    let _ = POLICY.set(SignPolicy::new(
+        tip_lamports.unwrap_or(DEFAULT_MAX_TIP_LAMPORTS),
+        allowed_program_ids,
+    ));
+}
+
+/// What the wallet asked the server to build. A swap going the other way
+/// is refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intent {
+    Sell,
+    /// A buy spending at most `max_lamports`, slippage included: what its
+    /// swaps may spend at most and what it may wrap into WSOL beyond rent.
+    Buy {
+        max_lamports: u64,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Sell,
+    Buy,
+}
+
+/// Why an unsigned transaction was not signed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyViolation {
+    /// Set when the transaction calls a program outside the allowlist.
+    pub program: Option<Pubkey>,
+    pub reason: String,
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "refused to sign: {}", self.reason)
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+fn violation(reason: impl Into<String>) -> PolicyViolation {
+    PolicyViolation {
+        program: None,
+        reason: reason.into(),
+    }
+}
+
+/// Checks `tx` against the signing policy for `wallet` and `intent`.
+pub fn check(
+    tx: &VersionedTransaction,
+    wallet: &Pubkey,
+    intent: Intent,
+) -> Result<(), PolicyViolation> {
+    let policy = POLICY
+        .get_or_init(|| SignPolicy::new(DEFAULT_MAX_TIP_LAMPORTS, &DEFAULT_ALLOWED_PROGRAM_IDS));
+    check_with(policy, tx, wallet, intent)
+}
+
+fn check_with(
+    policy: &SignPolicy,
+    tx: &VersionedTransaction,
+    wallet: &Pubkey,
+    intent: Intent,
+) -> Result<(), PolicyViolation> {
+    let message = &tx.message;
+    let keys = message.static_account_keys();
+    if keys.first() != Some(wallet) {
+        return Err(violation("the wallet is not the fee payer"));
+    }
+    let signers = message.header().num_required_signatures;
+    if signers != 1 {
+        return Err(violation(format!(
+            "{signers} signers required; a sell has one"
+        )));
+    }
+    // Accounts loaded from lookup tables are not in `keys`; they are never
+    // the wallet's own, which is what the checks below need.
+    let key_at = |index: u8| keys.get(index as usize);
+    let own_wsol = [TOKEN_PROGRAM, TOKEN_2022_PROGRAM].map(|program| {
+        associated_token_address(wallet, &spl_token::native_mint::id(), &pubkey(program))
+    });
+    // What a sell pays out into and a buy pays from: the wallet's own WSOL
+    // and USD1 accounts.
+    let own_quote: Vec<Pubkey> = [
+        spl_token::native_mint::id(),
+        pubkey(crate::market::USD1_MINT),
+    ]
+    .iter()
+    .flat_map(|mint| {
+        [TOKEN_PROGRAM, TOKEN_2022_PROGRAM]
+            .map(|program| associated_token_address(wallet, mint, &pubkey(program)))
+    })
+    .collect();
+    let expected = match intent {
+        Intent::Sell => Direction::Sell,
+        Intent::Buy { .. } => Direction::Buy,
+    };
+    let max_wrap_lamports = match intent {
+        Intent::Sell => TOKEN_ACCOUNT_RENT_LAMPORTS,
+        Intent::Buy { max_lamports } => max_lamports.saturating_add(TOKEN_ACCOUNT_RENT_LAMPORTS),
+    };
+    let mut transferred = 0u64;
+    let mut wrapped = 0u64;
+    let mut spent = 0u64;
+    let mut unit_limit = None;
+    let mut unit_price = 0u64;
+    let instructions = message.instructions();
+    for (index, ix) in instructions.iter().enumerate() {
+        let program = key_at(ix.program_id_index)
+            .ok_or_else(|| violation(format!("instruction {index} has no program")))?;
+        let account = |position: usize| ix.accounts.get(position).and_then(|i| key_at(*i));
+        let refuse = |what: &str| violation(format!("instruction {index}: {what}"));
+        let data = ix.data.as_slice();
+        if !policy.programs.contains(program) {
+            return Err(PolicyViolation {
+                program: Some(*program),
+                reason: format!(
+                    "instruction {index}: program {program} is not in security.allowed_program_ids"
+                ),
+            });
+        }
+        match program.to_string().as_str() {
+            COMPUTE_BUDGET_PROGRAM => match data.first() {
+                Some(2) => unit_limit = read_u32(data).map(u64::from),
+                Some(3) => unit_price = read_u64(data).unwrap_or(u64::MAX),
+                _ => {}
+            },
+            SYSTEM_PROGRAM => {
+                if data.get(..4) != Some(&2u32.to_le_bytes()[..]) {
+                    return Err(refuse("system instruction other than a transfer"));
+                }
+                let lamports = data
+                    .get(4..12)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or_else(|| refuse("malformed transfer"))?;
+                // Wrapping SOL into the wallet's own WSOL account is not a
+                // tip; it has its own, tighter bound below.
+                if account(1).is_some_and(|to| own_wsol.contains(to)) {
+                    wrapped = wrapped.saturating_add(lamports);
+                } else {
+                    transferred = transferred.saturating_add(lamports);
+                }
+            }
+            TOKEN_PROGRAM | TOKEN_2022_PROGRAM => match data.first() {
+                // InitializeAccount names the owner as its third account,
+                // InitializeAccount2 and 3 in the data.
+                Some(1) if account(2) == Some(wallet) => {}
+                Some(16 | 18) if data.get(1..33) == Some(wallet.as_ref()) => {}
+                // CloseAccount must return the rent to the wallet.
+                Some(9) if account(1) == Some(wallet) => {}
+                // SyncNative.
+                Some(17) => {}
+                Some(1 | 16 | 18) => return Err(refuse("token account for another owner")),
+                Some(9) => return Err(refuse("closes a token account to another wallet")),
+                Some(code) => return Err(refuse(&format!("token instruction {code}"))),
+                None => return Err(refuse("empty token instruction")),
+            },
+            // Create and CreateIdempotent, for the wallet's own accounts;
+            // not RecoverNested.
+            ASSOCIATED_TOKEN_PROGRAM
+                if matches!(data, [] | [0] | [1]) && account(2) == Some(wallet) => {}
+            ASSOCIATED_TOKEN_PROGRAM if matches!(data, [] | [0] | [1]) => {
+                return Err(refuse("token account for another owner"))
+            }
+            ASSOCIATED_TOKEN_PROGRAM => return Err(refuse("associated token instruction")),
+            market @ (PUMP_FUN_PROGRAM
+            | PUMP_SWAP_PROGRAM
+            | METEORA_DBC_PROGRAM
+            | METEORA_DAMM_V2_PROGRAM
+            | RAYDIUM_LAUNCHPAD_PROGRAM
+            | RAYDIUM_CPMM_PROGRAM) => {
+                let paid_in = |position| account(position).is_some_and(|a| own_quote.contains(a));
+                match swap_direction(market, data, paid_in) {
+                    Some(Direction::Buy) if expected == Direction::Buy => {
+                        let spend = buy_spend(market, data)
+                            .ok_or_else(|| refuse("a buy whose spend has no bound"))?;
+                        spent = spent.saturating_add(spend);
+                    }
+                    Some(direction) if direction == expected => {}
+                    Some(Direction::Buy) => {
+                        return Err(refuse("a buy, where a sell was asked for"))
+                    }
+                    Some(Direction::Sell) => {
+                        return Err(refuse("a sell, where a buy was asked for"))
+                    }
+                    None => return Err(refuse(&format!("not a recognized swap on {market}"))),
+                }
+            }
+            // Programs added to the allowlist that this version does not
+            // know are trusted as configured.
+            _ => {}
+        }
+    }
+    if transferred > policy.max_tip_lamports {
+        return Err(violation(format!(
+            "transfers {transferred} lamports out of the wallet; the tip allows {}",
+            policy.max_tip_lamports
+        )));
+    }
+    if let Intent::Buy { max_lamports } = intent {
+        if spent > max_lamports {
+            return Err(violation(format!(
+                "buys for up to {spent} lamports; {max_lamports} were asked for"
+            )));
+        }
+    }
+    if wrapped > max_wrap_lamports {
+        return Err(violation(format!(
+            "wraps {wrapped} lamports into WSOL; at most {max_wrap_lamports} for this {}",
+            match intent {
+                Intent::Sell => "sell",
+                Intent::Buy { .. } => "buy",
+            }
+        )));
+    }
+    let units = unit_limit.unwrap_or_else(|| {
+        (instructions.len() as u64 * DEFAULT_UNITS_PER_INSTRUCTION).min(MAX_COMPUTE_UNITS)
+    });
+    let priority_fee = (units as u128 * unit_price as u128 / 1_000_000) as u64;
+    if priority_fee > MAX_PRIORITY_FEE_LAMPORTS {
+        return Err(violation(format!(
+            "priority fee {priority_fee} lamports is above {MAX_PRIORITY_FEE_LAMPORTS}"
+        )));
+    }
+    Ok(())
+}
+
+/// Which way a swap instruction on `market` trades. Pump.fun, PumpSwap and
+/// Raydium Launchpad name it in the instruction; Meteora and Raydium CPMM
+/// swaps go either way, so `paid_in(position)` says whether the account at
+/// that position is one of the wallet's quote accounts. `None` for any
+/// other instruction.
+fn swap_direction(market: &str, data: &[u8], paid_in: impl Fn(usize) -> bool) -> Option<Direction> {
+    let named = |names: &[(&str, Direction)]| {
+        names
+            .iter()
+            .find(|(name, _)| data.starts_with(&anchor_discriminator(name)))
+            .map(|(_, direction)| *direction)
+    };
+    // Input and output token account positions of the either-way swaps.
+    let (names, input, output): (&[&str], usize, usize) = match market {
+        PUMP_FUN_PROGRAM => {
+            return named(&[
+                ("sell", Direction::Sell),
+                ("buy", Direction::Buy),
+                ("buy_exact_sol_in", Direction::Buy),
+            ])
+        }
+        PUMP_SWAP_PROGRAM => {
+            return named(&[
+                ("sell", Direction::Sell),
+                ("buy", Direction::Buy),
+                ("buy_exact_quote_in", Direction::Buy),
+            ])
+        }
+        RAYDIUM_LAUNCHPAD_PROGRAM => {
+            return named(&[
+                ("sell_exact_in", Direction::Sell),
+                ("sell_exact_out", Direction::Sell),
+                ("buy_exact_in", Direction::Buy),
+                ("buy_exact_out", Direction::Buy),
+            ])
+        }
+        METEORA_DBC_PROGRAM => (&["swap", "swap2"], 3, 4),
+        METEORA_DAMM_V2_PROGRAM => (&["swap", "swap2"], 2, 3),
+        RAYDIUM_CPMM_PROGRAM => (&["swap_base_input", "swap_base_output"], 4, 5),
+        _ => return None,
+    };
+    if !names
+        .iter()
+        .any(|name| data.starts_with(&anchor_discriminator(name)))
+    {
+        return None;
+    }
+    match (paid_in(input), paid_in(output)) {
+        (false, true) => Some(Direction::Sell),
+        (true, false) => Some(Direction::Buy),
+        _ => None,
+    }
+}
+
+/// The most a buy instruction on `market` may spend, from its max-cost or
+/// exact-in argument. Meteora's `swap2` names which one by its swap mode:
+/// 0 and 1 spend `amount_0`, 2 (exact out) at most `amount_1`. `None` when
+/// the instruction carries no such bound.
+fn buy_spend(market: &str, data: &[u8]) -> Option<u64> {
+    let called = |name: &str| data.starts_with(&anchor_discriminator(name));
+    let arg = |index: usize| {
+        let at = 8 + index * 8;
+        Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?))
+    };
+    match market {
+        // buy(amount, max_sol_cost), buy_exact_sol_in(spendable_sol_in, ..)
+        PUMP_FUN_PROGRAM if called("buy") => arg(1),
+        PUMP_FUN_PROGRAM if called("buy_exact_sol_in") => arg(0),
+        // buy(base_amount_out, max_quote_amount_in),
+        // buy_exact_quote_in(spendable_quote_in, ..)
+        PUMP_SWAP_PROGRAM if called("buy") => arg(1),
+        PUMP_SWAP_PROGRAM if called("buy_exact_quote_in") => arg(0),
+        // buy_exact_in(amount_in, ..), buy_exact_out(amount_out, maximum_amount_in, ..)
+        RAYDIUM_LAUNCHPAD_PROGRAM if called("buy_exact_in") => arg(0),
+        RAYDIUM_LAUNCHPAD_PROGRAM if called("buy_exact_out") => arg(1),
+        // swap_base_input(amount_in, ..), swap_base_output(max_amount_in, ..)
+        RAYDIUM_CPMM_PROGRAM => arg(0),
+        METEORA_DBC_PROGRAM | METEORA_DAMM_V2_PROGRAM if called("swap") => arg(0),
+        METEORA_DBC_PROGRAM | METEORA_DAMM_V2_PROGRAM if called("swap2") => match data.get(24) {
+            Some(0 | 1) => arg(0),
+            Some(2) => arg(1),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The first eight bytes of an Anchor instruction's data.
+fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("global:{name}").as_bytes());
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash[..8]);
+    discriminator
+}
+
+fn pubkey(address: &str) -> Pubkey {
+    Pubkey::from_str(address).expect("valid program id")
+}
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &pubkey(ASSOCIATED_TOKEN_PROGRAM),
+    )
+    .0
+}
+
+fn read_u32(data: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(1..5)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8]) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(1..9)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::Message;
+    use solana_sdk::transaction::Transaction;
+    use solana_system_interface::instruction as system_instruction;
+
+    fn tx(wallet: &Pubkey, instructions: &[Instruction]) -> VersionedTransaction {
+        VersionedTransaction::from(Transaction::new_unsigned(Message::new(
+            instructions,
+            Some(wallet),
+        )))
+    }
+
+    fn budget(units: u32, micro_lamports: u64) -> [Instruction; 2] {
+        let program = pubkey(COMPUTE_BUDGET_PROGRAM);
+        let mut limit = vec![2];
+        limit.extend_from_slice(&units.to_le_bytes());
+        let mut price = vec![3];
+        price.extend_from_slice(&micro_lamports.to_le_bytes());
+        [
+            Instruction::new_with_bytes(program, &limit, vec![]),
+            Instruction::new_with_bytes(program, &price, vec![]),
+        ]
+    }
+
+    fn close(account: Pubkey, destination: Pubkey, owner: Pubkey) -> Instruction {
+        spl_token::instruction::close_account(&spl_token::id(), &account, &destination, &owner, &[])
+            .unwrap()
+    }
+
+    #[test]
+    fn accepts_a_sell_and_refuses_drains() {
+        let policy = SignPolicy::new(1_000_000, &DEFAULT_ALLOWED_PROGRAM_IDS);
+        let wallet = Pubkey::new_unique();
+        let attacker = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let swap = Instruction::new_with_bytes(
+            pubkey(DEFAULT_ALLOWED_PROGRAM_IDS[5]),
+            &[51, 230, 133, 164, 1, 127, 131, 173],
+            vec![
+                AccountMeta::new(wallet, true),
+                AccountMeta::new(token_account, false),
+            ],
+        );
+        let [limit, price] = budget(200_000, 50_000);
+        let tip = system_instruction::transfer(&wallet, &attacker, 1_000_000);
+        let sell = [
+            limit.clone(),
+            price.clone(),
+            swap.clone(),
+            close(token_account, wallet, wallet),
+            tip,
+        ];
+        assert_eq!(
+            check_with(&policy, &tx(&wallet, &sell), &wallet, Intent::Sell),
+            Ok(())
+        );
+
+        let refused = |instructions: &[Instruction]| {
+            check_with(&policy, &tx(&wallet, instructions), &wallet, Intent::Sell)
+                .unwrap_err()
+                .to_string()
+        };
+        let drain = system_instruction::transfer(&wallet, &attacker, 1_000_001);
+        assert!(refused(&[swap.clone(), drain]).contains("transfers 1000001 lamports"));
+        let close_to_attacker = close(token_account, attacker, wallet);
+        assert!(refused(&[close_to_attacker]).contains("closes a token account"));
+        let transfer_tokens = spl_token::instruction::transfer(
+            &spl_token::id(),
+            &token_account,
+            &attacker,
+            &wallet,
+            &[],
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            refused(&[transfer_tokens]),
+            "refused to sign: instruction 0: token instruction 3"
+        );
+        let unknown = Instruction::new_with_bytes(attacker, &[0], vec![]);
+        let err = check_with(&policy, &tx(&wallet, &[unknown]), &wallet, Intent::Sell).unwrap_err();
+        assert_eq!(err.program, Some(attacker));
+        assert!(err
+            .reason
+            .contains("is not in security.allowed_program_ids"));
+        let [limit, price] = budget(1_400_000, 100_000_000);
+        assert!(refused(&[limit, price, swap.clone()]).contains("priority fee"));
+        assert!(check_with(
+            &policy,
+            &tx(&attacker, std::slice::from_ref(&swap)),
+            &wallet,
+            Intent::Sell
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("not the fee payer"));
+
+        let wsol =
+            associated_token_address(&wallet, &spl_token::native_mint::id(), &spl_token::id());
+        let wrap = system_instruction::transfer(&wallet, &wsol, 5_000_000_000);
+        let sync = spl_token::instruction::sync_native(&spl_token::id(), &wsol).unwrap();
+        assert!(refused(&[wrap.clone(), sync.clone(), swap]).contains("wraps 5000000000 lamports"));
+
+        // Pump.fun buy(amount, max_sol_cost) of up to 5 SOL.
+        let pump_buy = |name: &str, args: [u64; 2]| {
+            let mut data = anchor_discriminator(name).to_vec();
+            data.extend(args.iter().flat_map(|arg| arg.to_le_bytes()));
+            Instruction::new_with_bytes(
+                pubkey(PUMP_FUN_PROGRAM),
+                &data,
+                vec![AccountMeta::new(wallet, true)],
+            )
+        };
+        let buy = pump_buy("buy", [1_000, 5_000_000_000]);
+        assert!(refused(std::slice::from_ref(&buy)).contains("a buy, where a sell was asked for"));
+        let buy_5_sol = Intent::Buy {
+            max_lamports: 5_000_000_000,
+        };
+        let buy_tx = tx(&wallet, &[wrap, sync, buy.clone()]);
+        assert_eq!(check_with(&policy, &buy_tx, &wallet, buy_5_sol), Ok(()));
+        let buy_1_sol = Intent::Buy {
+            max_lamports: 1_000_000_000,
+        };
+        assert!(check_with(&policy, &buy_tx, &wallet, buy_1_sol).is_err());
+        // Spent through the bonding curve without wrapping any SOL.
+        let check_buy = |instruction: Instruction| {
+            check_with(&policy, &tx(&wallet, &[instruction]), &wallet, buy_1_sol)
+        };
+        assert!(check_buy(buy)
+            .unwrap_err()
+            .to_string()
+            .contains("buys for up to 5000000000 lamports"));
+        assert!(check_buy(pump_buy("buy_exact_sol_in", [2_000_000_000, 1])).is_err());
+        assert_eq!(
+            check_buy(pump_buy("buy_exact_sol_in", [1_000_000_000, 1])),
+            Ok(())
+        );
+        let unbounded = Instruction::new_with_bytes(
+            pubkey(PUMP_FUN_PROGRAM),
+            &anchor_discriminator("buy"),
+            vec![AccountMeta::new(wallet, true)],
+        );
+        assert!(check_buy(unbounded)
+            .unwrap_err()
+            .to_string()
+            .contains("spend has no bound"));
+
+        let mint = Pubkey::new_unique();
+        // CreateIdempotent: payer, account, owner, mint, system, token.
+        let create = |owner: &Pubkey| {
+            let account = associated_token_address(owner, &mint, &spl_token::id());
+            Instruction::new_with_bytes(
+                pubkey(ASSOCIATED_TOKEN_PROGRAM),
+                &[1],
+                vec![
+                    AccountMeta::new(wallet, true),
+                    AccountMeta::new(account, false),
+                    AccountMeta::new_readonly(*owner, false),
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new_readonly(pubkey(SYSTEM_PROGRAM), false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+            )
+        };
+        let sell_check = |instruction: Instruction| {
+            check_with(&policy, &tx(&wallet, &[instruction]), &wallet, Intent::Sell)
+        };
+        assert_eq!(sell_check(create(&wallet)), Ok(()));
+        assert!(sell_check(create(&attacker)).is_err());
+        let unknown_instruction = Instruction::new_with_bytes(
+            pubkey(PUMP_FUN_PROGRAM),
+            &anchor_discriminator("withdraw"),
+            vec![],
+        );
+        assert!(refused(&[unknown_instruction]).contains("not a recognized swap"));
+    }
+
+    #[test]
+    fn reads_either_way_swaps_from_their_token_accounts() {
+        let policy = SignPolicy::new(1_000_000, &DEFAULT_ALLOWED_PROGRAM_IDS);
+        let wallet = Pubkey::new_unique();
+        let wsol =
+            associated_token_address(&wallet, &spl_token::native_mint::id(), &spl_token::id());
+        let token_account = Pubkey::new_unique();
+        // Raydium CPMM swap_base_input(amount_in, minimum_amount_out):
+        // input token account at 4, output at 5.
+        let swap = |input: Pubkey, output: Pubkey| {
+            let mut accounts: Vec<_> = (0..4)
+                .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+                .collect();
+            accounts[0] = AccountMeta::new(wallet, true);
+            accounts.push(AccountMeta::new(input, false));
+            accounts.push(AccountMeta::new(output, false));
+            let mut data = anchor_discriminator("swap_base_input").to_vec();
+            data.extend(1_000_000_000u64.to_le_bytes());
+            data.extend(1u64.to_le_bytes());
+            Instruction::new_with_bytes(pubkey(RAYDIUM_CPMM_PROGRAM), &data, accounts)
+        };
+        let check = |instruction: Instruction, intent| {
+            check_with(&policy, &tx(&wallet, &[instruction]), &wallet, intent)
+        };
+        let buy = Intent::Buy {
+            max_lamports: 1_000_000_000,
+        };
+        assert_eq!(check(swap(token_account, wsol), Intent::Sell), Ok(()));
+        assert!(check(swap(wsol, token_account), Intent::Sell).is_err());
+        assert_eq!(check(swap(wsol, token_account), buy), Ok(()));
+        let smaller_buy = Intent::Buy {
+            max_lamports: 999_999_999,
+        };
+        assert!(check(swap(wsol, token_account), smaller_buy).is_err());
+        let elsewhere = Pubkey::new_unique();
+        assert!(check(swap(token_account, elsewhere), Intent::Sell).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use solana_sdk::hash::Hash;
+use solana_sdk::transaction::VersionedTransaction;
+
+use crate::network::rpc_result;
+
+/// The transaction's recent blockhash is no longer valid, so sending it can
+/// only end in a confirm timeout.
+#[derive(Debug)]
+pub struct BlockhashExpired {
+    pub blockhash: Hash,
+}
+
+impl std::fmt::Display for BlockhashExpired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "recent blockhash {} has expired", self.blockhash)
+    }
+}
+
+impl std::error::Error for BlockhashExpired {}
+
+/// Asks the RPC node whether `tx`'s recent blockhash can still land. An
+/// expired one comes back as [`BlockhashExpired`]; any other error means the
+/// check itself could not be made. `tx` is the unsigned transaction, so one
+/// that cannot land is never signed.
+pub async fn check_blockhash(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    tx: &VersionedTransaction,
+) -> Result<()> {
+    let blockhash = *tx.message.recent_blockhash();
+    let result = rpc_result(
+        http,
+        rpc_url,
+        "isBlockhashValid",
+        serde_json::json!([blockhash.to_string(), { "commitment": "processed" }]),
+    )
+    .await?;
+    match blockhash_valid(&result) {
+        Some(true) => Ok(()),
+        Some(false) => Err(BlockhashExpired { blockhash }.into()),
+        None => Err(anyhow!("isBlockhashValid result missing value")),
+    }
+}
+
+fn blockhash_valid(result: &Value) -> Option<bool> {
+    result.get("value")?.as_bool()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_validity_flag() {
+        let result = |value: Value| serde_json::json!({ "context": { "slot": 1 }, "value": value });
+        assert_eq!(blockhash_valid(&result(Value::Bool(true))), Some(true));
+        assert_eq!(blockhash_valid(&result(Value::Bool(false))), Some(false));
+        assert_eq!(blockhash_valid(&result(Value::Null)), None);
+    }
+}
@@ -0,0 +1,336 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use lasersell_sdk::tx::{send_transaction_b64_to, SendTarget, TxSubmitError};
+use reqwest::Client;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+use crate::config::{Config, JitoSenderConfig, WebhookSenderConfig};
+
+const ERROR_BODY_SNIPPET_LEN: usize = 220;
+
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<String, TxSubmitError>> + Send + 'a>>;
+
+/// Submits a signed sell and returns its signature. Confirmation is left to
+/// [`super::send_tx`], so a backend only has to get the tx accepted. Errors
+/// are [`TxSubmitError`]s so the retry policy classifies every backend the
+/// same way.
+pub trait SellSender: Send + Sync {
+    /// Short name for logs.
+    fn label(&self) -> &'static str;
+
+    fn send<'a>(&'a self, http: &'a Client, tx_b64: &'a str) -> SendFuture<'a>;
+}
+
+/// `helius_sender`, `astralane` and `rpc` are submitted by the SDK.
+impl SellSender for SendTarget {
+    fn label(&self) -> &'static str {
+        self.target_label()
+    }
+
+    fn send<'a>(&'a self, http: &'a Client, tx_b64: &'a str) -> SendFuture<'a> {
+        Box::pin(send_transaction_b64_to(http, self, tx_b64))
+    }
+}
+
+/// `jito`: a block engine's `sendTransaction`.
+struct JitoSender(JitoSenderConfig);
+
+impl SellSender for JitoSender {
+    fn label(&self) -> &'static str {
+        "jito"
+    }
+
+    fn send<'a>(&'a self, http: &'a Client, tx_b64: &'a str) -> SendFuture<'a> {
+        Box::pin(async move {
+            let headers: Vec<(&str, &str)> = self
+                .0
+                .uuid
+                .iter()
+                .map(|uuid| ("x-jito-auth", uuid.as_str()))
+                .collect();
+            post_send_transaction(http, self.0.url.trim(), "jito", tx_b64, &headers).await
+        })
+    }
+}
+
+/// `webhook`: any endpoint that answers `sendTransaction` like an RPC node.
+struct WebhookSender(WebhookSenderConfig);
+
+impl SellSender for WebhookSender {
+    fn label(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send<'a>(&'a self, http: &'a Client, tx_b64: &'a str) -> SendFuture<'a> {
+        Box::pin(async move {
+            let headers: Vec<(&str, &str)> = self
+                .0
+                .headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            post_send_transaction(http, self.0.url.trim(), "webhook", tx_b64, &headers).await
+        })
+    }
+}
+
+/// `rpc` with `account.send_rpc_url`: the SDK's RPC submit on a client of
+/// its own, so a send never waits on connections or timeouts tuned for the
+/// read RPC.
+struct SendRpcSender {
+    target: SendTarget,
+    http: Client,
+}
+
+impl SellSender for SendRpcSender {
+    fn label(&self) -> &'static str {
+        self.target.target_label()
+    }
+
+    fn send<'a>(&'a self, _http: &'a Client, tx_b64: &'a str) -> SendFuture<'a> {
+        Box::pin(send_transaction_b64_to(&self.http, &self.target, tx_b64))
+    }
+}
+
+/// `race`: sends to every target at once and returns the first signature.
+/// The slower sends still run to completion; they carry the same tx, so at
+/// most one lands.
+struct RaceSender {
+    senders: Vec<Arc<dyn SellSender>>,
+}
+
+impl SellSender for RaceSender {
+    fn label(&self) -> &'static str {
+        "race"
+    }
+
+    fn send<'a>(&'a self, http: &'a Client, tx_b64: &'a str) -> SendFuture<'a> {
+        Box::pin(async move {
+            let (result_tx, mut result_rx) = mpsc::unbounded_channel();
+            for sender in &self.senders {
+                let sender = sender.clone();
+                let http = http.clone();
+                let tx_b64 = tx_b64.to_string();
+                let result_tx = result_tx.clone();
+                tokio::spawn(async move {
+                    let result = sender.send(&http, &tx_b64).await;
+                    if let Err(err) = &result {
+                        debug!(event = "race_send_failed", target = sender.label(), error = %err);
+                    }
+                    let _ = result_tx.send(result);
+                });
+            }
+            drop(result_tx);
+            let mut last_error = None;
+            while let Some(result) = result_rx.recv().await {
+                match result {
+                    Ok(signature) => return Ok(signature),
+                    Err(err) => last_error = Some(err),
+                }
+            }
+            Err(last_error.unwrap_or_else(|| TxSubmitError::MissingResult {
+                target: "race",
+                response: "no targets".to_string(),
+            }))
+        })
+    }
+}
+
+/// The sender for the configured send target (see
+/// [`Config::send_target_name`]).
+pub fn from_config(cfg: &Config) -> Result<Arc<dyn SellSender>> {
+    match cfg.send_target_name() {
+        "race" => {
+            let senders = cfg
+                .sell
+                .senders
+                .race
+                .iter()
+                .map(|target| sender_for(cfg, target.trim()))
+                .collect::<Result<Vec<_>>>()?;
+            if senders.len() < 2 {
+                return Err(anyhow!("sell.senders.race needs at least two targets"));
+            }
+            Ok(Arc::new(RaceSender { senders }))
+        }
+        target => sender_for(cfg, target),
+    }
+}
+
+fn sender_for(cfg: &Config, target: &str) -> Result<Arc<dyn SellSender>> {
+    if let ("rpc", Some(url)) = (target, cfg.send_rpc_url()) {
+        let http = cfg
+            .http_client_builder()?
+            .connect_timeout(cfg.send_rpc_connect_timeout())
+            .timeout(cfg.send_rpc_request_timeout())
+            .build()?;
+        return Ok(Arc::new(SendRpcSender {
+            target: SendTarget::Rpc { url },
+            http,
+        }));
+    }
+    if let Some(send_target) = cfg.resolve_send_target(target)? {
+        return Ok(Arc::new(send_target));
+    }
+    match target {
+        "jito" => Ok(Arc::new(JitoSender(
+            cfg.sell.senders.jito.clone().unwrap_or_default(),
+        ))),
+        "webhook" => {
+            let webhook = cfg.sell.senders.webhook.clone().ok_or_else(|| {
+                anyhow!("sell.senders.webhook.url is required for send_target \"webhook\"")
+            })?;
+            Ok(Arc::new(WebhookSender(webhook)))
+        }
+        other => Err(anyhow!("unknown send_target \"{other}\"")),
+    }
+}
+
+/// Posts a JSON-RPC `sendTransaction` for `tx_b64` the way the SDK does for
+/// its own targets, preflight skipped.
+async fn post_send_transaction(
+    http: &Client,
+    endpoint: &str,
+    target: &'static str,
+    tx_b64: &str,
+    headers: &[(&str, &str)],
+) -> Result<String, TxSubmitError> {
+    let payload = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "sendTransaction",
+        "params": [tx_b64, { "encoding": "base64", "skipPreflight": true, "maxRetries": 0 }],
+    });
+    debug!(event = "tx_submitting", target);
+    let mut request = http.post(endpoint).json(&payload);
+    for &(name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|source| TxSubmitError::RequestSend {
+            target,
+            kind: if source.is_timeout() {
+                "timeout"
+            } else if source.is_connect() {
+                "connect"
+            } else {
+                "send"
+            },
+            source,
+        })?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|source| TxSubmitError::ResponseRead {
+            target,
+            kind: if source.is_timeout() {
+                "timeout"
+            } else {
+                "read"
+            },
+            source,
+        })?;
+    if !status.is_success() {
+        return Err(TxSubmitError::HttpStatus {
+            target,
+            status,
+            body: body.chars().take(ERROR_BODY_SNIPPET_LEN).collect(),
+        });
+    }
+    let signature = parse_send_response(target, &body)?;
+    info!(event = "tx_submitted", target, signature = %signature);
+    Ok(signature)
+}
+
+fn parse_send_response(target: &'static str, body: &str) -> Result<String, TxSubmitError> {
+    let parsed: Value =
+        serde_json::from_str(body).map_err(|source| TxSubmitError::DecodeResponse {
+            target,
+            source,
+            body: body.chars().take(ERROR_BODY_SNIPPET_LEN).collect(),
+        })?;
+    if let Some(error) = parsed.get("error") {
+        return Err(TxSubmitError::RpcError {
+            target,
+            error: error.to_string(),
+        });
+    }
+    parsed
+        .get("result")
+        .and_then(Value::as_str)
+        .filter(|signature| !signature.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| TxSubmitError::MissingResult {
+            target,
+            response: parsed.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(Result<&'static str, &'static str>);
+
+    impl SellSender for Fixed {
+        fn label(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn send<'a>(&'a self, _http: &'a Client, _tx_b64: &'a str) -> SendFuture<'a> {
+            let result = self
+                .0
+                .map(str::to_string)
+                .map_err(|error| TxSubmitError::RpcError {
+                    target: "fixed",
+                    error: error.to_string(),
+                });
+            Box::pin(async move { result })
+        }
+    }
+
+    #[tokio::test]
+    async fn race_returns_the_first_accepted_signature() {
+        let http = Client::new();
+        let race = RaceSender {
+            senders: vec![Arc::new(Fixed(Err("busy"))), Arc::new(Fixed(Ok("5ig")))],
+        };
+        assert_eq!(race.send(&http, "tx").await.unwrap(), "5ig");
+
+        let race = RaceSender {
+            senders: vec![Arc::new(Fixed(Err("busy"))), Arc::new(Fixed(Err("busy")))],
+        };
+        assert!(matches!(
+            race.send(&http, "tx").await,
+            Err(TxSubmitError::RpcError { .. })
+        ));
+    }
+
+    #[test]
+    fn send_responses_map_to_submit_errors() {
+        assert_eq!(
+            parse_send_response("jito", r#"{"jsonrpc":"2.0","result":"5ig","id":1}"#).unwrap(),
+            "5ig"
+        );
+        assert!(matches!(
+            parse_send_response("jito", r#"{"error":{"code":-32602}}"#),
+            Err(TxSubmitError::RpcError { .. })
+        ));
+        assert!(matches!(
+            parse_send_response("webhook", r#"{"result":""}"#),
+            Err(TxSubmitError::MissingResult { .. })
+        ));
+        assert!(matches!(
+            parse_send_response("webhook", "<html>"),
+            Err(TxSubmitError::DecodeResponse { .. })
+        ));
+    }
+}
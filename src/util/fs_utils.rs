@@ -10,6 +10,9 @@ use std::os::unix::fs::OpenOptionsExt;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+/// Writes `contents` to a temp file beside `path` and renames it into
+/// place. `mode` sets Unix permissions; on Windows any `mode` limits the
+/// file's ACL to the current user instead.
 pub fn atomic_write(path: &Path, contents: &[u8], mode: Option<u32>) -> Result<()> {
     let parent = path.parent().unwrap_or_else(|| Path::new("."));
     fs::create_dir_all(parent).with_context(|| format!("create dir {}", parent.display()))?;
@@ -41,7 +44,43 @@ pub fn atomic_write(path: &Path, contents: &[u8], mode: Option<u32>) -> Result<(
         let perms = fs::Permissions::from_mode(mode);
         let _ = fs::set_permissions(&tmp_path, perms);
     }
+    #[cfg(windows)]
+    if mode.is_some() {
+        let _ = restrict_to_owner(&tmp_path);
+    }
 
     fs::rename(&tmp_path, path).with_context(|| format!("replace file {}", path.display()))?;
     Ok(())
 }
+
+/// Replaces the ACL of `path` with full control for the current user only,
+/// the Windows counterpart of mode 0600 (0700 for directories, whose
+/// grant is inherited by what is created inside them).
+#[cfg(windows)]
+pub fn restrict_to_owner(path: &Path) -> std::io::Result<()> {
+    use std::process::{Command, Stdio};
+
+    let user = std::env::var("USERNAME")
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::NotFound, "USERNAME is not set"))?;
+    let user = match std::env::var("USERDOMAIN") {
+        Ok(domain) if !domain.is_empty() => format!("{domain}\\{user}"),
+        _ => user,
+    };
+    let grant = if path.is_dir() {
+        format!("{user}:(OI)(CI)F")
+    } else {
+        format!("{user}:F")
+    };
+    let status = Command::new("icacls")
+        .arg(path)
+        .args(["/inheritance:r", "/grant:r"])
+        .arg(grant)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("icacls exited with {status}")))
+    }
+}
@@ -1,8 +1,16 @@
-use std::collections::HashSet;
-use std::io::{self, Write};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
+use std::time::Duration;
+
+use parking_lot::Mutex;
 
 use reqwest::Url;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Metadata};
 
 static REDACTIONS: OnceLock<Vec<String>> = OnceLock::new();
 
@@ -166,6 +174,355 @@ pub fn redact_url(raw: &str) -> String {
     "<invalid-url>".to_string()
 }
 
+/// Coarse grouping of `event = "..."` log lines, toggled via `logging.*`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogCategory {
+    Balance,
+    Stream,
+    Sells,
+    Rpc,
+}
+
+pub fn event_category(event: &str) -> Option<LogCategory> {
+    if event.contains("_fetch_") || event.starts_with("rpc_") {
+        Some(LogCategory::Rpc)
+    } else if event.contains("balance")
+        || matches!(event, "position_tokens_updated" | "cost_basis_set" | "pnl_update")
+    {
+        Some(LogCategory::Balance)
+    } else if event.starts_with("stream_")
+        || event.starts_with("mirror_")
+        || matches!(
+            event,
+            "app_stream_event"
+                | "liquidity_snapshot"
+                | "wallet_registered"
+                | "position_closed"
+                | "app_position_opened"
+                | "mint_detected"
+        )
+    {
+        Some(LogCategory::Stream)
+    } else if ["sell_", "app_autosell_", "autosell_", "session_", "app_exit_", "exit_signal_"]
+        .iter()
+        .any(|prefix| event.starts_with(prefix))
+        || event == "deadline_exhausted"
+    {
+        Some(LogCategory::Sells)
+    } else {
+        None
+    }
+}
+
+/// Drops info/debug events whose category is switched off. Warnings and
+/// errors always pass so failures are never hidden.
+#[derive(Clone, Debug, Default)]
+pub struct CategoryFilter {
+    disabled: Vec<LogCategory>,
+}
+
+impl CategoryFilter {
+    pub fn new(disabled: Vec<LogCategory>) -> Self {
+        Self { disabled }
+    }
+
+    fn allows(&self, level: &Level, event: Option<&str>) -> bool {
+        if *level > console_view().lock().min_level {
+            return false;
+        }
+        if *level <= Level::WARN || self.disabled.is_empty() {
+            return true;
+        }
+        match event.and_then(event_category) {
+            Some(category) => !self.disabled.contains(&category),
+            None => true,
+        }
+    }
+}
+
+impl<S> tracing_subscriber::layer::Filter<S> for CategoryFilter {
+    fn enabled(&self, _meta: &Metadata<'_>, _cx: &tracing_subscriber::layer::Context<'_, S>) -> bool {
+        true
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, _cx: &tracing_subscriber::layer::Context<'_, S>) -> bool {
+        let mut visitor = EventNameVisitor(None);
+        event.record(&mut visitor);
+        self.allows(event.metadata().level(), visitor.0.as_deref())
+    }
+}
+
+struct EventNameVisitor(Option<String>);
+
+impl Visit for EventNameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "event" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "event" && self.0.is_none() {
+            self.0 = Some(format!("{value:?}").trim_matches('"').to_string());
+        }
+    }
+}
+
+/// Lines kept while the console is paused; older ones are dropped.
+const CONSOLE_HOLD_LINES: usize = 2_000;
+
+/// Live adjustments to console output, typed as `log ...` commands.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleLogCommand {
+    /// Show only this level and more severe ones.
+    MinLevel(Level),
+    /// Show only lines containing the text (case-insensitive); `None` clears.
+    Search(Option<String>),
+    Pause,
+    Resume,
+    /// `Pause` while output flows, `Resume` while it is held.
+    TogglePause,
+}
+
+/// Size/age rotation for the on-disk logs. Rotated copies are kept as
+/// `<name>.1` (newest) through `<name>.<retention>`.
+#[derive(Clone, Copy, Debug)]
+pub struct LogRotation {
+    /// 0 disables the size cap.
+    pub max_bytes: u64,
+    pub max_age: Option<Duration>,
+    pub retention: usize,
+}
+
+impl LogRotation {
+    /// Rotates `path` when it is over the size cap or older than `max_age`.
+    /// Returns whether it rotated.
+    pub fn rotate_if_needed(&self, path: &Path) -> io::Result<bool> {
+        let meta = match fs::metadata(path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        if meta.len() == 0 {
+            return Ok(false);
+        }
+        let too_big = self.max_bytes > 0 && meta.len() >= self.max_bytes;
+        // Birth time is not available everywhere; without it only size applies.
+        let too_old = self.max_age.is_some_and(|max_age| {
+            meta.created()
+                .ok()
+                .and_then(|created| created.elapsed().ok())
+                .is_some_and(|age| age >= max_age)
+        });
+        if !too_big && !too_old {
+            return Ok(false);
+        }
+        self.rotate(path)?;
+        Ok(true)
+    }
+
+    fn rotate(&self, path: &Path) -> io::Result<()> {
+        if self.retention == 0 {
+            return fs::remove_file(path);
+        }
+        let _ = fs::remove_file(super::paths::rotated_log_path(path, self.retention));
+        for n in (1..self.retention).rev() {
+            let from = super::paths::rotated_log_path(path, n);
+            if from.exists() {
+                fs::rename(&from, super::paths::rotated_log_path(path, n + 1))?;
+            }
+        }
+        fs::rename(path, super::paths::rotated_log_path(path, 1))
+    }
+}
+
+/// Sizes of each log and its rotated copies, plus the last error line.
+pub fn logs_report(error_log: &Path, other_logs: &[PathBuf]) -> String {
+    let mut out = String::new();
+    for path in std::iter::once(error_log).chain(other_logs.iter().map(PathBuf::as_path)) {
+        let size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        let (mut rotated, mut rotated_bytes) = (0, 0);
+        while let Ok(meta) = fs::metadata(super::paths::rotated_log_path(path, rotated + 1)) {
+            rotated += 1;
+            rotated_bytes += meta.len();
+        }
+        let _ = writeln!(
+            out,
+            "{}  {}  (+{rotated} rotated, {})",
+            path.display(),
+            format_bytes(size),
+            format_bytes(rotated_bytes)
+        );
+    }
+    let last_error = last_line(error_log)
+        .or_else(|| last_line(&super::paths::rotated_log_path(error_log, 1)));
+    let _ = writeln!(out, "last error  {}", last_error.as_deref().unwrap_or("none"));
+    out
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1_023 => format!("{bytes} B"),
+        1_024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1_024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Last non-empty line, reading only the tail of the file.
+fn last_line(path: &Path) -> Option<String> {
+    const TAIL_BYTES: u64 = 16 * 1_024;
+    let mut file = fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES))).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line.trim().to_string())
+}
+
+/// Console filter state shared by [`CategoryFilter`] and [`ConsoleWriter`].
+#[derive(Debug)]
+pub struct ConsoleView {
+    min_level: Level,
+    search: Option<String>,
+    paused: bool,
+    held: VecDeque<String>,
+    dropped: usize,
+}
+
+impl Default for ConsoleView {
+    fn default() -> Self {
+        Self {
+            min_level: Level::TRACE,
+            search: None,
+            paused: false,
+            held: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+}
+
+impl ConsoleView {
+    /// Applies `cmd`, returning held lines to print when it resumes output.
+    pub fn apply(&mut self, cmd: ConsoleLogCommand) -> Vec<String> {
+        match cmd {
+            ConsoleLogCommand::TogglePause if self.paused => {
+                return self.apply(ConsoleLogCommand::Resume);
+            }
+            ConsoleLogCommand::TogglePause => self.paused = true,
+            ConsoleLogCommand::MinLevel(level) => self.min_level = level,
+            ConsoleLogCommand::Search(text) => {
+                self.search = text.map(|text| text.to_lowercase());
+            }
+            ConsoleLogCommand::Pause => self.paused = true,
+            ConsoleLogCommand::Resume => {
+                self.paused = false;
+                let mut lines: Vec<String> = self.held.drain(..).collect();
+                if self.dropped > 0 {
+                    lines.insert(0, format!("... {} log lines dropped while paused\n", self.dropped));
+                    self.dropped = 0;
+                }
+                return lines;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Returns the line if it should be printed now; holds it while paused.
+    fn admit(&mut self, line: String) -> Option<String> {
+        if let Some(search) = self.search.as_deref() {
+            if !strip_ansi(&line).to_lowercase().contains(search) {
+                return None;
+            }
+        }
+        if self.paused {
+            if self.held.len() >= CONSOLE_HOLD_LINES {
+                self.held.pop_front();
+                self.dropped += 1;
+            }
+            self.held.push_back(line);
+            return None;
+        }
+        Some(line)
+    }
+}
+
+fn console_view() -> &'static Mutex<ConsoleView> {
+    static VIEW: OnceLock<Mutex<ConsoleView>> = OnceLock::new();
+    VIEW.get_or_init(|| Mutex::new(ConsoleView::default()))
+}
+
+pub fn apply_console_command(cmd: ConsoleLogCommand) {
+    let flushed = console_view().lock().apply(cmd);
+    let mut out = io::stdout().lock();
+    for line in flushed {
+        let _ = out.write_all(line.as_bytes());
+    }
+    let _ = out.flush();
+}
+
+pub fn strip_ansi(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\u{1b}' {
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            output.push(ch);
+        }
+    }
+    output
+}
+
+/// Stdout writer for the console layer that applies the live search and
+/// pause state to each complete line.
+#[derive(Default)]
+pub struct ConsoleWriter {
+    buffer: Vec<u8>,
+}
+
+impl ConsoleWriter {
+    fn emit_line(&mut self, line: Vec<u8>) -> io::Result<()> {
+        let line = super::theme::current().log_line(String::from_utf8_lossy(&line).into_owned());
+        if let Some(line) = console_view().lock().admit(line) {
+            io::stdout().lock().write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for ConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            self.emit_line(line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            self.emit_line(line)?;
+        }
+        io::stdout().flush()
+    }
+}
+
+impl Drop for ConsoleWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +579,68 @@ mod tests {
         let input = "Authorization: Bearer abc.def";
         assert_eq!(scrub_sensitive(input), "Authorization: Bearer <redacted>");
     }
+
+    #[test]
+    fn category_filter_hides_only_quiet_levels() {
+        assert_eq!(event_category("usd1_balance_update"), Some(LogCategory::Balance));
+        assert_eq!(event_category("wallet_balance_fetch_error"), Some(LogCategory::Rpc));
+        assert_eq!(event_category("stream_connected"), Some(LogCategory::Stream));
+        assert_eq!(event_category("app_autosell_attempt"), Some(LogCategory::Sells));
+        assert_eq!(event_category("startup"), None);
+
+        let filter = CategoryFilter::new(vec![LogCategory::Balance]);
+        assert!(!filter.allows(&Level::INFO, Some("balance_update")));
+        assert!(filter.allows(&Level::WARN, Some("balance_update")));
+        assert!(filter.allows(&Level::INFO, Some("sell_complete")));
+        assert!(filter.allows(&Level::INFO, None));
+    }
+
+    #[test]
+    fn rotation_shifts_copies_and_drops_the_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("error.log");
+        let rotation = LogRotation {
+            max_bytes: 8,
+            max_age: None,
+            retention: 2,
+        };
+        for round in ["first", "second", "third"] {
+            fs::write(&log, format!("{round} error\n")).unwrap();
+            assert!(rotation.rotate_if_needed(&log).unwrap());
+        }
+        assert!(!log.exists());
+        let rotated =
+            |n| fs::read_to_string(crate::util::paths::rotated_log_path(&log, n)).unwrap();
+        assert_eq!(rotated(1), "third error\n");
+        assert_eq!(rotated(2), "second error\n");
+        assert!(!crate::util::paths::rotated_log_path(&log, 3).exists());
+
+        fs::write(&log, "tiny").unwrap();
+        assert!(!rotation.rotate_if_needed(&log).unwrap());
+        fs::write(&log, "WARN first\nERROR disk full\n\n").unwrap();
+        let report = logs_report(&log, &[]);
+        assert!(report.contains("+2 rotated"));
+        assert!(report.ends_with("last error  ERROR disk full\n"));
+    }
+
+    #[test]
+    fn console_view_searches_and_holds_while_paused() {
+        let mut view = ConsoleView::default();
+        view.apply(ConsoleLogCommand::Search(Some("Sell".to_string())));
+        assert!(view
+            .admit("\u{1b}[3mevent\u{1b}[0m=sell_complete\n".to_string())
+            .is_some());
+        assert!(view.admit("event=balance_update\n".to_string()).is_none());
+        view.apply(ConsoleLogCommand::Search(None));
+        view.apply(ConsoleLogCommand::Pause);
+        assert!(view.admit("held\n".to_string()).is_none());
+        assert_eq!(view.apply(ConsoleLogCommand::Resume), vec!["held\n".to_string()]);
+        assert_eq!(view.admit("live\n".to_string()).as_deref(), Some("live\n"));
+        view.apply(ConsoleLogCommand::TogglePause);
+        assert!(view.admit("held\n".to_string()).is_none());
+        assert_eq!(
+            view.apply(ConsoleLogCommand::TogglePause),
+            vec!["held\n".to_string()]
+        );
+    }
 }
@@ -1,6 +1,13 @@
+pub mod clock;
 pub mod format;
 pub mod fs_utils;
 pub mod logging;
 pub mod paths;
+pub mod self_update;
 pub mod support;
+pub mod support_bundle;
+pub mod systemd;
+pub mod terminal;
+pub mod theme;
 pub mod update_check;
+pub mod warn_dedup;
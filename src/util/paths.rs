@@ -1,21 +1,85 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use anyhow::{anyhow, Context, Result};
 use directories::BaseDirs;
+#[cfg(windows)]
+use directories::ProjectDirs;
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// `~/.lasersell`. On Windows, `%LOCALAPPDATA%\lasersell\data` unless the
+/// user already has a `~/.lasersell`.
 pub fn default_data_dir() -> Result<PathBuf> {
     let base_dirs = BaseDirs::new().ok_or_else(|| anyhow!("unable to determine home directory"))?;
-    Ok(base_dirs.home_dir().join(".lasersell"))
+    let home_dir = base_dirs.home_dir().join(".lasersell");
+    #[cfg(windows)]
+    if !home_dir.exists() {
+        if let Some(dirs) = ProjectDirs::from("", "", "lasersell") {
+            return Ok(dirs.data_local_dir().to_path_buf());
+        }
+    }
+    Ok(home_dir)
 }
 
 pub fn default_config_path() -> Result<PathBuf> {
     Ok(default_data_dir()?.join("config.yml"))
 }
 
+/// Where `--profile <name>` configs live.
+pub fn profiles_dir() -> Result<PathBuf> {
+    Ok(default_data_dir()?.join("profiles"))
+}
+
+/// `~/.lasersell/profiles/<name>.yml`. Names are a single path component of
+/// letters, digits, `-` and `_`.
+pub fn profile_config_path(name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow!(
+            "profile name {name:?} may only use letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(profiles_dir()?.join(format!("{name}.yml")))
+}
+
+/// Moves the trade history and sell state below to `profiles/<name>/` for
+/// the rest of the process, so profiles do not share them. Set once at
+/// startup for `--profile <name>`; later calls are ignored.
+pub fn set_profile(name: &str) {
+    let _ = PROFILE.set(name.to_string());
+}
+
+/// The data dir, or `profiles/<name>/` under `--profile <name>`.
+pub fn profile_data_dir() -> Result<PathBuf> {
+    match PROFILE.get() {
+        Some(name) => Ok(profiles_dir()?.join(name)),
+        None => default_data_dir(),
+    }
+}
+
+/// Names of the profiles in `profiles_dir`, sorted.
+pub fn list_profiles() -> Vec<String> {
+    let Ok(entries) = profiles_dir().and_then(|dir| Ok(fs::read_dir(dir)?)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            (path.extension()? == "yml").then(|| path.file_stem()?.to_str().map(str::to_string))?
+        })
+        .collect();
+    names.sort();
+    names
+}
+
 pub fn default_error_log_path() -> Result<PathBuf> {
     Ok(default_data_dir()?.join("error.log"))
 }
@@ -24,14 +88,75 @@ pub fn default_debug_log_path() -> Result<PathBuf> {
     Ok(default_data_dir()?.join("debug.log"))
 }
 
+/// `error.log` -> `error.log.<n>`; `.1` is the most recent rotation.
+pub fn rotated_log_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{n}"));
+    path.with_file_name(name)
+}
+
+pub fn default_journal_path() -> Result<PathBuf> {
+    Ok(profile_data_dir()?.join("journal.ndjson"))
+}
+
+pub fn default_imported_positions_path() -> Result<PathBuf> {
+    Ok(profile_data_dir()?.join("imported_positions.json"))
+}
+
+pub fn default_limits_path() -> Result<PathBuf> {
+    Ok(profile_data_dir()?.join("limits.json"))
+}
+
+/// Strategy insights hidden with `insights dismiss <id>`.
+pub fn default_dismissed_insights_path() -> Result<PathBuf> {
+    Ok(profile_data_dir()?.join("dismissed_insights.json"))
+}
+
+/// Exit signals already sold, so a resent one is not sold again.
+pub fn default_completed_sells_path() -> Result<PathBuf> {
+    Ok(profile_data_dir()?.join("completed_sells.json"))
+}
+
+/// Every transaction signed with the wallet key, hash-chained.
+pub fn default_tx_audit_path() -> Result<PathBuf> {
+    Ok(profile_data_dir()?.join("tx_audit.ndjson"))
+}
+
+/// `logging.event_journal` appends every app event here.
+pub fn default_event_journal_path() -> Result<PathBuf> {
+    Ok(default_data_dir()?.join("events.ndjson"))
+}
+
+/// The running daemon's socket for `lasersell attach`.
+pub fn attach_socket_path() -> Result<PathBuf> {
+    Ok(default_data_dir()?.join("attach.sock"))
+}
+
+/// Per-session reports go under `reports/<date>/`.
+pub fn default_reports_dir() -> Result<PathBuf> {
+    Ok(default_data_dir()?.join("reports"))
+}
+
+/// Creates the data dir, and the profile's own dir under `--profile`.
 pub fn ensure_data_dir_exists() -> Result<()> {
-    let dir = default_data_dir()?;
+    create_private_dir(&default_data_dir()?)?;
+    if PROFILE.get().is_some() {
+        create_private_dir(&profile_data_dir()?)?;
+    }
+    Ok(())
+}
+
+fn create_private_dir(dir: &Path) -> Result<()> {
     let existed = dir.exists();
-    fs::create_dir_all(&dir).with_context(|| format!("create data dir {}", dir.display()))?;
+    fs::create_dir_all(dir).with_context(|| format!("create data dir {}", dir.display()))?;
     #[cfg(unix)]
     if !existed {
         let perms = fs::Permissions::from_mode(0o700);
-        let _ = fs::set_permissions(&dir, perms);
+        let _ = fs::set_permissions(dir, perms);
+    }
+    #[cfg(windows)]
+    if !existed {
+        let _ = super::fs_utils::restrict_to_owner(dir);
     }
     Ok(())
 }
@@ -0,0 +1,168 @@
+use std::env;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use super::update_check;
+
+const RELEASES_URL: &str = "https://dl.lasersell.io/binaries/lasersell";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+/// The ed25519 key releases are signed with, as a base58 pubkey. Release
+/// builds set it; a build without it cannot verify a download and refuses
+/// to update itself.
+const RELEASE_SIGNING_KEY: Option<&str> = option_env!("LASERSELL_RELEASE_SIGNING_KEY");
+
+/// Whether this build can verify a release, and so update itself.
+pub fn available() -> bool {
+    RELEASE_SIGNING_KEY.is_some()
+}
+
+/// `self-update`: downloads the latest release for this platform, checks
+/// its signature and replaces the running binary with it. The daemon must
+/// be restarted to run the new version.
+pub async fn run() -> Result<()> {
+    let signing_key = RELEASE_SIGNING_KEY
+        .ok_or_else(|| {
+            anyhow!("this build has no release signing key; reinstall from dl.lasersell.io")
+        })
+        .and_then(|key| Pubkey::from_str(key).context("parse release signing key"))?;
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .context("build http client")?;
+    let current = env!("CARGO_PKG_VERSION");
+    let latest = update_check::fetch_latest(&client)
+        .await
+        .ok_or_else(|| anyhow!("could not read the latest version from dl.lasersell.io"))?;
+    if !update_check::is_newer(current, &latest) {
+        println!("lasersell {current} is up to date");
+        return Ok(());
+    }
+
+    let asset = asset_name(env::consts::OS, env::consts::ARCH);
+    let url = format!("{RELEASES_URL}/{latest}/{asset}");
+    println!("Downloading lasersell {latest} from {url}");
+    let binary = download(&client, &url).await?;
+    let signature = download(&client, &format!("{url}.sig")).await?;
+    let signature = String::from_utf8(signature).context("signature is not text")?;
+    verify_release(&binary, &latest, &asset, &signature, &signing_key)?;
+
+    let exe = env::current_exe()
+        .and_then(|path| path.canonicalize())
+        .context("locate the running binary")?;
+    replace_binary(&exe, &binary)?;
+    println!(
+        "Updated {} from {current} to {latest}; restart the daemon to use it.",
+        exe.display()
+    );
+    Ok(())
+}
+
+fn asset_name(os: &str, arch: &str) -> String {
+    let suffix = if os == "windows" { ".exe" } else { "" };
+    format!("lasersell-{arch}-{os}{suffix}")
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("download {url}"))?;
+    let body = response
+        .bytes()
+        .await
+        .with_context(|| format!("download {url}"))?;
+    Ok(body.to_vec())
+}
+
+/// What a release is signed over: the version and asset it was published
+/// as, and the binary's SHA-256. Signing the version too keeps a mirror
+/// from passing an older signed binary off as the latest release.
+fn release_manifest(version: &str, asset: &str, binary: &[u8]) -> String {
+    format!("lasersell {version} {asset} {:x}\n", Sha256::digest(binary))
+}
+
+/// Checks the base58 ed25519 `signature` over the manifest of `binary`
+/// published as `version`.
+fn verify_release(
+    binary: &[u8],
+    version: &str,
+    asset: &str,
+    signature: &str,
+    signing_key: &Pubkey,
+) -> Result<()> {
+    let signature = Signature::from_str(signature.trim()).context("parse release signature")?;
+    let manifest = release_manifest(version, asset, binary);
+    if !signature.verify(signing_key.as_ref(), manifest.as_bytes()) {
+        return Err(anyhow!(
+            "release signature does not match {asset} {version}; the download was not installed"
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the new binary next to the old one and renames it over it, so an
+/// interrupted update leaves the old binary in place. Windows cannot
+/// replace a running executable, but can rename it aside first.
+fn replace_binary(exe: &Path, binary: &[u8]) -> Result<()> {
+    #[cfg(windows)]
+    {
+        let old = exe.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(exe, &old).with_context(|| format!("move aside {}", exe.display()))?;
+        let written = super::fs_utils::atomic_write(exe, binary, Some(0o755));
+        if written.is_err() {
+            // Put the old binary back rather than leave none at all.
+            let _ = std::fs::rename(&old, exe);
+        }
+        written
+    }
+    #[cfg(not(windows))]
+    {
+        super::fs_utils::atomic_write(exe, binary, Some(0o755))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn only_a_matching_signature_is_accepted() {
+        let release = Keypair::new();
+        let asset = "lasersell-x86_64-linux";
+        let binary = b"\x7fELF new lasersell".to_vec();
+        let manifest = release_manifest("1.2.0", asset, &binary);
+        let signature = release.sign_message(manifest.as_bytes()).to_string();
+        let verify = |binary: &[u8], version, signature: &str, key: &Pubkey| {
+            verify_release(binary, version, asset, signature, key)
+        };
+
+        verify(
+            &binary,
+            "1.2.0",
+            &format!("{signature}\n"),
+            &release.pubkey(),
+        )
+        .unwrap();
+        assert!(verify(b"tampered", "1.2.0", &signature, &release.pubkey()).is_err());
+        assert!(verify(&binary, "1.2.0", &signature, &Keypair::new().pubkey()).is_err());
+        assert!(verify(&binary, "1.2.0", "not base58", &release.pubkey()).is_err());
+        // A validly signed 1.2.0 served as a newer release is refused.
+        assert!(verify(&binary, "1.3.0", &signature, &release.pubkey()).is_err());
+
+        assert_eq!(asset_name("linux", "x86_64"), "lasersell-x86_64-linux");
+        assert_eq!(
+            asset_name("windows", "x86_64"),
+            "lasersell-x86_64-windows.exe"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 const SUPPORT_URL: &str = "discord.gg/lasersell";
-const SUPPORT_HINT: &str = "Need help? Report it in discord.gg/lasersell.";
+const SUPPORT_HINT: &str =
+    "Need help? Run `lasersell support-bundle` and attach the file in discord.gg/lasersell.";
 
 pub fn with_support_hint(message: impl Into<String>) -> String {
     let mut text = message.into();
@@ -0,0 +1,186 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+use super::logging::scrub_sensitive;
+
+const BLOCK: usize = 512;
+const ZSTD_LEVEL: i32 = 3;
+/// Rotated error logs beyond this many are left out.
+const MAX_ROTATED_LOGS: usize = 5;
+
+/// What `lasersell support-bundle` collects, written as one zstd-compressed
+/// tar. Everything read from disk goes through the log scrubber first, so
+/// the redactions must be initialized before the logs are added.
+#[derive(Debug, Default)]
+pub struct SupportBundle {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl SupportBundle {
+    pub fn add(&mut self, name: impl Into<String>, contents: impl Into<Vec<u8>>) {
+        self.entries.push((name.into(), contents.into()));
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// `error.log` and its most recent rotated copies, scrubbed.
+    pub fn add_error_logs(&mut self, error_log: &Path) {
+        let copies = (1..=MAX_ROTATED_LOGS).map(|n| super::paths::rotated_log_path(error_log, n));
+        for path in std::iter::once(error_log.to_path_buf()).chain(copies) {
+            let Ok(raw) = fs::read(&path) else {
+                continue;
+            };
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            self.add(
+                format!("logs/{name}"),
+                scrub_sensitive(&String::from_utf8_lossy(&raw)),
+            );
+        }
+    }
+
+    /// The newest `limit` session reports under `reports_dir`, whose
+    /// `<date>/` directory names sort by date.
+    pub fn add_reports(&mut self, reports_dir: &Path, limit: usize) {
+        let mut days = read_dir_sorted(reports_dir);
+        days.reverse();
+        let mut added = 0;
+        for day in days {
+            let mut reports = read_dir_sorted(&reports_dir.join(&day));
+            reports.reverse();
+            for report in reports {
+                if added == limit {
+                    return;
+                }
+                let Ok(raw) = fs::read(reports_dir.join(&day).join(&report)) else {
+                    continue;
+                };
+                self.add(
+                    format!("reports/{day}/{report}"),
+                    scrub_sensitive(&String::from_utf8_lossy(&raw)),
+                );
+                added += 1;
+            }
+        }
+    }
+
+    pub fn write(&self, path: &Path, mtime: u64) -> Result<()> {
+        let tar = tar(&self.entries, mtime)?;
+        let file = fs::File::create(path).with_context(|| format!("create {}", path.display()))?;
+        let mut encoder = zstd::Encoder::new(file, ZSTD_LEVEL)
+            .with_context(|| format!("compress {}", path.display()))?;
+        encoder
+            .write_all(&tar)
+            .with_context(|| format!("write {}", path.display()))?;
+        encoder
+            .finish()
+            .with_context(|| format!("write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+fn read_dir_sorted(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// A plain ustar archive of regular files, mode 0600.
+fn tar(entries: &[(String, Vec<u8>)], mtime: u64) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for (name, contents) in entries {
+        out.extend_from_slice(&header(name, contents.len() as u64, mtime)?);
+        out.extend_from_slice(contents);
+        out.resize(out.len().next_multiple_of(BLOCK), 0);
+    }
+    out.resize(out.len() + 2 * BLOCK, 0);
+    Ok(out)
+}
+
+fn header(name: &str, size: u64, mtime: u64) -> Result<[u8; BLOCK]> {
+    if name.len() >= 100 {
+        return Err(anyhow!("bundle entry name too long: {name}"));
+    }
+    let mut header = [0u8; BLOCK];
+    let mut field = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    field(0, name.as_bytes());
+    field(100, b"0000600\0");
+    field(108, b"0000000\0");
+    field(116, b"0000000\0");
+    field(124, format!("{size:011o}\0").as_bytes());
+    field(136, format!("{mtime:011o}\0").as_bytes());
+    field(148, b"        ");
+    field(156, b"0");
+    field(257, b"ustar\0");
+    field(263, b"00");
+    let checksum: u32 = header.iter().map(|byte| u32::from(*byte)).sum();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn bundle_is_a_compressed_ustar_of_the_newest_reports() {
+        let dir = tempdir().unwrap();
+        let reports = dir.path().join("reports");
+        for (day, mint) in [
+            ("2026-10-13", "a"),
+            ("2026-10-14", "b"),
+            ("2026-10-14", "c"),
+        ] {
+            fs::create_dir_all(reports.join(day)).unwrap();
+            fs::write(reports.join(day).join(format!("{mint}.json")), "{}").unwrap();
+        }
+        let log = dir.path().join("error.log");
+        fs::write(&log, "ERROR sell failed api-key=secret\n").unwrap();
+
+        let mut bundle = SupportBundle::default();
+        bundle.add("version.txt", "lasersell 1.0.0\n");
+        bundle.add_error_logs(&log);
+        bundle.add_reports(&reports, 2);
+        assert_eq!(
+            bundle.names().collect::<Vec<_>>(),
+            [
+                "version.txt",
+                "logs/error.log",
+                "reports/2026-10-14/c.json",
+                "reports/2026-10-14/b.json"
+            ]
+        );
+        let logged = &bundle.entries[1].1;
+        assert!(!String::from_utf8_lossy(logged).contains("secret"));
+
+        let path = dir.path().join("bundle.tar.zst");
+        bundle.write(&path, 1_760_000_000).unwrap();
+        let tar = zstd::decode_all(fs::File::open(&path).unwrap()).unwrap();
+        // Four entries of one header and one data block each, then two
+        // zero blocks.
+        assert_eq!(tar.len(), 10 * BLOCK);
+        let first = &tar[..BLOCK];
+        assert_eq!(&first[..11], b"version.txt");
+        assert_eq!(&first[124..136], b"00000000020\0");
+        assert_eq!(&first[257..263], b"ustar\0");
+        let stored = std::str::from_utf8(&first[148..154]).unwrap();
+        let mut blank = first.to_vec();
+        blank[148..156].copy_from_slice(b"        ");
+        let sum: u32 = blank.iter().map(|byte| u32::from(*byte)).sum();
+        assert_eq!(u32::from_str_radix(stored, 8).unwrap(), sum);
+        assert_eq!(&tar[BLOCK..BLOCK + 16], b"lasersell 1.0.0\n");
+        assert!(tar[8 * BLOCK..].iter().all(|byte| *byte == 0));
+    }
+}
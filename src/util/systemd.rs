@@ -0,0 +1,140 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+/// `sd_notify` over `$NOTIFY_SOCKET`, so a `Type=notify` unit with
+/// `WatchdogSec=` restarts the daemon when the event loop stops turning.
+/// Every call is a no-op when the daemon was not started by systemd.
+struct Notifier {
+    #[cfg(unix)]
+    socket: std::os::unix::net::UnixDatagram,
+    #[cfg(unix)]
+    addr: std::os::unix::net::SocketAddr,
+    watchdog: Option<Duration>,
+}
+
+impl Notifier {
+    #[cfg(unix)]
+    fn from_env_values(
+        notify_socket: Option<&str>,
+        watchdog_usec: Option<&str>,
+        watchdog_pid: Option<&str>,
+        pid: u32,
+    ) -> Option<Self> {
+        use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+        let path = notify_socket.filter(|path| !path.is_empty())?;
+        let addr = match path.strip_prefix('@') {
+            #[cfg(target_os = "linux")]
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                SocketAddr::from_abstract_name(name).ok()?
+            }
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => return None,
+            None => SocketAddr::from_pathname(path).ok()?,
+        };
+        let socket = UnixDatagram::unbound().ok()?;
+        // WATCHDOG_PID names the process systemd expects pings from; a child
+        // that inherited the variables must stay quiet.
+        let for_us = watchdog_pid.is_none_or(|watchdog_pid| watchdog_pid.parse() == Ok(pid));
+        let watchdog = watchdog_usec
+            .and_then(|usec| usec.parse::<u64>().ok())
+            .filter(|usec| *usec > 0 && for_us)
+            .map(Duration::from_micros);
+        Some(Self {
+            socket,
+            addr,
+            watchdog,
+        })
+    }
+
+    fn send(&self, state: &str) {
+        #[cfg(unix)]
+        if let Err(err) = self.socket.send_to_addr(state.as_bytes(), &self.addr) {
+            debug!(event = "sd_notify_failed", state, error = %err);
+        }
+        #[cfg(not(unix))]
+        let _ = state;
+    }
+}
+
+fn notifier() -> Option<&'static Notifier> {
+    static NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
+    NOTIFIER
+        .get_or_init(|| {
+            #[cfg(unix)]
+            {
+                let var = |name| std::env::var(name).ok();
+                let notifier = Notifier::from_env_values(
+                    var("NOTIFY_SOCKET").as_deref(),
+                    var("WATCHDOG_USEC").as_deref(),
+                    var("WATCHDOG_PID").as_deref(),
+                    std::process::id(),
+                );
+                if let Some(notifier) = &notifier {
+                    info!(
+                        event = "systemd_notify_enabled",
+                        watchdog_ms = notifier
+                            .watchdog
+                            .map(|watchdog| watchdog.as_millis() as u64)
+                    );
+                }
+                notifier
+            }
+            #[cfg(not(unix))]
+            None
+        })
+        .as_ref()
+}
+
+/// The watchdog timeout systemd expects pings within, if it set one.
+pub fn watchdog_timeout() -> Option<Duration> {
+    notifier()?.watchdog
+}
+
+/// Tells systemd startup is done (the stream is authenticated).
+pub fn notify_ready() {
+    if let Some(notifier) = notifier() {
+        notifier.send("READY=1");
+    }
+}
+
+/// Pings the watchdog, when one is configured for this process.
+pub fn notify_watchdog() {
+    if let Some(notifier) = notifier().filter(|notifier| notifier.watchdog.is_some()) {
+        notifier.send("WATCHDOG=1");
+    }
+}
+
+pub fn notify_stopping() {
+    if let Some(notifier) = notifier() {
+        notifier.send("STOPPING=1");
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sends_states_and_honours_watchdog_pid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        let path = path.to_str().unwrap();
+
+        let notifier =
+            Notifier::from_env_values(Some(path), Some("20000000"), Some("42"), 42).unwrap();
+        assert_eq!(notifier.watchdog, Some(Duration::from_secs(20)));
+        notifier.send("READY=1");
+        let mut buf = [0u8; 64];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+
+        let child = Notifier::from_env_values(Some(path), Some("20000000"), Some("42"), 7).unwrap();
+        assert_eq!(child.watchdog, None);
+        assert!(Notifier::from_env_values(None, Some("20000000"), None, 42).is_none());
+    }
+}
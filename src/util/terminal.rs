@@ -0,0 +1,18 @@
+use std::sync::OnceLock;
+
+/// Whether console output may carry ANSI colour codes. On Windows this
+/// first switches the console to virtual-terminal mode through crossterm;
+/// a console that refuses (older conhost) gets plain text.
+pub fn ansi_enabled() -> bool {
+    static ANSI: OnceLock<bool> = OnceLock::new();
+    *ANSI.get_or_init(|| {
+        #[cfg(windows)]
+        {
+            crossterm::ansi_support::supports_ansi()
+        }
+        #[cfg(not(windows))]
+        {
+            true
+        }
+    })
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{UiConfig, UiTheme};
+
+const RESET: &str = "\x1b[0m";
+
+/// A `#rrggbb` colour from `ui.accent`, `ui.error` or `ui.background`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl TryFrom<String> for Rgb {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let hex = value
+            .trim()
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.chars().all(|ch| ch.is_ascii_hexdigit()))
+            .ok_or_else(|| anyhow!("expected a colour like \"#ffaa00\", got \"{value}\""))?;
+        let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).expect("hex digits");
+        Ok(Rgb(channel(0), channel(2), channel(4)))
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(rgb: Rgb) -> String {
+        rgb.to_string()
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Console colours as SGR parameters, from `ui`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// `false` under `no_color`, `NO_COLOR`, or a console without ANSI
+    /// support: every line is printed plain.
+    ansi: bool,
+    /// `ui.accessible`: no colour and no graphics, only lines of text.
+    accessible: bool,
+    accent: String,
+    /// Recolours warning and error lines; `None` keeps the log colours.
+    error: Option<String>,
+    background: Option<String>,
+}
+
+impl Theme {
+    fn from_config(ui: &UiConfig, ansi: bool) -> Self {
+        let (accent, error) = match ui.theme {
+            UiTheme::Default => ("33", None),
+            UiTheme::HighContrast => ("1;97", Some("1;91")),
+            UiTheme::NoColor => ("", None),
+        };
+        Self {
+            ansi: ansi && ui.theme != UiTheme::NoColor && !ui.accessible,
+            accessible: ui.accessible,
+            accent: ui
+                .accent
+                .map_or_else(|| accent.to_string(), |rgb| sgr(38, rgb)),
+            error: ui
+                .error
+                .map(|rgb| sgr(38, rgb))
+                .or_else(|| error.map(str::to_string)),
+            background: ui.background.map(|rgb| sgr(48, rgb)),
+        }
+    }
+
+    pub fn ansi(&self) -> bool {
+        self.ansi
+    }
+
+    pub fn accessible(&self) -> bool {
+        self.accessible
+    }
+
+    /// `text` in the accent colour: banners and headings.
+    pub fn accent(&self, text: &str) -> String {
+        self.paint(&self.accent, text)
+    }
+
+    /// A console log line as this theme prints it: plain without ANSI,
+    /// warnings and errors recoloured when the theme sets an error colour.
+    pub fn log_line(&self, line: String) -> String {
+        if !self.ansi {
+            return super::logging::strip_ansi(&line);
+        }
+        let Some(error) = self.error.as_deref() else {
+            return line;
+        };
+        let plain = super::logging::strip_ansi(&line);
+        // Console lines are `<timestamp> <LEVEL> <target>: ...`.
+        match plain.split_whitespace().nth(1) {
+            Some("WARN" | "ERROR") => {
+                let (text, newline) = match plain.strip_suffix('\n') {
+                    Some(text) => (text, "\n"),
+                    None => (plain.as_str(), ""),
+                };
+                format!("\x1b[{error}m{text}{RESET}{newline}")
+            }
+            _ => line,
+        }
+    }
+
+    /// The escape code that switches to the theme's background, or nothing.
+    /// Only full-screen views use it; console lines keep the terminal's.
+    pub fn background(&self) -> String {
+        match self.background.as_deref().filter(|_| self.ansi) {
+            Some(background) => format!("\x1b[{background}m"),
+            None => String::new(),
+        }
+    }
+
+    /// Escape codes that clear the screen to the theme's background.
+    pub fn clear_screen(&self) -> String {
+        format!("{}\x1b[2J\x1b[H", self.background())
+    }
+
+    fn paint(&self, sgr: &str, text: &str) -> String {
+        if !self.ansi || sgr.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{sgr}m{text}{RESET}")
+    }
+}
+
+fn sgr(layer: u8, Rgb(r, g, b): Rgb) -> String {
+    format!("{layer};2;{r};{g};{b}")
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Applies `ui` to console output. `NO_COLOR` in the environment wins over
+/// any theme. Called once, after the config loads; later calls are ignored.
+pub fn init(ui: &UiConfig) {
+    let _ = THEME.set(Theme::from_config(ui, ansi_allowed()));
+}
+
+/// The theme from `init`, or the default one before the config is loaded.
+pub fn current() -> &'static Theme {
+    THEME.get_or_init(|| Theme::from_config(&UiConfig::default(), ansi_allowed()))
+}
+
+fn ansi_allowed() -> bool {
+    super::terminal::ansi_enabled()
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WARN_LINE: &str =
+        "2026-01-01T00:00:00Z \x1b[33m WARN\x1b[0m lasersell::app: event=\"stream_degraded\"\n";
+
+    #[test]
+    fn rgb_round_trips_through_config_strings() {
+        assert_eq!(
+            Rgb::try_from("#FFaa00".to_string()).unwrap(),
+            Rgb(255, 170, 0)
+        );
+        assert_eq!(Rgb(1, 2, 255).to_string(), "#0102ff");
+        for bad in ["ffaa00", "#ffaa0", "#ggaa00"] {
+            assert!(Rgb::try_from(bad.to_string()).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn themes_recolour_warnings_or_strip_colour() {
+        let default = Theme::from_config(&UiConfig::default(), true);
+        assert_eq!(default.log_line(WARN_LINE.to_string()), WARN_LINE);
+        assert_eq!(default.accent("update"), "\x1b[33mupdate\x1b[0m");
+
+        let high_contrast = Theme::from_config(
+            &UiConfig {
+                theme: UiTheme::HighContrast,
+                ..UiConfig::default()
+            },
+            true,
+        );
+        assert_eq!(
+            high_contrast.log_line(WARN_LINE.to_string()),
+            "\x1b[1;91m2026-01-01T00:00:00Z  WARN lasersell::app: event=\"stream_degraded\"\x1b[0m\n"
+        );
+        let info = "2026-01-01T00:00:00Z  INFO lasersell::app: ok\n";
+        assert_eq!(high_contrast.log_line(info.to_string()), info);
+
+        let no_color = Theme::from_config(
+            &UiConfig {
+                theme: UiTheme::NoColor,
+                accent: Some(Rgb(255, 0, 0)),
+                ..UiConfig::default()
+            },
+            true,
+        );
+        assert!(!no_color.ansi());
+        assert_eq!(no_color.accent("update"), "update");
+        assert!(!no_color.log_line(WARN_LINE.to_string()).contains('\x1b'));
+        assert_eq!(no_color.clear_screen(), "\x1b[2J\x1b[H");
+    }
+
+    #[test]
+    fn rgb_overrides_use_truecolor() {
+        let custom = Theme::from_config(
+            &UiConfig {
+                accent: Some(Rgb(0, 200, 255)),
+                background: Some(Rgb(16, 16, 16)),
+                ..UiConfig::default()
+            },
+            true,
+        );
+        assert_eq!(custom.accent("x"), "\x1b[38;2;0;200;255mx\x1b[0m");
+        assert_eq!(custom.clear_screen(), "\x1b[48;2;16;16;16m\x1b[2J\x1b[H");
+    }
+}
@@ -3,6 +3,8 @@ use std::time::Duration;
 const LATEST_VERSION_URL: &str =
     "https://dl.lasersell.io/binaries/lasersell/latest.txt";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+const DAILY_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const INSTALL_SCRIPT_CMD: &str = "curl -fsSL https://dl.lasersell.io/install.sh | sh";
 
 pub struct UpdateAvailable {
     pub current: String,
@@ -22,24 +24,74 @@ async fn check_against(current: &str) -> Option<UpdateAvailable> {
         .build()
         .ok()?;
 
-    let response = client.get(LATEST_VERSION_URL).send().await.ok()?;
+    let latest = fetch_latest(&client).await?;
+    if is_newer(current, &latest) {
+        Some(UpdateAvailable {
+            current: current.to_string(),
+            latest,
+        })
+    } else {
+        None
+    }
+}
+
+/// The version in the release channel's `latest.txt`.
+pub async fn fetch_latest(client: &reqwest::Client) -> Option<String> {
+    let response = client
+        .get(LATEST_VERSION_URL)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
     let body = response.text().await.ok()?;
     let latest = body.trim();
+    (!latest.is_empty()).then(|| latest.to_string())
+}
 
-    if latest.is_empty() {
-        return None;
+pub fn is_newer(current: &str, latest: &str) -> bool {
+    match (parse_semver(current), parse_semver(latest)) {
+        (Some(current), Some(latest)) => latest > current,
+        _ => false,
     }
+}
 
-    let current_parts = parse_semver(current)?;
-    let latest_parts = parse_semver(latest)?;
+/// Checks again once a day while the daemon runs and logs
+/// `update_available` the first time each newer version shows up; the
+/// startup check already printed the banner.
+pub fn spawn_daily_check() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DAILY_CHECK_INTERVAL);
+        interval.tick().await;
+        let mut announced: Option<String> = None;
+        loop {
+            interval.tick().await;
+            let Some(update) = check_for_update().await else {
+                continue;
+            };
+            if announced.as_deref() == Some(update.latest.as_str()) {
+                continue;
+            }
+            tracing::warn!(
+                event = "update_available",
+                current = %update.current,
+                latest = %update.latest,
+                "lasersell {} is available; run `{}` after shutting down",
+                update.latest,
+                update_command()
+            );
+            announced = Some(update.latest);
+        }
+    });
+}
 
-    if latest_parts > current_parts {
-        Some(UpdateAvailable {
-            current: current.to_string(),
-            latest: latest.to_string(),
-        })
+/// How to install a newer release: `self-update` when this build can
+/// verify one, otherwise the install script.
+pub fn update_command() -> &'static str {
+    if super::self_update::available() {
+        "lasersell self-update"
     } else {
-        None
+        INSTALL_SCRIPT_CMD
     }
 }
 
@@ -55,47 +107,59 @@ fn parse_semver(version: &str) -> Option<(u64, u64, u64)> {
 /// Print a styled update banner to stderr. This is called before the TUI takes
 /// over the terminal, so stderr output is visible to the user.
 pub fn print_update_banner(update: &UpdateAvailable) {
-    let install_cmd = "curl -fsSL https://dl.lasersell.io/install.sh | sh";
+    let install_cmd = if super::self_update::available() {
+        "Run `lasersell self-update` to install it"
+    } else {
+        INSTALL_SCRIPT_CMD
+    };
     let version_line = format!("Update available: {} \u{2192} {}", update.current, update.latest);
     let changelog = "Changelog: https://github.com/lasersell/lasersell/releases";
+    let theme = super::theme::current();
+    if theme.accessible() {
+        eprintln!(
+            "Update available: {} to {}. {install_cmd}. {changelog}",
+            update.current, update.latest
+        );
+        return;
+    }
 
     // Calculate box width based on longest content line
     let content_lines = [&version_line, install_cmd, changelog];
     let max_len = content_lines.iter().map(|l| l.len()).max().unwrap_or(0);
     let inner_width = max_len + 2; // 1 space padding each side
 
-    let top = format!("  \x1b[33m╭{}╮\x1b[0m", "─".repeat(inner_width));
-    let bottom = format!("  \x1b[33m╰{}╯\x1b[0m", "─".repeat(inner_width));
-    let empty = format!(
-        "  \x1b[33m│\x1b[0m{}\x1b[33m│\x1b[0m",
-        " ".repeat(inner_width)
-    );
+    let top = format!("  {}", theme.accent(&format!("╭{}╮", "─".repeat(inner_width))));
+    let bottom = format!("  {}", theme.accent(&format!("╰{}╯", "─".repeat(inner_width))));
+    let side = theme.accent("│");
+    let empty = format!("  {side}{}{side}", " ".repeat(inner_width));
 
     let fmt_line = |text: &str, bold: bool| -> String {
         let padding = inner_width - text.len() - 1;
-        if bold {
-            format!(
-                "  \x1b[33m│\x1b[0m \x1b[1;33m{}\x1b[0m{}\x1b[33m│\x1b[0m",
-                text,
-                " ".repeat(padding)
-            )
+        let text = if bold {
+            format!("\x1b[1m{}", theme.accent(text))
         } else {
-            format!(
-                "  \x1b[33m│\x1b[0m \x1b[2m{}\x1b[0m{}\x1b[33m│\x1b[0m",
-                text,
-                " ".repeat(padding)
-            )
-        }
+            format!("\x1b[2m{text}\x1b[0m")
+        };
+        format!("  {side} {text}{}{side}", " ".repeat(padding))
     };
 
-    eprintln!();
-    eprintln!("{top}");
-    eprintln!("{}", fmt_line(&version_line, true));
-    eprintln!("{empty}");
-    eprintln!("{}", fmt_line(install_cmd, false));
-    eprintln!("{}", fmt_line(changelog, false));
-    eprintln!("{bottom}");
-    eprintln!();
+    let lines = [
+        String::new(),
+        top,
+        fmt_line(&version_line, true),
+        empty,
+        fmt_line(install_cmd, false),
+        fmt_line(changelog, false),
+        bottom,
+        String::new(),
+    ];
+    for line in lines {
+        if theme.ansi() {
+            eprintln!("{line}");
+        } else {
+            eprintln!("{}", super::logging::strip_ansi(&line));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -109,6 +173,14 @@ mod tests {
         assert_eq!(parse_semver("0.3.0"), Some((0, 3, 0)));
     }
 
+    #[test]
+    fn newer_compares_numerically() {
+        assert!(is_newer("1.9.0", "1.10.0"));
+        assert!(is_newer("1.1.0", "v1.1.1"));
+        assert!(!is_newer("1.1.0", "1.1.0"));
+        assert!(!is_newer("1.1.0", "garbage"));
+    }
+
     #[test]
     fn parse_semver_invalid() {
         assert_eq!(parse_semver("abc"), None);
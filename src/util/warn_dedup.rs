@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+const WINDOW: Duration = Duration::from_secs(60);
+const SUMMARY_EVENT: &str = "warning_repeated";
+
+struct Seen {
+    first_at: Instant,
+    suppressed: u64,
+}
+
+/// Collapses identical warnings (same event and fields) so a failing poller
+/// logs once per minute plus an "xN in the last 60s" summary, instead of
+/// every cycle. Shared by the console and the error log.
+#[derive(Default)]
+pub struct WarningDedup {
+    seen: Mutex<HashMap<String, Seen>>,
+}
+
+impl WarningDedup {
+    /// Whether a warning with this key should be logged at `now`.
+    fn admit(&self, key: &str, now: Instant) -> bool {
+        let mut seen = self.seen.lock();
+        match seen.get_mut(key) {
+            Some(entry) if now.duration_since(entry.first_at) < WINDOW => {
+                entry.suppressed += 1;
+                false
+            }
+            _ => {
+                seen.insert(
+                    key.to_string(),
+                    Seen {
+                        first_at: now,
+                        suppressed: 0,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Takes the warnings suppressed in windows that have closed by `now`.
+    fn drain_closed(&self, now: Instant) -> Vec<(String, u64)> {
+        let mut repeated = Vec::new();
+        self.seen.lock().retain(|key, entry| {
+            if now.duration_since(entry.first_at) < WINDOW {
+                return true;
+            }
+            if entry.suppressed > 0 {
+                repeated.push((key.clone(), entry.suppressed));
+            }
+            false
+        });
+        repeated.sort();
+        repeated
+    }
+}
+
+fn shared() -> &'static Arc<WarningDedup> {
+    static DEDUP: OnceLock<Arc<WarningDedup>> = OnceLock::new();
+    DEDUP.get_or_init(Arc::default)
+}
+
+/// Registry layer that drops repeated warnings for every other layer.
+pub struct WarningDedupLayer(Arc<WarningDedup>);
+
+pub fn layer() -> WarningDedupLayer {
+    WarningDedupLayer(shared().clone())
+}
+
+impl<S: Subscriber> Layer<S> for WarningDedupLayer {
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        if *event.metadata().level() != Level::WARN {
+            return true;
+        }
+        let mut key = KeyVisitor::default();
+        event.record(&mut key);
+        if key.event.as_deref() == Some(SUMMARY_EVENT) {
+            return true;
+        }
+        self.0.admit(&key.fields, Instant::now())
+    }
+}
+
+#[derive(Default)]
+struct KeyVisitor {
+    event: Option<String>,
+    fields: String,
+}
+
+impl Visit for KeyVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "event" {
+            self.event = Some(value.to_string());
+        }
+        let _ = write!(self.fields, "{}={value} ", field.name());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let _ = write!(self.fields, "{}={value:?} ", field.name());
+    }
+}
+
+/// Logs a summary for each warning that repeated, once its window closes.
+pub fn spawn_summaries() {
+    let dedup = shared().clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            interval.tick().await;
+            for (warning, count) in dedup.drain_closed(Instant::now()) {
+                tracing::warn!(
+                    event = SUMMARY_EVENT,
+                    count,
+                    "{} x{count} in the last 60s",
+                    warning.trim_end()
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suppresses_repeats_until_the_window_closes() {
+        let dedup = WarningDedup::default();
+        let start = Instant::now();
+        let key = "event=wallet_balance_fetch_error error=timeout ";
+        assert!(dedup.admit(key, start));
+        for secs in [5, 10, 15] {
+            assert!(!dedup.admit(key, start + Duration::from_secs(secs)));
+        }
+        assert!(dedup.admit("event=other ", start + Duration::from_secs(20)));
+        assert!(dedup
+            .drain_closed(start + Duration::from_secs(30))
+            .is_empty());
+
+        let drained = dedup.drain_closed(start + Duration::from_secs(61));
+        assert_eq!(drained, vec![(key.to_string(), 3)]);
+        assert!(dedup.admit(key, start + Duration::from_secs(62)));
+        // `event=other` never repeated, so its window closes without a summary.
+        assert!(dedup
+            .drain_closed(start + Duration::from_secs(200))
+            .is_empty());
+    }
+}
@@ -10,17 +10,20 @@ use rand::rngs::OsRng;
 use rand::RngCore;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{read_keypair_file, Keypair};
 use solana_sdk::signer::Signer;
 use zeroize::{Zeroize, Zeroizing};
 
+use crate::config::{KdfAlgorithm, KdfConfig};
 use crate::util::fs_utils::atomic_write;
 
-const KEYSTORE_VERSION: u8 = 1;
-const KEYSTORE_AAD: &[u8] = b"lasersell-keystore-v1";
-const ARGON2_M_KIB: u32 = 65_536;
-const ARGON2_T: u32 = 3;
+const KEYSTORE_VERSION: u8 = 2;
+const KEYSTORE_V1_AAD: &[u8] = b"lasersell-keystore-v1";
+const KEYSTORE_V2_AAD: &[u8] = b"lasersell-keystore-v2";
 const ARGON2_P: u32 = 1;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
 const SALT_LEN: usize = 16;
 const NONCE_LEN: usize = 24;
 
@@ -30,8 +33,10 @@ pub enum WalletFileKind {
     PlaintextSolanaJson,
 }
 
+/// Version 1 is always Argon2id. Version 2 adds scrypt; both record the
+/// parameters they were written with and otherwise share a layout.
 #[derive(Debug, Serialize, Deserialize)]
-struct KeystoreV1 {
+struct Keystore {
     version: u8,
     pubkey: String,
     kdf: KdfSpec,
@@ -39,10 +44,16 @@ struct KeystoreV1 {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct KdfSpec {
-    name: String,
-    params: Argon2Params,
-    salt_b64: String,
+#[serde(tag = "name", rename_all = "lowercase")]
+enum KdfSpec {
+    Argon2id {
+        params: Argon2Params,
+        salt_b64: String,
+    },
+    Scrypt {
+        params: ScryptParams,
+        salt_b64: String,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -52,6 +63,64 @@ struct Argon2Params {
     p: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct ScryptParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl KdfSpec {
+    fn new(cfg: &KdfConfig, salt: &[u8]) -> Self {
+        let salt_b64 = STANDARD.encode(salt);
+        match cfg.algorithm {
+            KdfAlgorithm::Argon2id => Self::Argon2id {
+                params: Argon2Params {
+                    m_kib: cfg.argon2_memory_kib,
+                    t: cfg.argon2_iterations,
+                    p: ARGON2_P,
+                },
+                salt_b64,
+            },
+            KdfAlgorithm::Scrypt => Self::Scrypt {
+                params: ScryptParams {
+                    log_n: cfg.scrypt_log_n,
+                    r: SCRYPT_R,
+                    p: SCRYPT_P,
+                },
+                salt_b64,
+            },
+        }
+    }
+
+    fn salt_b64(&self) -> &str {
+        match self {
+            Self::Argon2id { salt_b64, .. } | Self::Scrypt { salt_b64, .. } => salt_b64,
+        }
+    }
+
+    fn derive_key(&self, passphrase: &SecretString, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        let passphrase = passphrase.expose_secret().as_bytes();
+        match self {
+            Self::Argon2id { params, .. } => {
+                let params = Params::new(params.m_kib, params.t, params.p, Some(32))
+                    .map_err(|err| anyhow!("invalid argon2 params: {err}"))?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(passphrase, salt, key.as_mut())
+                    .map_err(|err| anyhow!("argon2 key derivation failed: {err}"))?;
+            }
+            Self::Scrypt { params, .. } => {
+                let params = scrypt::Params::new(params.log_n, params.r, params.p, 32)
+                    .map_err(|err| anyhow!("invalid scrypt params: {err}"))?;
+                scrypt::scrypt(passphrase, salt, &params, key.as_mut())
+                    .map_err(|err| anyhow!("scrypt key derivation failed: {err}"))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct CipherSpec {
     name: String,
@@ -84,7 +153,7 @@ pub fn detect_wallet_file_kind(path: &Path) -> Result<WalletFileKind> {
 /// Read the plaintext pubkey from an encrypted keystore without decrypting it.
 pub fn read_keystore_pubkey(path: &Path) -> Result<String> {
     let raw = fs::read(path).with_context(|| format!("read keystore {}", path.display()))?;
-    let keystore: KeystoreV1 = serde_json::from_slice(&raw)
+    let keystore: Keystore = serde_json::from_slice(&raw)
         .with_context(|| format!("parse keystore {}", path.display()))?;
     Ok(keystore.pubkey)
 }
@@ -103,20 +172,19 @@ pub fn load_keypair_from_path(
     }
 }
 
-pub fn write_keystore(path: &Path, keypair: &Keypair, passphrase: &SecretString) -> Result<()> {
+pub fn write_keystore(
+    path: &Path,
+    keypair: &Keypair,
+    passphrase: &SecretString,
+    kdf: &KdfConfig,
+) -> Result<()> {
     let mut salt = [0u8; SALT_LEN];
     OsRng.fill_bytes(&mut salt);
     let mut nonce = [0u8; NONCE_LEN];
     OsRng.fill_bytes(&mut nonce);
 
-    let params = Params::new(ARGON2_M_KIB, ARGON2_T, ARGON2_P, Some(32))
-        .map_err(|err| anyhow!("invalid argon2 params: {err}"))?;
-    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
-    let mut key = Zeroizing::new([0u8; 32]);
-    argon2
-        .hash_password_into(passphrase.expose_secret().as_bytes(), &salt, key.as_mut())
-        .map_err(|err| anyhow!("argon2 key derivation failed: {err}"))?;
-
+    let kdf = KdfSpec::new(kdf, &salt);
+    let key = kdf.derive_key(passphrase, &salt)?;
     let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
     let plaintext = Zeroizing::new(keypair.to_bytes());
     let ciphertext = cipher
@@ -124,23 +192,15 @@ pub fn write_keystore(path: &Path, keypair: &Keypair, passphrase: &SecretString)
             XNonce::from_slice(&nonce),
             Payload {
                 msg: plaintext.as_ref(),
-                aad: KEYSTORE_AAD,
+                aad: KEYSTORE_V2_AAD,
             },
         )
         .map_err(|err| anyhow!("keystore encryption failed: {err}"))?;
 
-    let keystore = KeystoreV1 {
+    let keystore = Keystore {
         version: KEYSTORE_VERSION,
         pubkey: keypair.pubkey().to_string(),
-        kdf: KdfSpec {
-            name: "argon2id".to_string(),
-            params: Argon2Params {
-                m_kib: ARGON2_M_KIB,
-                t: ARGON2_T,
-                p: ARGON2_P,
-            },
-            salt_b64: STANDARD.encode(salt),
-        },
+        kdf,
         cipher: CipherSpec {
             name: "xchacha20poly1305".to_string(),
             nonce_b64: STANDARD.encode(nonce),
@@ -157,15 +217,65 @@ pub fn migrate_plaintext_to_keystore(
     plaintext_path: &Path,
     keystore_path: &Path,
     passphrase: SecretString,
+    kdf: &KdfConfig,
     mut update_config_fn: impl FnMut(&Path) -> Result<()>,
 ) -> Result<()> {
     let keypair = read_keypair_file(plaintext_path)
         .map_err(|err| anyhow!("failed to read keypair {}: {err}", plaintext_path.display()))?;
-    write_keystore(keystore_path, &keypair, &passphrase)?;
+    write_keystore(keystore_path, &keypair, &passphrase, kdf)?;
     update_config_fn(keystore_path)?;
     Ok(())
 }
 
+/// Re-encrypts a keystore under a new passphrase, as a current-version
+/// keystore with `kdf`. The keypair is unchanged and the file is replaced
+/// atomically, so a crash leaves the old one.
+pub fn change_keystore_passphrase(
+    path: &Path,
+    current: SecretString,
+    new: &SecretString,
+    kdf: &KdfConfig,
+) -> Result<Pubkey> {
+    let keypair = load_keystore_keypair(path, current)?;
+    write_keystore(path, &keypair, new, kdf)?;
+    Ok(keypair.pubkey())
+}
+
+pub struct RotatedWallet {
+    pub old_pubkey: Pubkey,
+    pub new_pubkey: Pubkey,
+    pub keystore_path: PathBuf,
+}
+
+/// Generates a new keypair into its own keystore next to `path`, under the
+/// same passphrase. The old keystore is left alone: its wallet still holds
+/// whatever has not been moved off it.
+pub fn rotate_keystore(
+    path: &Path,
+    passphrase: SecretString,
+    kdf: &KdfConfig,
+) -> Result<RotatedWallet> {
+    let old = load_keystore_keypair(path, passphrase.clone())?;
+    let new = Keypair::new();
+    let new_pubkey = new.pubkey();
+    let keystore_path = path.with_file_name(format!(
+        "wallet-{}.keystore.json",
+        &new_pubkey.to_string()[..8]
+    ));
+    if keystore_path.exists() {
+        return Err(anyhow!(
+            "keystore {} already exists",
+            keystore_path.display()
+        ));
+    }
+    write_keystore(&keystore_path, &new, &passphrase, kdf)?;
+    Ok(RotatedWallet {
+        old_pubkey: old.pubkey(),
+        new_pubkey,
+        keystore_path,
+    })
+}
+
 pub fn default_keystore_path(path: &Path) -> PathBuf {
     let mut new_path = path.to_path_buf();
     new_path.set_extension("keystore.json");
@@ -174,20 +284,20 @@ pub fn default_keystore_path(path: &Path) -> PathBuf {
 
 fn load_keystore_keypair(path: &Path, passphrase: SecretString) -> Result<Keypair> {
     let raw = fs::read(path).with_context(|| format!("read keystore {}", path.display()))?;
-    let keystore: KeystoreV1 = serde_json::from_slice(&raw)
+    let keystore: Keystore = serde_json::from_slice(&raw)
         .with_context(|| format!("parse keystore {}", path.display()))?;
-    if keystore.version != KEYSTORE_VERSION {
-        return Err(anyhow!("unsupported keystore version {}", keystore.version));
-    }
-    if keystore.kdf.name != "argon2id" {
-        return Err(anyhow!("unsupported kdf {}", keystore.kdf.name));
-    }
+    let aad = match (keystore.version, &keystore.kdf) {
+        (1, KdfSpec::Argon2id { .. }) => KEYSTORE_V1_AAD,
+        (1, _) => return Err(anyhow!("keystore version 1 must use argon2id")),
+        (2, _) => KEYSTORE_V2_AAD,
+        (version, _) => return Err(anyhow!("unsupported keystore version {version}")),
+    };
     if keystore.cipher.name != "xchacha20poly1305" {
         return Err(anyhow!("unsupported cipher {}", keystore.cipher.name));
     }
 
     let salt = STANDARD
-        .decode(&keystore.kdf.salt_b64)
+        .decode(keystore.kdf.salt_b64())
         .context("decode kdf salt")?;
     if salt.len() < SALT_LEN {
         return Err(anyhow!("invalid kdf salt length"));
@@ -202,26 +312,14 @@ fn load_keystore_keypair(path: &Path, passphrase: SecretString) -> Result<Keypai
         .decode(&keystore.cipher.ciphertext_b64)
         .context("decode ciphertext")?;
 
-    let params = Params::new(
-        keystore.kdf.params.m_kib,
-        keystore.kdf.params.t,
-        keystore.kdf.params.p,
-        Some(32),
-    )
-    .map_err(|err| anyhow!("invalid argon2 params: {err}"))?;
-    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
-    let mut key = Zeroizing::new([0u8; 32]);
-    argon2
-        .hash_password_into(passphrase.expose_secret().as_bytes(), &salt, key.as_mut())
-        .map_err(|err| anyhow!("argon2 key derivation failed: {err}"))?;
-
+    let key = keystore.kdf.derive_key(&passphrase, &salt)?;
     let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
     let plaintext = cipher
         .decrypt(
             XNonce::from_slice(&nonce),
             Payload {
                 msg: &ciphertext,
-                aad: KEYSTORE_AAD,
+                aad,
             },
         )
         .map_err(|_| anyhow!("invalid passphrase or corrupted keystore"))?;
@@ -253,7 +351,7 @@ mod tests {
         let path = dir.path().join("wallet.keystore.json");
         let keypair = Keypair::new();
         let passphrase_secret = passphrase("correct horse");
-        write_keystore(&path, &keypair, &passphrase_secret).unwrap();
+        write_keystore(&path, &keypair, &passphrase_secret, &KdfConfig::default()).unwrap();
         let loaded = load_keypair_from_path(&path, || Ok(passphrase("correct horse"))).unwrap();
         assert_eq!(keypair.pubkey(), loaded.pubkey());
     }
@@ -264,7 +362,7 @@ mod tests {
         let path = dir.path().join("wallet.keystore.json");
         let keypair = Keypair::new();
         let passphrase_secret = passphrase("correct horse");
-        write_keystore(&path, &keypair, &passphrase_secret).unwrap();
+        write_keystore(&path, &keypair, &passphrase_secret, &KdfConfig::default()).unwrap();
         let loaded = load_keypair_from_path(&path, || Ok(passphrase("correct horse"))).unwrap();
 
         let bytes = Zeroizing::new(loaded.to_bytes());
@@ -280,20 +378,110 @@ mod tests {
         let path = dir.path().join("wallet.keystore.json");
         let keypair = Keypair::new();
         let passphrase_secret = passphrase("correct horse");
-        write_keystore(&path, &keypair, &passphrase_secret).unwrap();
+        write_keystore(&path, &keypair, &passphrase_secret, &KdfConfig::default()).unwrap();
         let err = load_keypair_from_path(&path, || Ok(passphrase("tr0ub4dor"))).unwrap_err();
         assert!(err.to_string().contains("invalid passphrase"));
     }
 
+    #[test]
+    fn passphrase_change_and_rotation_need_the_current_passphrase() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.keystore.json");
+        let keypair = Keypair::new();
+        let kdf = KdfConfig::default();
+        write_keystore(&path, &keypair, &passphrase("correct horse"), &kdf).unwrap();
+
+        assert!(
+            change_keystore_passphrase(&path, passphrase("nope"), &passphrase("x"), &kdf).is_err()
+        );
+        let pubkey = change_keystore_passphrase(
+            &path,
+            passphrase("correct horse"),
+            &passphrase("battery"),
+            &kdf,
+        )
+        .unwrap();
+        assert_eq!(pubkey, keypair.pubkey());
+        assert!(load_keypair_from_path(&path, || Ok(passphrase("correct horse"))).is_err());
+
+        assert!(rotate_keystore(&path, passphrase("correct horse"), &kdf).is_err());
+        let rotated = rotate_keystore(&path, passphrase("battery"), &kdf).unwrap();
+        assert_eq!(rotated.old_pubkey, keypair.pubkey());
+        assert_ne!(rotated.new_pubkey, keypair.pubkey());
+        let loaded =
+            load_keypair_from_path(&rotated.keystore_path, || Ok(passphrase("battery"))).unwrap();
+        assert_eq!(loaded.pubkey(), rotated.new_pubkey);
+        assert!(load_keypair_from_path(&path, || Ok(passphrase("battery"))).is_ok());
+    }
+
+    #[test]
+    fn reads_v1_and_writes_v2_with_configured_kdf() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.keystore.json");
+        let keypair = Keypair::new();
+
+        // A v1 keystore as earlier releases wrote it.
+        let salt = [7u8; SALT_LEN];
+        let nonce = [9u8; NONCE_LEN];
+        let kdf = KdfSpec::new(&KdfConfig::default(), &salt);
+        let key = kdf.derive_key(&passphrase("correct horse"), &salt).unwrap();
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: keypair.to_bytes().as_ref(),
+                    aad: KEYSTORE_V1_AAD,
+                },
+            )
+            .unwrap();
+        let v1 = Keystore {
+            version: 1,
+            pubkey: keypair.pubkey().to_string(),
+            kdf,
+            cipher: CipherSpec {
+                name: "xchacha20poly1305".to_string(),
+                nonce_b64: STANDARD.encode(nonce),
+                ciphertext_b64: STANDARD.encode(ciphertext),
+            },
+        };
+        fs::write(&path, serde_json::to_vec(&v1).unwrap()).unwrap();
+        let loaded = load_keypair_from_path(&path, || Ok(passphrase("correct horse"))).unwrap();
+        assert_eq!(loaded.pubkey(), keypair.pubkey());
+
+        let scrypt = KdfConfig {
+            algorithm: KdfAlgorithm::Scrypt,
+            scrypt_log_n: 10,
+            ..KdfConfig::default()
+        };
+        change_keystore_passphrase(
+            &path,
+            passphrase("correct horse"),
+            &passphrase("b"),
+            &scrypt,
+        )
+        .unwrap();
+        let v2: Keystore = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(v2.version, 2);
+        assert!(matches!(
+            v2.kdf,
+            KdfSpec::Scrypt {
+                params: ScryptParams { log_n: 10, .. },
+                ..
+            }
+        ));
+        let loaded = load_keypair_from_path(&path, || Ok(passphrase("b"))).unwrap();
+        assert_eq!(loaded.pubkey(), keypair.pubkey());
+    }
+
     #[test]
     fn keystore_tamper_detected() {
         let dir = tempdir().unwrap();
         let path = dir.path().join("wallet.keystore.json");
         let keypair = Keypair::new();
         let passphrase_secret = passphrase("correct horse");
-        write_keystore(&path, &keypair, &passphrase_secret).unwrap();
+        write_keystore(&path, &keypair, &passphrase_secret, &KdfConfig::default()).unwrap();
 
-        let mut keystore: KeystoreV1 = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        let mut keystore: Keystore = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
         let mut ciphertext = STANDARD.decode(&keystore.cipher.ciphertext_b64).unwrap();
         ciphertext[0] ^= 0x80;
         keystore.cipher.ciphertext_b64 = STANDARD.encode(ciphertext);